@@ -39,13 +39,20 @@ use tracing_subscriber::FmtSubscriber;
 use crate::gui::app_status_bar::AppStatusBar;
 use crate::gui::asset_editor::editor::{AssetEditor, AssetUpdated};
 use crate::gui::client::ViewerSyncClient;
+use crate::gui::expr::XMacroLibrary;
 use crate::gui::file_browser::browser::FileBrowser;
 use crate::gui::file_browser::events::{FileBrowserEvent, StatusBarEvent};
 use crate::gui::models::state::FromHanabi;
+use crate::gui::scene_picker::{ScenePicker, ScenePickerEvent};
 use crate::gui::server_adapter::ViewerServerAdapter;
 use crate::gui::styling::colors::*;
 use crate::gui::styling::fonts::*;
 use crate::gui::utils::asset_source::GuiAssets;
+use crate::gui::viewer_panel::{ViewerDock, ViewerPanel, ViewerPanelEvent};
+use crate::gui::viewer_settings::{ViewerSettingsEvent, ViewerSettingsPanel};
+
+const EDITOR_COLUMN_WIDTH: f32 = 600.0;
+const EMBEDDED_VIEWER_WIDTH: f32 = 800.0;
 
 // ====================
 // Actions.
@@ -65,6 +72,12 @@ pub struct GuiCliArgs
     pub skip_start_viewer: bool,
     #[clap(long)]
     pub assets_root:       Option<PathBuf>,
+    /// Viewer executable, overriding `DRAKKAR_VFX_VIEWER` and the default search.
+    #[clap(long)]
+    pub viewer_path:       Option<PathBuf>,
+    /// Show the viewer in a panel of the editor window instead of a separate window.
+    #[clap(long, default_value = "false")]
+    pub embedded_viewer:   bool,
 }
 
 impl GuiCliArgs
@@ -95,6 +108,10 @@ pub struct GuiAppState
     pub file_browser:      Entity<FileBrowser>,
     pub asset_editor:      Entity<AssetEditor>,
     pub bottom_status_bar: Entity<AppStatusBar>,
+    pub scene_picker:      Entity<ScenePicker>,
+    pub viewer_settings:   Entity<ViewerSettingsPanel>,
+    pub viewer_panel:      Option<Entity<ViewerPanel>>,
+    pub viewer_error:      Option<SharedString>,
 }
 
 impl GuiAppState
@@ -106,21 +123,48 @@ impl GuiAppState
             PathBuf::from(manifest_dir).join("assets")
         });
 
-        let mut server_wrapper = ViewerServerAdapter::new();
+        let mut server_wrapper = ViewerServerAdapter::new().with_viewer_path(args.viewer_path);
+        let mut viewer_error = None;
+        let viewer_args = if args.embedded_viewer {
+            vec!["--embedded".to_string()]
+        } else {
+            Vec::new()
+        };
         if !args.skip_start_viewer {
-            match server_wrapper.start() {
+            match server_wrapper.start_with_args(&viewer_args) {
                 Ok(()) => {
                     debug!("Viewer server started successfully")
                 }
                 Err(e) => {
-                    error!("Failed to start viewer server: {}", e)
+                    error!("Failed to start viewer server: {}", e);
+                    viewer_error = Some(SharedString::from(e));
                 }
             }
         }
 
+        // Expression macros of the project, shared by every expression input
+        let macros_error = match XMacroLibrary::load(&assets_root) {
+            Ok(macros) => {
+                cx.set_global(macros);
+                None
+            }
+            Err(e) => {
+                error!("Failed to load expression macros: {}", e);
+                Some(e.to_string())
+            }
+        };
+
         let asset_editor = cx.new(|cx| AssetEditor::new(cx));
+        if let Some(message) = macros_error {
+            let macros_path = XMacroLibrary::path(&assets_root);
+            asset_editor.update(cx, |asset_editor, cx| {
+                asset_editor.show_macros_error(&macros_path, message, cx)
+            });
+        }
         let file_browser = cx.new(|cx| FileBrowser::new(cx, assets_root.clone()));
         let bottom_status_bar = cx.new(|_| AppStatusBar::new());
+        let scene_picker = cx.new(|cx| ScenePicker::new(cx, assets_root.clone()));
+        let viewer_settings = cx.new(|cx| ViewerSettingsPanel::new(cx, assets_root.clone()));
         let file_browser_weak_ref = file_browser.downgrade();
 
         file_browser.update(cx, move |file_browser, cx| {
@@ -136,12 +180,29 @@ impl GuiAppState
 
         cx.subscribe(&asset_editor, Self::on_asset_changed).detach();
 
+        cx.subscribe(&scene_picker, Self::on_scene_picker_event)
+            .detach();
+
+        cx.subscribe(&viewer_settings, Self::on_viewer_settings_event)
+            .detach();
+
+        let viewer_panel = args.embedded_viewer.then(|| {
+            let viewer_panel = cx.new(|cx| ViewerPanel::new(cx));
+            cx.subscribe(&viewer_panel, Self::on_viewer_panel_event)
+                .detach();
+            viewer_panel
+        });
+
         Self {
             server_wrapper,
             file_browser,
             asset_editor,
             sync_client: ViewerSyncClient::new(),
             bottom_status_bar,
+            scene_picker,
+            viewer_settings,
+            viewer_panel,
+            viewer_error,
         }
     }
 
@@ -157,10 +218,14 @@ impl GuiAppState
     )
     {
         match ev {
-            FileBrowserEvent::BufferStateSelected { path, asset_state } => {
+            FileBrowserEvent::BufferStateSelected {
+                path,
+                asset_state,
+                warnings,
+            } => {
                 this.sync_client.send_open_asset_file(path);
                 this.asset_editor.update(cx, |editor, cx| {
-                    editor.on_buffer_state_selected(asset_state.clone(), cx);
+                    editor.on_buffer_state_selected(asset_state.clone(), warnings, cx);
                 });
             }
             FileBrowserEvent::AssetChanged {
@@ -209,6 +274,64 @@ impl GuiAppState
         });
     }
 
+    fn on_scene_picker_event(
+        this: &mut Self,
+        _: Entity<ScenePicker>,
+        ev: &ScenePickerEvent,
+        _cx: &mut Context<Self>,
+    )
+    {
+        match ev {
+            ScenePickerEvent::SceneSelected(scene) => {
+                this.sync_client.send_load_scene(scene.clone());
+            }
+            ScenePickerEvent::SaveRequested(path) => {
+                this.sync_client.send_save_scene(path);
+            }
+        }
+    }
+
+    fn on_viewer_settings_event(
+        this: &mut Self,
+        _: Entity<ViewerSettingsPanel>,
+        ev: &ViewerSettingsEvent,
+        _cx: &mut Context<Self>,
+    )
+    {
+        match ev {
+            ViewerSettingsEvent::CaptureProfileRequested { path, duration } => {
+                this.sync_client.send_capture_profile(path, *duration);
+            }
+            ViewerSettingsEvent::FixedTimestepChanged {
+                spawner_seed,
+                fixed_timestep,
+            } => {
+                this.sync_client
+                    .send_set_fixed_timestep(*spawner_seed, *fixed_timestep);
+            }
+        }
+    }
+
+    fn on_viewer_panel_event(
+        this: &mut Self,
+        _: Entity<ViewerPanel>,
+        ev: &ViewerPanelEvent,
+        cx: &mut Context<Self>,
+    )
+    {
+        match ev {
+            ViewerPanelEvent::Resized { width, height } => {
+                this.sync_client.send_resize_viewport(*width, *height);
+            }
+            ViewerPanelEvent::PanZoom { pan, zoom } => {
+                this.sync_client.send_pan_zoom_camera(*pan, *zoom);
+            }
+            ViewerPanelEvent::DockChanged(_) => {
+                cx.notify();
+            }
+        }
+    }
+
     fn on_status_bar_event(
         this: &mut Self,
         _: Entity<FileBrowser>,
@@ -222,6 +345,9 @@ impl GuiAppState
                     browser.set_update_file_list(cx, "", true);
                     browser.set_update_scm_statuses(cx);
                 });
+                this.scene_picker.update(cx, |picker, cx| {
+                    picker.refresh(cx);
+                });
             }
         }
     }
@@ -240,20 +366,24 @@ impl Drop for GuiAppState
 
 impl Render for GuiAppState
 {
-    fn render(&mut self, _window: &mut Window, _: &mut Context<Self>) -> impl IntoElement
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement
     {
         self.server_wrapper.drain_all_messages();
 
-        div()
+        let editor_column = div()
+            .id("editor-column")
             .size_full()
             .flex()
             .flex_col()
-            .bg(background_primary())
             .child(
                 div()
-                    .id("top-status-bar-container")
-                    .h(px(24.0))
-                    .child(self.bottom_status_bar.clone()),
+                    .id("scene-picker-container")
+                    .child(self.scene_picker.clone()),
+            )
+            .child(
+                div()
+                    .id("viewer-settings-container")
+                    .child(self.viewer_settings.clone()),
             )
             .child(
                 div()
@@ -277,7 +407,77 @@ impl Render for GuiAppState
                     .flex_1()
                     .flex_shrink_0()
                     .child(self.asset_editor.clone()),
+            );
+
+        let body = match &self.viewer_panel {
+            Some(viewer_panel) => {
+                let dock = viewer_panel.read(cx).dock;
+                div()
+                    .id("editor-body")
+                    .size_full()
+                    .flex_1()
+                    .flex()
+                    .when(dock == ViewerDock::Bottom, |el| el.flex_col())
+                    .child(
+                        editor_column
+                            .when(dock == ViewerDock::Right, |el| {
+                                el.w(px(EDITOR_COLUMN_WIDTH)).flex_shrink_0()
+                            })
+                            .when(dock == ViewerDock::Bottom, |el| el.flex_1()),
+                    )
+                    .child(
+                        div()
+                            .id("viewer-panel-container")
+                            .flex_1()
+                            .border_color(border_separator())
+                            .when(dock == ViewerDock::Right, |el| el.border_l_1())
+                            .when(dock == ViewerDock::Bottom, |el| el.border_t_1())
+                            .child(viewer_panel.clone()),
+                    )
+            }
+            None => div()
+                .id("editor-body")
+                .size_full()
+                .flex_1()
+                .child(editor_column),
+        };
+
+        div()
+            .size_full()
+            .flex()
+            .flex_col()
+            .bg(background_primary())
+            .child(
+                div()
+                    .id("top-status-bar-container")
+                    .h(px(24.0))
+                    .child(self.bottom_status_bar.clone()),
             )
+            .when_some(self.viewer_error.clone(), |el, message| {
+                el.child(
+                    div()
+                        .id("viewer-error-banner")
+                        .px(px(12.0))
+                        .py(px(8.0))
+                        .bg(error_panel_red_bg())
+                        .border_b_1()
+                        .border_color(error_panel_red_border())
+                        .child(
+                            with_default_font(div())
+                                .text_size(px(12.0))
+                                .text_color(text_primary())
+                                .child("Failed to start the viewer"),
+                        )
+                        .child(
+                            div()
+                                .text_xs()
+                                .font_family("mono")
+                                .text_color(error_panel_text())
+                                .child(message),
+                        ),
+                )
+            })
+            .child(body)
     }
 }
 
@@ -372,6 +572,16 @@ pub fn gui_main()
                 KeyBinding::new("cmd-v", crate::gui::primitives::text_input::Paste, None),
                 KeyBinding::new("cmd-c", crate::gui::primitives::text_input::Copy, None),
                 KeyBinding::new("cmd-x", crate::gui::primitives::text_input::Cut, None),
+                KeyBinding::new(
+                    "cmd-shift-f",
+                    crate::gui::primitives::expr_input::actions::Format,
+                    Some("ExprInput"),
+                ),
+                KeyBinding::new(
+                    "ctrl-shift-f",
+                    crate::gui::primitives::expr_input::actions::Format,
+                    Some("ExprInput"),
+                ),
                 KeyBinding::new(
                     "up",
                     crate::gui::file_browser::item::actions::Up,
@@ -389,7 +599,12 @@ pub fn gui_main()
                 ),
             ]);
 
-            let window_size = size(px(600.0), px(960.0));
+            let window_width = if args.embedded_viewer {
+                EDITOR_COLUMN_WIDTH + EMBEDDED_VIEWER_WIDTH
+            } else {
+                EDITOR_COLUMN_WIDTH
+            };
+            let window_size = size(px(window_width), px(960.0));
             let window_bounds = Bounds::centered(None, window_size, cx);
             let options = WindowOptions {
                 window_bounds: Some(WindowBounds::Windowed(window_bounds)),
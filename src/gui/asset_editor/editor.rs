@@ -1,5 +1,5 @@
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 // ====================
 // Deps.
@@ -37,15 +37,17 @@ use crate::gui::inspectors::force_field::ForceFieldSourceInspector;
 use crate::gui::inspectors::init_modifier::InitModifierInspector;
 use crate::gui::inspectors::integer::IntegerInspector;
 use crate::gui::inspectors::render_modifier::RenderModifierInspector;
-use crate::gui::inspectors::spawner::{SpawnerData, SpawnerInspector};
+use crate::gui::inspectors::spawner::SpawnerInspector;
 use crate::gui::inspectors::text::TextInspector;
 use crate::gui::inspectors::time_color::{TimeColor, TimeColorInspector};
 use crate::gui::inspectors::time_vec2::{TimeVec2, TimeVec2Inspector};
 use crate::gui::inspectors::update_modifier::UpdateModifierInspector;
 use crate::gui::inspectors::{Inspector, InspectorEvent};
 use crate::gui::models::color::HdrColor;
+use crate::gui::models::key_value::PropertyTypes;
 use crate::gui::models::modifier::{XInitModifier, XRenderModifier, XUpdateModifier};
-use crate::gui::models::state::{AssetState, ToHanabi};
+use crate::gui::models::spawner::SpawnerData;
+use crate::gui::models::state::{AssetState, PreviewEnv, ToHanabi, XImportWarning};
 use crate::gui::section::BlockSection;
 use crate::gui::styling::colors::*;
 
@@ -168,6 +170,8 @@ impl AssetEditor
     /// Helper method to convert current state to EffectAsset and emit AssetChanged event
     fn emit_asset_change(&mut self, cx: &mut Context<Self>)
     {
+        let preview_env = PreviewEnv::from_state(self.state.read(cx));
+        cx.set_global(preview_env);
         match ToHanabi::effect_asset(self.state.read(cx)) {
             Ok(effect_asset) => {
                 cx.emit(AssetUpdated { effect_asset });
@@ -227,13 +231,26 @@ impl AssetEditor
     // ====================
 
     /// Load asset state from buffer (in-memory version)
-    pub fn on_buffer_state_selected(&mut self, asset_state: AssetState, cx: &mut Context<Self>)
+    pub fn on_buffer_state_selected(
+        &mut self,
+        asset_state: AssetState,
+        warnings: &[XImportWarning],
+        cx: &mut Context<Self>,
+    )
     {
         // Clear previous state
         self.clear_editor_fields(cx);
         self.initialize_editor_from_state(&asset_state, cx);
         self.editor_state = EditorState::Loaded;
 
+        // Report the parts of the asset that are kept without being editable, and the
+        // values that changed when it was loaded
+        let unsupported = asset_state.raw_summary();
+        let warnings: Vec<String> = warnings.iter().map(|w| w.to_string()).collect();
+        if !unsupported.is_empty() || !warnings.is_empty() {
+            self.error_panel = Some(cx.new(|_| ErrorPanel::import_report(unsupported, warnings)));
+        }
+
         self.state.update(cx, |state, _cx| {
             *state = asset_state;
         });
@@ -248,6 +265,15 @@ impl AssetEditor
         self.error_panel = Some(cx.new(|_| ErrorPanel::new(file_path, message.clone())));
     }
 
+    /// Show an error panel for a macro library file that failed to load
+    pub fn show_macros_error(&mut self, file_path: &Path, message: String, cx: &mut Context<Self>)
+    {
+        self.editor_state = EditorState::Error;
+        self.error_panel = Some(cx.new(|_| {
+            ErrorPanel::new(file_path, message).with_title("Failed to load expression macros")
+        }));
+    }
+
     /// Clear all editor fields
     fn clear_editor_fields(&mut self, cx: &mut Context<Self>)
     {
@@ -290,6 +316,9 @@ impl AssetEditor
     /// Initialize all editor fields from the converted AssetState
     fn initialize_editor_from_state(&mut self, state: &AssetState, cx: &mut Context<Self>)
     {
+        cx.set_global(PropertyTypes::from_entries(&state.properties));
+        cx.set_global(PreviewEnv::from_state(state));
+
         // Section 1 – General Properties
         self.name = Some(cx.new(|cx| {
             ScalarBlock::<TextInspector>::new("Name", state.name.clone(), cx).with_index(0)
@@ -639,6 +668,7 @@ impl AssetEditor
                     this.state.update(cx, |state, _cx| {
                         state.properties = v.clone();
                     });
+                    cx.set_global(PropertyTypes::from_entries(v));
                     this.emit_asset_change(cx);
                 },
             );
@@ -734,6 +764,7 @@ impl AssetEditor
                     }
 
                     parent
+                        .children(self.error_panel.clone())
                         .children(self.section_1.clone())
                         .children(self.section_2.clone())
                         .children(self.section_3.clone())
@@ -10,10 +10,14 @@ use crate::gui::styling::icons::ProductIcon;
 ///
 /// Shows error information when an asset file cannot be parsed
 /// or converted to the editor's state representation.
+/// Also reports, above the editor fields, the parts of a loaded asset that are kept
+/// without being editable and the values that changed when it was loaded.
 pub struct ErrorPanel
 {
-    file_path:     String,
+    title:         &'static str,
+    file_path:     Option<String>,
     error_message: String,
+    entries:       Vec<String>,
 }
 
 impl ErrorPanel
@@ -22,84 +26,144 @@ impl ErrorPanel
     pub fn new(file_path: impl AsRef<Path>, error_message: String) -> Self
     {
         Self {
-            file_path: file_path.as_ref().display().to_string(),
+            title: "Failed to load asset",
+            file_path: Some(file_path.as_ref().display().to_string()),
             error_message,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Create a panel reporting the parts of a loaded asset that cannot be edited, and
+    /// the values that the editor changed.
+    pub fn import_report(unsupported: Vec<String>, warnings: Vec<String>) -> Self
+    {
+        let mut summary = Vec::new();
+        if !unsupported.is_empty() {
+            summary.push(format!(
+                "{} part(s) cannot be edited, and are saved back unchanged.",
+                unsupported.len()
+            ));
+        }
+        if !warnings.is_empty() {
+            summary.push(format!(
+                "{} value(s) changed on load, and are saved as edited.",
+                warnings.len()
+            ));
+        }
+        Self {
+            title:         "Import report",
+            file_path:     None,
+            error_message: summary.join(" "),
+            entries:       unsupported.into_iter().chain(warnings).collect(),
         }
     }
 }
 
+impl ErrorPanel
+{
+    pub fn with_title(mut self, title: &'static str) -> Self
+    {
+        self.title = title;
+        self
+    }
+}
+
 impl Render for ErrorPanel
 {
     fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement
     {
-        div()
-            .id("error-panel")
-            .size_full()
+        let card = div()
             .flex()
             .flex_col()
-            .items_center()
-            .justify_start()
-            .bg(panel_asset_editor())
-            .pt_8()
-            .px_8()
+            .max_w(px(500.0))
+            .bg(background_darker())
+            .border_1()
+            .border_color(border_subtle())
+            .rounded_lg()
+            .overflow_hidden()
             .child(
+                // Header
                 div()
                     .flex()
-                    .flex_col()
-                    .max_w(px(500.0))
-                    .bg(background_darker())
-                    .border_1()
-                    .border_color(border_subtle())
-                    .rounded_lg()
-                    .overflow_hidden()
+                    .items_center()
+                    .gap_3()
+                    .px_4()
+                    .py_3()
+                    .bg(surface_elevated())
                     .child(
-                        // Header
-                        div()
-                            .flex()
-                            .items_center()
-                            .gap_3()
-                            .px_4()
-                            .py_3()
-                            .bg(surface_elevated())
-                            .child(
-                                ProductIcon::OctagonAlert
-                                    .to_svg()
-                                    .text_color(text_warning()),
-                            )
-                            .child(
-                                div()
-                                    .text_sm()
-                                    .font_weight(gpui::FontWeight::MEDIUM)
-                                    .text_color(text_primary())
-                                    .child("Failed to load asset"),
-                            ),
+                        ProductIcon::OctagonAlert
+                            .to_svg()
+                            .text_color(text_warning()),
                     )
                     .child(
-                        // File path
                         div()
-                            .px_4()
-                            .py_3()
-                            .border_b_1()
-                            .border_color(border_subtle())
-                            .child(div().text_xs().text_color(text_muted()).child("File:"))
-                            .child(
-                                div()
-                                    .text_xs()
-                                    .text_color(text_secondary())
-                                    .child(self.file_path.clone()),
-                            ),
-                    )
-                    .child(
-                        // Error message
-                        div().px_4().py_4().max_h(px(200.0)).child(
+                            .text_sm()
+                            .font_weight(gpui::FontWeight::MEDIUM)
+                            .text_color(text_primary())
+                            .child(self.title),
+                    ),
+            )
+            .when_some(self.file_path.clone(), |el, file_path| {
+                el.child(
+                    // File path
+                    div()
+                        .px_4()
+                        .py_3()
+                        .border_b_1()
+                        .border_color(border_subtle())
+                        .child(div().text_xs().text_color(text_muted()).child("File:"))
+                        .child(
                             div()
                                 .text_xs()
-                                .font_family("mono")
-                                .text_color(text_accent())
-                                .line_height(rems(1.4))
-                                .child(self.error_message.clone()),
+                                .text_color(text_secondary())
+                                .child(file_path),
                         ),
-                    ),
+                )
+            })
+            .child(
+                // Error message
+                div().px_4().py_4().max_h(px(200.0)).child(
+                    div()
+                        .text_xs()
+                        .font_family("mono")
+                        .text_color(text_accent())
+                        .line_height(rems(1.4))
+                        .child(self.error_message.clone()),
+                ),
             )
+            .when(!self.entries.is_empty(), |el| {
+                el.child(
+                    // Parts that cannot be edited
+                    div().flex().flex_col().gap_1().px_4().pb_4().children(
+                        self.entries.iter().map(|entry| {
+                            div()
+                                .text_xs()
+                                .font_family("mono")
+                                .text_color(text_secondary())
+                                .child(entry.clone())
+                        }),
+                    ),
+                )
+            });
+
+        match &self.file_path {
+            Some(_) => div()
+                .id("error-panel")
+                .size_full()
+                .flex()
+                .flex_col()
+                .items_center()
+                .justify_start()
+                .bg(panel_asset_editor())
+                .pt_8()
+                .px_8()
+                .child(card)
+                .into_any_element(),
+            None => div()
+                .id("error-panel")
+                .mb_2()
+                .child(card)
+                .into_any_element(),
+        }
     }
 }
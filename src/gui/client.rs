@@ -17,6 +17,7 @@ use tracing::{debug, error, warn};
 // Shared.
 // ====================
 use crate::shared::proto::ServerCommandMessage;
+use crate::shared::scene::ViewerScene;
 
 // Internal message types for the background thread
 enum ClientMessage
@@ -92,6 +93,41 @@ impl ViewerSyncClient
         }
     }
 
+    pub fn send_capture_profile(&self, csv_path: &Path, duration: f32)
+    {
+        let path = csv_path.to_string_lossy().to_string();
+        self.send_message(&ServerCommandMessage::CaptureProfile { path, duration });
+    }
+
+    pub fn send_set_fixed_timestep(&self, spawner_seed: Option<u32>, fixed_timestep: Option<f32>)
+    {
+        self.send_message(&ServerCommandMessage::SetFixedTimestep {
+            spawner_seed,
+            fixed_timestep,
+        });
+    }
+
+    pub fn send_load_scene(&self, scene: ViewerScene)
+    {
+        self.send_message(&ServerCommandMessage::LoadScene { scene });
+    }
+
+    pub fn send_save_scene(&self, scene_path: &Path)
+    {
+        let path = scene_path.to_string_lossy().to_string();
+        self.send_message(&ServerCommandMessage::SaveScene { path });
+    }
+
+    pub fn send_resize_viewport(&self, width: u32, height: u32)
+    {
+        self.send_message(&ServerCommandMessage::ResizeViewport { width, height });
+    }
+
+    pub fn send_pan_zoom_camera(&self, pan: [f32; 2], zoom: f32)
+    {
+        self.send_message(&ServerCommandMessage::PanZoomCamera { pan, zoom });
+    }
+
     pub fn ping(&self)
     {
         self.send_message(&ServerCommandMessage::Ping);
@@ -1,19 +1,45 @@
 pub mod tests;
 pub mod tokenizer;
-pub mod xexpr;
-pub mod xop;
+pub mod xformat;
+pub mod xmacro;
 pub mod xparser;
-pub mod xval;
+pub mod xplot;
+pub mod xsimplify;
+pub mod xtype;
+pub mod xunit;
 
 pub use tokenizer::{
+    CompletionContext,
     CompletionItem,
+    SignatureHelp,
     SyntaxHighlight,
     Token,
     TokenKind,
     Tokenizer,
+    get_completion_context,
     get_completions,
+    get_context_completions,
+    get_signature_help,
+};
+pub use xformat::XFormatOptions;
+pub use xmacro::{XMacro, XMacroError, XMacroLibrary};
+pub use xparser::{XParseDiagnostic, XParseError};
+pub use xplot::{XPlot, XPlotCurve, XPlotVariable};
+pub use xtype::XTypeError;
+pub use xunit::{DISTANCE, XBaseUnit, XQuantity, XUnit, XUnitWarning};
+
+pub use crate::shared::expr::{
+    XBinaryOp,
+    XBuiltInOp,
+    XEnv,
+    XEvalError,
+    XExpr,
+    XExprReturnType,
+    XTernaryOp,
+    XUnaryOp,
+    XValue,
+    xeval,
+    xexpr,
+    xop,
+    xval,
 };
-pub use xexpr::XExpr;
-pub use xop::{XBinaryOp, XBuiltInOp, XUnaryOp};
-pub use xparser::XParseError;
-pub use xval::{XExprReturnType, XValue};
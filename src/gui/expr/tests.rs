@@ -3,10 +3,30 @@ mod tests
 {
     use std::collections::HashMap;
 
+    use bevy_hanabi::graph::{ScalarValue, Value};
+    use bevy_hanabi::{
+        BinaryOperator,
+        BuiltInOperator,
+        Expr,
+        ExprHandle,
+        Module,
+        ScalarType,
+        UnaryOperator,
+        ValueType,
+    };
+
+    use crate::gui::expr::tokenizer::{TokenKind, Tokenizer, get_context_completions};
+    use crate::gui::expr::xeval::{XEnv, XEvalError};
     use crate::gui::expr::xexpr::XExpr;
-    use crate::gui::expr::xop::{XBinaryOp, XBuiltInOp, XUnaryOp};
+    use crate::gui::expr::xformat::XFormatOptions;
+    use crate::gui::expr::xmacro::{XMacroDef, XMacroError, XMacroLibrary};
+    use crate::gui::expr::xop::{XBinaryOp, XBuiltInOp, XTernaryOp, XUnaryOp};
     use crate::gui::expr::xparser::*;
+    use crate::gui::expr::xplot::{RANDOM_SAMPLES, XPlot, XPlotVariable};
+    use crate::gui::expr::xtype::XTypeError;
+    use crate::gui::expr::xunit::{XBaseUnit, XQuantity, XUnit, XUnitWarning};
     use crate::gui::expr::xval::{XExprReturnType, XValue};
+    use crate::gui::models::state::{FromHanabi, ToHanabi, XError};
 
     #[test]
     fn test_value_constructors()
@@ -97,6 +117,38 @@ mod tests
         );
     }
 
+    #[test]
+    fn test_negative_literals()
+    {
+        // Negative literals are wrapped, and read back as literals.
+        for value in [
+            XValue::Float(-1.5),
+            XValue::Float(-2.0),
+            XValue::Integer(-3),
+        ] {
+            let expr = XExpr::binary(XExpr::attr("age"), XBinaryOp::Sub, XExpr::lit(value));
+            let text = expr.to_string();
+            assert!(text.ends_with(')'), "{}", text);
+            assert_eq!(XExpr::parse(&text), Ok(expr.clone()));
+            assert_eq!(XExpr::parse(&expr.format()), Ok(expr));
+        }
+        assert_eq!(XExpr::parse("( -3 )"), Ok(XExpr::lit(XValue::Integer(-3))));
+
+        let negated = XExpr::unary(XUnaryOp::Neg, XExpr::lit(XValue::Float(-2.0)));
+        assert_eq!(negated.to_string(), "-(-2.0)");
+        assert_eq!(XExpr::parse("-(-2.0)"), Ok(negated));
+
+        // Other parenthesized expressions starting with a minus sign are left as they are.
+        assert_eq!(
+            XExpr::parse("(-2 + 1)"),
+            Ok(XExpr::binary(
+                XExpr::unary(XUnaryOp::Neg, XExpr::lit(XValue::Integer(2))),
+                XBinaryOp::Add,
+                XExpr::lit(XValue::Integer(1))
+            ))
+        );
+    }
+
     #[test]
     fn test_parse_builtin_operators()
     {
@@ -344,6 +396,45 @@ mod tests
         ));
     }
 
+    #[test]
+    fn test_parse_error_spans()
+    {
+        let span = |input: &str| {
+            XExpr::parse_with_diagnostics(input).unwrap_err()[0]
+                .span
+                .clone()
+        };
+
+        assert_eq!(span("unknown_func(x)"), 0..12);
+        assert_eq!(span("(1 + 2"), 0..1);
+        assert_eq!(span("1 + $"), 4..5);
+        assert_eq!(span("1.2.3"), 3..4);
+        assert_eq!(span("sin(1.0) 2.0"), 9..12);
+    }
+
+    #[test]
+    fn test_parse_error_recovery()
+    {
+        let diagnostics = XExpr::parse_with_diagnostics("min(1.0 +, 2.0 *)").unwrap_err();
+        let spans: Vec<_> = diagnostics.iter().map(|d| d.span.clone()).collect();
+        assert_eq!(spans, vec![9..10, 16..17]);
+
+        let diagnostics = XExpr::parse_with_diagnostics("vec3(1.0, $, 2.0) + foo(1)").unwrap_err();
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(
+            diagnostics[0].error,
+            XParseError::UnexpectedToken("$".to_string())
+        );
+        assert_eq!(diagnostics[0].span, 10..11);
+        assert_eq!(
+            diagnostics[1].error,
+            XParseError::UnknownFunction("foo".to_string())
+        );
+        assert_eq!(diagnostics[1].span, 20..23);
+
+        assert!(XExpr::parse_with_diagnostics("min(1.0, 2.0)").is_ok());
+    }
+
     #[test]
     fn test_all_any_functions()
     {
@@ -497,13 +588,13 @@ mod tests
         assert_eq!(
             XExpr::norm(XExpr::lit(XValue::Vec3(1.0, 2.0, 3.0)))
                 .get_result_type(&attributes, &props),
-            Some(XExprReturnType::Float)
+            Some(XExprReturnType::Vec3)
         );
 
         assert_eq!(
             XExpr::unary(XUnaryOp::All, XExpr::lit(XValue::Vec2(1.0, 0.0)))
                 .get_result_type(&attributes, &props),
-            Some(XExprReturnType::Float)
+            Some(XExprReturnType::Bool)
         );
 
         assert_eq!(
@@ -592,7 +683,7 @@ mod tests
                 XExpr::lit(XValue::Float(2.0))
             )
             .get_result_type(&attributes, &props),
-            Some(XExprReturnType::Float)
+            Some(XExprReturnType::Bool)
         );
     }
 
@@ -662,7 +753,7 @@ mod tests
             ("time", Some(XExprReturnType::Float)),
             ("rand", Some(XExprReturnType::Float)),
             ("sin(attr(age))", Some(XExprReturnType::Float)),
-            ("norm(attr(position))", Some(XExprReturnType::Float)),
+            ("norm(attr(position))", Some(XExprReturnType::Vec3)),
             (
                 "attr(velocity) + prop(wind_force)",
                 Some(XExprReturnType::Vec2),
@@ -757,4 +848,2339 @@ mod tests
             "dot(vec3(1.0, 2.0, 3.0), vec3(4.0, 5.0, 6.0))"
         );
     }
+
+    #[test]
+    fn test_hanabi_dynamic_vector_construction()
+    {
+        let mut module = Module::default();
+        let expr = XExpr::parse("vec2(sin(time), 0.0)").unwrap();
+        let handle = ToHanabi::convert_expr(&expr, &mut module).unwrap();
+        assert!(matches!(
+            module.get(handle),
+            Some(Expr::Binary {
+                op: BinaryOperator::Mul,
+                ..
+            })
+        ));
+
+        let expr = XExpr::parse("vec3(sin(time), 1.0, attr(age))").unwrap();
+        let handle = ToHanabi::convert_expr(&expr, &mut module).unwrap();
+        assert!(matches!(
+            module.get(handle),
+            Some(Expr::Binary {
+                op: BinaryOperator::Add,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_hanabi_vector_round_trip()
+    {
+        for source in [
+            "vec3(attr(size2), attr(age))",
+            "vec2(sin(time), 1.5)",
+            "vec3(1.5, attr(age), 2.0)",
+            "attr(velocity).zy",
+            "vec4(attr(velocity).xy, attr(size2))",
+            "vec2(attr(velocity).x, attr(velocity).y)",
+            "rand_vec2.yx",
+        ] {
+            assert_eq!(
+                hanabi_round_trip(source),
+                XExpr::parse(source).unwrap(),
+                "{}",
+                source
+            );
+        }
+        assert_eq!(
+            hanabi_round_trip("vec3(1.5, 2.0, attr(age))"),
+            XExpr::parse("vec3(1.5, 2.0, attr(age))")
+                .unwrap()
+                .simplify()
+        );
+
+        // Random built-in vectors are converted once, and split into their components. Each
+        // component draws the vector again, which leaves them independent as in one draw.
+        fn draws(module: &Module, handle: ExprHandle, seen: &mut Vec<ExprHandle>) -> usize
+        {
+            if seen.contains(&handle) {
+                return 0;
+            }
+            seen.push(handle);
+            match module.get(handle) {
+                Some(Expr::BuiltIn(_)) => 1,
+                Some(Expr::Unary { expr, .. }) => draws(module, *expr, seen),
+                Some(Expr::Binary { left, right, .. }) => {
+                    draws(module, *left, seen) + draws(module, *right, seen)
+                }
+                _ => 0,
+            }
+        }
+        let mut module = Module::default();
+        for source in ["vec3(rand_vec2, 1.5)", "rand_vec3.zx"] {
+            let expr = XExpr::parse(source).unwrap();
+            let handle = ToHanabi::convert_expr(&expr, &mut module).unwrap();
+            assert_eq!(draws(&module, handle, &mut Vec::new()), 1, "{}", source);
+        }
+    }
+
+    #[test]
+    fn test_hanabi_shared_values_are_bound()
+    {
+        assert_eq!(
+            hanabi_round_trip("let s = attr(age) * 2.0; s * s"),
+            XExpr::parse("let v0 = attr(age) * 2.0; v0 * v0").unwrap()
+        );
+        assert_eq!(
+            hanabi_round_trip("let s = sin(time); let t = s * 2.0; t + t * s"),
+            XExpr::parse("let v0 = sin(time); let v1 = v0 * 2.0; v1 + v1 * v0").unwrap()
+        );
+        // Plain values and operands shared by a lowering are not bound.
+        assert_eq!(
+            hanabi_round_trip("let v = attr(velocity); v.x + v.y"),
+            XExpr::parse("attr(velocity).x + attr(velocity).y").unwrap()
+        );
+        assert_eq!(
+            hanabi_round_trip("tan(sin(time))"),
+            XExpr::parse("tan(sin(time))").unwrap()
+        );
+        assert_eq!(
+            hanabi_round_trip("mix(sin(time), 2.0, attr(age))"),
+            XExpr::parse("mix(sin(time), 2.0, attr(age))").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_hanabi_random_values_are_not_shared()
+    {
+        // Hanabi draws a random value again at each reference to it, so lowerings must not
+        // refer to one twice.
+        let mut module = Module::default();
+        for source in [
+            "let r = rand(); r * r",
+            "let r = rand(); tan(r)",
+            "tan(rand())",
+            "mix(rand(), 2.0, attr(age))",
+            "uniform(0.0, 1.0) == attr(age)",
+            "rand_vec3.xx",
+            "norm(rand_vec3).xy",
+            "vec4(rand_vec3 * 2.0, 1.5)",
+        ] {
+            let expr = XExpr::parse(source).unwrap();
+            assert!(
+                matches!(
+                    ToHanabi::convert_expr(&expr, &mut module),
+                    Err(XError::SharedRandomValue(_))
+                ),
+                "{}",
+                source
+            );
+        }
+        for source in [
+            "let r = rand(); r * 2.0",
+            "let v = rand_vec3(); vec4(v, 1.5)",
+            "mix(2.0, rand(), attr(age))",
+            "clamp(rand(), 0.25, attr(age))",
+            "rand_vec3.zx",
+        ] {
+            let expr = XExpr::parse(source).unwrap();
+            assert!(
+                ToHanabi::convert_expr(&expr, &mut module).is_ok(),
+                "{}",
+                source
+            );
+        }
+
+        // Random values the graph shares read back as separate draws.
+        let mut module = Module::default();
+        let rand = module.builtin(BuiltInOperator::Rand(ValueType::Scalar(ScalarType::Float)));
+        let square = module.binary(BinaryOperator::Mul, rand, rand);
+        assert_eq!(
+            FromHanabi::xepr(module.get(square).unwrap(), &module).unwrap(),
+            XExpr::parse("rand * rand").unwrap()
+        );
+        let sin = module.unary(UnaryOperator::Sin, rand);
+        let cos = module.unary(UnaryOperator::Cos, rand);
+        let ratio = module.binary(BinaryOperator::Div, sin, cos);
+        assert_eq!(
+            FromHanabi::xepr(module.get(ratio).unwrap(), &module).unwrap(),
+            XExpr::parse("sin(rand) / cos(rand)").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_hanabi_constant_vector_folds_to_literal()
+    {
+        let mut module = Module::default();
+        let expr = XExpr::binary(XExpr::lit(1.0f32), XBinaryOp::Vec2, XExpr::lit(2));
+        let handle = ToHanabi::convert_expr(&expr, &mut module).unwrap();
+        assert!(matches!(module.get(handle), Some(Expr::Literal(_))));
+    }
+
+    fn hanabi_round_trip(source: &str) -> XExpr
+    {
+        let expr = XExpr::parse(source).unwrap();
+        let mut module = Module::default();
+        let handle = ToHanabi::convert_expr(&expr, &mut module).unwrap();
+        FromHanabi::xepr(module.get(handle).unwrap(), &module).unwrap()
+    }
+
+    #[test]
+    fn test_hanabi_round_trip_negation()
+    {
+        for source in ["-attr(velocity)", "-(attr(age) * 2.0)", "-sin(time)"] {
+            assert_eq!(
+                hanabi_round_trip(source),
+                XExpr::parse(source).unwrap(),
+                "{}",
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn test_hanabi_negated_literal_folds()
+    {
+        assert_eq!(hanabi_round_trip("-5"), XExpr::lit(XValue::Integer(-5)));
+        assert_eq!(hanabi_round_trip("-2.5"), XExpr::lit(XValue::Float(-2.5)));
+    }
+
+    #[test]
+    fn test_hanabi_round_trip_equality()
+    {
+        for source in [
+            "attr(age) == attr(lifetime)",
+            "attr(age) != 1.0",
+            "all(-attr(velocity) == attr(velocity))",
+        ] {
+            assert_eq!(
+                hanabi_round_trip(source),
+                XExpr::parse(source).unwrap(),
+                "{}",
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn test_hanabi_vector_and_integer_operands()
+    {
+        // Vector comparisons are component-wise, and must be reduced with all or any.
+        for source in [
+            "all(attr(velocity) == attr(axis_x))",
+            "any(attr(velocity) != attr(axis_x))",
+            "all(attr(position) < attr(velocity))",
+        ] {
+            assert_eq!(
+                hanabi_round_trip(source),
+                XExpr::parse(source).unwrap(),
+                "{}",
+                source
+            );
+        }
+        let mut module = Module::default();
+        let expr = XExpr::parse("attr(velocity) == attr(axis_x)").unwrap();
+        assert!(ToHanabi::convert_expr(&expr, &mut module).is_err());
+
+        // Integers are negated with an integer literal.
+        let mut props = HashMap::new();
+        props.insert("count".to_string(), XExprReturnType::Integer);
+        let expr = XExpr::parse("-prop(count)").unwrap();
+        let handle = ToHanabi::convert_expr_with_props(&expr, &mut module, &props).unwrap();
+        let Some(Expr::Binary { right, .. }) = module.get(handle) else {
+            panic!("Expected a product");
+        };
+        assert!(matches!(
+            module.get(*right),
+            Some(Expr::Literal(lit)) if matches!(lit.value, Value::Scalar(ScalarValue::Int(-1)))
+        ));
+        assert_eq!(
+            FromHanabi::xepr(module.get(handle).unwrap(), &module).unwrap(),
+            expr
+        );
+    }
+
+    #[test]
+    fn test_parse_swizzle()
+    {
+        assert_eq!(
+            XExpr::parse("velocity.x").unwrap(),
+            XExpr::swizzle(XExpr::attr("velocity"), "x")
+        );
+        assert_eq!(
+            XExpr::parse("(position + velocity).zy * 2.0").unwrap(),
+            XExpr::swizzle(
+                XExpr::binary(
+                    XExpr::attr("position"),
+                    XBinaryOp::Add,
+                    XExpr::attr("velocity")
+                ),
+                "zy"
+            )
+            .mul(XExpr::lit(2.0f32))
+        );
+        assert_eq!(
+            XExpr::parse("-velocity.x").unwrap(),
+            XExpr::unary(XUnaryOp::Neg, XExpr::swizzle(XExpr::attr("velocity"), "x"))
+        );
+    }
+
+    #[test]
+    fn test_swizzle_result_type()
+    {
+        let mut attributes = HashMap::new();
+        attributes.insert("velocity".to_string(), XExprReturnType::Vec3);
+        attributes.insert("size2".to_string(), XExprReturnType::Vec2);
+        attributes.insert("age".to_string(), XExprReturnType::Float);
+        let props = HashMap::new();
+        let result_type = |source: &str| {
+            XExpr::parse(source)
+                .unwrap()
+                .get_result_type(&attributes, &props)
+        };
+
+        assert_eq!(result_type("velocity.x"), Some(XExprReturnType::Float));
+        assert_eq!(result_type("velocity.xy"), Some(XExprReturnType::Vec2));
+        assert_eq!(result_type("size2.yxy"), Some(XExprReturnType::Vec3));
+        assert_eq!(result_type("size2.z"), Some(XExprReturnType::Error));
+        assert_eq!(result_type("age.x"), Some(XExprReturnType::Error));
+    }
+
+    #[test]
+    fn test_swizzle_display()
+    {
+        for source in [
+            "attr(velocity).x",
+            "(attr(position) + attr(velocity)).xy",
+            "(-attr(velocity)).z",
+        ] {
+            let expr = XExpr::parse(source).unwrap();
+            assert_eq!(expr.to_string(), source);
+            assert_eq!(XExpr::parse(&expr.to_string()).unwrap(), expr);
+        }
+    }
+
+    #[test]
+    fn test_hanabi_swizzle()
+    {
+        assert_eq!(
+            hanabi_round_trip("velocity.y"),
+            XExpr::parse("velocity.y").unwrap()
+        );
+
+        let mut module = Module::default();
+        let expr = XExpr::swizzle(XExpr::lit(XValue::vec3(1.0, 2.0, 3.0)), "zx");
+        let handle = ToHanabi::convert_expr(&expr, &mut module).unwrap();
+        assert_eq!(
+            FromHanabi::xepr(module.get(handle).unwrap(), &module).unwrap(),
+            XExpr::lit(XValue::vec2(3.0, 1.0))
+        );
+
+        let expr = XExpr::parse("velocity.xy").unwrap();
+        assert!(ToHanabi::convert_expr(&expr, &mut module).is_ok());
+
+        let expr = XExpr::parse("size2.z").unwrap();
+        assert!(ToHanabi::convert_expr(&expr, &mut module).is_err());
+    }
+
+    #[test]
+    fn test_parse_function_library()
+    {
+        assert_eq!(
+            XExpr::parse("sign(attr(age))").unwrap(),
+            XExpr::unary(XUnaryOp::Sign, XExpr::attr("age"))
+        );
+        assert_eq!(
+            XExpr::parse("step(attr(age), 2.0)").unwrap(),
+            XExpr::binary(XExpr::attr("age"), XBinaryOp::Step, XExpr::lit(2.0f32))
+        );
+        assert_eq!(
+            XExpr::parse("lerp(0.0, 1.0, attr(age))").unwrap(),
+            XExpr::ternary(
+                XTernaryOp::Mix,
+                XExpr::lit(0.0f32),
+                XExpr::lit(1.0f32),
+                XExpr::attr("age")
+            )
+        );
+        assert!(XExpr::parse("clamp(1.0, 2.0)").is_err());
+
+        for source in [
+            "sign(attr(age)) + length(attr(velocity))",
+            "distance(attr(age), 1.0)",
+            "clamp(attr(age), 0.0, 1.0)",
+            "mix(attr(velocity), attr(position), 0.5)",
+        ] {
+            let expr = XExpr::parse(source).unwrap();
+            assert_eq!(expr.to_string(), source);
+        }
+    }
+
+    #[test]
+    fn test_function_library_result_type()
+    {
+        let mut attributes = HashMap::new();
+        attributes.insert("velocity".to_string(), XExprReturnType::Vec3);
+        attributes.insert("age".to_string(), XExprReturnType::Float);
+        let props = HashMap::new();
+        let result_type = |source: &str| {
+            XExpr::parse(source)
+                .unwrap()
+                .get_result_type(&attributes, &props)
+        };
+
+        assert_eq!(result_type("sign(velocity)"), Some(XExprReturnType::Vec3));
+        assert_eq!(
+            result_type("length(velocity)"),
+            Some(XExprReturnType::Float)
+        );
+        assert_eq!(
+            result_type("distance(velocity, velocity)"),
+            Some(XExprReturnType::Float)
+        );
+        assert_eq!(result_type("step(0.5, age)"), Some(XExprReturnType::Float));
+        assert_eq!(
+            result_type("step(velocity, age)"),
+            Some(XExprReturnType::Error)
+        );
+        assert_eq!(
+            result_type("mix(velocity, velocity, age)"),
+            Some(XExprReturnType::Vec3)
+        );
+        assert_eq!(
+            result_type("clamp(velocity, 0.0, 1.0)"),
+            Some(XExprReturnType::Error)
+        );
+        assert_eq!(
+            result_type("clamp(age, 0.0, 1.0)"),
+            Some(XExprReturnType::Float)
+        );
+    }
+
+    #[test]
+    fn test_hanabi_function_library()
+    {
+        for source in [
+            "clamp(attr(age), 0.0, 1.0)",
+            "mix(attr(velocity), attr(position), attr(age))",
+            "tan(attr(age) * 2.0)",
+            "sign(attr(age))",
+            "sign(attr(velocity) - attr(position))",
+            "step(0.5, attr(age))",
+            "step(attr(velocity), attr(position))",
+            "1.0 + length(attr(velocity))",
+            "distance(attr(position), attr(velocity)) * 2.0",
+            "length(vec2(attr(age), 1.5))",
+        ] {
+            assert_eq!(
+                hanabi_round_trip(source),
+                XExpr::parse(source).unwrap(),
+                "{}",
+                source
+            );
+            assert_eq!(
+                XExpr::parse(source).unwrap().check_hanabi(),
+                Ok(()),
+                "{}",
+                source
+            );
+        }
+
+        // Scalar lengths and distances are absolute values, and lengths of differences
+        // read back as distances.
+        for (source, converted) in [
+            ("length(attr(age))", "abs(attr(age))"),
+            ("distance(attr(age), 1.5)", "abs(attr(age) - 1.5)"),
+            (
+                "length(attr(velocity) - attr(position))",
+                "distance(attr(velocity), attr(position))",
+            ),
+        ] {
+            assert_eq!(
+                hanabi_round_trip(source),
+                XExpr::parse(converted).unwrap(),
+                "{}",
+                source
+            );
+        }
+
+        // Functions Hanabi cannot build are not part of the language.
+        for name in [
+            "exp", "log", "sqrt", "floor", "ceil", "fract", "pow", "atan2",
+        ] {
+            assert_eq!(
+                XExpr::parse(&format!("{}(attr(age))", name)),
+                Err(XParseError::UnknownFunction(name.to_string())),
+                "{}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_random_functions()
+    {
+        assert_eq!(
+            XExpr::parse("rand_vec3()").unwrap(),
+            XExpr::builtin(XBuiltInOp::RandVec3)
+        );
+        assert_eq!(
+            XExpr::parse("rand_vec2").unwrap(),
+            XExpr::builtin(XBuiltInOp::RandVec2)
+        );
+        assert_eq!(
+            XExpr::parse("uniform(1.0, attr(lifetime))").unwrap(),
+            XExpr::binary(
+                XExpr::lit(1.0f32),
+                XBinaryOp::Uniform,
+                XExpr::attr("lifetime")
+            )
+        );
+        assert_eq!(
+            XExpr::parse("normal(2.0, 0.5)").unwrap().to_string(),
+            "normal(2.0, 0.5)"
+        );
+        assert!(XExpr::parse("rand_vec3(1.0)").is_err());
+    }
+
+    #[test]
+    fn test_random_result_type()
+    {
+        let mut attributes = HashMap::new();
+        attributes.insert("velocity".to_string(), XExprReturnType::Vec3);
+        let props = HashMap::new();
+        let result_type = |source: &str| {
+            XExpr::parse(source)
+                .unwrap()
+                .get_result_type(&attributes, &props)
+        };
+
+        assert_eq!(result_type("rand"), Some(XExprReturnType::Float));
+        assert_eq!(result_type("rand_vec2()"), Some(XExprReturnType::Vec2));
+        assert_eq!(
+            result_type("rand_vec3() * 2.0"),
+            Some(XExprReturnType::Vec3)
+        );
+        assert_eq!(
+            result_type("uniform(0.0, 1.0)"),
+            Some(XExprReturnType::Float)
+        );
+        assert_eq!(
+            result_type("uniform(velocity, 1.0)"),
+            Some(XExprReturnType::Error)
+        );
+        assert_eq!(
+            result_type("normal(velocity, 0.5)"),
+            Some(XExprReturnType::Vec3)
+        );
+    }
+
+    #[test]
+    fn test_hanabi_random_round_trip()
+    {
+        for source in [
+            "rand",
+            "rand_vec2",
+            "rand_vec3 * 2.0",
+            "uniform(0.5, attr(lifetime))",
+            "uniform(-attr(velocity), attr(velocity))",
+            "normal(1.0, 0.25)",
+            "normal(attr(velocity), 2.0)",
+        ] {
+            assert_eq!(
+                hanabi_round_trip(source),
+                XExpr::parse(source).unwrap(),
+                "{}",
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn test_hanabi_uniform_import()
+    {
+        let mut module = Module::default();
+        let low = module.lit(Value::Scalar(ScalarValue::Float(1.0)));
+        let high = module.lit(Value::Scalar(ScalarValue::Float(3.0)));
+        let handle = module.binary(BinaryOperator::UniformRand, low, high);
+        assert_eq!(
+            FromHanabi::xepr(module.get(handle).unwrap(), &module).unwrap(),
+            XExpr::binary(XExpr::lit(1.0f32), XBinaryOp::Uniform, XExpr::lit(3.0f32))
+        );
+    }
+
+    #[test]
+    fn test_parse_bool_and_vec4()
+    {
+        assert_eq!(XExpr::parse("true").unwrap(), XExpr::lit(true));
+        assert_eq!(
+            XExpr::parse("false").unwrap(),
+            XExpr::lit(XValue::Bool(false))
+        );
+        assert_eq!(
+            XValue::vec4(1.0, 2.0, 3.0, 0.5).to_string(),
+            "vec4(1.0, 2.0, 3.0, 0.5)"
+        );
+
+        let expr = XExpr::parse("vec4(1.0, attr(age), 3.0, 4.0)").unwrap();
+        assert_eq!(expr.to_string(), "vec4(1.0, attr(age), 3.0, 4.0)");
+        assert_eq!(XExpr::parse(&expr.to_string()).unwrap(), expr);
+        assert_eq!(
+            XExpr::parse("vec3(1.0, 2.0, 3.0)").unwrap().to_string(),
+            "vec3(1.0, 2.0, 3.0)"
+        );
+    }
+
+    #[test]
+    fn test_bool_and_vec4_result_type()
+    {
+        let mut attributes = HashMap::new();
+        attributes.insert("velocity".to_string(), XExprReturnType::Vec3);
+        attributes.insert("age".to_string(), XExprReturnType::Float);
+        let props = HashMap::new();
+        let result_type = |source: &str| {
+            XExpr::parse(source)
+                .unwrap()
+                .get_result_type(&attributes, &props)
+        };
+
+        assert_eq!(result_type("true"), Some(XExprReturnType::Bool));
+        assert_eq!(result_type("attr(age) > 1.0"), Some(XExprReturnType::Bool));
+        assert_eq!(
+            result_type("velocity < velocity"),
+            Some(XExprReturnType::Error)
+        );
+        assert_eq!(
+            result_type("all(velocity < velocity)"),
+            Some(XExprReturnType::Bool)
+        );
+        assert_eq!(result_type("true + 1.0"), Some(XExprReturnType::Error));
+        assert_eq!(result_type("-true"), Some(XExprReturnType::Error));
+        assert_eq!(
+            result_type("vec4(1.0, 2.0, 3.0, age)"),
+            Some(XExprReturnType::Vec4)
+        );
+        assert_eq!(
+            result_type("vec4(1.0, 2.0, 3.0, 4.0) * 2.0"),
+            Some(XExprReturnType::Vec4)
+        );
+        assert_eq!(
+            result_type("vec4(1.0, 2.0, 3.0, 4.0).wzy"),
+            Some(XExprReturnType::Vec3)
+        );
+        assert_eq!(result_type("velocity.w"), Some(XExprReturnType::Error));
+    }
+
+    #[test]
+    fn test_hanabi_bool_and_vec4()
+    {
+        assert_eq!(hanabi_round_trip("true"), XExpr::lit(true));
+        assert_eq!(
+            hanabi_round_trip("-vec4(1.0, 2.0, 3.0, 4.0)"),
+            XExpr::lit(XValue::vec4(-1.0, -2.0, -3.0, -4.0))
+        );
+        // Swizzles of literals are folded before conversion.
+        assert_eq!(
+            hanabi_round_trip("vec4(1.0, 2.0, 3.0, 4.0).w"),
+            XExpr::lit(4.0f32)
+        );
+    }
+
+    #[test]
+    fn test_check_type_errors()
+    {
+        let mut attributes = HashMap::new();
+        attributes.insert("velocity".to_string(), XExprReturnType::Vec3);
+        attributes.insert("size2".to_string(), XExprReturnType::Vec2);
+        attributes.insert("age".to_string(), XExprReturnType::Float);
+        let props = HashMap::new();
+        let check = |source: &str| {
+            XExpr::parse(source)
+                .unwrap()
+                .check_type(&attributes, &props)
+        };
+
+        assert_eq!(check("norm(velocity) * 2.0"), Ok(XExprReturnType::Vec3));
+        assert_eq!(
+            check("attr(nothing) + 1.0"),
+            Err(XTypeError::UnknownAttribute("nothing".into()))
+        );
+        assert_eq!(
+            check("prop(mass)"),
+            Err(XTypeError::UnknownProperty("mass".into()))
+        );
+
+        let err = check("sin(age) + cross(velocity, size2)").unwrap_err();
+        assert_eq!(
+            err,
+            XTypeError::Mismatch {
+                expr:     "cross(attr(velocity), attr(size2))".to_string(),
+                expected: "vec3, vec3".to_string(),
+                found:    vec![XExprReturnType::Vec3, XExprReturnType::Vec2],
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "In 'cross(attr(velocity), attr(size2))': expected vec3, vec3, found vec3, vec2"
+        );
+        assert!(matches!(
+            check("norm(age)"),
+            Err(XTypeError::Mismatch { .. })
+        ));
+
+        // Vector comparisons are only valid when reduced to a bool.
+        assert_eq!(check("all(velocity < velocity)"), Ok(XExprReturnType::Bool));
+        assert_eq!(check("any(size2 != size2)"), Ok(XExprReturnType::Bool));
+        assert!(matches!(
+            check("velocity == velocity"),
+            Err(XTypeError::Mismatch { .. })
+        ));
+        assert!(matches!(
+            check("all(abs(velocity < velocity))"),
+            Err(XTypeError::Mismatch { .. })
+        ));
+        assert!(matches!(
+            check("all(velocity < 1.0)"),
+            Err(XTypeError::Mismatch { .. })
+        ));
+
+        // Vectors are float vectors, and only scaled by floats.
+        assert_eq!(check("2.0 * velocity"), Ok(XExprReturnType::Vec3));
+        assert!(matches!(
+            check("2 * velocity"),
+            Err(XTypeError::Mismatch { .. })
+        ));
+        assert!(matches!(
+            check("size2 / 2"),
+            Err(XTypeError::Mismatch { .. })
+        ));
+
+        // Trigonometry is float only.
+        assert_eq!(check("sin(age) + cos(1.0)"), Ok(XExprReturnType::Float));
+        assert!(matches!(check("sin(1)"), Err(XTypeError::Mismatch { .. })));
+        assert!(matches!(check("tan(2)"), Err(XTypeError::Mismatch { .. })));
+        assert_eq!(check("-abs(2)"), Ok(XExprReturnType::Integer));
+    }
+
+    #[test]
+    fn test_vector_constructor_shapes()
+    {
+        let mut attributes = HashMap::new();
+        attributes.insert("velocity".to_string(), XExprReturnType::Vec3);
+        attributes.insert("size2".to_string(), XExprReturnType::Vec2);
+        attributes.insert("age".to_string(), XExprReturnType::Float);
+        let props = HashMap::new();
+        let result_type = |source: &str| {
+            XExpr::parse(source)
+                .unwrap()
+                .get_result_type(&attributes, &props)
+        };
+
+        assert_eq!(result_type("vec3(size2, age)"), Some(XExprReturnType::Vec3));
+        assert_eq!(result_type("vec3(age, size2)"), Some(XExprReturnType::Vec3));
+        assert_eq!(
+            result_type("vec4(velocity, 1.0)"),
+            Some(XExprReturnType::Vec4)
+        );
+        assert_eq!(
+            result_type("vec4(size2, 0.0, 1.0)"),
+            Some(XExprReturnType::Vec4)
+        );
+        assert_eq!(
+            result_type("vec3(velocity, 1.0)"),
+            Some(XExprReturnType::Error)
+        );
+        assert!(XExpr::parse("vec2(1.0)").is_err());
+        assert!(XExpr::parse("vec2(1.0, 2.0, 3.0)").is_err());
+
+        let expr = XExpr::parse("vec3(attr(size2), 1.0)").unwrap();
+        assert_eq!(expr.to_string(), "vec3(attr(size2), 1.0)");
+
+        let mut module = Module::default();
+        let handle = ToHanabi::convert_expr(&expr, &mut module).unwrap();
+        assert!(matches!(
+            module.get(handle),
+            Some(Expr::Binary {
+                op: BinaryOperator::Add,
+                ..
+            })
+        ));
+        let mut props = HashMap::new();
+        props.insert("wind".to_string(), XExprReturnType::Vec2);
+        props.insert("dir".to_string(), XExprReturnType::Vec3);
+        for source in ["vec3(prop(wind), 1.0)", "prop(dir).x", "prop(dir).zy"] {
+            let expr = XExpr::parse(source).unwrap();
+            assert!(ToHanabi::convert_expr_with_props(&expr, &mut module, &props).is_ok());
+        }
+        let expr = XExpr::parse("vec3(prop(wind), 1.0)").unwrap();
+        assert!(ToHanabi::convert_expr(&expr, &mut module).is_err());
+    }
+
+    #[test]
+    fn test_let_bindings()
+    {
+        let source = "let r = rand() * 4.0; vec2(cos(time) * r, sin(time) * r)";
+        let expr = XExpr::parse(source).unwrap();
+        let XExpr::Let { name, value, body } = &expr else {
+            panic!("expected a binding, got {:?}", expr);
+        };
+        assert_eq!(name, "r");
+        assert_eq!(
+            **value,
+            XExpr::builtin(XBuiltInOp::Rand).mul(XExpr::lit(4.0f32))
+        );
+        assert_eq!(
+            **body,
+            XExpr::binary(
+                XExpr::cos(XExpr::builtin(XBuiltInOp::Time)).mul(XExpr::var("r")),
+                XBinaryOp::Vec2,
+                XExpr::sin(XExpr::builtin(XBuiltInOp::Time)).mul(XExpr::var("r")),
+            )
+        );
+        assert_eq!(
+            expr.to_string(),
+            "let r = rand * 4.0; vec2(cos(time) * r, sin(time) * r)"
+        );
+
+        // Later bindings see earlier ones; unbound names are still attributes.
+        let expr = XExpr::parse("let a = 1.0; let b = a + 1.0; b * age").unwrap();
+        assert_eq!(
+            expr.to_string(),
+            "let a = 1.0; let b = a + 1.0; b * attr(age)"
+        );
+        assert_eq!(XExpr::parse(&expr.to_string()).unwrap(), expr);
+
+        // Nested bindings are parenthesized.
+        let expr = XExpr::parse("2.0 * (let t = time; t * t)").unwrap();
+        assert_eq!(expr.to_string(), "2.0 * (let t = time; t * t)");
+        assert_eq!(XExpr::parse(&expr.to_string()).unwrap(), expr);
+
+        assert!(XExpr::parse("let a = 1.0 a").is_err());
+        assert!(XExpr::parse("let a == 1.0; a").is_err());
+        assert!(XExpr::parse("let = 1.0; a").is_err());
+    }
+
+    #[test]
+    fn test_let_bindings_types()
+    {
+        let attributes = HashMap::from([("velocity".to_string(), XExprReturnType::Vec3)]);
+        let props = HashMap::new();
+
+        let expr = XExpr::parse("let v = attr(velocity); v.xy * 2.0").unwrap();
+        assert_eq!(
+            expr.check_type(&attributes, &props),
+            Ok(XExprReturnType::Vec2)
+        );
+
+        let expr = XExpr::parse("let v = attr(velocity); cross(v, v.xy)").unwrap();
+        assert!(matches!(
+            expr.check_type(&attributes, &props),
+            Err(XTypeError::Mismatch { .. })
+        ));
+
+        assert_eq!(
+            XExpr::var("r").check_type(&attributes, &props),
+            Err(XTypeError::UnknownVariable("r".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_hanabi_let_bindings_share_handles()
+    {
+        let mut module = Module::default();
+        let expr = XExpr::parse("let s = attr(age) * 2.0; s * s").unwrap();
+        let handle = ToHanabi::convert_expr(&expr, &mut module).unwrap();
+        assert!(matches!(
+            module.get(handle),
+            Some(Expr::Binary { left, right, .. }) if left == right
+        ));
+
+        let expr = XExpr::parse("let v = attr(velocity); v.x + v.y").unwrap();
+        assert!(ToHanabi::convert_expr(&expr, &mut module).is_ok());
+
+        assert!(ToHanabi::convert_expr(&XExpr::var("r"), &mut module).is_err());
+    }
+
+    fn simplified(source: &str) -> String
+    {
+        XExpr::parse(source).unwrap().simplify().to_string()
+    }
+
+    #[test]
+    fn test_simplify_constant_folding()
+    {
+        assert_eq!(simplified("2.0 * 3.0 + attr(age) * 1.0"), "6.0 + attr(age)");
+        assert_eq!(simplified("1 + 2 * 3"), "7");
+        assert_eq!(simplified("-(-attr(age))"), "attr(age)");
+        assert_eq!(simplified("attr(age) + 0.0 - 0.0"), "attr(age)");
+        assert_eq!(simplified("1.0 * attr(velocity) / 1.0"), "attr(velocity)");
+        assert_eq!(simplified("length(vec2(3.0, 4.0)) < 6.0"), "true");
+        assert_eq!(simplified("clamp(2.0, 0.0, 1.0)"), "1.0");
+        assert_eq!(
+            simplified("mix(vec2(0.0, 0.0), vec2(2.0, 4.0), 0.5)"),
+            "vec2(1.0, 2.0)"
+        );
+        assert_eq!(simplified("vec3(1.0, 2.0, 3.0).zx"), "vec2(3.0, 1.0)");
+
+        // Random values, divisions by zero and unknown values are kept.
+        assert_eq!(simplified("uniform(0.0, 1.0)"), "uniform(0.0, 1.0)");
+        assert_eq!(simplified("1.0 / 0.0"), "1.0 / 0.0");
+        assert_eq!(simplified("1 / 0"), "1 / 0");
+        assert_eq!(simplified("attr(age) * 0.0"), "attr(age) * 0.0");
+    }
+
+    #[test]
+    fn test_simplify_vector_literals()
+    {
+        assert_eq!(
+            XExpr::parse("vec3(1.0, 2.0 * 2.0, 3.0)")
+                .unwrap()
+                .simplify(),
+            XExpr::lit(XValue::vec3(1.0, 4.0, 3.0))
+        );
+        assert_eq!(
+            XExpr::parse("vec4(vec2(1.0, 2.0), 3.0, 4.0)")
+                .unwrap()
+                .simplify(),
+            XExpr::lit(XValue::vec4(1.0, 2.0, 3.0, 4.0))
+        );
+        assert_eq!(
+            simplified("vec2(sin(time), 0.0 + 1.0)"),
+            "vec2(sin(time), 1.0)"
+        );
+        assert_eq!(simplified("vec2(1, 2)"), "vec2(1, 2)");
+    }
+
+    #[test]
+    fn test_simplify_let_bindings()
+    {
+        assert_eq!(
+            simplified("let a = 2.0; let b = a * 3.0; b * attr(age)"),
+            "6.0 * attr(age)"
+        );
+        assert_eq!(simplified("let r = rand(); r * r"), "let r = rand; r * r");
+        assert_eq!(simplified("let r = rand(); attr(age)"), "attr(age)");
+        assert_eq!(
+            simplified("let a = 1.0; let a = rand(); a + 0.0"),
+            "let a = rand; a"
+        );
+    }
+
+    #[test]
+    fn test_hanabi_converts_simplified()
+    {
+        let mut module = Module::default();
+        let expr = XExpr::parse("length(vec2(3.0, 4.0)) * attr(age)").unwrap();
+        let handle = ToHanabi::convert_expr(&expr, &mut module).unwrap();
+        assert_eq!(
+            FromHanabi::xepr(module.get(handle).unwrap(), &module).unwrap(),
+            XExpr::parse("5.0 * attr(age)").unwrap()
+        );
+    }
+
+    fn eval(source: &str, env: &mut XEnv) -> Result<XValue, XEvalError>
+    {
+        XExpr::parse(source).unwrap().eval(env)
+    }
+
+    #[test]
+    fn test_eval_expressions()
+    {
+        let mut env = XEnv::new()
+            .with_time(0.5)
+            .with_attribute("velocity", (1.0, 2.0, 2.0))
+            .with_prop("radius", 3.0);
+
+        assert_eq!(
+            eval("sin(time) * 2.0", &mut env),
+            Ok(XValue::Float(0.5f32.sin() * 2.0))
+        );
+        assert_eq!(eval("prop(radius) * 2.0", &mut env), Ok(XValue::Float(6.0)));
+        assert_eq!(
+            eval("length(attr(velocity))", &mut env),
+            Ok(XValue::Float(3.0))
+        );
+        assert_eq!(
+            eval("attr(velocity).zy * 2.0", &mut env),
+            Ok(XValue::vec2(4.0, 4.0))
+        );
+        assert_eq!(eval("7 / 2", &mut env), Ok(XValue::Integer(3)));
+        assert_eq!(eval("time < 1.0", &mut env), Ok(XValue::Bool(true)));
+        assert_eq!(
+            eval("all(attr(velocity) < vec3(2.0, 3.0, 3.0))", &mut env),
+            Ok(XValue::Bool(true))
+        );
+        assert_eq!(
+            eval("any(attr(velocity) == vec3(0.0, 0.0, 2.0))", &mut env),
+            Ok(XValue::Bool(true))
+        );
+        assert_eq!(
+            eval("attr(velocity) * 2.0", &mut env),
+            Ok(XValue::vec3(2.0, 4.0, 4.0))
+        );
+        assert_eq!(eval("-(7 / 0)", &mut env), Ok(XValue::Integer(-7)));
+        assert_eq!(
+            eval("let r = prop(radius); vec2(r, -r)", &mut env),
+            Ok(XValue::vec2(3.0, -3.0))
+        );
+        assert_eq!(
+            eval("mix(vec2(0.0, 0.0), vec2(2.0, 4.0), time)", &mut env),
+            Ok(XValue::vec2(1.0, 2.0))
+        );
+
+        assert_eq!(
+            eval("attr(age)", &mut env),
+            Err(XEvalError::UnknownAttribute("age".to_string()))
+        );
+        assert_eq!(
+            eval("prop(wind)", &mut env),
+            Err(XEvalError::UnknownProperty("wind".to_string()))
+        );
+        assert!(matches!(
+            eval("cross(attr(velocity), 1.0)", &mut env),
+            Err(XEvalError::InvalidOperands(_))
+        ));
+    }
+
+    #[test]
+    fn test_eval_random_is_seeded()
+    {
+        let sample = |seed| {
+            let mut env = XEnv::new().with_seed(seed);
+            ["rand", "uniform(2.0, 4.0)", "normal(vec2(0.0, 0.0), 1.0)"]
+                .map(|source| eval(source, &mut env).unwrap())
+        };
+        assert_eq!(sample(7), sample(7));
+        assert_ne!(sample(7), sample(8));
+
+        let mut env = XEnv::new().with_seed(1);
+        for _ in 0..100 {
+            let XValue::Float(v) = eval("uniform(2.0, 4.0)", &mut env).unwrap() else {
+                panic!("expected a float");
+            };
+            assert!((2.0..4.0).contains(&v));
+
+            // Normal values are a sum of uniform values, bounded as in Hanabi.
+            let XValue::Float(v) = eval("normal(0.0, 1.0)", &mut env).unwrap() else {
+                panic!("expected a float");
+            };
+            assert!(v.abs() <= 2.0 * 3.0f32.sqrt());
+        }
+    }
+
+    #[test]
+    fn test_plot_sampling()
+    {
+        let plot = |source: &str, variable| {
+            let expr = XExpr::parse(source).unwrap();
+            XPlot::sample(&expr, variable, 0.0..2.0, 5, &XEnv::new()).unwrap()
+        };
+
+        let linear = plot("age * 2.0", XPlotVariable::Age);
+        assert_eq!(linear.xs, vec![0.0, 0.5, 1.0, 1.5, 2.0]);
+        assert_eq!(linear.curves.len(), 1);
+        assert_eq!(linear.curves[0].values, vec![0.0, 1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(linear.curves[0].min, linear.curves[0].values);
+        assert!(!linear.is_random);
+        assert_eq!(linear.y_range(), 0.0..4.0);
+
+        let vector = plot("vec3(time, 1.0, -time)", XPlotVariable::Time);
+        assert_eq!(vector.curves.len(), 3);
+        assert_eq!(vector.curves[1].values, vec![1.0; 5]);
+        assert_eq!(vector.curves[2].values[4], -2.0);
+        assert_eq!(vector.y_range(), -2.0..2.0);
+
+        let flat = plot("1.0", XPlotVariable::Age);
+        assert_eq!(flat.y_range(), 0.0..2.0);
+
+        let expr = XExpr::parse("lifetime").unwrap();
+        assert_eq!(
+            XPlot::sample(&expr, XPlotVariable::Age, 0.0..1.0, 4, &XEnv::new()),
+            Err(XEvalError::UnknownAttribute("lifetime".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_plot_random_bands()
+    {
+        let expr = XExpr::parse("uniform(0.0, 1.0) + age").unwrap();
+        assert!(expr.is_random());
+        assert!(!XExpr::parse("age * 2.0").unwrap().is_random());
+
+        let env = XEnv::new();
+        let plot = XPlot::sample(&expr, XPlotVariable::Age, 0.0..1.0, 3, &env).unwrap();
+        assert!(plot.is_random);
+        for (i, x) in plot.xs.iter().enumerate() {
+            let curve = &plot.curves[0];
+            assert!(curve.min[i] >= *x && curve.max[i] < x + 1.0);
+            assert!(curve.min[i] <= curve.values[i] && curve.values[i] <= curve.max[i]);
+            assert!(
+                curve.max[i] - curve.min[i] > 0.5,
+                "{} samples",
+                RANDOM_SAMPLES
+            );
+        }
+        assert_eq!(
+            plot,
+            XPlot::sample(&expr, XPlotVariable::Age, 0.0..1.0, 3, &env).unwrap()
+        );
+
+        // Each point draws its own samples.
+        let expr = XExpr::parse("uniform(0.0, 1.0)").unwrap();
+        let plot = XPlot::sample(&expr, XPlotVariable::Age, 0.0..1.0, 3, &env).unwrap();
+        assert_ne!(plot.curves[0].values[0], plot.curves[0].values[1]);
+    }
+
+    #[test]
+    fn test_format_round_trip()
+    {
+        let sources = [
+            "1.0 + 2.0 * 3.0",
+            "(1.0 + 2.0) * 3.0",
+            "a - (b - c)",
+            "a / (b * c) / d",
+            "-(a + b)",
+            "-(-a)",
+            "(a + b).xy",
+            "vec3(position.x, 1.5, -age).zyx",
+            "clamp(age / lifetime, 0.0, 1.0) * vec4(1.0, 0.5, 0.25, 1.0)",
+            "let t = age / lifetime; mix(vec3(1.0, 0.0, 0.0), vec3(0.0, 0.0, 1.0), t)",
+            "let age = 2.0; attr(age) * age",
+            "prop(speed) * normal(0.0, 1.0) + uniform(vec2(0.0, 0.0), rand_vec2)",
+            "sin(time * 2.0) > 0.5",
+            "length(cross(axis_x, velocity)) + dot(position, velocity)",
+        ];
+        for source in sources {
+            let expr = XExpr::parse(source).unwrap();
+            for width in [usize::MAX, 40, 20, 1] {
+                let options = XFormatOptions::default().with_max_width(width);
+                let formatted = expr.format_with(&options);
+                assert_eq!(
+                    XExpr::parse(&formatted),
+                    Ok(expr.clone()),
+                    "{} formatted as {}",
+                    source,
+                    formatted
+                );
+                // Formatting is stable.
+                assert_eq!(
+                    XExpr::parse(&formatted).unwrap().format_with(&options),
+                    formatted
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_format_deep_nesting()
+    {
+        // Every level is laid out once, however deep the negations and calls nest.
+        let mut expr = XExpr::attr("age");
+        for _ in 0..200 {
+            expr = XExpr::unary(XUnaryOp::Neg, XExpr::sin(expr.add(XExpr::lit(1.0))));
+        }
+        for width in [usize::MAX, 40] {
+            let formatted = expr.format_with(&XFormatOptions::default().with_max_width(width));
+            assert_eq!(XExpr::parse(&formatted), Ok(expr.clone()));
+        }
+    }
+
+    #[test]
+    fn test_format_layout()
+    {
+        let format = |source: &str, width: usize| {
+            let options = XFormatOptions::default()
+                .with_max_width(width)
+                .with_indent(2);
+            XExpr::parse(source).unwrap().format_with(&options)
+        };
+
+        assert_eq!(format("a+b *  2", 80), "a + b * 2");
+        assert_eq!(format("attr(age)", 80), "age");
+        assert_eq!(
+            format("clamp(age / lifetime, 0.0, 1.0)", 20),
+            "clamp(\n  age / lifetime,\n  0.0,\n  1.0\n)"
+        );
+        assert_eq!(
+            format("position + velocity * time - axis_x", 20),
+            "position\n  + velocity * time\n  - axis_x"
+        );
+        assert_eq!(
+            format("let t = age / lifetime; t * t", 24),
+            "let t = age / lifetime;\nt * t"
+        );
+        assert_eq!(
+            XExpr::parse("a  +  b")
+                .unwrap()
+                .format_with(&XFormatOptions::single_line()),
+            "a + b"
+        );
+    }
+
+    fn macro_library() -> XMacroLibrary
+    {
+        XMacroLibrary::from_ron(
+            r#"[
+                (
+                    name: "pulse",
+                    params: ["t", "freq"],
+                    body: "sin(t * freq) * 0.5 + 0.5",
+                    description: "Pulse between 0 and 1",
+                ),
+                (name: "fade", params: ["t"], body: "1.0 - pulse(t, 2.0)"),
+                (name: "twice", params: ["x"], body: "let x = x * 2.0; x + x"),
+                (name: "square", params: ["v"], body: "v * v"),
+                (name: "shift", params: ["v"], body: "let x = 1.0; v + x"),
+            ]"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_macro_expansion()
+    {
+        let macros = macro_library();
+        let expand = |source: &str| XExpr::parse_with_macros(source, &macros);
+
+        assert_eq!(
+            expand("pulse(time, 4.0) + 1.0"),
+            Ok(XExpr::parse("(sin(time * 4.0) * 0.5 + 0.5) + 1.0").unwrap())
+        );
+        assert_eq!(
+            expand("fade(age)"),
+            Ok(XExpr::parse("1.0 - (sin(age * 2.0) * 0.5 + 0.5)").unwrap())
+        );
+        // Parameters shadowed by a binding of the template are not substituted.
+        assert_eq!(
+            expand("twice(age)"),
+            Ok(XExpr::parse("let x = age * 2.0; x + x").unwrap())
+        );
+
+        assert!(expand("pulse(time)").is_err());
+        assert!(expand("pulse(time, 1.0, 2.0)").is_err());
+        assert!(matches!(
+            XExpr::parse("pulse(time, 4.0)"),
+            Err(XParseError::UnknownFunction(name)) if name == "pulse"
+        ));
+    }
+
+    #[test]
+    fn test_macro_hygiene()
+    {
+        let macros = macro_library();
+        let expand = |source: &str| XExpr::parse_with_macros(source, &macros);
+
+        // Bindings of the template do not capture the variables of the arguments.
+        assert_eq!(
+            expand("let x = age; shift(x)"),
+            Ok(XExpr::parse("let x = age; let x_1 = 1.0; x + x_1").unwrap())
+        );
+        assert_eq!(
+            expand("shift(age)"),
+            Ok(XExpr::parse("let x = 1.0; age + x").unwrap())
+        );
+
+        // Arguments used twice are evaluated once, unless they are plain values.
+        assert_eq!(
+            expand("square(age)"),
+            Ok(XExpr::parse("age * age").unwrap())
+        );
+        assert_eq!(
+            expand("square(rand)"),
+            Ok(XExpr::parse("let v = rand; v * v").unwrap())
+        );
+        assert_eq!(
+            expand("let v = 2.0; square(v + rand)"),
+            Ok(XExpr::parse("let v = 2.0; let v_1 = v + rand; v_1 * v_1").unwrap())
+        );
+        // The expansion draws one value, and squares it.
+        let mut env = XEnv::new();
+        let value = expand("square(uniform(1.0, 2.0))")
+            .unwrap()
+            .eval(&mut env.clone());
+        let drawn = XExpr::parse("uniform(1.0, 2.0)").unwrap().eval(&mut env);
+        match (value, drawn) {
+            (Ok(XValue::Float(value)), Ok(XValue::Float(drawn))) => {
+                assert!((value - drawn * drawn).abs() < 1e-5)
+            }
+            other => panic!("Unexpected values {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_macro_library_errors()
+    {
+        assert!(matches!(
+            XMacroLibrary::from_ron(r#"[(name: "a", body: "1.0"), (name: "a", body: "2.0")]"#),
+            Err(XMacroError::Duplicate { name }) if name == "a"
+        ));
+        assert!(matches!(
+            XMacroLibrary::from_ron(r#"[(name: "a", params: ["x"], body: "x +")]"#),
+            Err(XMacroError::Body { name, .. }) if name == "a"
+        ));
+        // Macros may only call the ones defined before them.
+        assert!(matches!(
+            XMacroLibrary::from_ron(r#"[(name: "a", body: "b()"), (name: "b", body: "1.0")]"#),
+            Err(XMacroError::Body { .. })
+        ));
+        assert!(matches!(
+            XMacroLibrary::from_ron("[("),
+            Err(XMacroError::Parse { .. })
+        ));
+        // Macros may not hide functions, builtins or keywords.
+        for name in ["sin", "lerp", "rand_vec2", "let", "true"] {
+            assert!(matches!(
+                XMacroLibrary::from_defs(&[XMacroDef {
+                    name:        name.to_string(),
+                    params:      Vec::new(),
+                    body:        "1.0".to_string(),
+                    description: String::new(),
+                }]),
+                Err(XMacroError::Reserved { name: reserved }) if reserved == name
+            ));
+        }
+        assert!(matches!(
+            XMacroLibrary::load(std::path::Path::new("/nonexistent")),
+            Ok(library) if library.is_empty()
+        ));
+    }
+
+    #[test]
+    fn test_macro_library_file()
+    {
+        let dir = std::env::temp_dir().join(format!("drakkar_macros_{}", std::process::id()));
+        let assets_root = dir.join("assets");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&assets_root).unwrap();
+        assert_eq!(
+            XMacroLibrary::path(&assets_root),
+            dir.join(XMacroLibrary::FILE_NAME)
+        );
+
+        // The library sits next to the assets root, not in it.
+        let content = r#"[(name: "half", params: ["x"], body: "x / 2.0")]"#;
+        std::fs::write(assets_root.join(XMacroLibrary::FILE_NAME), content).unwrap();
+        assert!(XMacroLibrary::load(&assets_root).unwrap().is_empty());
+        std::fs::write(dir.join(XMacroLibrary::FILE_NAME), content).unwrap();
+        let library = XMacroLibrary::load(&assets_root).unwrap();
+        assert!(library.get("half").is_some());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_macro_completions()
+    {
+        let macros = macro_library();
+        let completions =
+            get_context_completions("pu", 2, &HashMap::new(), &HashMap::new(), &macros);
+        let pulse = completions.iter().find(|c| c.label == "pulse").unwrap();
+        assert_eq!(pulse.insert_text, "pulse(t, freq)");
+        assert_eq!(
+            pulse.detail.as_deref(),
+            Some("Pulse between 0 and 1 - pulse(t, freq)")
+        );
+        assert!(!completions.iter().any(|c| c.label == "fade"));
+    }
+
+    #[test]
+    fn test_macro_recognition()
+    {
+        let macros = macro_library();
+        let options = XFormatOptions::single_line();
+        let format = |expr: &XExpr| expr.format_with_macros(&options, &macros);
+
+        let expr = hanabi_round_trip("sin(time * 4.0) * 0.5 + 0.5 + 1.0");
+        assert_eq!(format(&expr), "pulse(time, 4.0) + 1.0");
+        assert_eq!(XExpr::parse_with_macros(&format(&expr), &macros), Ok(expr));
+
+        // Later macros are preferred, as they may be built on earlier ones.
+        let expr = XExpr::parse("1.0 - (sin(age * 2.0) * 0.5 + 0.5)").unwrap();
+        assert_eq!(format(&expr), "fade(age)");
+
+        // A parameter used twice must stand for the same expression.
+        let expr = XExpr::parse("let x = age * 2.0; x + x").unwrap();
+        assert_eq!(format(&expr), "twice(age)");
+        let expr = XExpr::parse("let x = age * 2.0; x + age").unwrap();
+        assert_eq!(format(&expr), "let x = age * 2.0; x + age");
+
+        // Arguments may not refer to the bindings of the template.
+        let expr = XExpr::parse("let x = 1.0; age + x").unwrap();
+        assert_eq!(format(&expr), "shift(age)");
+        let expr = XExpr::parse("let x = 1.0; x + x").unwrap();
+        assert_eq!(format(&expr), "let x = 1.0; x + x");
+    }
+
+    // ====================
+    // Units.
+    // ====================
+
+    fn attribute_quantities() -> HashMap<String, XQuantity>
+    {
+        let mut attributes = HashMap::new();
+        attributes.insert("position".to_string(), XQuantity::DISTANCE);
+        attributes.insert("velocity".to_string(), XQuantity::DISTANCE.per_second());
+        attributes.insert("age".to_string(), XQuantity::TIME);
+        attributes.insert("lifetime".to_string(), XQuantity::TIME);
+        attributes.insert("alpha".to_string(), XQuantity::NONE);
+        attributes
+    }
+
+    #[test]
+    fn test_unit_quantities()
+    {
+        let world = XUnit::new(XBaseUnit::WorldUnits);
+        assert_eq!(world.to_string(), "u");
+        assert_eq!(world.per_second().to_string(), "u/s");
+        assert_eq!(world.per_second().per_second().to_string(), "u/s²");
+        assert_eq!(XUnit::rate().to_string(), "1/s");
+        assert_eq!(XUnit::new(XBaseUnit::Degrees).to_string(), "°");
+
+        assert_eq!(
+            world.per_second().per_second().quantity(),
+            XQuantity::new(1, -2, 0)
+        );
+        assert_eq!(XUnit::rate().quantity(), XQuantity::new(0, -1, 0));
+        assert_eq!(XUnit::new(XBaseUnit::Degrees).quantity(), XQuantity::ANGLE);
+        assert_eq!(
+            XQuantity::DISTANCE * XQuantity::TIME / XQuantity::TIME,
+            XQuantity::DISTANCE
+        );
+
+        assert_eq!(
+            XQuantity::DISTANCE.per_second().to_string(),
+            "distance/time"
+        );
+        assert_eq!((XQuantity::NONE / XQuantity::TIME).to_string(), "1/time");
+        assert_eq!(XQuantity::NONE.to_string(), "a plain number");
+
+        // Units of attribute quantities, as set attribute modifiers show them.
+        assert_eq!(XUnit::from_quantity(XQuantity::DISTANCE), Some(world));
+        assert_eq!(
+            XUnit::from_quantity(XQuantity::DISTANCE.per_second()),
+            Some(world.per_second())
+        );
+        assert_eq!(
+            XUnit::from_quantity(XQuantity::TIME),
+            Some(XUnit::new(XBaseUnit::Seconds))
+        );
+        assert_eq!(
+            XUnit::from_quantity(XQuantity::ANGLE),
+            Some(XUnit::new(XBaseUnit::Radians))
+        );
+        assert_eq!(
+            XUnit::from_quantity(XQuantity::NONE.per_second()),
+            Some(XUnit::rate())
+        );
+        assert_eq!(XUnit::from_quantity(XQuantity::NONE), None);
+        assert_eq!(XUnit::from_quantity(XQuantity::new(2, 0, 0)), None);
+    }
+
+    #[test]
+    fn test_unit_warnings()
+    {
+        let attributes = attribute_quantities();
+        let check = |input: &str, expected: Option<XQuantity>| {
+            XExpr::parse(input)
+                .unwrap()
+                .check_units(&attributes, expected)
+        };
+
+        // Consistent expressions.
+        assert!(check("position + velocity * delta_time", None).is_empty());
+        assert!(check("1.0 - age / lifetime", None).is_empty());
+        assert!(check("mix(0.0, 2.0, age / lifetime) + position.x", None).is_empty());
+        assert!(check("position * alpha + sin(time)", None).is_empty());
+        let speed = XQuantity::DISTANCE.per_second();
+        assert!(check("velocity * 2.0", Some(speed)).is_empty());
+        assert!(check("2.5", Some(speed)).is_empty());
+        let with_prop = XExpr::binary(XExpr::prop("scale"), XBinaryOp::Add, XExpr::attr("age"));
+        assert!(with_prop.check_units(&attributes, None).is_empty());
+
+        // Mixed time and distance.
+        let expr = XExpr::parse("age + position.x").unwrap();
+        assert_eq!(
+            expr.check_units(&attributes, None),
+            vec![XUnitWarning::Mixed {
+                expr:  expr.to_string(),
+                left:  XQuantity::TIME,
+                right: XQuantity::DISTANCE,
+            }]
+        );
+        assert!(matches!(
+            check("max(position, velocity)", None).as_slice(),
+            [XUnitWarning::Mixed { .. }]
+        ));
+        assert!(matches!(
+            check("let d = time; clamp(position.y, 0.0, d)", None).as_slice(),
+            [XUnitWarning::Mixed { .. }]
+        ));
+        // Only the innermost mix is reported.
+        assert_eq!(
+            check("(age + position.x) * 2.0 + position.y", None).len(),
+            1
+        );
+
+        // Oscillations over time are not angles, but are common enough.
+        assert!(check("sin(age * 3.0)", None).is_empty());
+        let expr = XExpr::parse("cos(position.x)").unwrap();
+        assert_eq!(
+            expr.check_units(&attributes, None),
+            vec![XUnitWarning::NotAnAngle {
+                expr:  expr.to_string(),
+                found: XQuantity::DISTANCE,
+            }]
+        );
+
+        assert_eq!(
+            check("age * 2.0", Some(speed)),
+            vec![XUnitWarning::Mismatch {
+                expected: speed,
+                found:    XQuantity::TIME,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_degree_conversion()
+    {
+        let typed = XExpr::lit(90.0f32);
+        let radians = typed.degrees_to_radians();
+        assert_eq!(radians.radians_to_degrees(), typed);
+        // Constant angles are folded on their way to Hanabi.
+        let folded = radians.simplify();
+        assert!(matches!(folded, XExpr::Lit(_)));
+        assert_eq!(folded.radians_to_degrees(), typed);
+        let XValue::Float(angle) = folded.eval(&mut XEnv::new()).unwrap() else {
+            panic!("expected a float angle");
+        };
+        assert!((angle - std::f32::consts::FRAC_PI_2).abs() < 1e-6);
+
+        let whole = XExpr::Lit(XValue::Integer(45)).degrees_to_radians();
+        assert_eq!(
+            whole.check_type(&HashMap::new(), &HashMap::new()),
+            Ok(XExprReturnType::Float)
+        );
+        assert_eq!(whole.radians_to_degrees(), XExpr::lit(45.0f32));
+
+        let age = XExpr::attr("age");
+        assert_eq!(age.degrees_to_radians().radians_to_degrees(), age);
+    }
+
+    // ====================
+    // Round-trip properties.
+    // ====================
+
+    /// Number of random expressions each round-trip property is checked on.
+    ///
+    const GENERATED_CASES: u64 = 500;
+
+    /// Expressions a generator produces.
+    ///
+    #[derive(Clone, Copy, PartialEq)]
+    enum GenProfile
+    {
+        /// Everything the parser reads.
+        Full,
+        /// What Hanabi represents: no integers, vec4 or built-ins Hanabi lacks, and no
+        /// operations on constants only, which are folded before conversion. Conversion back
+        /// gives the same tree, but for the forms in `hanabi_form`, and for forms Hanabi
+        /// cannot tell apart that are not generated: swizzles of all of a vector in order,
+        /// which read back as the vector, and bindings side by side in vector constructors,
+        /// which read back as one swizzle. Operands a lowering refers to twice draw no
+        /// random values, which Hanabi would draw again.
+        Hanabi,
+    }
+
+    /// Generator of random well-typed expressions, in the form the parser builds them.
+    ///
+    struct ExprGen
+    {
+        rng:            XEnv,
+        profile:        GenProfile,
+        vars:           Vec<(String, XExprReturnType)>,
+        allow_literals: bool,
+        allow_random:   bool,
+    }
+
+    impl ExprGen
+    {
+        fn new(seed: u64, profile: GenProfile) -> Self
+        {
+            Self {
+                rng: XEnv::new().with_seed(seed),
+                profile,
+                vars: Vec::new(),
+                allow_literals: true,
+                allow_random: true,
+            }
+        }
+
+        /// Generate with `generate`, drawing no random values for Hanabi.
+        ///
+        fn without_random<T>(&mut self, generate: impl FnOnce(&mut Self) -> T) -> T
+        {
+            let allow_random = self.allow_random;
+            self.allow_random = self.is_full();
+            let generated = generate(self);
+            self.allow_random = allow_random;
+            generated
+        }
+
+        fn pick(&mut self, count: usize) -> usize
+        {
+            ((self.rng.rand() * count as f32) as usize).min(count - 1)
+        }
+
+        fn choose<T: Clone>(&mut self, items: &[T]) -> T
+        {
+            items[self.pick(items.len())].clone()
+        }
+
+        fn chance(&mut self, probability: f32) -> bool
+        {
+            self.rng.rand() < probability
+        }
+
+        fn is_full(&self) -> bool
+        {
+            self.profile == GenProfile::Full
+        }
+
+        fn return_type(&mut self) -> XExprReturnType
+        {
+            use XExprReturnType as T;
+            self.choose(&[T::Float, T::Integer, T::Vec2, T::Vec3, T::Vec4, T::Bool])
+        }
+
+        fn vector_type(&mut self) -> XExprReturnType
+        {
+            use XExprReturnType as T;
+            self.choose(&[T::Vec2, T::Vec3, T::Vec4])
+        }
+
+        fn expr(&mut self, ty: &XExprReturnType, depth: usize) -> XExpr
+        {
+            if depth == 0 || self.chance(0.2) {
+                self.leaf(ty)
+            } else {
+                self.node(ty, depth - 1)
+            }
+        }
+
+        /// Operands of the given types. For Hanabi, the last one is not a literal when all
+        /// the others are.
+        ///
+        fn operands<const N: usize>(
+            &mut self,
+            types: [XExprReturnType; N],
+            depth: usize,
+        ) -> [XExpr; N]
+        {
+            let mut operands = Vec::new();
+            for (i, ty) in types.iter().enumerate() {
+                let is_constant = operands.iter().all(|o| matches!(o, XExpr::Lit(_)));
+                self.allow_literals = self.is_full() || i + 1 < N || !is_constant;
+                operands.push(self.expr(ty, depth));
+            }
+            operands.try_into().unwrap()
+        }
+
+        fn leaf(&mut self, ty: &XExprReturnType) -> XExpr
+        {
+            use XExprReturnType as T;
+            if let Some(name) = self.var(ty) {
+                return XExpr::var(name);
+            }
+            let mut leaves = match ty {
+                T::Float => {
+                    if self.allow_literals && self.chance(0.4) {
+                        return XExpr::lit(self.float());
+                    }
+                    vec![
+                        XExpr::attr("age"),
+                        XExpr::attr("lifetime"),
+                        XExpr::attr("alpha"),
+                        XExpr::attr("size"),
+                        XExpr::builtin(XBuiltInOp::Time),
+                        XExpr::builtin(XBuiltInOp::DeltaTime),
+                        XExpr::builtin(XBuiltInOp::Rand),
+                    ]
+                }
+                T::Vec2 => vec![XExpr::attr("size2"), XExpr::builtin(XBuiltInOp::RandVec2)],
+                T::Vec3 => vec![
+                    XExpr::attr("position"),
+                    XExpr::attr("velocity"),
+                    XExpr::attr("axis_x"),
+                    XExpr::attr("axis_y"),
+                    XExpr::attr("axis_z"),
+                    XExpr::builtin(XBuiltInOp::RandVec3),
+                ],
+                T::Integer => {
+                    if self.allow_literals && self.chance(0.5) {
+                        return XExpr::lit(self.integer_literal());
+                    }
+                    vec![XExpr::prop("count")]
+                }
+                T::Bool if self.is_full() && self.chance(0.5) => {
+                    let value = self.chance(0.5);
+                    return XExpr::lit(value);
+                }
+                T::Bool => return self.comparison(0),
+                _ => return self.vector(4, 0),
+            };
+            if !self.allow_random {
+                leaves.retain(|leaf| !leaf.is_random());
+            }
+            match ty {
+                T::Float => leaves.push(XExpr::prop("speed")),
+                T::Vec3 => leaves.push(XExpr::prop("spread")),
+                _ => {}
+            }
+            if self.is_full() {
+                match ty {
+                    T::Float => leaves.extend([
+                        XExpr::builtin(XBuiltInOp::AlphaCutoff),
+                        XExpr::builtin(XBuiltInOp::ParticleId),
+                    ]),
+                    T::Vec3 => leaves.push(XExpr::attr("color")),
+                    _ => {}
+                }
+            }
+            self.choose(&leaves)
+        }
+
+        fn float(&mut self) -> f32
+        {
+            // Hanabi literals avoid `-1.0`, as `x * -1.0` reads back as a negation.
+            if self.is_full() {
+                (self.pick(2000) as f32 - 1000.0) / 8.0
+            } else {
+                self.choose(&[-2.5, -0.5, 0.0, 0.25, 0.5, 1.0, 1.5, 2.0, 3.0, 10.0])
+            }
+        }
+
+        fn integer_literal(&mut self) -> i32
+        {
+            // As for floats, Hanabi literals avoid `-1`.
+            if self.is_full() {
+                self.pick(200) as i32 - 100
+            } else {
+                self.choose(&[-3, -2, 0, 1, 2, 5, 10])
+            }
+        }
+
+        /// Binding in scope with the given type and not shadowed, now and then.
+        ///
+        fn var(&mut self, ty: &XExprReturnType) -> Option<String>
+        {
+            let names: Vec<String> = self
+                .vars
+                .iter()
+                .enumerate()
+                .filter(|(i, (name, var_type))| {
+                    var_type == ty && !self.vars[i + 1..].iter().any(|(other, _)| other == name)
+                })
+                .map(|(_, (name, _))| name.clone())
+                .collect();
+            if names.is_empty() || !self.chance(0.5) {
+                return None;
+            }
+            Some(self.choose(&names))
+        }
+
+        fn node(&mut self, ty: &XExprReturnType, depth: usize) -> XExpr
+        {
+            if self.chance(0.1) {
+                return self.binding(ty, depth);
+            }
+            if *ty == XExprReturnType::Bool {
+                return self.comparison(depth);
+            }
+            if *ty == XExprReturnType::Integer {
+                return self.integer(depth);
+            }
+            match self.pick(6) {
+                0 => self.unary(ty, depth),
+                1 | 2 => self.arithmetic(ty, depth),
+                3 => self.function(ty, depth),
+                4 => self.ternary(ty, depth),
+                _ => self.swizzle(ty, depth),
+            }
+        }
+
+        fn binding(&mut self, ty: &XExprReturnType, depth: usize) -> XExpr
+        {
+            use XExprReturnType as T;
+            let value_type = self.choose(&[T::Float, T::Vec2, T::Vec3]);
+            // Constant bindings are inlined by the simplifier, which would fold them into
+            // literals Hanabi reads back as other expressions, e.g. unit vectors as swizzles.
+            let allow_literals = self.allow_literals;
+            self.allow_literals = self.is_full();
+            let value = self.without_random(|generator| generator.expr(&value_type, depth));
+            self.allow_literals = allow_literals;
+            // Names are reused now and then, so that bindings shadow each other.
+            let vars = self.vars.clone();
+            let name = if !vars.is_empty() && self.chance(0.3) {
+                self.choose(&vars).0
+            } else {
+                format!("v{}", vars.len())
+            };
+            self.vars.push((name.clone(), value_type));
+            let body = self.expr(ty, depth);
+            self.vars.pop();
+            XExpr::bind(name, value, body)
+        }
+
+        fn comparison(&mut self, depth: usize) -> XExpr
+        {
+            use XExprReturnType as T;
+            if depth > 0 && self.chance(0.2) {
+                let op = self.choose(&[XUnaryOp::All, XUnaryOp::Any]);
+                // Vector comparisons are only valid when reduced.
+                if self.chance(0.5) {
+                    let vector_type = self.vector_type();
+                    return XExpr::unary(op, self.compare(vector_type, depth - 1));
+                }
+                let [operand] = self.operands([T::Bool], depth - 1);
+                return XExpr::unary(op, operand);
+            }
+            if self.chance(0.3) {
+                return self.compare(T::Integer, depth);
+            }
+            self.compare(T::Float, depth)
+        }
+
+        fn compare(&mut self, ty: XExprReturnType, depth: usize) -> XExpr
+        {
+            let op = self.choose(&[
+                XBinaryOp::Lt,
+                XBinaryOp::Lte,
+                XBinaryOp::Ge,
+                XBinaryOp::Gte,
+                XBinaryOp::Eq,
+                XBinaryOp::Neq,
+            ]);
+            let [left, right] = if matches!(op, XBinaryOp::Eq | XBinaryOp::Neq) {
+                self.without_random(|generator| generator.operands([ty.clone(), ty], depth))
+            } else {
+                self.operands([ty.clone(), ty], depth)
+            };
+            XExpr::binary(left, op, right)
+        }
+
+        /// Integers only take part in arithmetic and comparisons.
+        ///
+        fn integer(&mut self, depth: usize) -> XExpr
+        {
+            use XBinaryOp as B;
+            use XExprReturnType as T;
+            if self.chance(0.3) {
+                let op = self.choose(&[XUnaryOp::Abs, XUnaryOp::Neg]);
+                let [operand] = self.operands([T::Integer], depth);
+                return XExpr::unary(op, operand);
+            }
+            let op = self.choose(&[B::Add, B::Sub, B::Mul, B::Div, B::Min, B::Max]);
+            let [left, right] = self.operands([T::Integer, T::Integer], depth);
+            XExpr::binary(left, op, right)
+        }
+
+        fn unary(&mut self, ty: &XExprReturnType, depth: usize) -> XExpr
+        {
+            use XUnaryOp as U;
+            let mut ops = vec![U::Abs, U::Sin, U::Cos, U::Tan, U::Neg, U::Sign];
+            if ty.is_vector() {
+                ops.push(U::Norm);
+            }
+            let op = self.choose(&ops);
+            let [operand] = if op == U::Tan {
+                self.without_random(|generator| generator.operands([ty.clone()], depth))
+            } else {
+                self.operands([ty.clone()], depth)
+            };
+            XExpr::unary(op, operand)
+        }
+
+        fn arithmetic(&mut self, ty: &XExprReturnType, depth: usize) -> XExpr
+        {
+            use XBinaryOp as B;
+            let op = self.choose(&[B::Add, B::Sub, B::Mul, B::Div, B::Min, B::Max]);
+            // Vectors may be combined with scalars.
+            let types = if ty.is_vector() && self.chance(0.3) {
+                if self.chance(0.5) {
+                    [ty.clone(), XExprReturnType::Float]
+                } else {
+                    [XExprReturnType::Float, ty.clone()]
+                }
+            } else {
+                [ty.clone(), ty.clone()]
+            };
+            let [left, right] = self.operands(types, depth);
+            XExpr::binary(left, op, right)
+        }
+
+        fn function(&mut self, ty: &XExprReturnType, depth: usize) -> XExpr
+        {
+            use XBinaryOp as B;
+            use XExprReturnType as T;
+            if *ty == T::Float && self.chance(0.3) {
+                let vector_type = self.vector_type();
+                // Lengths and distances refer to their vector twice.
+                if self.chance(0.3) {
+                    let [operand] =
+                        self.without_random(|generator| generator.operands([vector_type], depth));
+                    return XExpr::unary(XUnaryOp::Length, operand);
+                }
+                let op = self.choose(&[B::Dot, B::Distance]);
+                let types = [vector_type.clone(), vector_type];
+                let [left, right] = if op == B::Distance {
+                    self.without_random(|generator| generator.operands(types, depth))
+                } else {
+                    self.operands(types, depth)
+                };
+                return XExpr::binary(left, op, right);
+            }
+            if ty.is_vector() && self.chance(0.3) {
+                return self.vector(ty.width().unwrap_or(2), depth);
+            }
+
+            let mut ops = vec![B::Uniform, B::Normal, B::Step];
+            if *ty == T::Vec3 {
+                ops.push(B::Cross);
+            }
+            if !self.allow_random {
+                ops.retain(|op| !matches!(op, B::Uniform | B::Normal));
+            }
+            let op = self.choose(&ops);
+            // normal(mean, sd) also takes a scalar deviation for vectors.
+            let right_type = if op == B::Normal && self.chance(0.3) {
+                T::Float
+            } else {
+                ty.clone()
+            };
+            let [left, right] = self.operands([ty.clone(), right_type], depth);
+            XExpr::binary(left, op, right)
+        }
+
+        fn ternary(&mut self, ty: &XExprReturnType, depth: usize) -> XExpr
+        {
+            let op = self.choose(&[XTernaryOp::Mix, XTernaryOp::Clamp]);
+            // mix(a, b, t) also takes a scalar factor for vectors.
+            let third_type = if op == XTernaryOp::Mix && self.chance(0.3) {
+                XExprReturnType::Float
+            } else {
+                ty.clone()
+            };
+            let types = [ty.clone(), ty.clone(), third_type];
+            let [first, second, third] = if op == XTernaryOp::Mix {
+                self.without_random(|generator| generator.operands(types, depth))
+            } else {
+                self.operands(types, depth)
+            };
+            XExpr::ternary(op, first, second, third)
+        }
+
+        fn swizzle(&mut self, ty: &XExprReturnType, depth: usize) -> XExpr
+        {
+            use XExprReturnType as T;
+            let count = ty.width().unwrap_or(1);
+            let width = self.choose(&[2, 3, 4]);
+            let components: String = (0..count)
+                .map(|_| ['x', 'y', 'z', 'w'][self.pick(width)])
+                .collect();
+            let operand_type = [T::with_width(width).unwrap()];
+            let [operand] = if count > 1 {
+                self.without_random(|generator| generator.operands(operand_type, depth))
+            } else {
+                self.operands(operand_type, depth)
+            };
+            XExpr::swizzle(operand, components)
+        }
+
+        /// Vector constructor with its arguments nested as `Parser::parse_vector` does.
+        ///
+        /// Only the first two arguments may be vectors: later ones follow a constructor
+        /// of a fixed size, e.g. `vec4(a, b, v)` nests `vec3(a, b)`, which is not typed.
+        ///
+        fn vector(&mut self, size: usize, depth: usize) -> XExpr
+        {
+            let first = 1 + self.pick(size - 1);
+            let second = 1 + self.pick(size - first);
+            let mut widths = vec![first, second];
+            widths.resize(size - first - second + 2, 1);
+
+            let count = widths.len();
+            let mut args = Vec::new();
+            for (i, width) in widths.into_iter().enumerate() {
+                let is_constant = args.iter().all(|a| matches!(a, XExpr::Lit(_)));
+                self.allow_literals = self.is_full() || i + 1 < count || !is_constant;
+                let arg_type = XExprReturnType::with_width(width).unwrap();
+                // Vector arguments are split into their components.
+                if width > 1 {
+                    args.push(self.without_random(|generator| generator.expr(&arg_type, depth)));
+                } else {
+                    args.push(self.expr(&arg_type, depth));
+                }
+            }
+            let mut args = args.into_iter().enumerate();
+            let (_, mut expr) = args.next().unwrap();
+            for (i, arg) in args {
+                let op = match size + 1 + i - count {
+                    2 => XBinaryOp::Vec2,
+                    3 => XBinaryOp::Vec3,
+                    _ => XBinaryOp::Vec4,
+                };
+                expr = XExpr::binary(expr, op, arg);
+            }
+            expr
+        }
+    }
+
+    /// Generate an expression, checking that it has the type it was generated for.
+    ///
+    fn generate(seed: u64, profile: GenProfile) -> XExpr
+    {
+        let mut generator = ExprGen::new(seed, profile);
+        let return_type = generator.return_type();
+        let expr = generator.expr(&return_type, 4);
+        let check = expr.check_type(&generated_attributes(), &generated_props());
+        assert_eq!(check, Ok(return_type), "{}", expr);
+        expr
+    }
+
+    /// Attribute types of generated expressions.
+    ///
+    fn generated_attributes() -> HashMap<String, XExprReturnType>
+    {
+        [
+            ("age", XExprReturnType::Float),
+            ("lifetime", XExprReturnType::Float),
+            ("alpha", XExprReturnType::Float),
+            ("size", XExprReturnType::Float),
+            ("size2", XExprReturnType::Vec2),
+            ("position", XExprReturnType::Vec3),
+            ("velocity", XExprReturnType::Vec3),
+            ("color", XExprReturnType::Vec3),
+            ("axis_x", XExprReturnType::Vec3),
+            ("axis_y", XExprReturnType::Vec3),
+            ("axis_z", XExprReturnType::Vec3),
+        ]
+        .into_iter()
+        .map(|(name, return_type)| (name.to_string(), return_type))
+        .collect()
+    }
+
+    /// Property types of generated expressions.
+    ///
+    fn generated_props() -> HashMap<String, XExprReturnType>
+    {
+        HashMap::from([
+            ("speed".to_string(), XExprReturnType::Float),
+            ("spread".to_string(), XExprReturnType::Vec3),
+            ("count".to_string(), XExprReturnType::Integer),
+        ])
+    }
+
+    /// Check that the tokens of `text` follow each other and hold all of its characters.
+    ///
+    fn assert_tokens_cover(text: &str)
+    {
+        let tokens = Tokenizer::new(text).tokenize();
+        let mut end = 0;
+        for token in &tokens {
+            assert_eq!(token.start, end, "gap before {:?} in {:?}", token, text);
+            assert!(
+                token.end > token.start,
+                "empty token {:?} in {:?}",
+                token,
+                text
+            );
+            end = token.end;
+        }
+        assert_eq!(end, text.chars().count(), "{:?}", text);
+        let joined: String = tokens.iter().map(|token| token.text.as_str()).collect();
+        assert_eq!(joined, text);
+    }
+
+    #[test]
+    fn test_generated_display_round_trip()
+    {
+        for seed in 0..GENERATED_CASES {
+            let expr = generate(seed, GenProfile::Full);
+            let text = expr.to_string();
+            assert_eq!(
+                XExpr::parse(&text),
+                Ok(expr.clone()),
+                "seed {}: {}",
+                seed,
+                text
+            );
+
+            for width in [usize::MAX, 20] {
+                let options = XFormatOptions::default().with_max_width(width);
+                let formatted = expr.format_with(&options);
+                assert_eq!(XExpr::parse(&formatted), Ok(expr.clone()), "seed {}", seed);
+            }
+        }
+    }
+
+    /// Expression normalized for what the Hanabi graph cannot represent, so that round
+    /// trips are compared modulo these forms:
+    /// - bindings are inlined, as Hanabi shares values without naming them,
+    /// - `min(max(x, low), high)` is the lowering of `clamp(x, low, high)`, and
+    ///   `length(a - b)` the lowering of `distance(a, b)`,
+    /// - swizzles of all of a vector in order are the vector,
+    /// - vector constructors are split into components, then joined back with runs of
+    ///   components of one vector as swizzles of it, as Hanabi reads runs of components
+    ///   of a shared vector.
+    ///
+    fn hanabi_form(expr: &XExpr) -> XExpr
+    {
+        match expr {
+            XExpr::Let { name, value, body } => {
+                hanabi_form(&body.substitute(&[name.clone()], &[(**value).clone()]))
+            }
+            XExpr::Binary {
+                op: XBinaryOp::Vec2 | XBinaryOp::Vec3 | XBinaryOp::Vec4,
+                ..
+            } => {
+                let mut components = Vec::new();
+                split_components(expr, &mut components);
+                join_components(components)
+            }
+            XExpr::Binary {
+                left,
+                op: XBinaryOp::Min,
+                right: high,
+            } => match &**left {
+                XExpr::Binary {
+                    left: x,
+                    op: XBinaryOp::Max,
+                    right: low,
+                } => XExpr::ternary(
+                    XTernaryOp::Clamp,
+                    hanabi_form(x),
+                    hanabi_form(low),
+                    hanabi_form(high),
+                ),
+                _ => XExpr::binary(hanabi_form(left), XBinaryOp::Min, hanabi_form(high)),
+            },
+            XExpr::Unary {
+                op: XUnaryOp::Length,
+                expr,
+            } => match &**expr {
+                XExpr::Binary {
+                    left,
+                    op: XBinaryOp::Sub,
+                    right,
+                } => XExpr::binary(hanabi_form(left), XBinaryOp::Distance, hanabi_form(right)),
+                _ => XExpr::unary(XUnaryOp::Length, hanabi_form(expr)),
+            },
+            XExpr::Unary { op, expr } => XExpr::unary(*op, hanabi_form(expr)),
+            XExpr::Binary { left, op, right } => {
+                XExpr::binary(hanabi_form(left), *op, hanabi_form(right))
+            }
+            XExpr::Ternary {
+                op,
+                first,
+                second,
+                third,
+            } => XExpr::ternary(
+                *op,
+                hanabi_form(first),
+                hanabi_form(second),
+                hanabi_form(third),
+            ),
+            XExpr::Swizzle { expr, components } => {
+                let expr = hanabi_form(expr);
+                if *components == "xyzw"[..generated_width(&expr)] {
+                    expr
+                } else {
+                    XExpr::swizzle(expr, components.clone())
+                }
+            }
+            XExpr::Lit(_) | XExpr::Attr(_) | XExpr::Prop(_) | XExpr::BuiltIn(_) | XExpr::Var(_) => {
+                expr.clone()
+            }
+        }
+    }
+
+    /// Number of components of a generated float or vector expression without bindings,
+    /// or 0 for other expressions.
+    ///
+    fn generated_width(expr: &XExpr) -> usize
+    {
+        let check = expr.check_type(&generated_attributes(), &generated_props());
+        check.ok().and_then(|ty| ty.width()).unwrap_or(0)
+    }
+
+    /// Components of the arguments of a vector constructor in Hanabi form, one per axis.
+    ///
+    fn split_components(expr: &XExpr, components: &mut Vec<XExpr>)
+    {
+        if let XExpr::Binary {
+            left,
+            op: XBinaryOp::Vec2 | XBinaryOp::Vec3 | XBinaryOp::Vec4,
+            right,
+        } = expr
+        {
+            split_components(left, components);
+            split_components(right, components);
+            return;
+        }
+        let arg = hanabi_form(expr);
+        match &arg {
+            XExpr::Lit(value) if value.get_type().is_vector() => {
+                let values = value.float_components().unwrap_or_default();
+                components.extend(values.into_iter().map(XExpr::lit));
+            }
+            XExpr::Swizzle {
+                expr,
+                components: letters,
+            } => {
+                components.extend(letters.chars().map(|c| XExpr::swizzle(expr.clone(), c)));
+            }
+            _ if generated_width(&arg) > 1 => {
+                let letters = &"xyzw"[..generated_width(&arg)];
+                components.extend(letters.chars().map(|c| XExpr::swizzle(arg.clone(), c)));
+            }
+            _ => components.push(arg),
+        }
+    }
+
+    /// Vector constructor of the given components, nested as `Parser::parse_vector` does,
+    /// with runs of components of one vector joined into a swizzle of it.
+    ///
+    fn join_components(components: Vec<XExpr>) -> XExpr
+    {
+        let size = components.len();
+        let mut args: Vec<XExpr> = Vec::new();
+        for component in components {
+            let joined = match (args.last(), &component) {
+                (
+                    Some(XExpr::Swizzle {
+                        expr: last,
+                        components: letters,
+                    }),
+                    XExpr::Swizzle {
+                        expr,
+                        components: letter,
+                    },
+                ) if last == expr && letters.len() < 4 => Some(XExpr::swizzle(
+                    expr.clone(),
+                    format!("{}{}", letters, letter),
+                )),
+                _ => None,
+            };
+            match joined {
+                Some(joined) => *args.last_mut().unwrap() = joined,
+                None => args.push(component),
+            }
+        }
+        let args: Vec<XExpr> = args
+            .into_iter()
+            .map(|arg| match arg {
+                XExpr::Swizzle { expr, components }
+                    if components == "xyzw"[..generated_width(&expr)] =>
+                {
+                    *expr
+                }
+                arg => arg,
+            })
+            .collect();
+
+        let count = args.len();
+        let mut args = args.into_iter().enumerate();
+        let (_, mut expr) = args.next().unwrap();
+        for (i, arg) in args {
+            let op = match size + 1 + i - count {
+                2 => XBinaryOp::Vec2,
+                3 => XBinaryOp::Vec3,
+                _ => XBinaryOp::Vec4,
+            };
+            expr = XExpr::binary(expr, op, arg);
+        }
+        expr
+    }
+
+    #[test]
+    fn test_generated_hanabi_round_trip()
+    {
+        for seed in 0..GENERATED_CASES {
+            let expr = generate(seed, GenProfile::Hanabi);
+            let mut module = Module::default();
+            let handle = ToHanabi::convert_expr_with_props(&expr, &mut module, &generated_props())
+                .unwrap_or_else(|e| panic!("seed {}: {}: {:?}", seed, expr, e));
+            let converted = FromHanabi::xepr(module.get(handle).unwrap(), &module).unwrap();
+            // Conversion simplifies first, which removes double negations and identities
+            // such as `x * 1.0`. The forms Hanabi cannot represent are compared after
+            // normalization, see `hanabi_form`.
+            assert_eq!(
+                hanabi_form(&converted),
+                hanabi_form(&expr.simplify()),
+                "seed {}: {}",
+                seed,
+                expr
+            );
+        }
+    }
+
+    #[test]
+    fn test_generated_eval()
+    {
+        // Every expression that type checks can be evaluated.
+        let mut env = XEnv::new()
+            .with_seed(3)
+            .with_time(1.5)
+            .with_prop("speed", 2.0)
+            .with_prop("spread", (0.5, 1.0, 2.0))
+            .with_prop("count", 3);
+        for (name, return_type) in generated_attributes() {
+            let value = match return_type {
+                XExprReturnType::Vec2 => XValue::vec2(0.5, 2.0),
+                XExprReturnType::Vec3 => XValue::vec3(0.5, 2.0, 1.5),
+                _ => XValue::Float(0.5),
+            };
+            env.attributes.insert(name, value);
+        }
+        for seed in 0..GENERATED_CASES {
+            let expr = generate(seed, GenProfile::Full);
+            let value = expr.eval(&mut env);
+            assert!(value.is_ok(), "seed {}: {}: {:?}", seed, expr, value);
+        }
+    }
+
+    #[test]
+    fn test_generated_token_spans()
+    {
+        for seed in 0..GENERATED_CASES {
+            let expr = generate(seed, GenProfile::Full);
+            for text in [
+                expr.to_string(),
+                expr.format_with(&XFormatOptions::default()),
+            ] {
+                assert_tokens_cover(&text);
+                let tokens = Tokenizer::new(&text).tokenize();
+                assert!(
+                    !tokens.iter().any(|token| token.kind == TokenKind::Error),
+                    "{}",
+                    text
+                );
+            }
+        }
+
+        // Any input is covered, including characters that are not part of the language.
+        let alphabet: Vec<char> = "ab_1.5 ()\"!=<>+-*/,;é\n\t$".chars().collect();
+        let mut rng = XEnv::new().with_seed(7);
+        for _ in 0..GENERATED_CASES {
+            let len = (rng.rand() * 24.0) as usize;
+            let text: String = (0..len)
+                .map(|_| {
+                    let index = (rng.rand() * alphabet.len() as f32) as usize;
+                    alphabet[index.min(alphabet.len() - 1)]
+                })
+                .collect();
+            assert_tokens_cover(&text);
+        }
+    }
 }
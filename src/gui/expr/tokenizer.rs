@@ -1,4 +1,9 @@
+use std::collections::HashMap;
+
+use crate::gui::expr::xexpr::XExpr;
+use crate::gui::expr::xmacro::XMacroLibrary;
 use crate::gui::expr::xop::{XBinaryOp, XBuiltInOp, XUnaryOp};
+use crate::gui::expr::xval::XExprReturnType;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Token
@@ -16,13 +21,16 @@ pub enum TokenKind
     Integer,
     Float,
     String,
+    Boolean,
 
     Identifier,
     Attribute,
     Property,
     BuiltIn(XBuiltInOp),
+    Swizzle,
 
     // Keywords/Functions
+    Let,
     Function(FunctionKind),
 
     // Operators
@@ -34,6 +42,8 @@ pub enum TokenKind
     RightParen,
     Comma,
     Dot,
+    Assign,
+    Semicolon,
 
     // Special
     Whitespace,
@@ -50,16 +60,30 @@ pub enum FunctionKind
     Norm,
     All,
     Any,
+    Sign,
+    Length,
+    Tan,
 
     // Binary
     Dot,
     Cross,
     Min,
     Max,
+    Step,
+    Distance,
+
+    // Ternary
+    Mix,
+    Clamp,
+
+    // Random
+    Uniform,
+    Normal,
 
     // Vector
     Vec2,
     Vec3,
+    Vec4,
 
     // Special
     Attr,
@@ -79,12 +103,22 @@ impl FunctionKind
             "norm" => Some(FunctionKind::Norm),
             "all" => Some(FunctionKind::All),
             "any" => Some(FunctionKind::Any),
+            "sign" => Some(FunctionKind::Sign),
+            "length" => Some(FunctionKind::Length),
+            "tan" => Some(FunctionKind::Tan),
             "dot" => Some(FunctionKind::Dot),
             "cross" => Some(FunctionKind::Cross),
             "min" => Some(FunctionKind::Min),
             "max" => Some(FunctionKind::Max),
+            "step" => Some(FunctionKind::Step),
+            "distance" => Some(FunctionKind::Distance),
+            "mix" | "lerp" => Some(FunctionKind::Mix),
+            "clamp" => Some(FunctionKind::Clamp),
+            "uniform" => Some(FunctionKind::Uniform),
+            "normal" => Some(FunctionKind::Normal),
             "vec2" => Some(FunctionKind::Vec2),
             "vec3" => Some(FunctionKind::Vec3),
+            "vec4" => Some(FunctionKind::Vec4),
             "attr" => Some(FunctionKind::Attr),
             "prop" => Some(FunctionKind::Prop),
             _ => None,
@@ -98,11 +132,126 @@ impl FunctionKind
         use FunctionKind::*;
         match self {
             Sin | Cos | Abs | Norm | All | Any => 1,
+            Sign | Length | Tan => 1,
             Dot | Cross | Min | Max | Vec2 => 2,
-            Vec3 => 3,
+            Step | Distance | Uniform | Normal => 2,
+            Mix | Clamp | Vec3 => 3,
+            Vec4 => 4,
             Attr | Prop => 1,
         }
     }
+
+    /// Get function name as written in expressions.
+    ///
+    pub fn name(&self) -> &'static str
+    {
+        use FunctionKind::*;
+        match self {
+            Sin => "sin",
+            Cos => "cos",
+            Abs => "abs",
+            Norm => "norm",
+            All => "all",
+            Any => "any",
+            Sign => "sign",
+            Length => "length",
+            Tan => "tan",
+            Dot => "dot",
+            Cross => "cross",
+            Min => "min",
+            Max => "max",
+            Step => "step",
+            Distance => "distance",
+            Mix => "mix",
+            Clamp => "clamp",
+            Uniform => "uniform",
+            Normal => "normal",
+            Vec2 => "vec2",
+            Vec3 => "vec3",
+            Vec4 => "vec4",
+            Attr => "attr",
+            Prop => "prop",
+        }
+    }
+
+    /// Get function signature, with `vecN` standing for `vec2`, `vec3` or `vec4`.
+    ///
+    pub fn signature(&self) -> &'static str
+    {
+        use FunctionKind::*;
+        match self {
+            Sin => "sin(x: float|vecN) -> same",
+            Cos => "cos(x: float|vecN) -> same",
+            Abs => "abs(x: float|int|vecN) -> same",
+            Norm => "norm(v: vecN) -> vecN",
+            All => "all(v: vecN) -> bool",
+            Any => "any(v: vecN) -> bool",
+            Sign => "sign(x: float|vecN) -> same",
+            Length => "length(v: float|vecN) -> float",
+            Tan => "tan(x: float|vecN) -> same",
+            Dot => "dot(a: vecN, b: vecN) -> float",
+            Cross => "cross(a: vec3, b: vec3) -> vec3",
+            Min => "min(a: T, b: T) -> T",
+            Max => "max(a: T, b: T) -> T",
+            Step => "step(edge: T, x: T) -> T",
+            Distance => "distance(a: T, b: T) -> float",
+            Mix => "mix(a: T, b: T, t: T|float) -> T",
+            Clamp => "clamp(x: T, low: T, high: T) -> T",
+            Uniform => "uniform(low: T, high: T) -> T",
+            Normal => "normal(mean: T, sd: T|float) -> T",
+            Vec2 => "vec2(x: float, y: float) -> vec2",
+            Vec3 => "vec3(x: float, y: float, z: float) -> vec3",
+            Vec4 => "vec4(x: float, y: float, z: float, w: float) -> vec4",
+            Attr => "attr(name) -> attribute type",
+            Prop => "prop(name) -> property type",
+        }
+    }
+
+    /// Get result type, when it does not depend on the arguments.
+    ///
+    pub fn return_type(&self) -> Option<XExprReturnType>
+    {
+        use FunctionKind::*;
+        match self {
+            All | Any => Some(XExprReturnType::Bool),
+            Dot | Length | Distance => Some(XExprReturnType::Float),
+            Cross | Vec3 => Some(XExprReturnType::Vec3),
+            Vec2 => Some(XExprReturnType::Vec2),
+            Vec4 => Some(XExprReturnType::Vec4),
+            _ => None,
+        }
+    }
+
+    /// Whether a call can return a value of `return_type` for some arguments.
+    ///
+    pub fn can_return(&self, return_type: &XExprReturnType) -> bool
+    {
+        use FunctionKind::*;
+        if let Some(fixed) = self.return_type() {
+            return fixed == *return_type;
+        }
+        match self {
+            Attr | Prop => *return_type != XExprReturnType::Error,
+            Norm => return_type.is_vector(),
+            Abs | Min | Max => {
+                return_type.width().is_some() || *return_type == XExprReturnType::Integer
+            }
+            _ => return_type.width().is_some(),
+        }
+    }
+
+    /// Get type expected for the argument at `index`, when the signature fixes it.
+    ///
+    pub fn argument_type(&self, index: usize) -> Option<XExprReturnType>
+    {
+        use FunctionKind::*;
+        match (self, index) {
+            (Vec2 | Vec3 | Vec4, _) => Some(XExprReturnType::Float),
+            (Cross, _) => Some(XExprReturnType::Vec3),
+            (Mix, 2) | (Normal, 1) => Some(XExprReturnType::Float),
+            _ => None,
+        }
+    }
 }
 
 // ====================
@@ -150,6 +299,7 @@ impl Tokenizer
             '(' => self.add_token(TokenKind::LeftParen, start),
             ')' => self.add_token(TokenKind::RightParen, start),
             ',' => self.add_token(TokenKind::Comma, start),
+            ';' => self.add_token(TokenKind::Semicolon, start),
             '.' => {
                 if self.peek().map_or(false, |c| c.is_ascii_digit()) {
                     self.pos = start;
@@ -180,7 +330,7 @@ impl Tokenizer
                 if self.match_char('=') {
                     self.add_token(TokenKind::BinaryOp(XBinaryOp::Eq), start);
                 } else {
-                    self.add_token_with_text(TokenKind::Error, "=", start);
+                    self.add_token(TokenKind::Assign, start);
                 }
             }
             '!' => {
@@ -273,8 +423,18 @@ impl Tokenizer
         }
 
         let text: String = self.input[start..self.pos].iter().collect();
+        let after_dot = self
+            .tokens
+            .last()
+            .is_some_and(|token| token.kind == TokenKind::Dot);
 
-        let kind = if let Some(func) = FunctionKind::from_str(&text) {
+        let kind = if after_dot && XExpr::swizzle_indices(&text).is_some() {
+            TokenKind::Swizzle
+        } else if text == "true" || text == "false" {
+            TokenKind::Boolean
+        } else if text == "let" {
+            TokenKind::Let
+        } else if let Some(func) = FunctionKind::from_str(&text) {
             TokenKind::Function(func)
         } else if let Ok(builtin) = text.parse::<XBuiltInOp>() {
             TokenKind::BuiltIn(builtin)
@@ -355,6 +515,8 @@ impl std::str::FromStr for XBuiltInOp
             "time" => Ok(XBuiltInOp::Time),
             "delta_time" => Ok(XBuiltInOp::DeltaTime),
             "rand" => Ok(XBuiltInOp::Rand),
+            "rand_vec2" => Ok(XBuiltInOp::RandVec2),
+            "rand_vec3" => Ok(XBuiltInOp::RandVec3),
             "alpha_cutoff" => Ok(XBuiltInOp::AlphaCutoff),
             "particle_id" => Ok(XBuiltInOp::ParticleId),
             _ => Err(()),
@@ -377,6 +539,7 @@ pub enum SyntaxHighlight
     Identifier,
     BuiltIn,
     Punctuation,
+    Swizzle,
     Error,
 }
 
@@ -390,11 +553,13 @@ impl Token
         match &self.kind {
             Integer | Float => Some(SyntaxHighlight::Number),
             String => Some(SyntaxHighlight::String),
+            Boolean | Let => Some(SyntaxHighlight::Keyword),
             Function(_) => Some(SyntaxHighlight::Function),
             BuiltIn(_) => Some(SyntaxHighlight::BuiltIn),
-            UnaryOp(_) | BinaryOp(_) => Some(SyntaxHighlight::Operator),
+            UnaryOp(_) | BinaryOp(_) | Assign => Some(SyntaxHighlight::Operator),
             Identifier | Attribute | Property => Some(SyntaxHighlight::Identifier),
-            LeftParen | RightParen | Comma | Dot => Some(SyntaxHighlight::Punctuation),
+            LeftParen | RightParen | Comma | Dot | Semicolon => Some(SyntaxHighlight::Punctuation),
+            Swizzle => Some(SyntaxHighlight::Swizzle),
             Error => Some(SyntaxHighlight::Error),
             Whitespace => None,
         }
@@ -411,6 +576,8 @@ pub struct CompletionItem
     pub kind:        CompletionKind,
     pub detail:      Option<String>,
     pub insert_text: String,
+    /// Type of the completed value, when it does not depend on arguments.
+    pub return_type: Option<XExprReturnType>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -421,88 +588,198 @@ pub enum CompletionKind
     Attribute,
     Property,
     Keyword,
+    Swizzle,
 }
 
-/// Get completion suggestions for given prefix.
+/// Get completion suggestions for the word before the cursor position in `text`.
 ///
-pub fn get_completions(prefix: &str, _cursor_pos: usize) -> Vec<CompletionItem>
+pub fn get_completions(text: &str, cursor_pos: usize) -> Vec<CompletionItem>
 {
+    let chars: Vec<char> = text.chars().take(cursor_pos).collect();
+    let prefix: String = chars[word_start(&chars, chars.len())..].iter().collect();
     let mut completions = Vec::new();
     let functions = [
-        ("sin", "sin(1.0)", "Sine function"),
-        ("cos", "cos(1.0)", "Cosine function"),
-        ("abs", "abs(1.0)", "Absolute value"),
-        ("norm", "norm(vec2(1.0, 1.0))", "Normalize vector"),
+        (FunctionKind::Sin, "sin(1.0)", "Sine function"),
+        (FunctionKind::Cos, "cos(1.0)", "Cosine function"),
+        (FunctionKind::Tan, "tan(1.0)", "Tangent function"),
+        (FunctionKind::Abs, "abs(1.0)", "Absolute value"),
         (
-            "all",
+            FunctionKind::Sign,
+            "sign(1.0)",
+            "Sign of value (-1, 0 or 1)",
+        ),
+        (
+            FunctionKind::Norm,
+            "norm(vec2(1.0, 1.0))",
+            "Normalize vector",
+        ),
+        (
+            FunctionKind::Length,
+            "length(vec2(1.0, 1.0))",
+            "Vector length",
+        ),
+        (
+            FunctionKind::Distance,
+            "distance(vec2(0.0, 0.0), vec2(1.0, 1.0))",
+            "Distance between points",
+        ),
+        (
+            FunctionKind::All,
             "all(vec2(1.0, 1.0))",
             "Check if all components are true",
         ),
         (
-            "any",
+            FunctionKind::Any,
             "any(vec2(1.0, 1.0))",
             "Check if any component is true",
         ),
-        ("dot", "dot(vec2(1.0, 1.0), vec2(1.0, 1.0))", "Dot product"),
         (
-            "cross",
+            FunctionKind::Dot,
+            "dot(vec2(1.0, 1.0), vec2(1.0, 1.0))",
+            "Dot product",
+        ),
+        (
+            FunctionKind::Cross,
             "cross(vec3(1.0, 1.0, 1.0), vec3(1.0, 1.0, 1.0))",
             "Cross product",
         ),
-        ("min", "min(1.0, 1.0)", "Minimum value"),
-        ("max", "max(1.0, 1.0)", "Maximum value"),
-        ("vec2", "vec2(1.0, 1.0)", "2D vector constructor"),
-        ("vec3", "vec3(1.0, 1.0, 1.0)", "3D vector constructor"),
-        ("attr", "attr(\"name\")", "Access particle attribute"),
-        ("prop", "prop(\"name\")", "Access effect property"),
+        (FunctionKind::Min, "min(1.0, 1.0)", "Minimum value"),
+        (FunctionKind::Max, "max(1.0, 1.0)", "Maximum value"),
+        (
+            FunctionKind::Clamp,
+            "clamp(1.0, 0.0, 1.0)",
+            "Clamp to range",
+        ),
+        (
+            FunctionKind::Mix,
+            "mix(0.0, 1.0, 0.5)",
+            "Linear interpolation (alias lerp)",
+        ),
+        (
+            FunctionKind::Step,
+            "step(0.5, 1.0)",
+            "0 below edge, 1 otherwise",
+        ),
+        (
+            FunctionKind::Uniform,
+            "uniform(0.0, 1.0)",
+            "Uniform random value in range",
+        ),
+        (
+            FunctionKind::Normal,
+            "normal(0.0, 1.0)",
+            "Normally distributed random value",
+        ),
+        (
+            FunctionKind::Vec2,
+            "vec2(1.0, 1.0)",
+            "2D vector constructor",
+        ),
+        (
+            FunctionKind::Vec3,
+            "vec3(1.0, 1.0, 1.0)",
+            "3D vector constructor",
+        ),
+        (
+            FunctionKind::Vec4,
+            "vec4(1.0, 1.0, 1.0, 1.0)",
+            "4D vector constructor",
+        ),
+        (
+            FunctionKind::Attr,
+            "attr(\"name\")",
+            "Access particle attribute",
+        ),
+        (
+            FunctionKind::Prop,
+            "prop(\"name\")",
+            "Access effect property",
+        ),
     ];
 
-    for (name, insert, detail) in functions {
-        if name.starts_with(prefix) {
+    for (kind, insert, detail) in functions {
+        if kind.name().starts_with(&prefix) {
             completions.push(CompletionItem {
-                label:       name.to_string(),
+                label:       kind.name().to_string(),
                 kind:        CompletionKind::Function,
-                detail:      Some(detail.to_string()),
+                detail:      Some(format!("{} - {}", detail, kind.signature())),
                 insert_text: insert.to_string(),
+                return_type: kind.return_type(),
             });
         }
     }
 
     let builtins = [
-        ("time", "Current simulation time"),
-        ("delta_time", "Time since last update"),
-        ("rand", "Random value [0, 1]"),
-        ("alpha_cutoff", "Alpha mask threshold"),
-        ("particle_id", "Unique particle identifier"),
+        ("time", "Current simulation time", XExprReturnType::Float),
+        (
+            "delta_time",
+            "Time since last update",
+            XExprReturnType::Float,
+        ),
+        ("rand", "Random value [0, 1]", XExprReturnType::Float),
+        (
+            "rand_vec2",
+            "Random vec2, components in [0, 1]",
+            XExprReturnType::Vec2,
+        ),
+        (
+            "rand_vec3",
+            "Random vec3, components in [0, 1]",
+            XExprReturnType::Vec3,
+        ),
+        (
+            "alpha_cutoff",
+            "Alpha mask threshold",
+            XExprReturnType::Float,
+        ),
+        (
+            "particle_id",
+            "Unique particle identifier",
+            XExprReturnType::Float,
+        ),
     ];
 
-    for (name, detail) in builtins {
-        if name.starts_with(prefix) {
+    for (name, detail, return_type) in builtins {
+        if name.starts_with(&prefix) {
             completions.push(CompletionItem {
                 label:       name.to_string(),
                 kind:        CompletionKind::BuiltIn,
                 detail:      Some(detail.to_string()),
                 insert_text: name.to_string(),
+                return_type: Some(return_type),
+            });
+        }
+    }
+
+    for keyword in ["true", "false"] {
+        if keyword.starts_with(&prefix) {
+            completions.push(CompletionItem {
+                label:       keyword.to_string(),
+                kind:        CompletionKind::Keyword,
+                detail:      Some("Boolean literal".to_string()),
+                insert_text: keyword.to_string(),
+                return_type: Some(XExprReturnType::Bool),
             });
         }
     }
 
     let attributes = [
-        ("position", "Particle position"),
-        ("velocity", "Particle velocity"),
-        ("lifetime", "Particle lifetime"),
-        ("age", "Particle age"),
-        ("color", "Particle color"),
-        ("size", "Particle size"),
+        ("position", "Particle position", XExprReturnType::Vec3),
+        ("velocity", "Particle velocity", XExprReturnType::Vec3),
+        ("lifetime", "Particle lifetime", XExprReturnType::Float),
+        ("age", "Particle age", XExprReturnType::Float),
+        ("color", "Particle color", XExprReturnType::Vec3),
+        ("size", "Particle size", XExprReturnType::Float),
     ];
 
-    for (name, detail) in attributes {
-        if name.starts_with(prefix) {
+    for (name, detail, return_type) in attributes {
+        if name.starts_with(&prefix) {
             completions.push(CompletionItem {
                 label:       name.to_string(),
                 kind:        CompletionKind::Attribute,
                 detail:      Some(detail.to_string()),
                 insert_text: name.to_string(),
+                return_type: Some(return_type),
             });
         }
     }
@@ -510,6 +787,285 @@ pub fn get_completions(prefix: &str, _cursor_pos: usize) -> Vec<CompletionItem>
     completions
 }
 
+// ====================
+// Context-aware completion.
+// ====================
+
+/// What the cursor is placed to complete.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub enum CompletionContext
+{
+    /// A value, with the type the enclosing function argument expects, if known.
+    Expression(Option<XExprReturnType>),
+    /// Name of a property in `prop(`, after the opening quote or not.
+    PropertyName
+    {
+        quoted: bool
+    },
+    /// Name of an attribute in `attr(`, after the opening quote or not.
+    AttributeName
+    {
+        quoted: bool
+    },
+    /// Swizzle after a dot, with the number of components of the receiver, if known.
+    Swizzle(Option<usize>),
+}
+
+/// Function whose call the cursor is in, and the index of the argument being typed.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SignatureHelp
+{
+    pub function: FunctionKind,
+    pub argument: usize,
+}
+
+impl SignatureHelp
+{
+    pub fn signature(&self) -> &'static str
+    {
+        self.function.signature()
+    }
+}
+
+/// Start of the identifier that ends at `end`.
+///
+fn word_start(chars: &[char], end: usize) -> usize
+{
+    let mut start = end;
+    while start > 0 && (chars[start - 1].is_alphanumeric() || chars[start - 1] == '_') {
+        start -= 1;
+    }
+    start
+}
+
+/// Position before the whitespace that ends at `end`.
+///
+fn skip_whitespace_back(chars: &[char], end: usize) -> usize
+{
+    let mut pos = end;
+    while pos > 0 && chars[pos - 1].is_whitespace() {
+        pos -= 1;
+    }
+    pos
+}
+
+/// Find what the cursor is placed to complete. Attribute types give the width of
+/// swizzled attributes.
+///
+pub fn get_completion_context(
+    text: &str,
+    cursor_pos: usize,
+    attributes: &HashMap<String, XExprReturnType>,
+) -> CompletionContext
+{
+    let chars: Vec<char> = text.chars().take(cursor_pos).collect();
+    let start = word_start(&chars, chars.len());
+
+    let quoted = start > 0 && chars[start - 1] == '"';
+    let before = skip_whitespace_back(&chars, if quoted { start - 1 } else { start });
+    if before > 0 && chars[before - 1] == '(' {
+        let name_end = skip_whitespace_back(&chars, before - 1);
+        let name: String = chars[word_start(&chars, name_end)..name_end]
+            .iter()
+            .collect();
+        match name.as_str() {
+            "prop" => return CompletionContext::PropertyName { quoted },
+            "attr" => return CompletionContext::AttributeName { quoted },
+            _ => {}
+        }
+    }
+
+    if !quoted && before > 0 && chars[before - 1] == '.' {
+        let receiver_end = skip_whitespace_back(&chars, before - 1);
+        let receiver: String = chars[word_start(&chars, receiver_end)..receiver_end]
+            .iter()
+            .collect();
+        // A dot after digits is a decimal point, not a swizzle.
+        if !receiver.starts_with(|c: char| c.is_ascii_digit()) {
+            let width = attributes.get(&receiver).and_then(XExprReturnType::width);
+            return CompletionContext::Swizzle(width);
+        }
+    }
+
+    let expected = get_signature_help(text, cursor_pos)
+        .and_then(|help| help.function.argument_type(help.argument));
+    CompletionContext::Expression(expected)
+}
+
+/// Find the innermost function call the cursor is in, skipping grouping parentheses.
+///
+pub fn get_signature_help(text: &str, cursor_pos: usize) -> Option<SignatureHelp>
+{
+    let chars: Vec<char> = text.chars().take(cursor_pos).collect();
+    let mut depth = 0;
+    let mut argument = 0;
+
+    for pos in (0..chars.len()).rev() {
+        match chars[pos] {
+            ')' => depth += 1,
+            '(' if depth > 0 => depth -= 1,
+            '(' => {
+                let name_end = skip_whitespace_back(&chars, pos);
+                let name: String = chars[word_start(&chars, name_end)..name_end]
+                    .iter()
+                    .collect();
+                if let Some(function) = FunctionKind::from_str(&name) {
+                    return Some(SignatureHelp { function, argument });
+                }
+            }
+            ',' if depth == 0 => argument += 1,
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// Get completion suggestions for the cursor position in `text`.
+///
+/// Inside `prop(` and `attr(`, suggests the names of the given properties and attributes.
+/// After a dot, suggests swizzles. Elsewhere, suggests the values matching the word being
+/// typed, including the given properties, with values of the type expected by the
+/// enclosing function argument first.
+///
+pub fn get_context_completions(
+    text: &str,
+    cursor_pos: usize,
+    attributes: &HashMap<String, XExprReturnType>,
+    props: &HashMap<String, XExprReturnType>,
+    macros: &XMacroLibrary,
+) -> Vec<CompletionItem>
+{
+    let chars: Vec<char> = text.chars().take(cursor_pos).collect();
+    let prefix: String = chars[word_start(&chars, chars.len())..].iter().collect();
+
+    match get_completion_context(text, cursor_pos, attributes) {
+        CompletionContext::PropertyName { quoted } => {
+            name_completions(props, &prefix, quoted, CompletionKind::Property)
+        }
+        CompletionContext::AttributeName { quoted } => {
+            name_completions(attributes, &prefix, quoted, CompletionKind::Attribute)
+        }
+        CompletionContext::Swizzle(width) => swizzle_completions(&prefix, width.unwrap_or(4)),
+        CompletionContext::Expression(expected) => {
+            let mut completions = get_completions(text, cursor_pos);
+
+            let mut names: Vec<_> = attributes
+                .iter()
+                .filter(|(name, _)| name.starts_with(&prefix))
+                .filter(|(name, _)| !completions.iter().any(|c| &c.label == *name))
+                .collect();
+            names.sort_by(|a, b| a.0.cmp(b.0));
+            for (name, return_type) in names {
+                completions.push(CompletionItem {
+                    label:       name.clone(),
+                    kind:        CompletionKind::Attribute,
+                    detail:      Some(format!("Attribute ({})", return_type)),
+                    insert_text: name.clone(),
+                    return_type: Some(return_type.clone()),
+                });
+            }
+
+            let mut names: Vec<_> = props
+                .iter()
+                .filter(|(name, _)| name.starts_with(&prefix))
+                .collect();
+            names.sort_by(|a, b| a.0.cmp(b.0));
+            for (name, return_type) in names {
+                completions.push(CompletionItem {
+                    label:       name.clone(),
+                    kind:        CompletionKind::Property,
+                    detail:      Some(format!("Effect property ({})", return_type)),
+                    insert_text: format!("prop(\"{}\")", name),
+                    return_type: Some(return_type.clone()),
+                });
+            }
+
+            for definition in macros.iter().filter(|m| m.name.starts_with(&prefix)) {
+                let detail = if definition.description.is_empty() {
+                    format!("Macro - {}", definition.signature())
+                } else {
+                    format!("{} - {}", definition.description, definition.signature())
+                };
+                completions.push(CompletionItem {
+                    label:       definition.name.clone(),
+                    kind:        CompletionKind::Function,
+                    detail:      Some(detail),
+                    insert_text: definition.signature(),
+                    return_type: None,
+                });
+            }
+
+            if let Some(expected) = expected {
+                completions.sort_by_key(|c| expected_rank(c, &expected));
+            }
+            completions
+        }
+    }
+}
+
+/// Rank of a completion where a value of type `expected` is wanted: values of that type
+/// first, then functions and macros that can return it for some arguments, then the others.
+fn expected_rank(item: &CompletionItem, expected: &XExprReturnType) -> u8
+{
+    match &item.return_type {
+        Some(return_type) if return_type == expected => 0,
+        Some(_) => 2,
+        None => match FunctionKind::from_str(&item.label) {
+            Some(function) if !function.can_return(expected) => 2,
+            _ => 1,
+        },
+    }
+}
+
+fn name_completions(
+    types: &HashMap<String, XExprReturnType>,
+    prefix: &str,
+    quoted: bool,
+    kind: CompletionKind,
+) -> Vec<CompletionItem>
+{
+    let mut names: Vec<_> = types
+        .iter()
+        .filter(|(name, _)| name.starts_with(prefix))
+        .collect();
+    names.sort_by(|a, b| a.0.cmp(b.0));
+    names
+        .into_iter()
+        .map(|(name, return_type)| CompletionItem {
+            label: name.clone(),
+            kind,
+            detail: Some(return_type.to_string()),
+            insert_text: if quoted {
+                format!("{}\"", name)
+            } else {
+                format!("\"{}\"", name)
+            },
+            return_type: Some(return_type.clone()),
+        })
+        .collect()
+}
+
+fn swizzle_completions(prefix: &str, width: usize) -> Vec<CompletionItem>
+{
+    let components = &"xyzw"[..width.clamp(1, 4)];
+    let singles = components.char_indices().map(|(i, _)| &components[i..=i]);
+    let sequences = (2..=components.len()).map(|len| &components[..len]);
+    singles
+        .chain(sequences)
+        .filter(|swizzle| swizzle.starts_with(prefix))
+        .map(|swizzle| CompletionItem {
+            label:       swizzle.to_string(),
+            kind:        CompletionKind::Swizzle,
+            detail:      Some(format!("{} of {} components", swizzle.len(), width)),
+            insert_text: swizzle.to_string(),
+            return_type: XExprReturnType::with_width(swizzle.len()),
+        })
+        .collect()
+}
+
 // ====================
 // Tests.
 // ====================
@@ -575,17 +1131,142 @@ mod tests
         assert_eq!(non_whitespace[3].kind, TokenKind::RightParen);
     }
 
+    #[test]
+    fn test_tokenize_swizzle()
+    {
+        let tokens = Tokenizer::new("velocity.xy + x").tokenize();
+        let non_whitespace: Vec<_> = tokens
+            .into_iter()
+            .filter(|t| t.kind != TokenKind::Whitespace)
+            .collect();
+
+        assert_eq!(non_whitespace.len(), 5);
+        assert_eq!(non_whitespace[0].kind, TokenKind::Identifier);
+        assert_eq!(non_whitespace[1].kind, TokenKind::Dot);
+        assert_eq!(non_whitespace[2].kind, TokenKind::Swizzle);
+        assert_eq!(non_whitespace[2].text, "xy");
+        assert_eq!(non_whitespace[4].kind, TokenKind::Identifier);
+    }
+
     #[test]
     fn test_completions()
     {
         let completions = get_completions("si", 2);
-        assert_eq!(completions.len(), 2);
+        assert_eq!(completions.len(), 3);
         assert!(completions.iter().any(|c| c.label == "sin"));
+        assert!(completions.iter().any(|c| c.label == "sign"));
         assert!(completions.iter().any(|c| c.label == "size"));
 
         let completions = get_completions("vec", 3);
-        assert_eq!(completions.len(), 2);
+        assert_eq!(completions.len(), 3);
         assert!(completions.iter().any(|c| c.label == "vec2"));
         assert!(completions.iter().any(|c| c.label == "vec3"));
+        assert!(completions.iter().any(|c| c.label == "vec4"));
+
+        // Only the word before the cursor is completed
+        let completions = get_completions("abs(vec + 1.0)", 7);
+        assert_eq!(completions.len(), 3);
+        assert!(get_completions("sin(x) + si", 5).is_empty());
+    }
+
+    #[test]
+    fn test_completion_context()
+    {
+        let attributes = HashMap::from([
+            ("position".to_string(), XExprReturnType::Vec3),
+            ("size2".to_string(), XExprReturnType::Vec2),
+        ]);
+        let context = |text: &str| get_completion_context(text, text.len(), &attributes);
+
+        assert_eq!(context("si"), CompletionContext::Expression(None));
+        assert_eq!(
+            context("vec3(1.0, ag"),
+            CompletionContext::Expression(Some(XExprReturnType::Float))
+        );
+        assert_eq!(
+            context("prop("),
+            CompletionContext::PropertyName { quoted: false }
+        );
+        assert_eq!(
+            context("prop( \"spe"),
+            CompletionContext::PropertyName { quoted: true }
+        );
+        assert_eq!(
+            context("attr(\""),
+            CompletionContext::AttributeName { quoted: true }
+        );
+        assert_eq!(context("size2."), CompletionContext::Swizzle(Some(2)));
+        assert_eq!(context("(a + b).x"), CompletionContext::Swizzle(None));
+        assert_eq!(context("1."), CompletionContext::Expression(None));
+    }
+
+    #[test]
+    fn test_signature_help()
+    {
+        let help = |text: &str| get_signature_help(text, text.len());
+
+        assert_eq!(help("sin"), None);
+        assert_eq!(
+            help("clamp(age, 0.0, "),
+            Some(SignatureHelp {
+                function: FunctionKind::Clamp,
+                argument: 2,
+            })
+        );
+        assert_eq!(
+            help("mix(a, vec2(1.0, 2.0), (time"),
+            Some(SignatureHelp {
+                function: FunctionKind::Mix,
+                argument: 2,
+            })
+        );
+        assert_eq!(
+            help("step(max(a, b), "),
+            Some(SignatureHelp {
+                function: FunctionKind::Step,
+                argument: 1,
+            })
+        );
+    }
+
+    #[test]
+    fn test_context_completions()
+    {
+        let attributes = HashMap::from([("size2".to_string(), XExprReturnType::Vec2)]);
+        let props = HashMap::from([
+            ("speed".to_string(), XExprReturnType::Float),
+            ("spread".to_string(), XExprReturnType::Vec3),
+        ]);
+        let macros = XMacroLibrary::default();
+        let complete =
+            |text: &str| get_context_completions(text, text.len(), &attributes, &props, &macros);
+
+        let completions = complete("prop(");
+        let labels: Vec<_> = completions.iter().map(|c| c.label.as_str()).collect();
+        assert_eq!(labels, vec!["speed", "spread"]);
+        assert_eq!(completions[0].insert_text, "\"speed\"");
+        assert_eq!(complete("prop(\"spr")[0].insert_text, "spread\"");
+
+        let completions = complete("sp");
+        assert!(
+            completions
+                .iter()
+                .any(|c| c.insert_text == "prop(\"speed\")")
+        );
+
+        let labels: Vec<_> = complete("size2.").into_iter().map(|c| c.label).collect();
+        assert_eq!(labels, vec!["x", "y", "xy"]);
+
+        // Arguments of `cross` are vec3, so the vec3 property comes before the float one
+        let completions = complete("cross(a, sp");
+        assert_eq!(completions[0].label, "spread");
+        assert_eq!(completions[0].kind, CompletionKind::Property);
+
+        // `normal` can return the float `vec3` wants, `norm` only returns vectors
+        let labels: Vec<_> = complete("vec3(1.0, no")
+            .into_iter()
+            .map(|c| c.label)
+            .collect();
+        assert_eq!(labels, vec!["normal", "norm"]);
     }
 }
@@ -0,0 +1,472 @@
+use crate::gui::expr::xexpr::XExpr;
+use crate::gui::expr::xmacro::XMacroLibrary;
+use crate::gui::expr::xop::XUnaryOp;
+
+// ====================
+// Options.
+// ====================
+
+/// Layout of formatted expressions.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct XFormatOptions
+{
+    /// Column past which calls, operator chains and bindings are broken over several lines.
+    pub max_width: usize,
+    /// Spaces per nesting level of broken lines.
+    pub indent:    usize,
+}
+
+impl Default for XFormatOptions
+{
+    fn default() -> Self
+    {
+        Self {
+            max_width: 60,
+            indent:    4,
+        }
+    }
+}
+
+impl XFormatOptions
+{
+    /// Options that never break lines, for single line inputs.
+    ///
+    pub fn single_line() -> Self
+    {
+        Self {
+            max_width: usize::MAX,
+            indent:    0,
+        }
+    }
+
+    pub fn with_max_width(mut self, max_width: usize) -> Self
+    {
+        self.max_width = max_width;
+        self
+    }
+
+    pub fn with_indent(mut self, indent: usize) -> Self
+    {
+        self.indent = indent;
+        self
+    }
+}
+
+// ====================
+// Formatting.
+// ====================
+
+impl XExpr
+{
+    /// Pretty-print an expression with the default options.
+    ///
+    pub fn format(&self) -> String
+    {
+        self.format_with(&XFormatOptions::default())
+    }
+
+    /// Pretty-print an expression in its canonical form.
+    ///
+    /// Parts that fit in `max_width` stay on one line. Longer calls put each argument on
+    /// its own indented line, operator chains break before each operator, and bindings put
+    /// their body on the next line. The output parses back to an equal expression.
+    ///
+    pub fn format_with(&self, options: &XFormatOptions) -> String
+    {
+        self.format_with_macros(options, &XMacroLibrary::default())
+    }
+
+    /// Pretty-print an expression, writing the parts that a macro expands to as calls to
+    /// that macro.
+    ///
+    pub fn format_with_macros(&self, options: &XFormatOptions, macros: &XMacroLibrary) -> String
+    {
+        let scope = Scope {
+            bound: Vec::new(),
+            macros,
+        };
+        let mut out = String::new();
+        Doc::from_expr(self, &scope).render(options, 0, &mut out);
+        out
+    }
+}
+
+/// Names bound by the enclosing `let` expressions, and macros to recognize.
+///
+struct Scope<'a>
+{
+    bound:  Vec<String>,
+    macros: &'a XMacroLibrary,
+}
+
+impl Scope<'_>
+{
+    fn bind(&self, name: &str) -> Self
+    {
+        let mut bound = self.bound.clone();
+        bound.push(name.to_string());
+        Scope {
+            bound,
+            macros: self.macros,
+        }
+    }
+}
+
+/// Layout tree of an expression, before line breaking, with the width of its single line
+/// text.
+///
+struct Doc
+{
+    kind:  DocKind,
+    width: usize,
+}
+
+enum DocKind
+{
+    /// Text that is never broken.
+    Text(String),
+    /// Function call, with one argument per line when broken.
+    Call
+    {
+        name: String,
+        args: Vec<Doc>,
+    },
+    /// Operands of left-associative operators of the same precedence.
+    Infix
+    {
+        first: Box<Doc>,
+        rest:  Vec<(String, Doc)>,
+    },
+    Parens(Box<Doc>),
+    /// Binding, with its body on the next line when broken.
+    Let
+    {
+        name:  String,
+        value: Box<Doc>,
+        body:  Box<Doc>,
+    },
+    /// Documents written one after another, as a swizzle after its receiver.
+    Concat(Vec<Doc>),
+}
+
+impl Doc
+{
+    /// Create a document, measuring its single line text from the widths of its parts.
+    ///
+    fn new(kind: DocKind) -> Doc
+    {
+        let width = match &kind {
+            DocKind::Text(text) => text.len(),
+            DocKind::Call { name, args } => {
+                let separators = 2 * args.len().saturating_sub(1);
+                name.len() + 2 + separators + args.iter().map(|arg| arg.width).sum::<usize>()
+            }
+            DocKind::Infix { first, rest } => {
+                let rest: usize = rest.iter().map(|(op, doc)| op.len() + 2 + doc.width).sum();
+                first.width + rest
+            }
+            DocKind::Parens(inner) => inner.width + 2,
+            DocKind::Let { name, value, body } => {
+                "let  = ; ".len() + name.len() + value.width + body.width
+            }
+            DocKind::Concat(docs) => docs.iter().map(|doc| doc.width).sum(),
+        };
+        Doc { kind, width }
+    }
+
+    fn text(text: impl Into<String>) -> Doc
+    {
+        Doc::new(DocKind::Text(text.into()))
+    }
+
+    fn call(name: impl Into<String>, args: Vec<Doc>) -> Doc
+    {
+        Doc::new(DocKind::Call {
+            name: name.into(),
+            args,
+        })
+    }
+
+    /// Build the layout of an expression in a scope.
+    ///
+    fn from_expr(expr: &XExpr, scope: &Scope) -> Doc
+    {
+        if let Some((definition, args)) = scope.macros.recognize(expr) {
+            return Doc::call(
+                definition.name.clone(),
+                args.iter().map(|arg| Doc::from_expr(arg, scope)).collect(),
+            );
+        }
+
+        match expr {
+            XExpr::Lit(value) => Doc::text(value.to_literal()),
+            XExpr::Attr(name) => Doc::text(if is_bare_attribute(name, &scope.bound) {
+                name.clone()
+            } else {
+                format!("attr({})", quote(name))
+            }),
+            XExpr::Prop(name) => Doc::text(format!("prop({})", quote(name))),
+            XExpr::BuiltIn(op) => Doc::text(op.to_string()),
+            XExpr::Var(name) => Doc::text(name.clone()),
+
+            XExpr::Let { name, value, body } => {
+                let value = Doc::operand(value, scope, matches!(**value, XExpr::Let { .. }));
+                Doc::new(DocKind::Let {
+                    name:  name.clone(),
+                    value: Box::new(value),
+                    body:  Box::new(Doc::from_expr(body, &scope.bind(name))),
+                })
+            }
+
+            XExpr::Unary {
+                op: XUnaryOp::Neg,
+                expr: operand,
+            } => {
+                // Parenthesized so that signs never stack up as `--x`.
+                let doc = Doc::from_expr(operand, scope);
+                let needs_parens = operand.get_precedence() < 90
+                    || matches!(
+                        **operand,
+                        XExpr::Unary {
+                            op: XUnaryOp::Neg,
+                            ..
+                        }
+                    )
+                    || doc.starts_with_minus();
+                Doc::new(DocKind::Concat(vec![
+                    Doc::text("-"),
+                    doc.parenthesized(needs_parens),
+                ]))
+            }
+            XExpr::Unary { op, expr } => {
+                Doc::call(op.to_string(), vec![Doc::from_expr(expr, scope)])
+            }
+
+            XExpr::Binary { .. } if expr.vector_components().is_some() => {
+                let components = expr.vector_components().unwrap_or_default();
+                Doc::call(
+                    format!("vec{}", components.len()),
+                    components
+                        .into_iter()
+                        .map(|component| Doc::from_expr(component, scope))
+                        .collect(),
+                )
+            }
+            // Function-like operators have the highest precedence.
+            XExpr::Binary { left, op, right } if expr.get_precedence() == 100 => Doc::call(
+                op.to_string(),
+                vec![Doc::from_expr(left, scope), Doc::from_expr(right, scope)],
+            ),
+            XExpr::Binary { left, op, right } => {
+                let precedence = expr.get_precedence();
+                let left = if left.get_precedence() == precedence {
+                    Doc::from_expr(left, scope)
+                } else {
+                    Doc::operand(left, scope, left.get_precedence() < precedence)
+                };
+                let (first, mut rest) = match left.kind {
+                    DocKind::Infix { first, rest } => (first, rest),
+                    kind => (Box::new(Doc::new(kind)), Vec::new()),
+                };
+                // Right operands of the same precedence keep their parentheses, since
+                // operators associate to the left.
+                let right = Doc::operand(right, scope, right.get_precedence() <= precedence);
+                rest.push((op.to_string(), right));
+                Doc::new(DocKind::Infix { first, rest })
+            }
+
+            XExpr::Ternary {
+                op,
+                first,
+                second,
+                third,
+            } => Doc::call(
+                op.to_string(),
+                [first, second, third]
+                    .into_iter()
+                    .map(|arg| Doc::from_expr(arg, scope))
+                    .collect(),
+            ),
+
+            XExpr::Swizzle { expr, components } => {
+                // Literals are wrapped, so that the dot is not read as a decimal point.
+                let needs_parens = expr.get_precedence() < 100 || matches!(**expr, XExpr::Lit(_));
+                Doc::new(DocKind::Concat(vec![
+                    Doc::operand(expr, scope, needs_parens),
+                    Doc::text(format!(".{}", components)),
+                ]))
+            }
+        }
+    }
+
+    fn operand(expr: &XExpr, scope: &Scope, needs_parens: bool) -> Doc
+    {
+        Doc::from_expr(expr, scope).parenthesized(needs_parens)
+    }
+
+    fn parenthesized(self, needs_parens: bool) -> Doc
+    {
+        if needs_parens {
+            Doc::new(DocKind::Parens(Box::new(self)))
+        } else {
+            self
+        }
+    }
+
+    /// Whether the single line text of the document starts with a minus sign.
+    ///
+    fn starts_with_minus(&self) -> bool
+    {
+        match &self.kind {
+            DocKind::Text(text) => text.starts_with('-'),
+            DocKind::Infix { first, .. } => first.starts_with_minus(),
+            DocKind::Concat(docs) => docs.first().is_some_and(Doc::starts_with_minus),
+            DocKind::Call { .. } | DocKind::Parens(_) | DocKind::Let { .. } => false,
+        }
+    }
+
+    /// Write the single line text of the document.
+    ///
+    fn write_flat(&self, out: &mut String)
+    {
+        match &self.kind {
+            DocKind::Text(text) => out.push_str(text),
+            DocKind::Call { name, args } => {
+                out.push_str(name);
+                out.push('(');
+                for (i, arg) in args.iter().enumerate() {
+                    if i > 0 {
+                        out.push_str(", ");
+                    }
+                    arg.write_flat(out);
+                }
+                out.push(')');
+            }
+            DocKind::Infix { first, rest } => {
+                first.write_flat(out);
+                for (op, operand) in rest {
+                    out.push(' ');
+                    out.push_str(op);
+                    out.push(' ');
+                    operand.write_flat(out);
+                }
+            }
+            DocKind::Parens(inner) => {
+                out.push('(');
+                inner.write_flat(out);
+                out.push(')');
+            }
+            DocKind::Let { name, value, body } => {
+                out.push_str(&format!("let {} = ", name));
+                value.write_flat(out);
+                out.push_str("; ");
+                body.write_flat(out);
+            }
+            DocKind::Concat(docs) => {
+                for doc in docs {
+                    doc.write_flat(out);
+                }
+            }
+        }
+    }
+
+    /// Write the document at nesting `level`, breaking the parts that do not fit.
+    ///
+    fn render(&self, options: &XFormatOptions, level: usize, out: &mut String)
+    {
+        let column = out.len() - out.rfind('\n').map_or(0, |newline| newline + 1);
+        if column.saturating_add(self.width) <= options.max_width {
+            self.write_flat(out);
+            return;
+        }
+
+        let newline = |out: &mut String, level: usize| {
+            out.push('\n');
+            out.push_str(&" ".repeat(level * options.indent));
+        };
+
+        match &self.kind {
+            DocKind::Text(text) => out.push_str(text),
+            DocKind::Call { name, args } => {
+                out.push_str(name);
+                out.push('(');
+                for (i, arg) in args.iter().enumerate() {
+                    newline(out, level + 1);
+                    arg.render(options, level + 1, out);
+                    if i + 1 < args.len() {
+                        out.push(',');
+                    }
+                }
+                newline(out, level);
+                out.push(')');
+            }
+            DocKind::Infix { first, rest } => {
+                first.render(options, level, out);
+                for (op, operand) in rest {
+                    newline(out, level + 1);
+                    out.push_str(op);
+                    out.push(' ');
+                    operand.render(options, level + 1, out);
+                }
+            }
+            DocKind::Parens(inner) => {
+                out.push('(');
+                inner.render(options, level, out);
+                out.push(')');
+            }
+            DocKind::Let { name, value, body } => {
+                out.push_str(&format!("let {} = ", name));
+                value.render(options, level, out);
+                out.push(';');
+                newline(out, level);
+                body.render(options, level, out);
+            }
+            DocKind::Concat(docs) => {
+                for doc in docs {
+                    doc.render(options, level, out);
+                }
+            }
+        }
+    }
+}
+
+/// Whether an attribute can be written as a bare name, which would otherwise be read as a
+/// keyword, a builtin or a binding.
+///
+fn is_bare_attribute(name: &str, bound: &[String]) -> bool
+{
+    let reserved = [
+        "let",
+        "true",
+        "false",
+        "time",
+        "delta_time",
+        "rand",
+        "rand_vec2",
+        "rand_vec3",
+        "alpha_cutoff",
+        "particle_id",
+    ];
+    is_identifier(name) && !reserved.contains(&name) && !bound.iter().any(|var| var == name)
+}
+
+fn is_identifier(name: &str) -> bool
+{
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_alphabetic() || first == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_')
+}
+
+/// Attribute or property name, quoted unless it is an identifier.
+///
+fn quote(name: &str) -> String
+{
+    if is_identifier(name) {
+        name.to_string()
+    } else {
+        format!("\"{}\"", name)
+    }
+}
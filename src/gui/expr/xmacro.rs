@@ -0,0 +1,427 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use gpui::Global;
+use serde::{Deserialize, Serialize};
+
+use crate::gui::expr::tokenizer::FunctionKind;
+use crate::gui::expr::xexpr::XExpr;
+use crate::gui::expr::xparser::{Parser, XParseError};
+
+// ====================
+// Errors.
+// ====================
+
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum XMacroError
+{
+    #[error("File error for {}: {message}", path.display())]
+    File
+    {
+        path: PathBuf, message: String
+    },
+    #[error("Parse error for macros: {message}")]
+    Parse
+    {
+        message: String
+    },
+    #[error("Invalid body for macro '{name}': {error}")]
+    Body
+    {
+        name: String, error: XParseError
+    },
+    #[error("Macro '{name}' is defined twice")]
+    Duplicate
+    {
+        name: String
+    },
+    #[error("Macro '{name}' has the name of a function, builtin or keyword")]
+    Reserved
+    {
+        name: String
+    },
+}
+
+// ====================
+// Macros.
+// ====================
+
+/// Macro as written in the macro library file.
+///
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct XMacroDef
+{
+    pub name:        String,
+    #[serde(default)]
+    pub params:      Vec<String>,
+    /// Expression source, in which parameters are referred to by name.
+    pub body:        String,
+    #[serde(default)]
+    pub description: String,
+}
+
+/// Named expression template, called like a function and expanded by the parser.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct XMacro
+{
+    pub name:        String,
+    pub params:      Vec<String>,
+    /// Template, in which parameters are variables.
+    pub body:        XExpr,
+    pub description: String,
+}
+
+impl XMacro
+{
+    /// Parse the body of a macro definition, which may call the macros of `library`.
+    ///
+    pub fn from_def(def: &XMacroDef, library: &XMacroLibrary) -> Result<Self, XMacroError>
+    {
+        let body = Parser::new(&def.body)
+            .with_bindings(def.params.clone())
+            .with_macros(library)
+            .parse()
+            .map_err(|error| XMacroError::Body {
+                name: def.name.clone(),
+                error,
+            })?;
+        Ok(Self {
+            name: def.name.clone(),
+            params: def.params.clone(),
+            body,
+            description: def.description.clone(),
+        })
+    }
+
+    /// Call signature, e.g. `pulse(t, freq)`.
+    ///
+    pub fn signature(&self) -> String
+    {
+        format!("{}({})", self.name, self.params.join(", "))
+    }
+
+    /// Replace the parameters of the template by the arguments of a call.
+    ///
+    /// Arguments are substituted, so the expansion has the same structure as the macro
+    /// written out by hand. Arguments used more than once that are not plain values are
+    /// bound instead, so that they are evaluated once: `square(rand())` draws a single value.
+    ///
+    pub fn expand(&self, args: &[XExpr]) -> Option<XExpr>
+    {
+        if args.len() != self.params.len() {
+            return None;
+        }
+        let mut bound: Vec<(String, XExpr)> = Vec::new();
+        let mut substituted = Vec::with_capacity(args.len());
+        for (param, arg) in self.params.iter().zip(args) {
+            if is_plain(arg) || self.body.count_var(param) < 2 {
+                substituted.push(arg.clone());
+                continue;
+            }
+            // Bindings enclose the arguments bound after them, which must not be captured.
+            let name = fresh_name(param, |name| {
+                args.iter().any(|arg| arg.uses_var(name))
+                    || bound.iter().any(|(other, _)| other == name)
+            });
+            substituted.push(XExpr::var(name.clone()));
+            bound.push((name, arg.clone()));
+        }
+        let body = self.body.substitute(&self.params, &substituted);
+        Some(
+            bound
+                .into_iter()
+                .rev()
+                .fold(body, |body, (name, value)| XExpr::bind(name, value, body)),
+        )
+    }
+
+    /// Find the arguments the macro would be called with to expand to `expr`.
+    ///
+    pub fn matches(&self, expr: &XExpr) -> Option<Vec<XExpr>>
+    {
+        // A template made of a single parameter would match any expression.
+        if matches!(&self.body, XExpr::Var(name) if self.params.contains(name)) {
+            return None;
+        }
+        let mut args = vec![None; self.params.len()];
+        if !unify(&self.body, expr, &self.params, &mut args) {
+            return None;
+        }
+        // Arguments that refer to the bindings of the template would not expand back to
+        // `expr`, since expansion renames the bindings.
+        let args: Vec<XExpr> = args.into_iter().collect::<Option<_>>()?;
+        (self.expand(&args).as_ref() == Some(expr)).then_some(args)
+    }
+}
+
+impl XExpr
+{
+    /// Replace the local bindings `params` by `args`, renaming the bindings of the
+    /// expression that would capture the variables of the arguments.
+    ///
+    pub fn substitute(&self, params: &[String], args: &[XExpr]) -> XExpr
+    {
+        let recurse = |expr: &XExpr| expr.substitute(params, args);
+        match self {
+            XExpr::Var(name) => match params.iter().position(|param| param == name) {
+                Some(index) => args[index].clone(),
+                None => self.clone(),
+            },
+            XExpr::Lit(_) | XExpr::Attr(_) | XExpr::Prop(_) | XExpr::BuiltIn(_) => self.clone(),
+            XExpr::Let { name, value, body } => {
+                // Parameters shadowed by the binding are left alone in its body.
+                let mut params = params.to_vec();
+                let mut args = args.to_vec();
+                if let Some(index) = params.iter().position(|param| param == name) {
+                    params.remove(index);
+                    args.remove(index);
+                }
+                let mut bound = name.clone();
+                if args.iter().any(|arg| arg.uses_var(name)) {
+                    bound = fresh_name(name, |candidate| {
+                        args.iter().any(|arg| arg.uses_var(candidate)) || body.uses_var(candidate)
+                    });
+                    params.push(name.clone());
+                    args.push(XExpr::var(bound.clone()));
+                }
+                XExpr::bind(bound, recurse(value), body.substitute(&params, &args))
+            }
+            XExpr::Unary { op, expr } => XExpr::unary(*op, recurse(expr)),
+            XExpr::Binary { left, op, right } => XExpr::binary(recurse(left), *op, recurse(right)),
+            XExpr::Ternary {
+                op,
+                first,
+                second,
+                third,
+            } => XExpr::ternary(*op, recurse(first), recurse(second), recurse(third)),
+            XExpr::Swizzle { expr, components } => {
+                XExpr::swizzle(recurse(expr), components.clone())
+            }
+        }
+    }
+}
+
+/// Whether an argument can be repeated in an expansion without changing its value or
+/// its cost.
+///
+fn is_plain(expr: &XExpr) -> bool
+{
+    match expr {
+        XExpr::Lit(_) | XExpr::Attr(_) | XExpr::Prop(_) | XExpr::Var(_) => true,
+        XExpr::BuiltIn(_) => !expr.is_random(),
+        _ => false,
+    }
+}
+
+/// `name`, or the first of `name_1`, `name_2`... that is not `taken`.
+///
+fn fresh_name(name: &str, taken: impl Fn(&str) -> bool) -> String
+{
+    let mut candidate = name.to_string();
+    let mut index = 0;
+    while taken(&candidate) {
+        index += 1;
+        candidate = format!("{}_{}", name, index);
+    }
+    candidate
+}
+
+/// Match `expr` against `template`, binding each parameter to the same subexpression
+/// wherever it occurs.
+///
+fn unify(template: &XExpr, expr: &XExpr, params: &[String], args: &mut [Option<XExpr>]) -> bool
+{
+    if let XExpr::Var(name) = template {
+        if let Some(index) = params.iter().position(|param| param == name) {
+            return match &args[index] {
+                Some(bound) => bound == expr,
+                None => {
+                    args[index] = Some(expr.clone());
+                    true
+                }
+            };
+        }
+    }
+
+    match (template, expr) {
+        (
+            XExpr::Let { name, value, body },
+            XExpr::Let {
+                name: other_name,
+                value: other_value,
+                body: other_body,
+            },
+        ) => {
+            // Shadowed parameters are not in the body, as in `substitute`.
+            let params: Vec<String> = params
+                .iter()
+                .map(|p| if p == name { String::new() } else { p.clone() })
+                .collect();
+            name == other_name
+                && unify(value, other_value, &params, args)
+                && unify(body, other_body, &params, args)
+        }
+        (
+            XExpr::Unary { op, expr },
+            XExpr::Unary {
+                op: other_op,
+                expr: other,
+            },
+        ) => op == other_op && unify(expr, other, params, args),
+        (
+            XExpr::Binary { left, op, right },
+            XExpr::Binary {
+                left: other_left,
+                op: other_op,
+                right: other_right,
+            },
+        ) => {
+            op == other_op
+                && unify(left, other_left, params, args)
+                && unify(right, other_right, params, args)
+        }
+        (
+            XExpr::Ternary {
+                op,
+                first,
+                second,
+                third,
+            },
+            XExpr::Ternary {
+                op: other_op,
+                first: other_first,
+                second: other_second,
+                third: other_third,
+            },
+        ) => {
+            op == other_op
+                && unify(first, other_first, params, args)
+                && unify(second, other_second, params, args)
+                && unify(third, other_third, params, args)
+        }
+        (
+            XExpr::Swizzle { expr, components },
+            XExpr::Swizzle {
+                expr: other,
+                components: other_components,
+            },
+        ) => components == other_components && unify(expr, other, params, args),
+        _ => template == expr,
+    }
+}
+
+// ====================
+// Library.
+// ====================
+
+/// Macros of a project, loaded from `macros.ron` next to the assets root.
+///
+/// Shared as a global so that every expression input parses and completes them.
+///
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct XMacroLibrary
+{
+    macros: Vec<XMacro>,
+}
+
+impl Global for XMacroLibrary {}
+
+impl XMacroLibrary
+{
+    pub const FILE_NAME: &'static str = "macros.ron";
+
+    /// Path of the macro library of an assets root, in the directory that contains it.
+    ///
+    pub fn path(assets_root: &Path) -> PathBuf
+    {
+        assets_root
+            .parent()
+            .unwrap_or(assets_root)
+            .join(Self::FILE_NAME)
+    }
+
+    /// Load the macro library of an assets root. A missing file is an empty library.
+    ///
+    pub fn load(assets_root: &Path) -> Result<Self, XMacroError>
+    {
+        let path = Self::path(assets_root);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(&path).map_err(|e| XMacroError::File {
+            path:    path.clone(),
+            message: e.to_string(),
+        })?;
+        Self::from_ron(&content)
+    }
+
+    /// Parse a list of macro definitions. Macros may call the ones defined before them.
+    ///
+    pub fn from_ron(content: &str) -> Result<Self, XMacroError>
+    {
+        let defs = ron::from_str::<Vec<XMacroDef>>(content).map_err(|e| XMacroError::Parse {
+            message: e.to_string(),
+        })?;
+        Self::from_defs(&defs)
+    }
+
+    pub fn from_defs(defs: &[XMacroDef]) -> Result<Self, XMacroError>
+    {
+        let mut library = Self::default();
+        for def in defs {
+            if is_reserved(&def.name) {
+                return Err(XMacroError::Reserved {
+                    name: def.name.clone(),
+                });
+            }
+            if library.get(&def.name).is_some() {
+                return Err(XMacroError::Duplicate {
+                    name: def.name.clone(),
+                });
+            }
+            let definition = XMacro::from_def(def, &library)?;
+            library.macros.push(definition);
+        }
+        Ok(library)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&XMacro>
+    {
+        self.macros
+            .iter()
+            .find(|definition| definition.name == name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &XMacro>
+    {
+        self.macros.iter()
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.macros.is_empty()
+    }
+
+    /// Find a macro that expands to `expr`, with the arguments of its call.
+    ///
+    /// Later macros are tried first, as they may be built on earlier ones.
+    ///
+    pub fn recognize(&self, expr: &XExpr) -> Option<(&XMacro, Vec<XExpr>)>
+    {
+        self.macros
+            .iter()
+            .rev()
+            .find_map(|definition| Some((definition, definition.matches(expr)?)))
+    }
+}
+
+/// Whether a name is taken by a function, a builtin or a keyword, which a macro of the
+/// same name would hide.
+///
+fn is_reserved(name: &str) -> bool
+{
+    FunctionKind::from_str(name).is_some()
+        || Parser::new("").parse_builtin(name).is_ok()
+        || ["let", "true", "false"].contains(&name)
+}
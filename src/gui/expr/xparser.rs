@@ -1,7 +1,9 @@
 use std::fmt;
+use std::ops::Range;
 
 use crate::gui::expr::xexpr::XExpr;
-use crate::gui::expr::xop::{XBinaryOp, XBuiltInOp, XUnaryOp};
+use crate::gui::expr::xmacro::{XMacro, XMacroLibrary};
+use crate::gui::expr::xop::{XBinaryOp, XBuiltInOp, XTernaryOp, XUnaryOp};
 use crate::gui::expr::xval::XValue;
 
 #[derive(Debug, Clone, PartialEq)]
@@ -34,50 +36,178 @@ impl fmt::Display for XParseError
 
 impl std::error::Error for XParseError {}
 
+/// Parse error with the byte range of the input it applies to.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct XParseDiagnostic
+{
+    pub error: XParseError,
+    pub span:  Range<usize>,
+}
+
+impl fmt::Display for XParseDiagnostic
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        write!(f, "{}", self.error)
+    }
+}
+
+impl std::error::Error for XParseDiagnostic {}
+
+impl XExpr
+{
+    /// Parse expression from string.
+    ///
+    pub fn parse(input: &str) -> Result<Self, XParseError>
+    {
+        Parser::new(input).parse()
+    }
+
+    /// Parse expression from string, reporting every error with its byte span.
+    ///
+    pub fn parse_with_diagnostics(input: &str) -> Result<Self, Vec<XParseDiagnostic>>
+    {
+        Parser::new(input).parse_with_diagnostics()
+    }
+
+    /// Parse expression from string, expanding calls to the macros of a library.
+    ///
+    pub fn parse_with_macros(
+        input: &str,
+        macros: &XMacroLibrary,
+    ) -> Result<Self, Vec<XParseDiagnostic>>
+    {
+        Parser::new(input)
+            .with_macros(macros)
+            .parse_with_diagnostics()
+    }
+}
+
 // ====================
 // Parser Implementation.
 // ====================
 
-pub struct Parser
+pub struct Parser<'a>
 {
-    input: Vec<char>,
-    pos:   usize,
+    input:       Vec<char>,
+    pos:         usize,
+    open_parens: Vec<usize>,
+    bindings:    Vec<String>,
+    macros:      Option<&'a XMacroLibrary>,
+    error_span:  Option<Range<usize>>,
+    diagnostics: Vec<XParseDiagnostic>,
 }
 
-impl Parser
+impl<'a> Parser<'a>
 {
     /// Create new parser for input string.
     ///
     pub fn new(input: &str) -> Self
     {
         Self {
-            input: input.chars().collect(),
-            pos:   0,
+            input:       input.chars().collect(),
+            pos:         0,
+            open_parens: Vec::new(),
+            bindings:    Vec::new(),
+            macros:      None,
+            error_span:  None,
+            diagnostics: Vec::new(),
         }
     }
 
-    /// Parse complete expression from input.
+    /// Read the given names as local variables, as in the body of a `let`.
+    ///
+    pub fn with_bindings(mut self, names: Vec<String>) -> Self
+    {
+        self.bindings = names;
+        self
+    }
+
+    /// Expand calls to the macros of a library.
+    ///
+    pub fn with_macros(mut self, macros: &'a XMacroLibrary) -> Self
+    {
+        self.macros = Some(macros);
+        self
+    }
+
+    /// Parse complete expression from input, returning the first error.
     ///
     pub fn parse(&mut self) -> Result<XExpr, XParseError>
+    {
+        self.parse_with_diagnostics().map_err(|diagnostics| {
+            diagnostics
+                .into_iter()
+                .next()
+                .map_or(XParseError::UnexpectedEndOfInput, |d| d.error)
+        })
+    }
+
+    /// Parse complete expression from input, returning every error with its span.
+    ///
+    /// Errors in call arguments and parentheses are recovered from by skipping to the
+    /// next `,` or `)`, so several of them can be reported at once.
+    ///
+    pub fn parse_with_diagnostics(&mut self) -> Result<XExpr, Vec<XParseDiagnostic>>
     {
         self.skip_whitespace();
-        let expr = self.parse_expression()?;
-        self.skip_whitespace();
-        if self.pos < self.input.len() {
-            return Err(XParseError::UnexpectedToken(
-                self.input[self.pos].to_string(),
-            ));
+        match self.parse_expression() {
+            Ok(expr) => {
+                self.skip_whitespace();
+                if self.pos < self.input.len() {
+                    let token = self.input[self.pos].to_string();
+                    let span = self.pos..self.input.len();
+                    let error = self.fail(XParseError::UnexpectedToken(token), span);
+                    self.report(error);
+                }
+                if self.diagnostics.is_empty() {
+                    return Ok(expr);
+                }
+            }
+            Err(error) => self.report(error),
         }
-        Ok(expr)
+        Err(std::mem::take(&mut self.diagnostics))
     }
 
-    /// Parse top-level expression.
+    /// Parse top-level expression, with leading `let` bindings.
     ///
     pub fn parse_expression(&mut self) -> Result<XExpr, XParseError>
     {
+        self.skip_whitespace();
+        let start = self.pos;
+        if self.parse_identifier_opt().as_deref() == Some("let") {
+            return self.parse_let();
+        }
+        self.pos = start;
         self.parse_comparison()
     }
 
+    /// Parse `name = value; body` after the `let` keyword.
+    ///
+    pub fn parse_let(&mut self) -> Result<XExpr, XParseError>
+    {
+        self.skip_whitespace();
+        let name = self.parse_identifier()?;
+        self.skip_whitespace();
+        // `==` is a comparison, not the assignment.
+        if self.peek() != Some('=') || self.input.get(self.pos + 1) == Some(&'=') {
+            return Err(self.fail(self.unexpected(), self.here()));
+        }
+        self.advance();
+
+        let value = self.parse_comparison()?;
+        self.skip_whitespace();
+        if !self.match_char(';') {
+            return Err(self.fail(self.unexpected(), self.here()));
+        }
+
+        self.bindings.push(name.clone());
+        let body = self.parse_expression();
+        self.bindings.pop();
+        Ok(XExpr::bind(name, value, body?))
+    }
+
     /// Parse comparison operators (==, !=, <, >, <=, >=).
     ///
     pub fn parse_comparison(&mut self) -> Result<XExpr, XParseError>
@@ -173,7 +303,7 @@ impl Parser
         self.parse_postfix()
     }
 
-    /// Parse postfix operators (dot access).
+    /// Parse postfix operators (swizzles and dot access).
     ///
     pub fn parse_postfix(&mut self) -> Result<XExpr, XParseError>
     {
@@ -183,10 +313,15 @@ impl Parser
             self.skip_whitespace();
             if self.match_char('.') {
                 if self.peek().map_or(false, |c| c.is_ascii_digit()) {
-                    return Err(XParseError::UnexpectedToken(".".to_string()));
+                    let span = self.pos - 1..self.pos;
+                    return Err(self.fail(XParseError::UnexpectedToken(".".to_string()), span));
                 }
                 let field = self.parse_identifier()?;
-                expr = XExpr::binary(expr, XBinaryOp::Dot, XExpr::attr(field));
+                expr = if XExpr::swizzle_indices(&field).is_some() {
+                    XExpr::swizzle(expr, field)
+                } else {
+                    XExpr::binary(expr, XBinaryOp::Dot, XExpr::attr(field))
+                };
             } else {
                 break;
             }
@@ -202,11 +337,12 @@ impl Parser
         self.skip_whitespace();
 
         if self.match_char('(') {
-            let expr = self.parse_expression()?;
-            self.skip_whitespace();
-            if !self.match_char(')') {
-                return Err(XParseError::UnmatchedParenthesis);
+            if let Some(value) = self.parse_negative_literal()? {
+                return Ok(XExpr::lit(value));
             }
+            self.open_parens.push(self.pos - 1);
+            let expr = self.parse_recovering();
+            self.expect_close()?;
             return Ok(expr);
         }
 
@@ -215,10 +351,24 @@ impl Parser
         }
 
         if let Some(ident) = self.parse_identifier_opt() {
+            match ident.as_str() {
+                "true" => return Ok(XExpr::lit(true)),
+                "false" => return Ok(XExpr::lit(false)),
+                _ => {}
+            }
+
             self.skip_whitespace();
 
             if self.match_char('(') {
-                return self.parse_function_call(&ident);
+                let depth = self.open_parens.len();
+                self.open_parens.push(self.pos - 1);
+                let call = self.parse_function_call(&ident);
+                self.open_parens.truncate(depth);
+                return call;
+            }
+
+            if self.bindings.contains(&ident) {
+                return Ok(XExpr::var(ident));
             }
 
             if let Ok(builtin) = self.parse_builtin(&ident) {
@@ -228,7 +378,7 @@ impl Parser
             return Ok(XExpr::attr(ident));
         }
 
-        Err(XParseError::UnexpectedEndOfInput)
+        Err(self.fail(self.unexpected(), self.here()))
     }
 
     /// Parse function call expressions.
@@ -236,178 +386,164 @@ impl Parser
     pub fn parse_function_call(&mut self, name: &str) -> Result<XExpr, XParseError>
     {
         match name {
-            "sin" => {
-                let arg = self.parse_expression()?;
-                self.skip_whitespace();
-                if !self.match_char(')') {
-                    return Err(XParseError::UnmatchedParenthesis);
-                }
-                Ok(XExpr::sin(arg))
-            }
-            "cos" => {
-                let arg = self.parse_expression()?;
-                self.skip_whitespace();
-                if !self.match_char(')') {
-                    return Err(XParseError::UnmatchedParenthesis);
-                }
-                Ok(XExpr::cos(arg))
-            }
-            "abs" => {
-                let arg = self.parse_expression()?;
-                self.skip_whitespace();
-                if !self.match_char(')') {
-                    return Err(XParseError::UnmatchedParenthesis);
-                }
-                Ok(XExpr::abs(arg))
-            }
-            "norm" => {
-                let arg = self.parse_expression()?;
-                self.skip_whitespace();
-                if !self.match_char(')') {
-                    return Err(XParseError::UnmatchedParenthesis);
-                }
-                Ok(XExpr::norm(arg))
-            }
-            "all" => {
-                let arg = self.parse_expression()?;
-                self.skip_whitespace();
-                if !self.match_char(')') {
-                    return Err(XParseError::UnmatchedParenthesis);
-                }
-                Ok(XExpr::unary(XUnaryOp::All, arg))
+            "sin" | "cos" | "abs" | "norm" | "all" | "any" | "sign" | "length" | "tan" => {
+                let op = match name {
+                    "sin" => XUnaryOp::Sin,
+                    "cos" => XUnaryOp::Cos,
+                    "abs" => XUnaryOp::Abs,
+                    "norm" => XUnaryOp::Norm,
+                    "all" => XUnaryOp::All,
+                    "any" => XUnaryOp::Any,
+                    "sign" => XUnaryOp::Sign,
+                    "length" => XUnaryOp::Length,
+                    _ => XUnaryOp::Tan,
+                };
+                let [arg] = self.parse_arguments()?;
+                Ok(XExpr::unary(op, arg))
             }
-            "any" => {
-                let arg = self.parse_expression()?;
-                self.skip_whitespace();
-                if !self.match_char(')') {
-                    return Err(XParseError::UnmatchedParenthesis);
-                }
-                Ok(XExpr::unary(XUnaryOp::Any, arg))
+            "dot" | "cross" | "min" | "max" | "step" | "distance" | "uniform" | "normal" => {
+                let op = match name {
+                    "dot" => XBinaryOp::Dot,
+                    "cross" => XBinaryOp::Cross,
+                    "min" => XBinaryOp::Min,
+                    "max" => XBinaryOp::Max,
+                    "step" => XBinaryOp::Step,
+                    "distance" => XBinaryOp::Distance,
+                    "uniform" => XBinaryOp::Uniform,
+                    _ => XBinaryOp::Normal,
+                };
+                let [left, right] = self.parse_arguments()?;
+                Ok(XExpr::binary(left, op, right))
             }
-            "dot" => {
-                let left = self.parse_expression()?;
-                self.skip_whitespace();
-                if !self.match_char(',') {
-                    return Err(XParseError::UnexpectedToken(
-                        self.peek().unwrap_or('\0').to_string(),
-                    ));
-                }
-                self.skip_whitespace();
-                let right = self.parse_expression()?;
-                self.skip_whitespace();
-                if !self.match_char(')') {
-                    return Err(XParseError::UnmatchedParenthesis);
-                }
-                Ok(XExpr::binary(left, XBinaryOp::Dot, right))
+            "vec2" => self.parse_vector(2),
+            "vec3" => self.parse_vector(3),
+            "vec4" => self.parse_vector(4),
+            "mix" | "lerp" | "clamp" => {
+                let op = if name == "clamp" {
+                    XTernaryOp::Clamp
+                } else {
+                    XTernaryOp::Mix
+                };
+                let [first, second, third] = self.parse_arguments()?;
+                Ok(XExpr::ternary(op, first, second, third))
             }
-            "cross" => {
-                let left = self.parse_expression()?;
-                self.skip_whitespace();
-                if !self.match_char(',') {
-                    return Err(XParseError::UnexpectedToken(
-                        self.peek().unwrap_or('\0').to_string(),
-                    ));
-                }
-                self.skip_whitespace();
-                let right = self.parse_expression()?;
+            "attr" | "prop" => {
                 self.skip_whitespace();
-                if !self.match_char(')') {
-                    return Err(XParseError::UnmatchedParenthesis);
-                }
-                Ok(XExpr::binary(left, XBinaryOp::Cross, right))
+                let field = self.parse_string_literal()?;
+                self.expect_close()?;
+                Ok(if name == "attr" {
+                    XExpr::attr(field)
+                } else {
+                    XExpr::prop(field)
+                })
             }
-            "min" => {
-                let left = self.parse_expression()?;
-                self.skip_whitespace();
-                if !self.match_char(',') {
-                    return Err(XParseError::UnexpectedToken(
-                        self.peek().unwrap_or('\0').to_string(),
-                    ));
-                }
-                self.skip_whitespace();
-                let right = self.parse_expression()?;
-                self.skip_whitespace();
-                if !self.match_char(')') {
-                    return Err(XParseError::UnmatchedParenthesis);
+            _ => {
+                if let Some(definition) = self.macros.and_then(|macros| macros.get(name)) {
+                    return self.parse_macro_call(definition);
                 }
-                Ok(XExpr::binary(left, XBinaryOp::Min, right))
+                // Builtins may also be called with no arguments, as in `rand_vec3()`.
+                let Ok(builtin) = self.parse_builtin(name) else {
+                    let span = self.call_name_span(name);
+                    return Err(self.fail(XParseError::UnknownFunction(name.to_string()), span));
+                };
+                self.expect_close()?;
+                Ok(XExpr::builtin(builtin))
             }
-            "max" => {
-                let left = self.parse_expression()?;
+        }
+    }
+
+    /// Parse comma separated call arguments up to the closing parenthesis.
+    ///
+    pub fn parse_arguments<const N: usize>(&mut self) -> Result<[XExpr; N], XParseError>
+    {
+        let mut args = Vec::with_capacity(N);
+        for i in 0..N {
+            if i > 0 {
                 self.skip_whitespace();
                 if !self.match_char(',') {
-                    return Err(XParseError::UnexpectedToken(
-                        self.peek().unwrap_or('\0').to_string(),
-                    ));
+                    let token = self.peek().unwrap_or('\0').to_string();
+                    return Err(self.fail(XParseError::UnexpectedToken(token), self.here()));
                 }
                 self.skip_whitespace();
-                let right = self.parse_expression()?;
-                self.skip_whitespace();
-                if !self.match_char(')') {
-                    return Err(XParseError::UnmatchedParenthesis);
-                }
-                Ok(XExpr::binary(left, XBinaryOp::Max, right))
             }
-            "vec2" => {
-                let x = self.parse_expression()?;
-                self.skip_whitespace();
-                if !self.match_char(',') {
-                    return Err(XParseError::InvalidVectorLiteral);
-                }
-                self.skip_whitespace();
-                let y = self.parse_expression()?;
-                self.skip_whitespace();
-                if !self.match_char(')') {
-                    return Err(XParseError::UnmatchedParenthesis);
-                }
+            args.push(self.parse_recovering());
+        }
+        self.expect_close()?;
+        args.try_into()
+            .map_err(|_| XParseError::UnexpectedEndOfInput)
+    }
 
-                // Create a binary expression that constructs vec2 from the two expressions
-                Ok(XExpr::binary(x, XBinaryOp::Vec2, y))
-            }
-            "vec3" => {
-                let x = self.parse_expression()?;
+    /// Parse the arguments of a macro call up to the closing parenthesis, and expand it.
+    ///
+    pub fn parse_macro_call(&mut self, definition: &XMacro) -> Result<XExpr, XParseError>
+    {
+        let mut args = Vec::new();
+        self.skip_whitespace();
+        if !definition.params.is_empty() && self.peek().is_some_and(|c| c != ')') {
+            args.push(self.parse_recovering());
+            loop {
                 self.skip_whitespace();
                 if !self.match_char(',') {
-                    return Err(XParseError::InvalidVectorLiteral);
+                    break;
                 }
-                self.skip_whitespace();
-                let y = self.parse_expression()?;
-                self.skip_whitespace();
-                if !self.match_char(',') {
-                    return Err(XParseError::InvalidVectorLiteral);
+                if args.len() == definition.params.len() {
+                    let span = self.pos - 1..self.pos;
+                    return Err(self.fail(XParseError::UnexpectedToken(",".to_string()), span));
                 }
                 self.skip_whitespace();
-                let z = self.parse_expression()?;
-                self.skip_whitespace();
-                if !self.match_char(')') {
-                    return Err(XParseError::UnmatchedParenthesis);
-                }
-
-                // Create a binary expression that constructs vec3 from the three expressions
-                // We use a nested binary structure: (x, y, z) -> ((x, y), z)
-                let xy = XExpr::binary(x, XBinaryOp::Vec2, y);
-                Ok(XExpr::binary(xy, XBinaryOp::Vec3, z))
+                args.push(self.parse_recovering());
             }
-            "attr" => {
-                self.skip_whitespace();
-                let name = self.parse_string_literal()?;
-                self.skip_whitespace();
-                if !self.match_char(')') {
-                    return Err(XParseError::UnmatchedParenthesis);
-                }
-                Ok(XExpr::attr(name))
+        }
+        if args.len() < definition.params.len() {
+            let token = self.peek().map_or(XParseError::UnexpectedEndOfInput, |c| {
+                XParseError::UnexpectedToken(c.to_string())
+            });
+            return Err(self.fail(token, self.here()));
+        }
+        self.expect_close()?;
+        definition
+            .expand(&args)
+            .ok_or(XParseError::UnknownFunction(definition.name.clone()))
+    }
+
+    /// Parse the arguments of a `vecN` constructor up to the closing parenthesis.
+    ///
+    /// Arguments may be floats or vectors, e.g. `vec3(attr(size2), 1.0)`. They are nested
+    /// left to right, so `vec3(x, y, z)` is `Vec3(Vec2(x, y), z)`.
+    ///
+    pub fn parse_vector(&mut self, size: usize) -> Result<XExpr, XParseError>
+    {
+        let open = self.open_parens.last().copied().unwrap_or(self.pos);
+        let mut args = vec![self.parse_recovering()];
+        loop {
+            self.skip_whitespace();
+            if self.peek().is_none() || self.peek() == Some(')') {
+                self.expect_close()?;
+                break;
             }
-            "prop" => {
-                self.skip_whitespace();
-                let name = self.parse_string_literal()?;
-                self.skip_whitespace();
-                if !self.match_char(')') {
-                    return Err(XParseError::UnmatchedParenthesis);
-                }
-                Ok(XExpr::prop(name))
+            if !self.match_char(',') || args.len() == size {
+                return Err(self.fail(XParseError::InvalidVectorLiteral, self.here()));
             }
-            _ => Err(XParseError::UnknownFunction(name.to_string())),
+            self.skip_whitespace();
+            args.push(self.parse_recovering());
         }
+        if args.len() < 2 {
+            let span = open..self.pos;
+            return Err(self.fail(XParseError::InvalidVectorLiteral, span));
+        }
+
+        let count = args.len();
+        let mut args = args.into_iter().enumerate();
+        let (_, mut expr) = args.next().ok_or(XParseError::InvalidVectorLiteral)?;
+        for (i, arg) in args {
+            let op = match size + 1 + i - count {
+                2 => XBinaryOp::Vec2,
+                3 => XBinaryOp::Vec3,
+                _ => XBinaryOp::Vec4,
+            };
+            expr = XExpr::binary(expr, op, arg);
+        }
+        Ok(expr)
     }
 
     /// Parse builtin operator from identifier.
@@ -418,6 +554,8 @@ impl Parser
             "time" => Ok(XBuiltInOp::Time),
             "delta_time" => Ok(XBuiltInOp::DeltaTime),
             "rand" => Ok(XBuiltInOp::Rand),
+            "rand_vec2" => Ok(XBuiltInOp::RandVec2),
+            "rand_vec3" => Ok(XBuiltInOp::RandVec3),
             "alpha_cutoff" => Ok(XBuiltInOp::AlphaCutoff),
             "particle_id" => Ok(XBuiltInOp::ParticleId),
             _ => Err(XParseError::InvalidIdentifier(name.to_string())),
@@ -428,6 +566,7 @@ impl Parser
     ///
     pub fn parse_string_literal(&mut self) -> Result<String, XParseError>
     {
+        let start = self.pos;
         if !self.match_char('"') {
             return self.parse_identifier();
         }
@@ -441,7 +580,7 @@ impl Parser
             result.push(ch);
             self.advance();
         }
-        Err(XParseError::UnmatchedParenthesis)
+        Err(self.fail(XParseError::UnmatchedParenthesis, start..self.pos))
     }
 
     /// Parse numeric literal.
@@ -468,17 +607,34 @@ impl Parser
 
         let num_str: String = self.input[start..self.pos].iter().collect();
 
-        if has_dot {
-            match num_str.parse::<f32>() {
-                Ok(f) => Ok(Some(XValue::Float(f))),
-                Err(_) => Err(XParseError::InvalidNumber(num_str)),
-            }
+        let value = if has_dot {
+            num_str.parse::<f32>().ok().map(XValue::Float)
         } else {
-            match num_str.parse::<i32>() {
-                Ok(i) => Ok(Some(XValue::Integer(i))),
-                Err(_) => Err(XParseError::InvalidNumber(num_str)),
+            num_str.parse::<i32>().ok().map(XValue::Integer)
+        };
+        match value {
+            Some(value) => Ok(Some(value)),
+            None => Err(self.fail(XParseError::InvalidNumber(num_str), start..self.pos)),
+        }
+    }
+
+    /// Parse the rest of `(-number)`, the text of a negative literal, after its opening
+    /// parenthesis. The input is left as is when it is anything else.
+    ///
+    pub fn parse_negative_literal(&mut self) -> Result<Option<XValue>, XParseError>
+    {
+        let start = self.pos;
+        self.skip_whitespace();
+        if self.match_char('-') && self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            let value = self.parse_number()?;
+            self.skip_whitespace();
+            let negated = value.and_then(|value| value.unary(XUnaryOp::Neg));
+            if negated.is_some() && self.match_char(')') {
+                return Ok(negated);
             }
         }
+        self.pos = start;
+        Ok(None)
     }
 
     /// Parse identifier (required).
@@ -487,7 +643,7 @@ impl Parser
     {
         match self.parse_identifier_opt() {
             Some(ident) => Ok(ident),
-            None => Err(XParseError::InvalidIdentifier(String::new())),
+            None => Err(self.fail(XParseError::InvalidIdentifier(String::new()), self.here())),
         }
     }
 
@@ -517,6 +673,119 @@ impl Parser
         Some(self.input[start..self.pos].iter().collect())
     }
 
+    // ====================
+    // Error recovery.
+    // ====================
+
+    /// Parse an expression, recording an error instead of returning it.
+    ///
+    /// On error the input is skipped up to the next `,` or `)` of the enclosing call,
+    /// and a placeholder is returned so that the call can still be parsed.
+    ///
+    fn parse_recovering(&mut self) -> XExpr
+    {
+        let depth = self.open_parens.len();
+        match self.parse_expression() {
+            Ok(expr) => expr,
+            Err(error) => {
+                self.open_parens.truncate(depth);
+                self.report(error);
+                self.skip_to_delimiter();
+                XExpr::lit(0.0f32)
+            }
+        }
+    }
+
+    /// Consume the closing parenthesis of the innermost open one.
+    ///
+    fn expect_close(&mut self) -> Result<(), XParseError>
+    {
+        self.skip_whitespace();
+        let open = self.open_parens.pop();
+        if self.match_char(')') {
+            return Ok(());
+        }
+        let span = match (self.peek(), open) {
+            (None, Some(open)) => open..open + 1,
+            _ => self.here(),
+        };
+        Err(self.fail(XParseError::UnmatchedParenthesis, span))
+    }
+
+    /// Skip to the next `,` or `)` that is not nested in parentheses.
+    ///
+    fn skip_to_delimiter(&mut self)
+    {
+        let mut depth = 0;
+        while let Some(ch) = self.peek() {
+            match ch {
+                ',' | ')' if depth == 0 => break,
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                _ => {}
+            }
+            self.advance();
+        }
+    }
+
+    /// Remember the span of an error about to be returned.
+    ///
+    fn fail(&mut self, error: XParseError, span: Range<usize>) -> XParseError
+    {
+        self.error_span = Some(span);
+        error
+    }
+
+    /// Record an error with the span it was raised with.
+    ///
+    fn report(&mut self, error: XParseError)
+    {
+        let span = self.error_span.take().unwrap_or_else(|| self.here());
+        self.diagnostics.push(XParseDiagnostic {
+            error,
+            span: self.byte_offset(span.start)..self.byte_offset(span.end),
+        });
+    }
+
+    /// Error for the current character, or for the end of input.
+    ///
+    fn unexpected(&self) -> XParseError
+    {
+        match self.peek() {
+            Some(ch) => XParseError::UnexpectedToken(ch.to_string()),
+            None => XParseError::UnexpectedEndOfInput,
+        }
+    }
+
+    /// Span of the current character, or of the last one at the end of input.
+    ///
+    fn here(&self) -> Range<usize>
+    {
+        let start = self.pos.min(self.input.len().saturating_sub(1));
+        start..(start + 1).min(self.input.len())
+    }
+
+    /// Span of the name of the call whose parenthesis is the innermost open one.
+    ///
+    fn call_name_span(&self, name: &str) -> Range<usize>
+    {
+        let mut end = self.open_parens.last().copied().unwrap_or(self.pos);
+        while end > 0 && self.input[end - 1].is_whitespace() {
+            end -= 1;
+        }
+        end.saturating_sub(name.chars().count())..end
+    }
+
+    /// Byte offset of a character position.
+    ///
+    fn byte_offset(&self, pos: usize) -> usize
+    {
+        self.input[..pos.min(self.input.len())]
+            .iter()
+            .map(|ch| ch.len_utf8())
+            .sum()
+    }
+
     // ====================
     // Character helpers.
     // ====================
@@ -0,0 +1,199 @@
+use std::fmt;
+use std::ops::Range;
+
+use crate::gui::expr::xeval::{XEnv, XEvalError};
+use crate::gui::expr::xexpr::XExpr;
+use crate::gui::expr::xop::{XBinaryOp, XBuiltInOp};
+use crate::gui::expr::xval::XValue;
+
+/// Number of seeds a random expression is evaluated with at each point of a plot.
+///
+pub const RANDOM_SAMPLES: u64 = 32;
+
+// ====================
+// Plot variable.
+// ====================
+
+/// Variable an expression is plotted over.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum XPlotVariable
+{
+    /// The `age` attribute of a particle.
+    #[default]
+    Age,
+    /// The simulation time.
+    Time,
+}
+
+impl XPlotVariable
+{
+    /// Set the variable to `value` in an evaluation environment.
+    ///
+    pub fn apply(&self, env: &mut XEnv, value: f32)
+    {
+        match self {
+            XPlotVariable::Age => {
+                env.attributes
+                    .insert("age".to_string(), XValue::Float(value));
+            }
+            XPlotVariable::Time => env.time = value,
+        }
+    }
+}
+
+impl fmt::Display for XPlotVariable
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        match self {
+            XPlotVariable::Age => write!(f, "age"),
+            XPlotVariable::Time => write!(f, "time"),
+        }
+    }
+}
+
+// ====================
+// Plot data.
+// ====================
+
+/// One component of a plotted expression.
+///
+/// Holds the value at each point of the plot, and the lowest and highest values over the
+/// random samples. For expressions that are not random, all three are equal.
+///
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct XPlotCurve
+{
+    pub values: Vec<f32>,
+    pub min:    Vec<f32>,
+    pub max:    Vec<f32>,
+}
+
+/// Samples of an expression over a range of a variable, with one curve per component.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct XPlot
+{
+    pub variable:  XPlotVariable,
+    pub xs:        Vec<f32>,
+    pub curves:    Vec<XPlotCurve>,
+    pub is_random: bool,
+}
+
+impl XPlot
+{
+    /// Evaluate an expression at `count` evenly spaced points of `range`.
+    ///
+    /// Random expressions are evaluated with `RANDOM_SAMPLES` seeds of their own at each
+    /// point, so the bands do not follow the same few draws across the plot, and their curves
+    /// hold the mean of the samples. Integers and booleans are plotted as
+    /// floats.
+    ///
+    pub fn sample(
+        expr: &XExpr,
+        variable: XPlotVariable,
+        range: Range<f32>,
+        count: usize,
+        env: &XEnv,
+    ) -> Result<Self, XEvalError>
+    {
+        let is_random = expr.is_random();
+        let seeds = if is_random { RANDOM_SAMPLES } else { 1 };
+        let count = count.max(2);
+        let step = (range.end - range.start) / (count - 1) as f32;
+        let xs: Vec<f32> = (0..count).map(|i| range.start + step * i as f32).collect();
+
+        let mut curves: Vec<XPlotCurve> = Vec::new();
+        for (i, x) in xs.iter().enumerate() {
+            let mut env = env.clone();
+            variable.apply(&mut env, *x);
+
+            let mut samples = Vec::new();
+            for seed in 0..seeds {
+                let mut env = env.clone().with_seed(i as u64 * seeds + seed);
+                samples.push(plot_components(&expr.eval(&mut env)?));
+            }
+
+            if curves.is_empty() {
+                curves = vec![XPlotCurve::default(); samples[0].len()];
+            }
+            for (component, curve) in curves.iter_mut().enumerate() {
+                let values = samples.iter().filter_map(|sample| sample.get(component));
+                let (sum, min, max) = values.fold(
+                    (0.0, f32::INFINITY, f32::NEG_INFINITY),
+                    |(sum, min, max), v| (sum + v, min.min(*v), max.max(*v)),
+                );
+                curve.values.push(sum / samples.len() as f32);
+                curve.min.push(min);
+                curve.max.push(max);
+            }
+        }
+
+        Ok(Self {
+            variable,
+            xs,
+            curves,
+            is_random,
+        })
+    }
+
+    /// Lowest and highest finite values of all curves, widened when the plot is flat.
+    ///
+    pub fn y_range(&self) -> Range<f32>
+    {
+        let values = self
+            .curves
+            .iter()
+            .flat_map(|curve| curve.min.iter().chain(&curve.max))
+            .filter(|v| v.is_finite());
+        let (low, high) = values.fold((f32::INFINITY, f32::NEG_INFINITY), |(low, high), v| {
+            (low.min(*v), high.max(*v))
+        });
+        if low > high {
+            return -1.0..1.0;
+        }
+        if (high - low).abs() < f32::EPSILON {
+            return low - 1.0..high + 1.0;
+        }
+        low..high
+    }
+}
+
+fn plot_components(value: &XValue) -> Vec<f32>
+{
+    match value {
+        XValue::Integer(i) => vec![*i as f32],
+        XValue::Bool(b) => vec![if *b { 1.0 } else { 0.0 }],
+        _ => value.float_components().unwrap_or_default(),
+    }
+}
+
+impl XExpr
+{
+    /// Whether the expression draws random values.
+    ///
+    pub fn is_random(&self) -> bool
+    {
+        match self {
+            XExpr::BuiltIn(op) => matches!(
+                op,
+                XBuiltInOp::Rand | XBuiltInOp::RandVec2 | XBuiltInOp::RandVec3
+            ),
+            XExpr::Lit(_) | XExpr::Attr(_) | XExpr::Prop(_) | XExpr::Var(_) => false,
+            XExpr::Unary { expr, .. } | XExpr::Swizzle { expr, .. } => expr.is_random(),
+            XExpr::Binary { left, op, right } => {
+                matches!(op, XBinaryOp::Uniform | XBinaryOp::Normal)
+                    || left.is_random()
+                    || right.is_random()
+            }
+            XExpr::Ternary {
+                first,
+                second,
+                third,
+                ..
+            } => first.is_random() || second.is_random() || third.is_random(),
+            XExpr::Let { value, body, .. } => value.is_random() || body.is_random(),
+        }
+    }
+}
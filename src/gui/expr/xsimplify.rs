@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+
+use crate::gui::expr::xexpr::XExpr;
+use crate::gui::expr::xop::{XBinaryOp, XUnaryOp};
+use crate::gui::expr::xval::XValue;
+
+// ====================
+// Simplification.
+// ====================
+
+impl XExpr
+{
+    /// Simplify an expression without changing its value.
+    ///
+    /// Folds operators whose operands are literals, removes identities such as `x * 1.0`
+    /// and `x + 0.0`, turns `vecN` constructors of constants into vector literals, and
+    /// inlines constant `let` bindings. Random functions and non-finite results are never
+    /// folded.
+    ///
+    pub fn simplify(&self) -> XExpr
+    {
+        self.simplify_in(&HashMap::new())
+    }
+
+    fn simplify_in(&self, constants: &HashMap<String, XValue>) -> XExpr
+    {
+        match self {
+            XExpr::Lit(_) | XExpr::Attr(_) | XExpr::Prop(_) | XExpr::BuiltIn(_) => self.clone(),
+            XExpr::Var(name) => match constants.get(name) {
+                Some(value) => XExpr::Lit(value.clone()),
+                None => self.clone(),
+            },
+            XExpr::Let { name, value, body } => {
+                let value = value.simplify_in(constants);
+                let mut constants = constants.clone();
+                if let XExpr::Lit(literal) = &value {
+                    constants.insert(name.clone(), literal.clone());
+                    return body.simplify_in(&constants);
+                }
+                // The binding shadows any outer constant of the same name.
+                constants.remove(name);
+                let body = body.simplify_in(&constants);
+                if body.uses_var(name) {
+                    XExpr::bind(name.clone(), value, body)
+                } else {
+                    body
+                }
+            }
+            XExpr::Unary { op, expr } => {
+                let expr = expr.simplify_in(constants);
+                if let XExpr::Lit(value) = &expr {
+                    if let Some(folded) = value.unary(*op).filter(XValue::is_finite) {
+                        return XExpr::Lit(folded);
+                    }
+                }
+                match (op, expr) {
+                    (
+                        XUnaryOp::Neg,
+                        XExpr::Unary {
+                            op: XUnaryOp::Neg,
+                            expr,
+                        },
+                    ) => *expr,
+                    (op, expr) => XExpr::unary(*op, expr),
+                }
+            }
+            XExpr::Binary { left, op, right } => {
+                let left = left.simplify_in(constants);
+                let right = right.simplify_in(constants);
+                if let (XExpr::Lit(l), XExpr::Lit(r)) = (&left, &right) {
+                    if let Some(folded) = XValue::binary(*op, l, r).filter(XValue::is_finite) {
+                        return XExpr::Lit(folded);
+                    }
+                }
+                match op {
+                    XBinaryOp::Add if is_literal(&right, 0.0) => left,
+                    XBinaryOp::Add if is_literal(&left, 0.0) => right,
+                    XBinaryOp::Sub if is_literal(&right, 0.0) => left,
+                    XBinaryOp::Mul if is_literal(&right, 1.0) => left,
+                    XBinaryOp::Mul if is_literal(&left, 1.0) => right,
+                    XBinaryOp::Div if is_literal(&right, 1.0) => left,
+                    _ => XExpr::binary(left, *op, right),
+                }
+            }
+            XExpr::Ternary {
+                op,
+                first,
+                second,
+                third,
+            } => {
+                let first = first.simplify_in(constants);
+                let second = second.simplify_in(constants);
+                let third = third.simplify_in(constants);
+                if let (XExpr::Lit(a), XExpr::Lit(b), XExpr::Lit(c)) = (&first, &second, &third) {
+                    let folded = XValue::ternary(*op, a, b, c).filter(XValue::is_finite);
+                    if let Some(folded) = folded {
+                        return XExpr::Lit(folded);
+                    }
+                }
+                XExpr::ternary(*op, first, second, third)
+            }
+            XExpr::Swizzle { expr, components } => {
+                let expr = expr.simplify_in(constants);
+                let picked = match &expr {
+                    XExpr::Lit(value) => value.swizzle(components),
+                    _ => None,
+                };
+                match picked {
+                    Some(value) => XExpr::Lit(value),
+                    None => XExpr::swizzle(expr, components.clone()),
+                }
+            }
+        }
+    }
+
+    /// Whether the local binding `name` is referenced, outside of bindings shadowing it.
+    ///
+    pub fn uses_var(&self, name: &str) -> bool
+    {
+        match self {
+            XExpr::Var(var) => var == name,
+            XExpr::Lit(_) | XExpr::Attr(_) | XExpr::Prop(_) | XExpr::BuiltIn(_) => false,
+            XExpr::Unary { expr, .. } | XExpr::Swizzle { expr, .. } => expr.uses_var(name),
+            XExpr::Binary { left, right, .. } => left.uses_var(name) || right.uses_var(name),
+            XExpr::Ternary {
+                first,
+                second,
+                third,
+                ..
+            } => first.uses_var(name) || second.uses_var(name) || third.uses_var(name),
+            XExpr::Let {
+                name: bound,
+                value,
+                body,
+            } => value.uses_var(name) || (bound != name && body.uses_var(name)),
+        }
+    }
+
+    /// Number of references to the local binding `name`, outside of bindings shadowing it.
+    ///
+    pub fn count_var(&self, name: &str) -> usize
+    {
+        match self {
+            XExpr::Var(var) => usize::from(var == name),
+            XExpr::Lit(_) | XExpr::Attr(_) | XExpr::Prop(_) | XExpr::BuiltIn(_) => 0,
+            XExpr::Unary { expr, .. } | XExpr::Swizzle { expr, .. } => expr.count_var(name),
+            XExpr::Binary { left, right, .. } => left.count_var(name) + right.count_var(name),
+            XExpr::Ternary {
+                first,
+                second,
+                third,
+                ..
+            } => first.count_var(name) + second.count_var(name) + third.count_var(name),
+            XExpr::Let {
+                name: bound,
+                value,
+                body,
+            } => {
+                let in_body = if bound == name {
+                    0
+                } else {
+                    body.count_var(name)
+                };
+                value.count_var(name) + in_body
+            }
+        }
+    }
+}
+
+fn is_literal(expr: &XExpr, expected: f32) -> bool
+{
+    match expr {
+        XExpr::Lit(XValue::Float(f)) => *f == expected,
+        XExpr::Lit(XValue::Integer(i)) => *i as f32 == expected,
+        _ => false,
+    }
+}
@@ -0,0 +1,391 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::gui::expr::xexpr::XExpr;
+use crate::gui::expr::xop::{XBinaryOp, XBuiltInOp, XTernaryOp, XUnaryOp};
+use crate::gui::expr::xval::XExprReturnType;
+
+// ====================
+// Type errors.
+// ====================
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum XTypeError
+{
+    UnknownAttribute(String),
+    UnknownProperty(String),
+    UnknownVariable(String),
+    /// Operands of `expr` do not have types its operator accepts.
+    Mismatch
+    {
+        expr:     String,
+        expected: String,
+        found:    Vec<XExprReturnType>,
+    },
+    /// Function the Hanabi expression graph has no equivalent for.
+    NotInHanabi(String),
+}
+
+impl XTypeError
+{
+    fn mismatch(expr: &XExpr, expected: impl Into<String>, found: &[XExprReturnType]) -> Self
+    {
+        XTypeError::Mismatch {
+            expr:     expr.to_string(),
+            expected: expected.into(),
+            found:    found.to_vec(),
+        }
+    }
+}
+
+impl fmt::Display for XTypeError
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        match self {
+            XTypeError::UnknownAttribute(name) => write!(f, "Unknown attribute: '{}'", name),
+            XTypeError::UnknownProperty(name) => write!(f, "Unknown property: '{}'", name),
+            XTypeError::UnknownVariable(name) => write!(f, "Unknown variable: '{}'", name),
+            XTypeError::Mismatch {
+                expr,
+                expected,
+                found,
+            } => {
+                let found = found
+                    .iter()
+                    .map(|t| t.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                write!(f, "In '{}': expected {}, found {}", expr, expected, found)
+            }
+            XTypeError::NotInHanabi(name) => write!(f, "'{}' is not supported by Hanabi", name),
+        }
+    }
+}
+
+impl std::error::Error for XTypeError {}
+
+// ====================
+// Type checker.
+// ====================
+
+impl XExpr
+{
+    /// Infer return type of expression.
+    ///
+    /// Returns `None` for unknown attributes or properties, and `Error` for type errors
+    /// that [`XExpr::check_type`] describes.
+    ///
+    pub fn get_result_type(
+        &self,
+        attributes: &HashMap<String, XExprReturnType>,
+        props: &HashMap<String, XExprReturnType>,
+    ) -> Option<XExprReturnType>
+    {
+        self.get_result_type_in(attributes, props, &HashMap::new())
+    }
+
+    /// Infer return type of expression whose free variables have the types of `locals`.
+    ///
+    pub fn get_result_type_in(
+        &self,
+        attributes: &HashMap<String, XExprReturnType>,
+        props: &HashMap<String, XExprReturnType>,
+        locals: &HashMap<String, XExprReturnType>,
+    ) -> Option<XExprReturnType>
+    {
+        match self.check_type_in(attributes, props, locals) {
+            Ok(result_type) => Some(result_type),
+            Err(
+                XTypeError::UnknownAttribute(_)
+                | XTypeError::UnknownProperty(_)
+                | XTypeError::UnknownVariable(_),
+            ) => None,
+            Err(XTypeError::Mismatch { .. } | XTypeError::NotInHanabi(_)) => {
+                Some(XExprReturnType::Error)
+            }
+        }
+    }
+
+    /// Check the types of an expression and infer its return type.
+    ///
+    /// Errors name the innermost subexpression whose operands have the wrong types.
+    /// Comparisons of vectors are component-wise, and only valid directly under `all` or
+    /// `any`, which reduce them to a bool.
+    ///
+    pub fn check_type(
+        &self,
+        attributes: &HashMap<String, XExprReturnType>,
+        props: &HashMap<String, XExprReturnType>,
+    ) -> Result<XExprReturnType, XTypeError>
+    {
+        self.check_type_in(attributes, props, &HashMap::new())
+    }
+
+    /// Check the types of an expression whose free variables have the types of `locals`.
+    ///
+    pub fn check_type_in(
+        &self,
+        attributes: &HashMap<String, XExprReturnType>,
+        props: &HashMap<String, XExprReturnType>,
+        locals: &HashMap<String, XExprReturnType>,
+    ) -> Result<XExprReturnType, XTypeError>
+    {
+        use XExprReturnType as T;
+
+        match self {
+            XExpr::Lit(value) => Ok(value.get_type()),
+            XExpr::Attr(name) => attributes
+                .get(name)
+                .cloned()
+                .ok_or_else(|| XTypeError::UnknownAttribute(name.clone())),
+            XExpr::Prop(name) => props
+                .get(name)
+                .cloned()
+                .ok_or_else(|| XTypeError::UnknownProperty(name.clone())),
+            XExpr::Var(name) => locals
+                .get(name)
+                .cloned()
+                .ok_or_else(|| XTypeError::UnknownVariable(name.clone())),
+            XExpr::Let { name, value, body } => {
+                let value_type = value.check_type_in(attributes, props, locals)?;
+                let mut locals = locals.clone();
+                locals.insert(name.clone(), value_type);
+                body.check_type_in(attributes, props, &locals)
+            }
+            XExpr::BuiltIn(op) => Ok(match op {
+                XBuiltInOp::RandVec2 => T::Vec2,
+                XBuiltInOp::RandVec3 => T::Vec3,
+                _ => T::Float,
+            }),
+            XExpr::Unary { op, expr } => {
+                let inner = match (op, &**expr) {
+                    (
+                        XUnaryOp::All | XUnaryOp::Any,
+                        XExpr::Binary {
+                            left,
+                            op: cmp,
+                            right,
+                        },
+                    ) if cmp.is_comparison() => {
+                        expr.check_comparison(left, right, true, attributes, props, locals)?
+                    }
+                    _ => expr.check_type_in(attributes, props, locals)?,
+                };
+                let (result, expected) = match op {
+                    XUnaryOp::Abs | XUnaryOp::Neg => (
+                        (inner.width().is_some() || inner == T::Integer).then(|| inner.clone()),
+                        "a float, int or vector",
+                    ),
+                    XUnaryOp::Sin | XUnaryOp::Cos | XUnaryOp::Sign | XUnaryOp::Tan => {
+                        (inner.width().map(|_| inner.clone()), "a float or vector")
+                    }
+                    XUnaryOp::Norm => (inner.is_vector().then(|| inner.clone()), "a vector"),
+                    XUnaryOp::Length => (inner.width().map(|_| T::Float), "a float or vector"),
+                    XUnaryOp::All | XUnaryOp::Any => (
+                        (inner == T::Bool || inner.is_vector()).then_some(T::Bool),
+                        "a bool or vector",
+                    ),
+                };
+                result.ok_or_else(|| XTypeError::mismatch(self, expected, &[inner]))
+            }
+            XExpr::Binary { left, op, right } => {
+                let left_type = left.check_type_in(attributes, props, locals)?;
+                let right_type = right.check_type_in(attributes, props, locals)?;
+                let same_width = left_type == right_type && left_type.width().is_some();
+
+                let (result, expected) = match op {
+                    XBinaryOp::Add
+                    | XBinaryOp::Sub
+                    | XBinaryOp::Mul
+                    | XBinaryOp::Div
+                    | XBinaryOp::Min
+                    | XBinaryOp::Max => (
+                        match (&left_type, &right_type) {
+                            _ if same_width => Some(left_type.clone()),
+                            (T::Integer, T::Integer) => Some(T::Integer),
+                            (T::Float, vector) | (vector, T::Float) if vector.is_vector() => {
+                                Some(vector.clone())
+                            }
+                            _ => None,
+                        },
+                        "operands of the same type, or a float and a vector".to_string(),
+                    ),
+                    XBinaryOp::Dot => (
+                        (same_width && left_type.is_vector()).then_some(T::Float),
+                        "two vectors of the same size".to_string(),
+                    ),
+                    XBinaryOp::Cross => (
+                        (left_type == T::Vec3 && right_type == T::Vec3).then_some(T::Vec3),
+                        "vec3, vec3".to_string(),
+                    ),
+                    XBinaryOp::Vec2 | XBinaryOp::Vec3 | XBinaryOp::Vec4 => {
+                        let size = match op {
+                            XBinaryOp::Vec2 => 2,
+                            XBinaryOp::Vec3 => 3,
+                            _ => 4,
+                        };
+                        let width = left_type
+                            .width()
+                            .zip(right_type.width())
+                            .map(|(l, r)| l + r);
+                        (
+                            width.filter(|&width| width == size).and_then(T::with_width),
+                            format!("floats and vectors with {} components in total", size),
+                        )
+                    }
+                    XBinaryOp::Lt
+                    | XBinaryOp::Lte
+                    | XBinaryOp::Ge
+                    | XBinaryOp::Gte
+                    | XBinaryOp::Eq
+                    | XBinaryOp::Neq => {
+                        return self
+                            .check_comparison(left, right, false, attributes, props, locals);
+                    }
+                    XBinaryOp::Step | XBinaryOp::Uniform => (
+                        same_width.then(|| left_type.clone()),
+                        "two floats or vectors of the same type".to_string(),
+                    ),
+                    // normal(mean, sd) also accepts a scalar deviation for vectors.
+                    XBinaryOp::Normal => (
+                        (left_type.width().is_some()
+                            && (right_type == left_type || right_type == T::Float))
+                            .then(|| left_type.clone()),
+                        "a float or vector mean, and a deviation of the same type or float"
+                            .to_string(),
+                    ),
+                    XBinaryOp::Distance => (
+                        same_width.then_some(T::Float),
+                        "two floats or vectors of the same type".to_string(),
+                    ),
+                };
+                let found = [left_type, right_type];
+                result.ok_or_else(|| XTypeError::mismatch(self, expected, &found))
+            }
+            XExpr::Ternary {
+                op,
+                first,
+                second,
+                third,
+            } => {
+                let first_type = first.check_type_in(attributes, props, locals)?;
+                let second_type = second.check_type_in(attributes, props, locals)?;
+                let third_type = third.check_type_in(attributes, props, locals)?;
+                let is_float_or_vec = first_type.width().is_some() && first_type == second_type;
+
+                let (valid, expected) = match op {
+                    // mix(a, b, t) also accepts a scalar factor for vectors.
+                    XTernaryOp::Mix => (
+                        is_float_or_vec && (third_type == first_type || third_type == T::Float),
+                        "two floats or vectors of the same type, and a factor of that type",
+                    ),
+                    XTernaryOp::Clamp => (
+                        is_float_or_vec && third_type == first_type,
+                        "three floats or vectors of the same type",
+                    ),
+                };
+                if valid {
+                    Ok(first_type)
+                } else {
+                    Err(XTypeError::mismatch(
+                        self,
+                        expected,
+                        &[first_type, second_type, third_type],
+                    ))
+                }
+            }
+            XExpr::Swizzle { expr, components } => {
+                let inner = expr.check_type_in(attributes, props, locals)?;
+                let width = inner.width().filter(|&width| width > 1);
+                let result = width
+                    .zip(Self::swizzle_indices(components))
+                    .filter(|(width, indices)| indices.iter().all(|i| i < width))
+                    .and_then(|(_, indices)| T::with_width(indices.len()));
+                result.ok_or_else(|| {
+                    let expected = format!("a vector with components '{}'", components);
+                    XTypeError::mismatch(self, expected, &[inner])
+                })
+            }
+        }
+    }
+}
+
+impl XExpr
+{
+    /// Check a comparison, whose operands are two scalars of the same type, or two vectors
+    /// of the same type when `reduced` by `all` or `any`.
+    ///
+    fn check_comparison(
+        &self,
+        left: &XExpr,
+        right: &XExpr,
+        reduced: bool,
+        attributes: &HashMap<String, XExprReturnType>,
+        props: &HashMap<String, XExprReturnType>,
+        locals: &HashMap<String, XExprReturnType>,
+    ) -> Result<XExprReturnType, XTypeError>
+    {
+        use XExprReturnType as T;
+
+        let left_type = left.check_type_in(attributes, props, locals)?;
+        let right_type = right.check_type_in(attributes, props, locals)?;
+        let valid = match (&left_type, &right_type) {
+            (T::Float, T::Float) | (T::Integer, T::Integer) => true,
+            _ => reduced && left_type == right_type && left_type.is_vector(),
+        };
+        if valid {
+            Ok(T::Bool)
+        } else {
+            let expected = "two scalars of the same type, or two vectors of the same type \
+                            in all() or any()";
+            Err(XTypeError::mismatch(
+                self,
+                expected,
+                &[left_type, right_type],
+            ))
+        }
+    }
+}
+
+// ====================
+// Hanabi support.
+// ====================
+
+impl XExpr
+{
+    /// Check that every built-in of the expression can be read in a Hanabi graph.
+    ///
+    /// The other built-ins are still evaluated in previews and plots.
+    ///
+    pub fn check_hanabi(&self) -> Result<(), XTypeError>
+    {
+        let unsupported = |name: String| Err(XTypeError::NotInHanabi(name));
+        match self {
+            XExpr::Lit(_) | XExpr::Attr(_) | XExpr::Prop(_) | XExpr::Var(_) => Ok(()),
+            XExpr::BuiltIn(op) => match op {
+                XBuiltInOp::AlphaCutoff | XBuiltInOp::ParticleId => unsupported(op.to_string()),
+                _ => Ok(()),
+            },
+            XExpr::Unary { expr, .. } | XExpr::Swizzle { expr, .. } => expr.check_hanabi(),
+            XExpr::Binary { left, right, .. } => {
+                left.check_hanabi()?;
+                right.check_hanabi()
+            }
+            XExpr::Ternary {
+                first,
+                second,
+                third,
+                ..
+            } => {
+                first.check_hanabi()?;
+                second.check_hanabi()?;
+                third.check_hanabi()
+            }
+            XExpr::Let { value, body, .. } => {
+                value.check_hanabi()?;
+                body.check_hanabi()
+            }
+        }
+    }
+}
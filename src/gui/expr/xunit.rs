@@ -0,0 +1,520 @@
+use std::collections::HashMap;
+use std::f32::consts::PI;
+use std::fmt;
+use std::ops::{Div, Mul};
+
+use crate::gui::expr::xexpr::XExpr;
+use crate::gui::expr::xop::{XBinaryOp, XBuiltInOp, XTernaryOp, XUnaryOp};
+use crate::gui::expr::xval::XValue;
+
+/// Factor from degrees to radians.
+///
+pub const DEGREES_TO_RADIANS: f32 = PI / 180.0;
+
+/// Unit of positions and lengths, in world space.
+///
+pub const DISTANCE: XUnit = XUnit::new(XBaseUnit::WorldUnits);
+
+// ====================
+// Quantities.
+// ====================
+
+/// Physical dimension of a value, as powers of distance, time and angle.
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct XQuantity
+{
+    pub distance: i8,
+    pub time:     i8,
+    pub angle:    i8,
+}
+
+impl XQuantity
+{
+    pub const NONE: Self = Self::new(0, 0, 0);
+    pub const DISTANCE: Self = Self::new(1, 0, 0);
+    pub const TIME: Self = Self::new(0, 1, 0);
+    pub const ANGLE: Self = Self::new(0, 0, 1);
+
+    pub const fn new(distance: i8, time: i8, angle: i8) -> Self
+    {
+        Self {
+            distance,
+            time,
+            angle,
+        }
+    }
+
+    /// The quantity divided by a time, as a speed is a distance per second.
+    ///
+    pub fn per_second(self) -> Self
+    {
+        Self::new(self.distance, self.time - 1, self.angle)
+    }
+
+    pub fn is_none(&self) -> bool
+    {
+        *self == Self::NONE
+    }
+
+    /// Whether values of both quantities can be added or compared. Plain numbers scale
+    /// any quantity, so they are compatible with all of them.
+    ///
+    pub fn is_compatible(&self, other: &Self) -> bool
+    {
+        self == other || self.is_none() || other.is_none()
+    }
+}
+
+impl Mul for XQuantity
+{
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self
+    {
+        Self::new(
+            self.distance + other.distance,
+            self.time + other.time,
+            self.angle + other.angle,
+        )
+    }
+}
+
+impl Div for XQuantity
+{
+    type Output = Self;
+
+    fn div(self, other: Self) -> Self
+    {
+        Self::new(
+            self.distance - other.distance,
+            self.time - other.time,
+            self.angle - other.angle,
+        )
+    }
+}
+
+impl fmt::Display for XQuantity
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        if self.is_none() {
+            return write!(f, "a plain number");
+        }
+        let powers = [
+            ("distance", self.distance),
+            ("time", self.time),
+            ("angle", self.angle),
+        ];
+        let part = |name: &str, power: i8| match power.abs() {
+            1 => name.to_string(),
+            2 => format!("{}²", name),
+            3 => format!("{}³", name),
+            n => format!("{}^{}", name, n),
+        };
+        let numerator: Vec<String> = powers
+            .iter()
+            .filter(|(_, power)| *power > 0)
+            .map(|(name, power)| part(name, *power))
+            .collect();
+        let denominator: Vec<String> = powers
+            .iter()
+            .filter(|(_, power)| *power < 0)
+            .map(|(name, power)| part(name, *power))
+            .collect();
+        match (numerator.is_empty(), denominator.is_empty()) {
+            (_, true) => write!(f, "{}", numerator.join("·")),
+            (true, false) => write!(f, "1/{}", denominator.join("·")),
+            (false, false) => write!(f, "{}/{}", numerator.join("·"), denominator.join("·")),
+        }
+    }
+}
+
+// ====================
+// Units.
+// ====================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XBaseUnit
+{
+    Pixels,
+    WorldUnits,
+    Seconds,
+    Radians,
+    /// Angles typed in degrees, and stored in radians.
+    Degrees,
+}
+
+impl XBaseUnit
+{
+    pub fn symbol(&self) -> &'static str
+    {
+        match self {
+            XBaseUnit::Pixels => "px",
+            XBaseUnit::WorldUnits => "u",
+            XBaseUnit::Seconds => "s",
+            XBaseUnit::Radians => "rad",
+            XBaseUnit::Degrees => "°",
+        }
+    }
+
+    pub fn quantity(&self) -> XQuantity
+    {
+        match self {
+            XBaseUnit::Pixels | XBaseUnit::WorldUnits => XQuantity::DISTANCE,
+            XBaseUnit::Seconds => XQuantity::TIME,
+            XBaseUnit::Radians | XBaseUnit::Degrees => XQuantity::ANGLE,
+        }
+    }
+}
+
+/// Unit annotation of an expression field, e.g. world units per second for a speed.
+///
+/// Fields without a base unit are rates, such as a drag in `1/s`.
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct XUnit
+{
+    pub base:       Option<XBaseUnit>,
+    pub per_second: u8,
+}
+
+impl XUnit
+{
+    pub const fn new(base: XBaseUnit) -> Self
+    {
+        Self {
+            base:       Some(base),
+            per_second: 0,
+        }
+    }
+
+    /// Unit of a rate, such as a drag, in `1/s`.
+    ///
+    pub const fn rate() -> Self
+    {
+        Self {
+            base:       None,
+            per_second: 1,
+        }
+    }
+
+    /// The unit divided by a second, called twice for accelerations.
+    ///
+    pub const fn per_second(mut self) -> Self
+    {
+        self.per_second += 1;
+        self
+    }
+
+    /// Unit of values of a quantity, in world units, seconds and radians. Returns `None`
+    /// for plain numbers and for quantities with no unit, such as areas.
+    ///
+    pub fn from_quantity(quantity: XQuantity) -> Option<Self>
+    {
+        let per_second = quantity.time.min(0).unsigned_abs();
+        let base = match XQuantity::new(quantity.distance, quantity.time.max(0), quantity.angle) {
+            XQuantity::NONE if per_second == 0 => return None,
+            XQuantity::NONE => None,
+            XQuantity::DISTANCE => Some(XBaseUnit::WorldUnits),
+            XQuantity::TIME => Some(XBaseUnit::Seconds),
+            XQuantity::ANGLE => Some(XBaseUnit::Radians),
+            _ => return None,
+        };
+        Some(Self { base, per_second })
+    }
+
+    pub fn is_degrees(&self) -> bool
+    {
+        self.base == Some(XBaseUnit::Degrees)
+    }
+
+    pub fn quantity(&self) -> XQuantity
+    {
+        let base = self.base.map_or(XQuantity::NONE, |base| base.quantity());
+        (0..self.per_second).fold(base, |quantity, _| quantity.per_second())
+    }
+}
+
+impl fmt::Display for XUnit
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        let base = self
+            .base
+            .map_or(if self.per_second > 0 { "1" } else { "" }, |base| {
+                base.symbol()
+            });
+        match self.per_second {
+            0 => write!(f, "{}", base),
+            1 => write!(f, "{}/s", base),
+            2 => write!(f, "{}/s²", base),
+            n => write!(f, "{}/s^{}", base, n),
+        }
+    }
+}
+
+// ====================
+// Degrees.
+// ====================
+
+impl XExpr
+{
+    /// Convert an expression typed in degrees to radians.
+    ///
+    pub fn degrees_to_radians(&self) -> XExpr
+    {
+        match self {
+            // Whole angles are typed as integers, which do not multiply floats.
+            XExpr::Lit(XValue::Integer(degrees)) => {
+                XExpr::lit(*degrees as f32).degrees_to_radians()
+            }
+            _ => XExpr::binary(self.clone(), XBinaryOp::Mul, XExpr::lit(DEGREES_TO_RADIANS)),
+        }
+    }
+
+    /// Convert an expression in radians back to the degrees it was typed in.
+    ///
+    /// Undoes `degrees_to_radians`, and rounds constant angles that were folded by the
+    /// conversion to Hanabi.
+    ///
+    pub fn radians_to_degrees(&self) -> XExpr
+    {
+        match self {
+            XExpr::Binary {
+                left,
+                op: XBinaryOp::Mul,
+                right,
+            } if **right == XExpr::lit(DEGREES_TO_RADIANS) => (**left).clone(),
+            XExpr::Lit(XValue::Float(radians)) => XExpr::lit(round_degrees(radians.to_degrees())),
+            _ => XExpr::binary(
+                self.clone(),
+                XBinaryOp::Mul,
+                XExpr::lit(1.0 / DEGREES_TO_RADIANS),
+            ),
+        }
+    }
+}
+
+/// Round an angle to a thousandth of a degree when that is within float precision.
+///
+fn round_degrees(degrees: f32) -> f32
+{
+    let rounded = (degrees * 1000.0).round() / 1000.0;
+    if (rounded - degrees).abs() <= degrees.abs() * 1e-6 {
+        rounded
+    } else {
+        degrees
+    }
+}
+
+// ====================
+// Unit checker.
+// ====================
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum XUnitWarning
+{
+    /// Operands of `expr` are added, compared or interpolated but have different quantities.
+    Mixed
+    {
+        expr:  String,
+        left:  XQuantity,
+        right: XQuantity,
+    },
+    /// A trigonometric function is applied to a distance.
+    NotAnAngle
+    {
+        expr: String, found: XQuantity
+    },
+    /// The expression does not have the quantity of its field.
+    Mismatch
+    {
+        expected: XQuantity,
+        found:    XQuantity,
+    },
+}
+
+impl fmt::Display for XUnitWarning
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        match self {
+            XUnitWarning::Mixed { expr, left, right } => {
+                write!(f, "In '{}': mixes {} and {}", expr, left, right)
+            }
+            XUnitWarning::NotAnAngle { expr, found } => {
+                write!(f, "In '{}': expected an angle, found {}", expr, found)
+            }
+            XUnitWarning::Mismatch { expected, found } => {
+                write!(f, "Expected {}, found {}", expected, found)
+            }
+        }
+    }
+}
+
+/// Quantity of a subexpression, as far as it is known.
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Dim
+{
+    /// A literal, which takes the quantity of what it is combined with.
+    Any,
+    Known(XQuantity),
+    /// A property or unknown attribute, which is never warned about.
+    Unknown,
+}
+
+impl XExpr
+{
+    /// Check that an expression does not mix quantities, such as adding a time to a
+    /// distance, and that it has the `expected` quantity of its field.
+    ///
+    /// Attributes have the quantities of `attributes`, and properties are not checked.
+    /// Plain numbers, such as `alpha` or `age / lifetime`, combine with any quantity.
+    ///
+    pub fn check_units(
+        &self,
+        attributes: &HashMap<String, XQuantity>,
+        expected: Option<XQuantity>,
+    ) -> Vec<XUnitWarning>
+    {
+        let mut warnings = Vec::new();
+        let dim = self.dim_in(attributes, &HashMap::new(), &mut warnings);
+        if let (Some(expected), Dim::Known(found)) = (expected, dim) {
+            if !expected.is_compatible(&found) {
+                warnings.push(XUnitWarning::Mismatch { expected, found });
+            }
+        }
+        warnings
+    }
+
+    fn dim_in(
+        &self,
+        attributes: &HashMap<String, XQuantity>,
+        locals: &HashMap<String, Dim>,
+        warnings: &mut Vec<XUnitWarning>,
+    ) -> Dim
+    {
+        let dim = |expr: &XExpr, warnings: &mut Vec<XUnitWarning>| {
+            expr.dim_in(attributes, locals, warnings)
+        };
+
+        match self {
+            XExpr::Lit(_) => Dim::Any,
+            XExpr::Attr(name) => attributes
+                .get(name)
+                .map_or(Dim::Unknown, |q| Dim::Known(*q)),
+            XExpr::Prop(_) => Dim::Unknown,
+            XExpr::Var(name) => locals.get(name).copied().unwrap_or(Dim::Unknown),
+            XExpr::BuiltIn(op) => Dim::Known(match op {
+                XBuiltInOp::Time | XBuiltInOp::DeltaTime => XQuantity::TIME,
+                _ => XQuantity::NONE,
+            }),
+            XExpr::Let { name, value, body } => {
+                let value = dim(value, warnings);
+                let mut locals = locals.clone();
+                locals.insert(name.clone(), value);
+                body.dim_in(attributes, &locals, warnings)
+            }
+            XExpr::Unary { op, expr } => {
+                let inner = dim(expr, warnings);
+                match op {
+                    XUnaryOp::Sin | XUnaryOp::Cos | XUnaryOp::Tan => {
+                        // Times are let through, as in `sin(time * frequency)`.
+                        if let Dim::Known(found) = inner {
+                            if found.distance != 0 {
+                                warnings.push(XUnitWarning::NotAnAngle {
+                                    expr: self.to_string(),
+                                    found,
+                                });
+                            }
+                        }
+                        Dim::Known(XQuantity::NONE)
+                    }
+                    XUnaryOp::Norm | XUnaryOp::Sign | XUnaryOp::All | XUnaryOp::Any => {
+                        Dim::Known(XQuantity::NONE)
+                    }
+                    XUnaryOp::Abs | XUnaryOp::Neg | XUnaryOp::Length => inner,
+                }
+            }
+            XExpr::Binary { left, op, right } => {
+                let (l, r) = (dim(left, warnings), dim(right, warnings));
+                match op {
+                    XBinaryOp::Mul | XBinaryOp::Dot | XBinaryOp::Cross => match (l, r) {
+                        (Dim::Any, other) | (other, Dim::Any) => other,
+                        (Dim::Known(a), Dim::Known(b)) => Dim::Known(a * b),
+                        _ => Dim::Unknown,
+                    },
+                    XBinaryOp::Div => match (l, r) {
+                        (other, Dim::Any) => other,
+                        (Dim::Any, Dim::Known(b)) => Dim::Known(XQuantity::NONE / b),
+                        (Dim::Known(a), Dim::Known(b)) => Dim::Known(a / b),
+                        _ => Dim::Unknown,
+                    },
+                    XBinaryOp::Lt
+                    | XBinaryOp::Lte
+                    | XBinaryOp::Ge
+                    | XBinaryOp::Gte
+                    | XBinaryOp::Eq
+                    | XBinaryOp::Neq
+                    | XBinaryOp::Step => {
+                        self.same_dim(l, r, warnings);
+                        Dim::Known(XQuantity::NONE)
+                    }
+                    XBinaryOp::Add
+                    | XBinaryOp::Sub
+                    | XBinaryOp::Min
+                    | XBinaryOp::Max
+                    | XBinaryOp::Distance
+                    | XBinaryOp::Uniform
+                    | XBinaryOp::Normal
+                    | XBinaryOp::Vec2
+                    | XBinaryOp::Vec3
+                    | XBinaryOp::Vec4 => self.same_dim(l, r, warnings),
+                }
+            }
+            XExpr::Ternary {
+                op,
+                first,
+                second,
+                third,
+            } => {
+                let a = dim(first, warnings);
+                let (b, c) = (dim(second, warnings), dim(third, warnings));
+                match op {
+                    XTernaryOp::Mix => self.same_dim(a, b, warnings),
+                    XTernaryOp::Clamp => {
+                        let bounds = self.same_dim(b, c, warnings);
+                        self.same_dim(a, bounds, warnings)
+                    }
+                }
+            }
+            XExpr::Swizzle { expr, .. } => dim(expr, warnings),
+        }
+    }
+
+    /// Quantity of an operator whose operands must have the same quantity, warning when
+    /// they do not.
+    ///
+    fn same_dim(&self, left: Dim, right: Dim, warnings: &mut Vec<XUnitWarning>) -> Dim
+    {
+        match (left, right) {
+            (Dim::Known(a), Dim::Known(b)) if a.is_compatible(&b) => {
+                Dim::Known(if a.is_none() { b } else { a })
+            }
+            (Dim::Known(a), Dim::Known(b)) => {
+                warnings.push(XUnitWarning::Mixed {
+                    expr:  self.to_string(),
+                    left:  a,
+                    right: b,
+                });
+                // Reported once, at the innermost operator.
+                Dim::Unknown
+            }
+            (Dim::Known(q), _) | (_, Dim::Known(q)) => Dim::Known(q),
+            (Dim::Unknown, _) | (_, Dim::Unknown) => Dim::Unknown,
+            (Dim::Any, Dim::Any) => Dim::Any,
+        }
+    }
+}
@@ -310,6 +310,7 @@ impl FileBrowser
 
         let mut selected = None;
         let mut selected_buffer_state = None;
+        let mut selected_warnings = Vec::new();
         let mut selected_buffer_error = None;
 
         for entry in &mut self.filtered_files {
@@ -319,6 +320,7 @@ impl FileBrowser
                 if entry.flags.is_selected {
                     selected = Some(entry.path.clone());
                     selected_buffer_state = entry.get_buffer_state().cloned();
+                    selected_warnings = entry.get_import_warnings();
 
                     // Check if buffer failed to load
                     if entry.buffer.is_none() {
@@ -340,7 +342,11 @@ impl FileBrowser
             }
         } else if let Some(asset_state) = selected_buffer_state {
             if let Some(path) = selected {
-                cx.emit(FileBrowserEvent::BufferStateSelected { path, asset_state });
+                cx.emit(FileBrowserEvent::BufferStateSelected {
+                    path,
+                    asset_state,
+                    warnings: selected_warnings,
+                });
             }
         }
 
@@ -436,9 +442,9 @@ impl FileBrowser
             }
             let path = file_item.read(cx).path.clone();
             match file_item.read(cx).load_buffer() {
-                Ok(asset_state) => {
+                Ok(import) => {
                     file_item.update(cx, |item, _| {
-                        item.init_buf(asset_state);
+                        item.init_buf(import);
                     });
                 }
                 Err(error) => {
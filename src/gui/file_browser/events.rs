@@ -5,7 +5,7 @@ use std::path::PathBuf;
 // ====================
 use crate::gui::file_browser::browser::FileBrowser;
 use crate::gui::file_browser::item::{BufferError, FileItem};
-use crate::gui::models::state::AssetState;
+use crate::gui::models::state::{AssetState, XImportWarning};
 
 /// Events sent by FileEntry objects.
 ///
@@ -53,6 +53,7 @@ pub enum FileBrowserEvent
     {
         path:        PathBuf,
         asset_state: AssetState,
+        warnings:    Vec<XImportWarning>,
     },
     AssetChanged
     {
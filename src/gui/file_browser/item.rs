@@ -28,7 +28,7 @@ use gpui::{
 // ====================
 use crate::gui::file_browser::browser::FileBrowser;
 use crate::gui::file_browser::events::FileEntryEvent;
-use crate::gui::models::state::{AssetState, FromHanabi, ToHanabi};
+use crate::gui::models::state::{AssetState, FromHanabi, ToHanabi, XImport, XImportWarning};
 use crate::gui::primitives::button::*;
 use crate::gui::primitives::events::TextInputEvent;
 use crate::gui::primitives::text_input::*;
@@ -72,7 +72,7 @@ pub enum BufferError
 }
 
 /// Result type for buffer state operations
-pub type BufferStateResult = Result<AssetState, BufferError>;
+pub type BufferStateResult = Result<XImport, BufferError>;
 
 /// In-memory buffer for asset state with history
 #[derive(Clone, Debug)]
@@ -86,6 +86,8 @@ pub struct AssetBuffer
     pub is_modified:     bool,
     /// File timestamp when buffer was last loaded from disk
     pub disk_timestamp:  Option<std::time::SystemTime>,
+    /// Changes made to the asset when it was loaded from disk
+    pub import_warnings: Vec<XImportWarning>,
 }
 
 const MAX_HISTORY: usize = 64;
@@ -101,6 +103,7 @@ impl AssetBuffer
             current_state_i: 0,
             is_modified: false,
             disk_timestamp: None,
+            import_warnings: Vec::new(),
         }
     }
 
@@ -303,18 +306,17 @@ impl FileItem
                 self.path.display()
             ),
         })?;
-        crate::gui::models::state::FromHanabi::asset_state(&asset).map_err(|e| {
-            BufferError::Conversion {
-                path:    self.path.clone(),
-                message: e.to_string(),
-            }
+        FromHanabi::import(&asset).map_err(|e| BufferError::Conversion {
+            path:    self.path.clone(),
+            message: e.to_string(),
         })
     }
 
     /// Initialize buffer with asset state from disk
-    pub fn init_buf(&mut self, asset_state: AssetState)
+    pub fn init_buf(&mut self, import: XImport)
     {
-        let mut buf = AssetBuffer::new(asset_state);
+        let mut buf = AssetBuffer::new(import.state);
+        buf.import_warnings = import.warnings;
 
         // TODO: make other way to compare disk <> buffer versions.
         buf.update_disk_timestamp(&self.path);
@@ -325,9 +327,9 @@ impl FileItem
     pub fn init_buf_from_disk(&mut self) -> BufferStateResult
     {
         match self.load_buffer() {
-            Ok(asset_state) => {
-                self.init_buf(asset_state.clone());
-                Ok(asset_state)
+            Ok(import) => {
+                self.init_buf(import.clone());
+                Ok(import)
             }
             Err(error) => Err(error),
         }
@@ -339,6 +341,15 @@ impl FileItem
         self.buffer.as_ref().map(|b| b.current_state())
     }
 
+    /// Get the changes made to the asset when its buffer was loaded from disk
+    pub fn get_import_warnings(&self) -> Vec<XImportWarning>
+    {
+        self.buffer
+            .as_ref()
+            .map(|b| b.import_warnings.clone())
+            .unwrap_or_default()
+    }
+
     /// Update buffer with new state
     pub fn update_buffer(&mut self, new_state: AssetState)
     {
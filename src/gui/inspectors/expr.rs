@@ -1,24 +1,45 @@
+use std::collections::HashMap;
+
 use gpui::prelude::*;
-use gpui::{Context, Entity, Window, div};
+use gpui::{App, Context, Entity, MouseButton, Window, div};
+use strum::IntoEnumIterator;
 
 // ====================
 // Editor.
 // ====================
-use crate::gui::expr::XParseError;
 use crate::gui::expr::xexpr::XExpr;
+use crate::gui::expr::{
+    XFormatOptions,
+    XMacroLibrary,
+    XParseError,
+    XQuantity,
+    XTypeError,
+    XUnit,
+    XUnitWarning,
+    XValue,
+};
 use crate::gui::inspectors::{Inspector, InspectorEvent};
+use crate::gui::models::attr::XAttr;
+use crate::gui::models::state::PreviewEnv;
 use crate::gui::primitives::events::ExprInputEvent;
 use crate::gui::primitives::expr_input::ExprInput;
+use crate::gui::primitives::expr_plot::ExprPlot;
 use crate::gui::primitives::text_input::SizeVariant;
 use crate::gui::styling::colors::*;
+use crate::gui::styling::fonts::*;
 use crate::gui::styling::icons::ProductIcon;
 
 /// Expression inspector field for models editor.
-/// Provides an expression input widget with syntax highlighting and auto-completion.
+/// Provides an expression input widget with syntax highlighting and auto-completion,
+/// and a plot of the expression that can be shown below it.
+/// Fields annotated with a unit show it, and warn about expressions that mix quantities.
 ///
 pub struct ExprInspector
 {
     expr_input:     Entity<ExprInput>,
+    plot:           Entity<ExprPlot>,
+    show_plot:      bool,
+    unit:           Option<XUnit>,
     _subscriptions: Vec<gpui::Subscription>,
 }
 
@@ -30,17 +51,25 @@ impl Inspector for ExprInspector
     ///
     fn new(cx: &mut Context<Self>, initial: Self::Value) -> Self
     {
-        let initial_content = if let Some(expr) = &initial {
-            expr.to_string()
-        } else {
-            String::new()
-        };
+        let initial_content = initial
+            .as_ref()
+            .map(|expr| Self::format_content(expr, cx))
+            .unwrap_or_default();
 
         let expr_input = cx.new(|cx| {
             ExprInput::new(cx)
                 .with_content(initial_content, cx)
                 .with_placeholder("Enter expression...")
                 .with_size_variant(SizeVariant::Medium)
+                .with_show_simplified(true)
+                .with_hanabi_only(true)
+        });
+
+        let env = Self::preview_env(cx);
+        let plot = cx.new(|cx| {
+            ExprPlot::new(cx)
+                .with_env(env.plot_env())
+                .with_expr(initial.clone())
         });
 
         // Subscribe to expression input events
@@ -48,9 +77,13 @@ impl Inspector for ExprInspector
             &expr_input,
             |this, _expr_input, event: &ExprInputEvent, cx| match event {
                 ExprInputEvent::Change(_) => {
-                    cx.emit(InspectorEvent::Updated {
-                        v: this.get_value(cx),
+                    let value = this.get_value(cx);
+                    let env = Self::preview_env(cx);
+                    this.plot.update(cx, |plot, cx| {
+                        plot.set_env(env.plot_env(), cx);
+                        plot.set_expr(value.clone(), cx);
                     });
+                    cx.emit(InspectorEvent::Updated { v: value });
                 }
                 ExprInputEvent::Submit(_) => {
                     cx.emit(InspectorEvent::Updated {
@@ -62,22 +95,84 @@ impl Inspector for ExprInspector
 
         Self {
             expr_input,
+            plot,
+            show_plot: false,
+            unit: None,
             _subscriptions: vec![subscription],
         }
     }
 
     /// Get current expression text from the input.
     /// Returns the raw expression string, which can be parsed later if needed.
+    /// Expressions typed in degrees are converted to radians.
     ///
     fn get_value<T>(&self, cx: &Context<T>) -> Self::Value
     {
         if let Some(Ok(parsed)) = &self.expr_input.read(cx).get_parsed_expr() {
+            if self.unit.is_some_and(|unit| unit.is_degrees()) {
+                return Some(parsed.degrees_to_radians());
+            }
             return Some(parsed.clone());
         }
         None
     }
 }
 
+impl ExprInspector
+{
+    /// Annotate the field with the unit its expression is in.
+    /// Fields in degrees show the initial angle in degrees.
+    ///
+    pub fn with_unit(mut self, unit: XUnit, cx: &mut Context<Self>) -> Self
+    {
+        if unit.is_degrees() {
+            if let Some(expr) = self.get_value(cx) {
+                let content = Self::format_content(&expr.radians_to_degrees(), cx);
+                self.expr_input
+                    .update(cx, |input, cx| input.set_content(content, cx));
+            }
+        }
+        self.unit = Some(unit);
+        self
+    }
+
+    /// Annotate the field with the unit of the attribute it sets, if the attribute has one.
+    ///
+    pub fn with_attribute_unit(mut self, attr: &XAttr, cx: &mut Context<Self>) -> Self
+    {
+        self.set_attribute_unit(attr, cx);
+        self
+    }
+
+    /// Change the unit of the field when the attribute it sets changes.
+    ///
+    pub fn set_attribute_unit(&mut self, attr: &XAttr, cx: &mut Context<Self>)
+    {
+        self.unit = XUnit::from_quantity(attr.get_quantity());
+        cx.notify();
+    }
+
+    /// Text of an expression in the input. Parts that a project macro expands to are
+    /// shown as its calls.
+    ///
+    fn format_content(expr: &XExpr, cx: &App) -> String
+    {
+        match cx.try_global::<XMacroLibrary>() {
+            Some(macros) if !macros.is_empty() => {
+                expr.format_with_macros(&XFormatOptions::single_line(), macros)
+            }
+            _ => expr.to_string(),
+        }
+    }
+
+    /// Property values and particle lifetime of the asset being edited.
+    ///
+    fn preview_env(cx: &App) -> PreviewEnv
+    {
+        cx.try_global::<PreviewEnv>().cloned().unwrap_or_default()
+    }
+}
+
 // ====================
 // Additional methods.
 // ====================
@@ -113,6 +208,40 @@ impl ExprInspector
         }
         None
     }
+
+    /// Get the value of the expression at time zero, as a hint for expressions that are
+    /// not constant. Properties have their values in the asset, and the lifetime is the one
+    /// particles are given; other attributes have no preview.
+    ///
+    pub fn get_preview_value<T>(&self, cx: &Context<T>) -> Option<XValue>
+    {
+        let expr = self.get_value(cx)?;
+        if matches!(expr.simplify(), XExpr::Lit(_)) {
+            return None;
+        }
+        expr.eval(&mut Self::preview_env(cx).0).ok()
+    }
+
+    /// Get the type error if the expression parses but does not type check.
+    ///
+    pub fn get_type_error<T>(&self, cx: &Context<T>) -> Option<XTypeError>
+    {
+        self.expr_input.read(cx).get_type_error()
+    }
+
+    /// Get the warnings about quantities the expression mixes, or that do not match the
+    /// unit of the field.
+    ///
+    pub fn get_unit_warnings<T>(&self, cx: &Context<T>) -> Vec<XUnitWarning>
+    {
+        let Some(expr) = self.get_value(cx) else {
+            return Vec::new();
+        };
+        let attributes: HashMap<String, XQuantity> = XAttr::iter()
+            .map(|attr| (attr.to_string(), attr.get_quantity()))
+            .collect();
+        expr.check_units(&attributes, self.unit.map(|unit| unit.quantity()))
+    }
 }
 
 // ====================
@@ -123,37 +252,115 @@ impl Render for ExprInspector
 {
     fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement
     {
-        let is_valid = self.is_valid(cx);
+        let is_valid = self.is_valid(cx)
+            && !matches!(
+                self.get_type_error(cx),
+                Some(XTypeError::Mismatch { .. } | XTypeError::NotInHanabi(_))
+            );
         let has_content = !self.expr_input.read_with(cx, |input, cx| {
             input.text_input.read(cx).content.trim().is_empty()
         });
 
+        let unit_warning = self.get_unit_warnings(cx).into_iter().next();
+        let show_plot = self.show_plot;
+        let plot_icon_color = if show_plot {
+            text_primary()
+        } else {
+            text_muted()
+        };
+
         div()
             .flex()
-            .items_center()
-            .gap_2()
+            .flex_col()
+            .gap_1()
             .w_full()
-            .child(self.expr_input.clone().into_element())
-            .when(has_content, |el| {
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .w_full()
+                    .child(self.expr_input.clone().into_element())
+                    .when_some(self.unit, |el, unit| {
+                        el.child(
+                            with_default_font(div())
+                                .flex_shrink_0()
+                                .text_xs()
+                                .text_color(text_muted())
+                                .child(unit.to_string()),
+                        )
+                    })
+                    .when_some(self.get_preview_value(cx), |el, value| {
+                        el.child(
+                            with_default_font(div())
+                                .flex_shrink_0()
+                                .text_xs()
+                                .text_color(text_muted())
+                                .child(format!("t = 0: {}", value)),
+                        )
+                    })
+                    .when(has_content, |el| {
+                        el.child(
+                            div()
+                                .flex()
+                                .items_center()
+                                .justify_center()
+                                .w_6()
+                                .h_6()
+                                .cursor_pointer()
+                                .on_mouse_down(
+                                    MouseButton::Left,
+                                    cx.listener(|this, _ev, _window, cx| {
+                                        this.show_plot = !this.show_plot;
+                                        if this.show_plot {
+                                            let env = Self::preview_env(cx);
+                                            this.plot.update(cx, |plot, cx| {
+                                                plot.set_env(env.plot_env(), cx)
+                                            });
+                                        }
+                                        cx.notify();
+                                    }),
+                                )
+                                .child(
+                                    ProductIcon::SquareFunction
+                                        .to_svg()
+                                        .size_4()
+                                        .text_color(plot_icon_color),
+                                ),
+                        )
+                    })
+                    .when(has_content, |el| {
+                        el.child(
+                            div()
+                                .flex()
+                                .items_center()
+                                .justify_center()
+                                .w_6()
+                                .h_6()
+                                .child(if is_valid {
+                                    ProductIcon::Check
+                                        .to_svg()
+                                        .size_4()
+                                        .text_color(text_success())
+                                } else {
+                                    ProductIcon::OctagonAlert
+                                        .to_svg()
+                                        .size_4()
+                                        .text_color(text_danger())
+                                }),
+                        )
+                    }),
+            )
+            .when_some(unit_warning, |el, warning| {
                 el.child(
-                    div()
-                        .flex()
-                        .items_center()
-                        .justify_center()
-                        .w_6()
-                        .h_6()
-                        .child(if is_valid {
-                            ProductIcon::Check
-                                .to_svg()
-                                .size_4()
-                                .text_color(text_success())
-                        } else {
-                            ProductIcon::OctagonAlert
-                                .to_svg()
-                                .size_4()
-                                .text_color(text_danger())
-                        }),
+                    with_default_font(div())
+                        .text_xs()
+                        .text_color(text_warning())
+                        .child(warning.to_string()),
                 )
             })
+            .when(show_plot && has_content, |el| {
+                el.child(self.plot.clone().into_element())
+            })
     }
 }
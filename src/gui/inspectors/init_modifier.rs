@@ -2,6 +2,7 @@ use gpui::prelude::*;
 use gpui::{AnyView, Context, Entity, Window, div};
 use strum::IntoEnumIterator;
 
+use crate::gui::expr::{DISTANCE, XUnit};
 use crate::gui::inspectors::enumeration::EnumInspector;
 use crate::gui::inspectors::expr::ExprInspector;
 use crate::gui::inspectors::{Inspector, InspectorEvent};
@@ -23,6 +24,8 @@ use crate::gui::styling::colors::*;
 use crate::gui::styling::fonts::*;
 use crate::gui::styling::icons::ProductIcon;
 
+const SPEED: XUnit = DISTANCE.per_second();
+
 pub struct InitModifierInspector
 {
     type_dropdown:    Entity<Dropdown>,
@@ -83,14 +86,17 @@ impl Inspector for InitModifierInspector
             attribute_init,
         ) = Self::extract_initial_values(&initial);
 
-        let center_expr = cx.new(|cx| ExprInspector::new(cx, center_init));
+        let center_expr = cx.new(|cx| ExprInspector::new(cx, center_init).with_unit(DISTANCE, cx));
         let axis_expr = cx.new(|cx| ExprInspector::new(cx, axis_init));
-        let radius_expr = cx.new(|cx| ExprInspector::new(cx, radius_init));
-        let speed_expr = cx.new(|cx| ExprInspector::new(cx, speed_init));
-        let value_expr = cx.new(|cx| ExprInspector::new(cx, value_init));
-        let height_expr = cx.new(|cx| ExprInspector::new(cx, height_init));
-        let base_radius_expr = cx.new(|cx| ExprInspector::new(cx, base_radius_init));
-        let top_radius_expr = cx.new(|cx| ExprInspector::new(cx, top_radius_init));
+        let radius_expr = cx.new(|cx| ExprInspector::new(cx, radius_init).with_unit(DISTANCE, cx));
+        let speed_expr = cx.new(|cx| ExprInspector::new(cx, speed_init).with_unit(SPEED, cx));
+        let value_expr = cx
+            .new(|cx| ExprInspector::new(cx, value_init).with_attribute_unit(&attribute_init, cx));
+        let height_expr = cx.new(|cx| ExprInspector::new(cx, height_init).with_unit(DISTANCE, cx));
+        let base_radius_expr =
+            cx.new(|cx| ExprInspector::new(cx, base_radius_init).with_unit(DISTANCE, cx));
+        let top_radius_expr =
+            cx.new(|cx| ExprInspector::new(cx, top_radius_init).with_unit(DISTANCE, cx));
 
         let dimension_enum = cx.new(|cx| EnumInspector::new(cx, dimension_init));
 
@@ -209,6 +215,9 @@ impl Inspector for InitModifierInspector
         subscriptions.push(cx.subscribe(
             &attribute_dropdown,
             |this, _dropdown, _event: &DropdownEvent, cx| {
+                let attr = this.selected_attr(cx);
+                this.value_expr
+                    .update(cx, |value, cx| value.set_attribute_unit(&attr, cx));
                 cx.emit(InspectorEvent::Updated {
                     v: this.get_value(cx),
                 });
@@ -340,15 +349,7 @@ impl Inspector for InitModifierInspector
                 })
             }
             XInitModifier::XSetAttribute(_) => {
-                let attr =
-                    if let Some(selected_text) = self.attribute_dropdown.read(cx).get_selected() {
-                        XAttr::iter()
-                            .find(|attr| attr.to_string() == selected_text.to_string())
-                            .unwrap_or_default()
-                    } else {
-                        XAttr::default()
-                    };
-
+                let attr = self.selected_attr(cx);
                 XInitModifier::XSetAttribute(XSetAttributeModifier {
                     attr,
                     value: self
@@ -364,6 +365,18 @@ impl Inspector for InitModifierInspector
 
 impl InitModifierInspector
 {
+    /// Attribute selected in the dropdown of the set attribute modifier.
+    ///
+    fn selected_attr<T>(&self, cx: &Context<T>) -> XAttr
+    {
+        match self.attribute_dropdown.read(cx).get_selected() {
+            Some(selected_text) => XAttr::iter()
+                .find(|attr| attr.to_string() == selected_text.to_string())
+                .unwrap_or_default(),
+            None => XAttr::default(),
+        }
+    }
+
     fn on_type_changed(&mut self, type_index: usize, cx: &mut Context<Self>)
     {
         let new_modifier = XInitModifier::iter().nth(type_index).unwrap_or_default();
@@ -384,14 +397,17 @@ impl InitModifierInspector
         ) = Self::extract_initial_values(&new_modifier);
 
         // Recreate the inspector instances with new values
-        self.center_expr = cx.new(|cx| ExprInspector::new(cx, center_init));
+        self.center_expr = cx.new(|cx| ExprInspector::new(cx, center_init).with_unit(DISTANCE, cx));
         self.axis_expr = cx.new(|cx| ExprInspector::new(cx, axis_init));
-        self.radius_expr = cx.new(|cx| ExprInspector::new(cx, radius_init));
-        self.speed_expr = cx.new(|cx| ExprInspector::new(cx, speed_init));
-        self.value_expr = cx.new(|cx| ExprInspector::new(cx, value_init));
-        self.height_expr = cx.new(|cx| ExprInspector::new(cx, height_init));
-        self.base_radius_expr = cx.new(|cx| ExprInspector::new(cx, base_radius_init));
-        self.top_radius_expr = cx.new(|cx| ExprInspector::new(cx, top_radius_init));
+        self.radius_expr = cx.new(|cx| ExprInspector::new(cx, radius_init).with_unit(DISTANCE, cx));
+        self.speed_expr = cx.new(|cx| ExprInspector::new(cx, speed_init).with_unit(SPEED, cx));
+        self.value_expr = cx
+            .new(|cx| ExprInspector::new(cx, value_init).with_attribute_unit(&attribute_init, cx));
+        self.height_expr = cx.new(|cx| ExprInspector::new(cx, height_init).with_unit(DISTANCE, cx));
+        self.base_radius_expr =
+            cx.new(|cx| ExprInspector::new(cx, base_radius_init).with_unit(DISTANCE, cx));
+        self.top_radius_expr =
+            cx.new(|cx| ExprInspector::new(cx, top_radius_init).with_unit(DISTANCE, cx));
 
         self.dimension_enum = cx.new(|cx| EnumInspector::new(cx, dimension_init));
 
@@ -512,6 +528,9 @@ impl InitModifierInspector
         self._subscriptions.push(cx.subscribe(
             &self.attribute_dropdown,
             |this, _dropdown, _event: &DropdownEvent, cx| {
+                let attr = this.selected_attr(cx);
+                this.value_expr
+                    .update(cx, |value, cx| value.set_attribute_unit(&attr, cx));
                 cx.emit(InspectorEvent::Updated {
                     v: this.get_value(cx),
                 });
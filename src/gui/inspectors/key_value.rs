@@ -7,9 +7,11 @@ use gpui::{AnyView, Context, Entity, SharedString, Window, div, px};
 use crate::gui::inspectors::{Inspector, InspectorEvent};
 use crate::gui::models::color::HdrColor;
 use crate::gui::models::key_value::{KeyValue, KeyValueEntry, ValueType};
+use crate::gui::primitives::checkbox_input::{Checkbox, SizeVariant as CheckboxSizeVariant};
 use crate::gui::primitives::color_picker_input::ColorPicker;
 use crate::gui::primitives::dropdown_input::{Dropdown, DropdownSizeVariant};
 use crate::gui::primitives::events::{
+    CheckboxEvent,
     ColorPickerEvent,
     DropdownEvent,
     TextInputEvent,
@@ -34,6 +36,7 @@ pub struct KeyValueInspector
     vec2_input:     Entity<Vec2Input>,
     vec3_input:     Entity<Vec3Input>,
     color_input:    Entity<ColorPicker>,
+    bool_input:     Entity<Checkbox>,
     _subscriptions: Vec<gpui::Subscription>,
 }
 
@@ -123,6 +126,16 @@ impl Inspector for KeyValueInspector
             ColorPicker::new(cx).with_color(value, cx)
         });
 
+        let bool_input = cx.new(|cx| {
+            let value = match &initial.value {
+                KeyValue::Bool(v) => *v,
+                _ => false,
+            };
+            Checkbox::new(cx)
+                .with_checked(value)
+                .with_size_variant(CheckboxSizeVariant::Small)
+        });
+
         // Subscribe to all relevant events
         let mut subscriptions = Vec::new();
 
@@ -232,6 +245,21 @@ impl Inspector for KeyValueInspector
         );
         subscriptions.push(color_subscription);
 
+        // Subscribe to bool input changes
+        let bool_subscription = cx.subscribe(
+            &bool_input,
+            |this, _input, event: &CheckboxEvent, cx| match event {
+                CheckboxEvent::Changed(_) => {
+                    if this.value_type == ValueType::Bool {
+                        cx.emit(InspectorEvent::Updated {
+                            v: this.get_value(cx),
+                        });
+                    }
+                }
+            },
+        );
+        subscriptions.push(bool_subscription);
+
         Self {
             key_input,
             type_dropdown,
@@ -241,6 +269,7 @@ impl Inspector for KeyValueInspector
             vec2_input,
             vec3_input,
             color_input,
+            bool_input,
             _subscriptions: subscriptions,
         }
     }
@@ -272,6 +301,7 @@ impl Inspector for KeyValueInspector
                 let val = self.color_input.read(cx).get_hdr_color();
                 KeyValue::Color(val)
             }
+            ValueType::Bool => KeyValue::Bool(self.bool_input.read(cx).is_checked()),
         };
 
         KeyValueEntry { key, value }
@@ -300,6 +330,7 @@ impl Render for KeyValueInspector
             ValueType::Vec2 => AnyView::from(self.vec2_input.clone().into_element()),
             ValueType::Vec3 => AnyView::from(self.vec3_input.clone().into_element()),
             ValueType::Color => AnyView::from(self.color_input.clone().into_element()),
+            ValueType::Bool => AnyView::from(self.bool_input.clone().into_element()),
         };
 
         div()
@@ -8,6 +8,7 @@ use strum::IntoEnumIterator;
 use crate::gui::inspectors::boolean::BoolInspector;
 use crate::gui::inspectors::float::FloatInspector;
 use crate::gui::inspectors::{Inspector, InspectorEvent};
+use crate::gui::models::spawner::{SpawnerData, SpawnerValue};
 use crate::gui::primitives::dropdown_input::{Dropdown, DropdownItem, DropdownSizeVariant};
 use crate::gui::primitives::events::DropdownEvent;
 use crate::gui::styling::colors::*;
@@ -15,6 +16,7 @@ use crate::gui::styling::fonts::*;
 use crate::gui::styling::icons::ProductIcon;
 
 impl EventEmitter<InspectorEvent<SpawnerData>> for SpawnerInspector {}
+impl EventEmitter<InspectorEvent<SpawnerValue>> for SpawnerValueInspector {}
 
 #[derive(Debug, Clone, PartialEq, strum::EnumIter, strum::Display)]
 pub enum SpawnerType
@@ -39,13 +41,13 @@ pub struct SpawnerInspector
     current_type:  SpawnerType,
 
     // Common properties
-    num_particles:      Entity<FloatInspector>,
+    num_particles:      Entity<SpawnerValueInspector>,
     starts_active:      Entity<BoolInspector>,
     starts_immediately: Entity<BoolInspector>,
 
     // Type-specific properties
-    spawn_time: Entity<FloatInspector>,
-    period:     Entity<FloatInspector>,
+    spawn_time: Entity<SpawnerValueInspector>,
+    period:     Entity<SpawnerValueInspector>,
 
     _subscriptions: Vec<gpui::Subscription>,
 }
@@ -78,11 +80,11 @@ impl Inspector for SpawnerInspector
                 .with_size_variant(DropdownSizeVariant::Small)
         });
 
-        let num_particles = cx.new(|cx| FloatInspector::new(cx, initial.num_particles));
+        let num_particles = cx.new(|cx| SpawnerValueInspector::new(cx, initial.num_particles));
         let starts_active = cx.new(|cx| BoolInspector::new(cx, initial.starts_active));
         let starts_immediately = cx.new(|cx| BoolInspector::new(cx, initial.starts_immediately));
-        let spawn_time = cx.new(|cx| FloatInspector::new(cx, initial.spawn_time));
-        let period = cx.new(|cx| FloatInspector::new(cx, initial.period));
+        let spawn_time = cx.new(|cx| SpawnerValueInspector::new(cx, initial.spawn_time));
+        let period = cx.new(|cx| SpawnerValueInspector::new(cx, initial.period));
 
         let dropdown_subscription = cx.subscribe(
             &type_dropdown,
@@ -98,7 +100,7 @@ impl Inspector for SpawnerInspector
         let mut subscriptions = vec![dropdown_subscription];
         subscriptions.push(cx.subscribe(
             &num_particles,
-            |this, _entity, _event: &InspectorEvent<f32>, cx| {
+            |this, _entity, _event: &InspectorEvent<SpawnerValue>, cx| {
                 cx.emit(InspectorEvent::Updated {
                     v: this.get_value(cx),
                 });
@@ -122,7 +124,7 @@ impl Inspector for SpawnerInspector
         ));
         subscriptions.push(cx.subscribe(
             &spawn_time,
-            |this, _entity, _event: &InspectorEvent<f32>, cx| {
+            |this, _entity, _event: &InspectorEvent<SpawnerValue>, cx| {
                 cx.emit(InspectorEvent::Updated {
                     v: this.get_value(cx),
                 });
@@ -130,7 +132,7 @@ impl Inspector for SpawnerInspector
         ));
         subscriptions.push(cx.subscribe(
             &period,
-            |this, _entity, _event: &InspectorEvent<f32>, cx| {
+            |this, _entity, _event: &InspectorEvent<SpawnerValue>, cx| {
                 cx.emit(InspectorEvent::Updated {
                     v: this.get_value(cx),
                 });
@@ -159,9 +161,9 @@ impl Inspector for SpawnerInspector
 
         // Adjust values based on spawner type
         let (adjusted_spawn_time, adjusted_period) = match self.current_type {
-            SpawnerType::Once => (0.0, 1000.0),
-            SpawnerType::Burst => (0.0, period),
-            SpawnerType::Rate => (1.0, 1.0),
+            SpawnerType::Once => (SpawnerValue::Single(0.0), SpawnerValue::Single(1000.0)),
+            SpawnerType::Burst => (SpawnerValue::Single(0.0), period),
+            SpawnerType::Rate => (SpawnerValue::Single(1.0), SpawnerValue::Single(1.0)),
             SpawnerType::Custom => (spawn_time, period),
         };
 
@@ -179,11 +181,14 @@ impl SpawnerInspector
 {
     fn determine_spawner_type(data: &SpawnerData) -> SpawnerType
     {
-        if data.period.is_infinite() {
+        if data.period.min().is_infinite() {
             SpawnerType::Once
-        } else if data.spawn_time < f32::EPSILON && data.period > 0.0 {
+        } else if data.spawn_time.max() < f32::EPSILON && data.period.min() > 0.0 {
             SpawnerType::Burst
-        } else if (data.period - data.spawn_time).abs() < f32::EPSILON {
+        } else if !data.period.is_range()
+            && !data.spawn_time.is_range()
+            && (data.period.min() - data.spawn_time.min()).abs() < f32::EPSILON
+        {
             SpawnerType::Rate
         } else {
             SpawnerType::Custom
@@ -271,12 +276,98 @@ impl Render for SpawnerInspector
     }
 }
 
-#[derive(Debug, Clone, Default, PartialEq)]
-pub struct SpawnerData
+// ====================
+// Spawner values.
+// ====================
+
+/// Spawner value field, with a toggle between a single value and a min/max range.
+///
+pub struct SpawnerValueInspector
+{
+    min:            Entity<FloatInspector>,
+    max:            Entity<FloatInspector>,
+    is_range:       Entity<BoolInspector>,
+    _subscriptions: Vec<gpui::Subscription>,
+}
+
+impl Inspector for SpawnerValueInspector
+{
+    type Value = SpawnerValue;
+
+    fn new(cx: &mut Context<Self>, initial: Self::Value) -> Self
+    {
+        let min = cx.new(|cx| FloatInspector::new(cx, initial.min()));
+        let max = cx.new(|cx| FloatInspector::new(cx, initial.max()));
+        let is_range = cx.new(|cx| BoolInspector::new(cx, initial.is_range()));
+
+        let mut subscriptions = Vec::new();
+        for bound in [&min, &max] {
+            subscriptions.push(cx.subscribe(
+                bound,
+                |this, _entity, _event: &InspectorEvent<f32>, cx| {
+                    cx.emit(InspectorEvent::Updated {
+                        v: this.get_value(cx),
+                    });
+                },
+            ));
+        }
+        subscriptions.push(cx.subscribe(
+            &is_range,
+            |this, _entity, _event: &InspectorEvent<bool>, cx| {
+                cx.emit(InspectorEvent::Updated {
+                    v: this.get_value(cx),
+                });
+                cx.notify();
+            },
+        ));
+
+        Self {
+            min,
+            max,
+            is_range,
+            _subscriptions: subscriptions,
+        }
+    }
+
+    fn get_value<T>(&self, cx: &Context<T>) -> Self::Value
+    {
+        let min = self.min.read(cx).get_value(cx);
+        if self.is_range.read(cx).get_value(cx) {
+            SpawnerValue::Uniform {
+                min,
+                max: self.max.read(cx).get_value(cx),
+            }
+        } else {
+            SpawnerValue::Single(min)
+        }
+    }
+}
+
+impl Render for SpawnerValueInspector
 {
-    pub num_particles:      f32,
-    pub spawn_time:         f32,
-    pub period:             f32,
-    pub starts_active:      bool,
-    pub starts_immediately: bool,
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement
+    {
+        let is_range = self.is_range.read(cx).get_value(cx);
+        div()
+            .flex()
+            .items_center()
+            .gap_2()
+            .child(div().flex_1().child(self.min.clone()))
+            .when(is_range, |el| {
+                el.child(
+                    with_default_font(div())
+                        .text_xs()
+                        .text_color(text_muted())
+                        .child("to"),
+                )
+                .child(div().flex_1().child(self.max.clone()))
+            })
+            .child(self.is_range.clone())
+            .child(
+                with_default_font(div())
+                    .text_xs()
+                    .text_color(text_muted())
+                    .child("Range"),
+            )
+    }
 }
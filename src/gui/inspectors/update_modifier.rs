@@ -5,6 +5,7 @@ use strum::IntoEnumIterator;
 // ====================
 // Editor.
 // ====================
+use crate::gui::expr::{DISTANCE, XUnit};
 use crate::gui::inspectors::expr::ExprInspector;
 use crate::gui::inspectors::{Inspector, InspectorEvent};
 use crate::gui::models::attr::XAttr;
@@ -22,6 +23,8 @@ use crate::gui::styling::colors::*;
 use crate::gui::styling::fonts::*;
 use crate::gui::styling::icons::ProductIcon;
 
+const ACCELERATION: XUnit = DISTANCE.per_second().per_second();
+
 pub struct UpdateModifierInspector
 {
     type_dropdown:      Entity<Dropdown>,
@@ -63,11 +66,13 @@ impl Inspector for UpdateModifierInspector
         let (accel_init, origin_init, axis_init, drag_init, value_init, attribute_init) =
             Self::extract_initial_values(&initial);
 
-        let accel_expr = cx.new(|cx| ExprInspector::new(cx, accel_init));
-        let origin_expr = cx.new(|cx| ExprInspector::new(cx, origin_init));
+        let accel_expr =
+            cx.new(|cx| ExprInspector::new(cx, accel_init).with_unit(ACCELERATION, cx));
+        let origin_expr = cx.new(|cx| ExprInspector::new(cx, origin_init).with_unit(DISTANCE, cx));
         let axis_expr = cx.new(|cx| ExprInspector::new(cx, axis_init));
-        let drag_expr = cx.new(|cx| ExprInspector::new(cx, drag_init));
-        let value_expr = cx.new(|cx| ExprInspector::new(cx, value_init));
+        let drag_expr = cx.new(|cx| ExprInspector::new(cx, drag_init).with_unit(XUnit::rate(), cx));
+        let value_expr = cx
+            .new(|cx| ExprInspector::new(cx, value_init).with_attribute_unit(&attribute_init, cx));
 
         // Create custom attribute dropdown with icons and type information
         let attribute_dropdown = cx.new(|cx| {
@@ -150,6 +155,9 @@ impl Inspector for UpdateModifierInspector
         subscriptions.push(cx.subscribe(
             &attribute_dropdown,
             |this, _dropdown, _event: &DropdownEvent, cx| {
+                let attr = this.selected_attr(cx);
+                this.value_expr
+                    .update(cx, |value, cx| value.set_attribute_unit(&attr, cx));
                 cx.emit(InspectorEvent::Updated {
                     v: this.get_value(cx),
                 });
@@ -223,15 +231,7 @@ impl Inspector for UpdateModifierInspector
                     .unwrap_or_else(|| crate::gui::expr::XExpr::lit(0.1)),
             }),
             XUpdateModifier::XSetAttribute(_) => {
-                let attr =
-                    if let Some(selected_text) = self.attribute_dropdown.read(cx).get_selected() {
-                        XAttr::iter()
-                            .find(|attr| attr.to_string() == selected_text.to_string())
-                            .unwrap_or_default()
-                    } else {
-                        XAttr::default()
-                    };
-
+                let attr = self.selected_attr(cx);
                 XUpdateModifier::XSetAttribute(XSetAttributeModifier {
                     attr,
                     value: self
@@ -247,6 +247,18 @@ impl Inspector for UpdateModifierInspector
 
 impl UpdateModifierInspector
 {
+    /// Attribute selected in the dropdown of the set attribute modifier.
+    ///
+    fn selected_attr<T>(&self, cx: &Context<T>) -> XAttr
+    {
+        match self.attribute_dropdown.read(cx).get_selected() {
+            Some(selected_text) => XAttr::iter()
+                .find(|attr| attr.to_string() == selected_text.to_string())
+                .unwrap_or_default(),
+            None => XAttr::default(),
+        }
+    }
+
     fn on_type_changed(&mut self, type_index: usize, cx: &mut Context<Self>)
     {
         let new_modifier = XUpdateModifier::iter().nth(type_index).unwrap_or_default();
@@ -257,11 +269,14 @@ impl UpdateModifierInspector
             Self::extract_initial_values(&new_modifier);
 
         // Recreate the inspector instances with new values
-        self.accel_expr = cx.new(|cx| ExprInspector::new(cx, accel_init));
-        self.origin_expr = cx.new(|cx| ExprInspector::new(cx, origin_init));
+        self.accel_expr =
+            cx.new(|cx| ExprInspector::new(cx, accel_init).with_unit(ACCELERATION, cx));
+        self.origin_expr = cx.new(|cx| ExprInspector::new(cx, origin_init).with_unit(DISTANCE, cx));
         self.axis_expr = cx.new(|cx| ExprInspector::new(cx, axis_init));
-        self.drag_expr = cx.new(|cx| ExprInspector::new(cx, drag_init));
-        self.value_expr = cx.new(|cx| ExprInspector::new(cx, value_init));
+        self.drag_expr =
+            cx.new(|cx| ExprInspector::new(cx, drag_init).with_unit(XUnit::rate(), cx));
+        self.value_expr = cx
+            .new(|cx| ExprInspector::new(cx, value_init).with_attribute_unit(&attribute_init, cx));
 
         // Recreate attribute dropdown with icons and type information
         self.attribute_dropdown = cx.new(|cx| {
@@ -344,6 +359,9 @@ impl UpdateModifierInspector
         self._subscriptions.push(cx.subscribe(
             &self.attribute_dropdown,
             |this, _dropdown, _event: &DropdownEvent, cx| {
+                let attr = this.selected_attr(cx);
+                this.value_expr
+                    .update(cx, |value, cx| value.set_attribute_unit(&attr, cx));
                 cx.emit(InspectorEvent::Updated {
                     v: this.get_value(cx),
                 });
@@ -8,8 +8,12 @@ pub mod file_browser;
 pub mod inspectors;
 pub mod models;
 pub mod primitives;
+pub mod scene_picker;
 pub mod scm;
 pub mod section;
 pub mod server_adapter;
 pub mod styling;
+pub mod tests;
 pub mod utils;
+pub mod viewer_panel;
+pub mod viewer_settings;
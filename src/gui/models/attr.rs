@@ -1,5 +1,6 @@
 use strum_macros::{Display, EnumIter, EnumString};
 
+use crate::gui::expr::xunit::XQuantity;
 use crate::gui::expr::xval::XExprReturnType;
 
 #[derive(Clone, Debug, PartialEq, EnumIter, Display, Default, EnumString)]
@@ -50,6 +51,24 @@ impl XAttr
         }
     }
 
+    /// Get the physical quantity of this attribute, for unit checks
+    pub fn get_quantity(&self) -> XQuantity
+    {
+        match self {
+            XAttr::Position => XQuantity::DISTANCE,
+            XAttr::Velocity => XQuantity::DISTANCE.per_second(),
+            XAttr::Age => XQuantity::TIME,
+            XAttr::Lifetime => XQuantity::TIME,
+            XAttr::Color => XQuantity::NONE,
+            XAttr::Alpha => XQuantity::NONE,
+            XAttr::Size => XQuantity::DISTANCE,
+            XAttr::Size2 => XQuantity::DISTANCE,
+            XAttr::AxisX => XQuantity::NONE,
+            XAttr::AxisY => XQuantity::NONE,
+            XAttr::AxisZ => XQuantity::NONE,
+        }
+    }
+
     /// Get a human-readable description of this attribute
     pub fn get_description(&self) -> &'static str
     {
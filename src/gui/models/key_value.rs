@@ -1,6 +1,9 @@
+use std::collections::HashMap;
+
 use bevy::math::{Vec2, Vec3};
-use gpui::SharedString;
+use gpui::{Global, SharedString};
 
+use crate::gui::expr::xval::{XExprReturnType, XValue};
 use crate::gui::models::color::HdrColor;
 use crate::gui::primitives::dropdown_input::DropdownItem;
 use crate::gui::styling::icons::ProductIcon;
@@ -13,6 +16,7 @@ pub enum ValueType
     Vec2,
     Vec3,
     Color,
+    Bool,
 }
 
 impl Default for ValueType
@@ -53,6 +57,11 @@ impl ValueType
                 icon:   Some(ProductIcon::SymbolColor),
                 detail: None,
             },
+            DropdownItem {
+                text:   SharedString::from("Bool"),
+                icon:   Some(ProductIcon::Check),
+                detail: None,
+            },
         ]
     }
 
@@ -64,6 +73,7 @@ impl ValueType
             2 => Self::Vec2,
             3 => Self::Vec3,
             4 => Self::Color,
+            5 => Self::Bool,
             _ => panic!("Invalid index for KeyValue index: {}", index),
         }
     }
@@ -76,6 +86,7 @@ impl ValueType
             Self::Vec2 => 2,
             Self::Vec3 => 3,
             Self::Color => 4,
+            Self::Bool => 5,
         }
     }
 }
@@ -88,6 +99,7 @@ pub enum KeyValue
     Vec2(Vec2),
     Vec3(Vec3),
     Color(HdrColor),
+    Bool(bool),
 }
 
 impl Default for KeyValue
@@ -108,6 +120,7 @@ impl KeyValue
             Self::Vec2(_) => ValueType::Vec2,
             Self::Vec3(_) => ValueType::Vec3,
             Self::Color(_) => ValueType::Color,
+            Self::Bool(_) => ValueType::Bool,
         }
     }
 
@@ -119,6 +132,39 @@ impl KeyValue
             ValueType::Vec2 => Self::Vec2(Vec2::ZERO),
             ValueType::Vec3 => Self::Vec3(Vec3::ZERO),
             ValueType::Color => Self::Color(HdrColor::default()),
+            ValueType::Bool => Self::Bool(false),
+        }
+    }
+
+    /// Type of the value when read in an expression with `prop("name")`.
+    pub fn get_expr_type(&self) -> XExprReturnType
+    {
+        match self {
+            Self::Float(_) => XExprReturnType::Float,
+            Self::Integer(_) => XExprReturnType::Integer,
+            Self::Vec2(_) => XExprReturnType::Vec2,
+            Self::Vec3(_) => XExprReturnType::Vec3,
+            Self::Color(_) => XExprReturnType::Vec4,
+            Self::Bool(_) => XExprReturnType::Bool,
+        }
+    }
+
+    /// Value read in an expression with `prop("name")`, as the effect sets it on the GPU.
+    ///
+    pub fn get_expr_value(&self) -> XValue
+    {
+        match self {
+            Self::Float(value) => XValue::Float(*value),
+            Self::Integer(value) => XValue::Integer(*value),
+            Self::Vec2(value) => XValue::Vec2(value.x, value.y),
+            Self::Vec3(value) => XValue::Vec3(value.x, value.y, value.z),
+            Self::Color(color) => XValue::Vec4(
+                color.r() * color.i(),
+                color.g() * color.i(),
+                color.b() * color.i(),
+                color.a(),
+            ),
+            Self::Bool(value) => XValue::Bool(*value),
         }
     }
 }
@@ -140,3 +186,23 @@ impl KeyValueEntry
         }
     }
 }
+
+/// Expression types of the properties of the asset being edited, shared with every
+/// expression input for completions and type checking.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PropertyTypes(pub HashMap<String, XExprReturnType>);
+
+impl Global for PropertyTypes {}
+
+impl PropertyTypes
+{
+    pub fn from_entries(entries: &[KeyValueEntry]) -> Self
+    {
+        Self(
+            entries
+                .iter()
+                .map(|entry| (entry.key.to_string(), entry.value.get_expr_type()))
+                .collect(),
+        )
+    }
+}
@@ -4,7 +4,9 @@ pub mod attr;
 pub mod color;
 pub mod key_value;
 pub mod modifier;
+pub mod spawner;
 pub mod state;
+pub mod tests;
 
 #[derive(Clone, Debug, PartialEq, EnumIter, Display, EnumString, Default)]
 pub enum XDimension
@@ -0,0 +1,69 @@
+/// Spawner of an effect.
+///
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SpawnerData
+{
+    pub num_particles:      SpawnerValue,
+    pub spawn_time:         SpawnerValue,
+    pub period:             SpawnerValue,
+    pub starts_active:      bool,
+    pub starts_immediately: bool,
+}
+
+/// Spawner value, either fixed or drawn uniformly from a range each time it is used.
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SpawnerValue
+{
+    Single(f32),
+    Uniform
+    {
+        min: f32,
+        max: f32,
+    },
+}
+
+impl Default for SpawnerValue
+{
+    fn default() -> Self
+    {
+        SpawnerValue::Single(0.0)
+    }
+}
+
+impl SpawnerValue
+{
+    pub fn min(&self) -> f32
+    {
+        match self {
+            SpawnerValue::Single(v) => *v,
+            SpawnerValue::Uniform { min, .. } => *min,
+        }
+    }
+
+    pub fn max(&self) -> f32
+    {
+        match self {
+            SpawnerValue::Single(v) => *v,
+            SpawnerValue::Uniform { max, .. } => *max,
+        }
+    }
+
+    pub fn is_range(&self) -> bool
+    {
+        matches!(self, SpawnerValue::Uniform { .. })
+    }
+
+    /// Apply `f` to the value, or to both bounds of a range.
+    ///
+    pub fn map(self, f: impl Fn(f32) -> f32) -> Self
+    {
+        match self {
+            SpawnerValue::Single(v) => SpawnerValue::Single(f(v)),
+            SpawnerValue::Uniform { min, max } => SpawnerValue::Uniform {
+                min: f(min),
+                max: f(max),
+            },
+        }
+    }
+}
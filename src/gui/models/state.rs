@@ -1,4 +1,5 @@
 use bevy::math::Vec2;
+use bevy::reflect::{Reflect, ReflectMut};
 // ====================
 // Particles.
 // ====================
@@ -27,16 +28,29 @@ use bevy_hanabi::{
     TangentAccelModifier,
     *,
 };
+use gpui::Global;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use strum::IntoEnumIterator;
 
 // ====================
 // Editor.
 // ====================
-use crate::gui::expr::{XBinaryOp, XBuiltInOp, XExpr, XUnaryOp, XValue};
-use crate::gui::inspectors::spawner::SpawnerData;
+use crate::gui::expr::xeval::{NORMAL_SAMPLES, NORMAL_SCALE};
+use crate::gui::expr::{
+    XBinaryOp,
+    XBuiltInOp,
+    XEnv,
+    XExpr,
+    XExprReturnType,
+    XTernaryOp,
+    XUnaryOp,
+    XValue,
+};
 use crate::gui::models::XDimension;
 use crate::gui::models::attr::XAttr;
 use crate::gui::models::color::HdrColor;
-use crate::gui::models::key_value::{KeyValue, KeyValueEntry};
+use crate::gui::models::key_value::{KeyValue, KeyValueEntry, PropertyTypes};
 use crate::gui::models::modifier::{
     XAccelModifier,
     XForceFieldSource,
@@ -56,6 +70,7 @@ use crate::gui::models::modifier::{
     XTangentAccelModifier,
     XUpdateModifier,
 };
+use crate::gui::models::spawner::{SpawnerData, SpawnerValue};
 
 pub type TimeVec2 = (f32, Vec2);
 pub type TimeColor = (f32, HdrColor);
@@ -74,6 +89,10 @@ pub enum XError
     UnsupportedModifierType(String),
     UnsupportedAttributeType(String),
     UnknownAttribute(String),
+    InvalidSwizzle(String),
+    UnknownVariable(String),
+    InvalidRawEntry(String),
+    SharedRandomValue(String),
 }
 
 impl std::fmt::Display for XError
@@ -112,6 +131,18 @@ impl std::fmt::Display for XError
             XError::UnknownAttribute(msg) => {
                 write!(f, "Unknown attribute: {}", msg)
             }
+            XError::InvalidSwizzle(msg) => {
+                write!(f, "Invalid swizzle: {}", msg)
+            }
+            XError::UnknownVariable(msg) => {
+                write!(f, "Unknown variable: {}", msg)
+            }
+            XError::InvalidRawEntry(msg) => {
+                write!(f, "Invalid raw entry: {}", msg)
+            }
+            XError::SharedRandomValue(msg) => {
+                write!(f, "Random value used more than once: {}", msg)
+            }
         }
     }
 }
@@ -121,11 +152,37 @@ impl std::error::Error for XError {}
 /// Converter for editor models to Hanabi.
 pub struct ToHanabi;
 
+/// `let` bindings in scope while converting an expression, and the property types.
+///
+/// `random` holds the bindings to random values, and whether each is a random built-in
+/// used as is.
+#[derive(Clone, Default)]
+struct Bindings
+{
+    handles: std::collections::HashMap<String, ExprHandle>,
+    types:   std::collections::HashMap<String, XExprReturnType>,
+    props:   std::collections::HashMap<String, XExprReturnType>,
+    random:  std::collections::HashMap<String, bool>,
+}
+
+/// Scale of the lowerings of `sign` and `step`, which saturate a value with `clamp`. Any
+/// normal float times it is at least 1 in magnitude.
+const SATURATE_SCALE: f32 = f32::MAX;
+
+/// Component of a vector built in the Hanabi graph: a constant, or a scalar expression.
+#[derive(Clone, Copy)]
+enum Component
+{
+    Constant(f32),
+    Scalar(ExprHandle),
+}
+
 impl ToHanabi
 {
     pub fn effect_asset(state: &AssetState) -> Result<EffectAsset, XError>
     {
-        let mut module = Module::default();
+        // Raw modifiers refer to the expressions of the imported module by handle
+        let mut module = state.raw_module.clone();
 
         // Create spawner
         let spawner = Self::create_spawner(state)?;
@@ -136,24 +193,27 @@ impl ToHanabi
             .iter()
             .map(|entry| Self::property(entry))
             .collect();
-        let properties = properties?;
+        let mut properties = properties?;
+        Self::insert_raw(&mut properties, &state.raw_properties)?;
+        let PropertyTypes(props) = PropertyTypes::from_entries(&state.properties);
 
         // Convert init modifiers
         let init_modifiers: Result<Vec<_>, _> = state
             .init_modifiers
             .iter()
-            .map(|modifier| Self::init_modifier(modifier, &mut module))
+            .map(|modifier| Self::init_modifier(modifier, &mut module, &props))
             .collect();
-        let init_modifiers = init_modifiers?;
+        let mut init_modifiers = init_modifiers?;
+        Self::insert_raw(&mut init_modifiers, &state.raw_init_modifiers)?;
 
-        // Convert update modifiers and extract force fields
-        let mut update_modifiers = Vec::new();
-        for modifier in &state.update_modifiers {
-            // Remove XForceField arm, only handle other update modifiers
-            if let Ok(converted_modifier) = Self::update_modifier(modifier, &mut module) {
-                update_modifiers.push(converted_modifier);
-            }
-        }
+        // Convert update modifiers
+        let update_modifiers: Result<Vec<_>, _> = state
+            .update_modifiers
+            .iter()
+            .map(|modifier| Self::update_modifier(modifier, &mut module, &props))
+            .collect();
+        let mut update_modifiers = update_modifiers?;
+        Self::insert_raw(&mut update_modifiers, &state.raw_update_modifiers)?;
 
         // Create a single ForceFieldModifier from all sources
         if !state.force_fields.is_empty() {
@@ -168,7 +228,8 @@ impl ToHanabi
             .iter()
             .map(|modifier| Self::render_modifier(modifier, &mut module))
             .collect();
-        let render_modifiers = render_modifiers?;
+        let mut render_modifiers = render_modifiers?;
+        Self::insert_raw(&mut render_modifiers, &state.raw_render_modifiers)?;
 
         // Create additional render modifiers from size and color over time
         let mut additional_render_modifiers = Vec::new();
@@ -224,41 +285,64 @@ impl ToHanabi
         Ok(effect)
     }
 
+    /// Insert the entries kept from an imported effect back at their positions.
+    fn insert_raw<T: DeserializeOwned>(
+        items: &mut Vec<T>,
+        entries: &[XRawEntry],
+    ) -> Result<(), XError>
+    {
+        for entry in entries {
+            let item = ron::from_str::<T>(&entry.ron)
+                .map_err(|e| XError::InvalidRawEntry(format!("{}: {}", entry.name, e)))?;
+            items.insert(entry.index.min(items.len()), item);
+        }
+        Ok(())
+    }
+
     fn create_spawner(state: &AssetState) -> Result<Spawner, XError>
     {
         let spawner_data = &state.spawner;
 
         // Ensure all spawner values are valid for Hanabi
-        let num_particles_value = if spawner_data.num_particles < 0.0 {
-            1.0 // Default to 1.0 if negative
-        } else {
-            spawner_data.num_particles
-        };
-        let num_particles = CpuValue::Single(num_particles_value);
+        let num_particles_value = spawner_data.num_particles.map(|v| {
+            if v < 0.0 {
+                1.0 // Default to 1.0 if negative
+            } else {
+                v
+            }
+        });
+        let num_particles = Self::cpu_value(num_particles_value);
 
-        let spawn_time_value = if spawner_data.spawn_time < 0.0 {
-            0.0 // Default to 0.0 if negative
-        } else {
-            spawner_data.spawn_time
-        };
-        let spawn_time = CpuValue::Single(spawn_time_value);
+        let spawn_time_value = spawner_data.spawn_time.map(|v| {
+            if v < 0.0 {
+                0.0 // Default to 0.0 if negative
+            } else {
+                v
+            }
+        });
+        let spawn_time = Self::cpu_value(spawn_time_value);
 
         // Ensure period is always positive for Hanabi
-        let period_value = if spawner_data.period <= 0.0 {
-            1.0 // Default to 1.0 if period is invalid
-        } else {
-            spawner_data.period
-        };
-        let period = CpuValue::Single(period_value);
+        let period_value = spawner_data.period.map(|v| {
+            if v <= 0.0 {
+                1.0 // Default to 1.0 if period is invalid
+            } else {
+                v
+            }
+        });
+        let period = Self::cpu_value(period_value);
 
         // Determine spawner type based on values
-        let spawner = if spawner_data.period.is_infinite() {
+        let spawner = if spawner_data.period.min().is_infinite() {
             // Once spawner
             Spawner::once(num_particles, spawner_data.starts_immediately)
-        } else if (period_value - spawn_time_value).abs() < f32::EPSILON {
+        } else if !period_value.is_range()
+            && !spawn_time_value.is_range()
+            && (period_value.min() - spawn_time_value.min()).abs() < f32::EPSILON
+        {
             // Rate spawner (period == spawn_time means continuous)
             Spawner::rate(num_particles)
-        } else if spawn_time_value < f32::EPSILON {
+        } else if spawn_time_value.max() < f32::EPSILON {
             // Burst spawner (spawn_time == 0)
             Spawner::burst(num_particles, period)
         } else {
@@ -269,6 +353,15 @@ impl ToHanabi
         Ok(spawner.with_starts_active(spawner_data.starts_active))
     }
 
+    /// Spawner value as a Hanabi value, with the bounds of a range in increasing order.
+    fn cpu_value(value: SpawnerValue) -> CpuValue<f32>
+    {
+        match value {
+            SpawnerValue::Single(v) => CpuValue::Single(v),
+            SpawnerValue::Uniform { min, max } => CpuValue::Uniform((min.min(max), min.max(max))),
+        }
+    }
+
     fn property(entry: &KeyValueEntry) -> Result<Property, XError>
     {
         let value = match &entry.value {
@@ -276,6 +369,7 @@ impl ToHanabi
             KeyValue::Integer(i) => Value::Scalar(ScalarValue::Int(*i)),
             KeyValue::Vec2(v) => Value::Vector((*v).into()),
             KeyValue::Vec3(v) => Value::Vector((*v).into()),
+            KeyValue::Bool(b) => Value::Scalar(ScalarValue::Bool(*b)),
             KeyValue::Color(color) => {
                 let vec4 = bevy::math::Vec4::new(
                     color.r() * color.i(),
@@ -290,14 +384,17 @@ impl ToHanabi
         Ok(Property::new(entry.key.clone(), value))
     }
 
-    fn init_modifier(modifier: &XInitModifier, module: &mut Module)
-    -> Result<BoxedModifier, XError>
+    fn init_modifier(
+        modifier: &XInitModifier,
+        module: &mut Module,
+        props: &std::collections::HashMap<String, XExprReturnType>,
+    ) -> Result<BoxedModifier, XError>
     {
         match modifier {
             XInitModifier::XSetPositionCircle(m) => {
-                let center = Self::convert_expr(&m.center, module)?;
-                let axis = Self::convert_expr(&m.axis, module)?;
-                let radius = Self::convert_expr(&m.radius, module)?;
+                let center = Self::convert_expr_with_props(&m.center, module, props)?;
+                let axis = Self::convert_expr_with_props(&m.axis, module, props)?;
+                let radius = Self::convert_expr_with_props(&m.radius, module, props)?;
                 let dimension = Self::convert_dimension(&m.dimension);
 
                 Ok(Box::new(SetPositionCircleModifier {
@@ -308,8 +405,8 @@ impl ToHanabi
                 }))
             }
             XInitModifier::XSetPositionSphere(m) => {
-                let center = Self::convert_expr(&m.center, module)?;
-                let radius = Self::convert_expr(&m.radius, module)?;
+                let center = Self::convert_expr_with_props(&m.center, module, props)?;
+                let radius = Self::convert_expr_with_props(&m.radius, module, props)?;
                 let dimension = Self::convert_dimension(&m.dimension);
 
                 Ok(Box::new(SetPositionSphereModifier {
@@ -319,9 +416,9 @@ impl ToHanabi
                 }))
             }
             XInitModifier::XSetPositionCone3d(m) => {
-                let height = Self::convert_expr(&m.height, module)?;
-                let base_radius = Self::convert_expr(&m.base_radius, module)?;
-                let top_radius = Self::convert_expr(&m.top_radius, module)?;
+                let height = Self::convert_expr_with_props(&m.height, module, props)?;
+                let base_radius = Self::convert_expr_with_props(&m.base_radius, module, props)?;
+                let top_radius = Self::convert_expr_with_props(&m.top_radius, module, props)?;
                 let dimension = Self::convert_dimension(&m.dimension);
 
                 Ok(Box::new(SetPositionCone3dModifier {
@@ -332,9 +429,9 @@ impl ToHanabi
                 }))
             }
             XInitModifier::XSetVelocityCircle(m) => {
-                let center = Self::convert_expr(&m.center, module)?;
-                let axis = Self::convert_expr(&m.axis, module)?;
-                let speed = Self::convert_expr(&m.speed, module)?;
+                let center = Self::convert_expr_with_props(&m.center, module, props)?;
+                let axis = Self::convert_expr_with_props(&m.axis, module, props)?;
+                let speed = Self::convert_expr_with_props(&m.speed, module, props)?;
 
                 Ok(Box::new(SetVelocityCircleModifier {
                     center,
@@ -343,15 +440,15 @@ impl ToHanabi
                 }))
             }
             XInitModifier::XSetVelocitySphere(m) => {
-                let center = Self::convert_expr(&m.center, module)?;
-                let speed = Self::convert_expr(&m.speed, module)?;
+                let center = Self::convert_expr_with_props(&m.center, module, props)?;
+                let speed = Self::convert_expr_with_props(&m.speed, module, props)?;
 
                 Ok(Box::new(SetVelocitySphereModifier { center, speed }))
             }
             XInitModifier::XSetVelocityTangent(m) => {
-                let origin = Self::convert_expr(&m.center, module)?;
+                let origin = Self::convert_expr_with_props(&m.center, module, props)?;
                 let axis = module.lit(bevy::math::Vec3::Y); // Default axis since editor model doesn't have axis
-                let speed = Self::convert_expr(&m.speed, module)?;
+                let speed = Self::convert_expr_with_props(&m.speed, module, props)?;
 
                 Ok(Box::new(SetVelocityTangentModifier {
                     origin,
@@ -361,7 +458,7 @@ impl ToHanabi
             }
             XInitModifier::XSetAttribute(m) => {
                 let attribute = Self::convert_attr(&m.attr)?;
-                let value = Self::convert_expr(&m.value, module)?;
+                let value = Self::convert_expr_with_props(&m.value, module, props)?;
 
                 Ok(Box::new(SetAttributeModifier { attribute, value }))
             }
@@ -371,27 +468,28 @@ impl ToHanabi
     fn update_modifier(
         modifier: &XUpdateModifier,
         module: &mut Module,
+        props: &std::collections::HashMap<String, XExprReturnType>,
     ) -> Result<BoxedModifier, XError>
     {
         match modifier {
             XUpdateModifier::XAccel(m) => Ok(Box::new(AccelModifier {
-                accel: Self::convert_expr(&m.accel, module)?,
+                accel: Self::convert_expr_with_props(&m.accel, module, props)?,
             })),
             XUpdateModifier::XRadialAccel(m) => Ok(Box::new(RadialAccelModifier {
-                origin: Self::convert_expr(&m.origin, module)?,
-                accel:  Self::convert_expr(&m.accel, module)?,
+                origin: Self::convert_expr_with_props(&m.origin, module, props)?,
+                accel:  Self::convert_expr_with_props(&m.accel, module, props)?,
             })),
             XUpdateModifier::XTangentAccel(m) => Ok(Box::new(TangentAccelModifier {
-                origin: Self::convert_expr(&m.origin, module)?,
-                axis:   Self::convert_expr(&m.axis, module)?,
-                accel:  Self::convert_expr(&m.accel, module)?,
+                origin: Self::convert_expr_with_props(&m.origin, module, props)?,
+                axis:   Self::convert_expr_with_props(&m.axis, module, props)?,
+                accel:  Self::convert_expr_with_props(&m.accel, module, props)?,
             })),
             XUpdateModifier::XLinearDrag(m) => Ok(Box::new(LinearDragModifier {
-                drag: Self::convert_expr(&m.drag, module)?,
+                drag: Self::convert_expr_with_props(&m.drag, module, props)?,
             })),
             XUpdateModifier::XSetAttribute(m) => Ok(Box::new(SetAttributeModifier {
                 attribute: Self::convert_attr(&m.attr)?,
-                value:     Self::convert_expr(&m.value, module)?,
+                value:     Self::convert_expr_with_props(&m.value, module, props)?,
             })),
         }
     }
@@ -406,7 +504,32 @@ impl ToHanabi
         }
     }
 
-    fn convert_expr(expr: &XExpr, module: &mut Module) -> Result<ExprHandle, XError>
+    /// Convert an expression to Hanabi, after simplifying it.
+    pub fn convert_expr(expr: &XExpr, module: &mut Module) -> Result<ExprHandle, XError>
+    {
+        Self::convert_bound(&expr.simplify(), module, &Bindings::default())
+    }
+
+    /// Convert an expression reading properties of the given types, after simplifying it.
+    pub fn convert_expr_with_props(
+        expr: &XExpr,
+        module: &mut Module,
+        props: &std::collections::HashMap<String, XExprReturnType>,
+    ) -> Result<ExprHandle, XError>
+    {
+        let bindings = Bindings {
+            props: props.clone(),
+            ..Default::default()
+        };
+        Self::convert_bound(&expr.simplify(), module, &bindings)
+    }
+
+    /// Convert an expression, with `let` values built once and shared by handle.
+    fn convert_bound(
+        expr: &XExpr,
+        module: &mut Module,
+        bindings: &Bindings,
+    ) -> Result<ExprHandle, XError>
     {
         use crate::gui::expr::{XBinaryOp, XBuiltInOp, XUnaryOp, XValue};
 
@@ -419,6 +542,10 @@ impl ToHanabi
                     XValue::Vec3(x, y, z) => {
                         Value::Vector(bevy::math::Vec3::new(*x, *y, *z).into())
                     }
+                    XValue::Vec4(x, y, z, w) => {
+                        Value::Vector(bevy::math::Vec4::new(*x, *y, *z, *w).into())
+                    }
+                    XValue::Bool(b) => Value::Scalar(ScalarValue::Bool(*b)),
                 };
                 Ok(module.lit(hanabi_value))
             }
@@ -432,6 +559,12 @@ impl ToHanabi
                     XBuiltInOp::Time => BuiltInOperator::Time,
                     XBuiltInOp::DeltaTime => BuiltInOperator::DeltaTime,
                     XBuiltInOp::Rand => BuiltInOperator::Rand(ValueType::Scalar(ScalarType::Float)),
+                    XBuiltInOp::RandVec2 => {
+                        BuiltInOperator::Rand(ValueType::Vector(VectorType::VEC2F))
+                    }
+                    XBuiltInOp::RandVec3 => {
+                        BuiltInOperator::Rand(ValueType::Vector(VectorType::VEC3F))
+                    }
                     _ => {
                         return Err(XError::UnknownBuiltInOperator(format!(
                             "Unsupported built-in operator: {:?}",
@@ -441,8 +574,56 @@ impl ToHanabi
                 };
                 Ok(module.builtin(hanabi_op))
             }
+            XExpr::Unary {
+                op: XUnaryOp::Neg,
+                expr,
+            } => {
+                // No negation operator in Hanabi: fold literals, otherwise multiply by -1.
+                if let XExpr::Lit(value) = &**expr {
+                    let negated = match value {
+                        XValue::Float(f) => XValue::Float(-f),
+                        XValue::Integer(i) => XValue::Integer(-i),
+                        XValue::Vec2(x, y) => XValue::Vec2(-x, -y),
+                        XValue::Vec3(x, y, z) => XValue::Vec3(-x, -y, -z),
+                        XValue::Vec4(x, y, z, w) => XValue::Vec4(-x, -y, -z, -w),
+                        XValue::Bool(_) => {
+                            return Err(XError::UnknownUnaryOperator(
+                                "Cannot negate a bool".to_string(),
+                            ));
+                        }
+                    };
+                    return Self::convert_bound(&XExpr::Lit(negated), module, bindings);
+                }
+                // Integers are multiplied by an integer, Hanabi does not mix scalar types.
+                let minus_one = match Self::result_type(expr, bindings) {
+                    Some(XExprReturnType::Integer) => ScalarValue::Int(-1),
+                    Some(XExprReturnType::Bool) => {
+                        return Err(XError::UnknownUnaryOperator(
+                            "Cannot negate a bool".to_string(),
+                        ));
+                    }
+                    _ => ScalarValue::Float(-1.0),
+                };
+                let inner = Self::convert_bound(expr, module, bindings)?;
+                let minus_one = module.lit(Value::Scalar(minus_one));
+                Ok(module.binary(BinaryOperator::Mul, inner, minus_one))
+            }
             XExpr::Unary { op, expr } => {
-                let inner = Self::convert_expr(expr, module)?;
+                let width = Self::vector_width(expr, bindings);
+                if *op == XUnaryOp::Tan || (*op == XUnaryOp::Length && width.is_some()) {
+                    Self::check_single_draw(expr, &op.to_string(), bindings)?;
+                }
+                // Vector comparisons are component-wise, and only valid when reduced here.
+                let inner = match &**expr {
+                    XExpr::Binary {
+                        left,
+                        op: cmp,
+                        right,
+                    } if matches!(op, XUnaryOp::All | XUnaryOp::Any) && cmp.is_comparison() => {
+                        Self::convert_comparison(left, *cmp, right, module, bindings)?
+                    }
+                    _ => Self::convert_bound(expr, module, bindings)?,
+                };
                 let hanabi_op = match op {
                     XUnaryOp::Abs => UnaryOperator::Abs,
                     XUnaryOp::All => UnaryOperator::All,
@@ -450,82 +631,484 @@ impl ToHanabi
                     XUnaryOp::Norm => UnaryOperator::Normalize,
                     XUnaryOp::Cos => UnaryOperator::Cos,
                     XUnaryOp::Sin => UnaryOperator::Sin,
-                    XUnaryOp::Neg => {
-                        return Err(XError::UnknownUnaryOperator(
-                            "Neg not supported in Hanabi conversion".to_string(),
-                        ));
+                    XUnaryOp::Neg => unreachable!("Neg is handled above"),
+                    // No tangent in Hanabi: tan(x) = sin(x) / cos(x).
+                    XUnaryOp::Tan => {
+                        let sin = module.unary(UnaryOperator::Sin, inner);
+                        let cos = module.unary(UnaryOperator::Cos, inner);
+                        return Ok(module.binary(BinaryOperator::Div, sin, cos));
+                    }
+                    // No sign in Hanabi: sign(x) = clamp(x * SATURATE_SCALE, -1, 1), exact
+                    // but for subnormal x.
+                    XUnaryOp::Sign => {
+                        let width = width.unwrap_or(1);
+                        return Ok(Self::saturate(inner, -1.0, width, module));
                     }
+                    // No length in Hanabi: length(v) = dot(v, normalize(v)), undefined for
+                    // a zero vector as normalize is, and abs(x) for floats.
+                    XUnaryOp::Length if width.is_some() => {
+                        let unit = module.unary(UnaryOperator::Normalize, inner);
+                        return Ok(module.binary(BinaryOperator::Dot, inner, unit));
+                    }
+                    XUnaryOp::Length => UnaryOperator::Abs,
                 };
                 Ok(module.unary(hanabi_op, inner))
             }
             XExpr::Binary { left, op, right } => {
                 // Handle special vector construction cases first
                 match op {
-                    XBinaryOp::Vec2 => {
-                        // For vec2, we need to evaluate the expressions and create a vector literal
-                        // This is a limitation - we can only create vector literals, not dynamic vector construction
-                        let x_val = Self::convert_scalar_expr(left, module)?;
-                        let y_val = Self::convert_scalar_expr(right, module)?;
-                        let vec2_value = Value::Vector(bevy::math::Vec2::new(x_val, y_val).into());
-                        return Ok(module.lit(vec2_value));
+                    XBinaryOp::Vec2 | XBinaryOp::Vec3 | XBinaryOp::Vec4 => {
+                        let size = match op {
+                            XBinaryOp::Vec2 => 2,
+                            XBinaryOp::Vec3 => 3,
+                            _ => 4,
+                        };
+                        let components = Self::vector_components(expr, module, bindings)?;
+                        if components.len() != size {
+                            return Err(XError::UnknownBinaryOperator(format!(
+                                "{} needs {} components, found {}",
+                                op,
+                                size,
+                                components.len()
+                            )));
+                        }
+                        return Ok(Self::build_vector(&components, module));
                     }
-                    XBinaryOp::Vec3 => {
-                        // For vec3, we need to handle the nested structure: ((x, y), z)
-                        // The left should be a vec2 expression, and right is the z component
-                        // We need to extract x and y from the left vec2 expression
-                        if let XExpr::Binary {
-                            left: vec2_left,
-                            op: XBinaryOp::Vec2,
-                            right: vec2_right,
-                        } = &**left
-                        {
-                            let x_val = Self::convert_scalar_expr(vec2_left, module)?;
-                            let y_val = Self::convert_scalar_expr(vec2_right, module)?;
-                            let z_val = Self::convert_scalar_expr(right, module)?;
-                            let vec3_value =
-                                Value::Vector(bevy::math::Vec3::new(x_val, y_val, z_val).into());
-                            return Ok(module.lit(vec3_value));
-                        } else {
-                            return Err(XError::UnknownBinaryOperator(
-                                "Vec3 requires nested Vec2 structure".to_string(),
-                            ));
+                    XBinaryOp::Normal => {
+                        return Self::convert_normal(left, right, module, bindings);
+                    }
+                    // No step in Hanabi: step(edge, x) = 1 - clamp((edge - x) * SATURATE_SCALE,
+                    // 0, 1), exact but for a subnormal difference.
+                    XBinaryOp::Step => {
+                        let width = Self::vector_width(left, bindings).unwrap_or(1);
+                        let edge = Self::convert_bound(left, module, bindings)?;
+                        let x = Self::convert_bound(right, module, bindings)?;
+                        let delta = module.binary(BinaryOperator::Sub, edge, x);
+                        let below = Self::saturate(delta, 0.0, width, module);
+                        let one = module.lit(Self::vector_value(&vec![1.0; width]));
+                        return Ok(module.binary(BinaryOperator::Sub, one, below));
+                    }
+                    // No distance in Hanabi: distance(a, b) = length(a - b).
+                    XBinaryOp::Distance => {
+                        let is_vector = Self::vector_width(left, bindings).is_some();
+                        if is_vector {
+                            Self::check_single_draw(left, "distance", bindings)?;
+                            Self::check_single_draw(right, "distance", bindings)?;
+                        }
+                        let a = Self::convert_bound(left, module, bindings)?;
+                        let b = Self::convert_bound(right, module, bindings)?;
+                        let delta = module.binary(BinaryOperator::Sub, a, b);
+                        if !is_vector {
+                            return Ok(module.unary(UnaryOperator::Abs, delta));
                         }
+                        let unit = module.unary(UnaryOperator::Normalize, delta);
+                        return Ok(module.binary(BinaryOperator::Dot, delta, unit));
+                    }
+                    _ if op.is_comparison() => {
+                        let is_vector = Self::vector_width(left, bindings).is_some()
+                            || Self::vector_width(right, bindings).is_some();
+                        if is_vector {
+                            return Err(XError::UnknownBinaryOperator(format!(
+                                "Vector comparison '{}' must be reduced with all() or any()",
+                                expr
+                            )));
+                        }
+                        return Self::convert_comparison(left, *op, right, module, bindings);
                     }
                     _ => {} // Continue to normal binary operator handling
                 }
 
-                let left_handle = Self::convert_expr(left, module)?;
-                let right_handle = Self::convert_expr(right, module)?;
+                let left_handle = Self::convert_bound(left, module, bindings)?;
+                let right_handle = Self::convert_bound(right, module, bindings)?;
                 let hanabi_op = match op {
                     XBinaryOp::Add => BinaryOperator::Add,
                     XBinaryOp::Sub => BinaryOperator::Sub,
                     XBinaryOp::Mul => BinaryOperator::Mul,
                     XBinaryOp::Div => BinaryOperator::Div,
-                    XBinaryOp::Lt => BinaryOperator::LessThan,
-                    XBinaryOp::Lte => BinaryOperator::LessThanOrEqual,
-                    XBinaryOp::Ge => BinaryOperator::GreaterThan,
-                    XBinaryOp::Gte => BinaryOperator::GreaterThanOrEqual,
                     XBinaryOp::Min => BinaryOperator::Min,
                     XBinaryOp::Max => BinaryOperator::Max,
                     XBinaryOp::Dot => BinaryOperator::Dot,
                     XBinaryOp::Cross => BinaryOperator::Cross,
-                    XBinaryOp::Vec2 | XBinaryOp::Vec3 => {
+                    XBinaryOp::Uniform => BinaryOperator::UniformRand,
+                    XBinaryOp::Vec2
+                    | XBinaryOp::Vec3
+                    | XBinaryOp::Vec4
+                    | XBinaryOp::Lt
+                    | XBinaryOp::Lte
+                    | XBinaryOp::Ge
+                    | XBinaryOp::Gte
+                    | XBinaryOp::Eq
+                    | XBinaryOp::Neq
+                    | XBinaryOp::Normal
+                    | XBinaryOp::Step
+                    | XBinaryOp::Distance => {
                         // These cases are handled above, but we need to satisfy the match
-                        unreachable!("Vec2 and Vec3 are handled above")
+                        unreachable!("Constructors, comparisons and lowerings are handled above")
                     }
-                    XBinaryOp::Eq => {
-                        return Err(XError::UnknownBinaryOperator(
-                            "Eq not supported in Hanabi conversion".to_string(),
-                        ));
+                };
+                Ok(module.binary(hanabi_op, left_handle, right_handle))
+            }
+            XExpr::Ternary {
+                op,
+                first,
+                second,
+                third,
+            } => {
+                // No ternary operators in Hanabi: operands are converted once and shared.
+                if *op == XTernaryOp::Mix {
+                    Self::check_single_draw(first, "mix", bindings)?;
+                }
+                let first = Self::convert_bound(first, module, bindings)?;
+                let second = Self::convert_bound(second, module, bindings)?;
+                let third = Self::convert_bound(third, module, bindings)?;
+                match op {
+                    // mix(a, b, t) = a + (b - a) * t
+                    XTernaryOp::Mix => {
+                        let delta = module.binary(BinaryOperator::Sub, second, first);
+                        let scaled = module.binary(BinaryOperator::Mul, delta, third);
+                        Ok(module.binary(BinaryOperator::Add, first, scaled))
                     }
-                    XBinaryOp::Neq => {
-                        return Err(XError::UnknownBinaryOperator(
-                            "Neq not supported in Hanabi conversion".to_string(),
-                        ));
+                    // clamp(x, low, high) = min(max(x, low), high)
+                    XTernaryOp::Clamp => {
+                        let low_bounded = module.binary(BinaryOperator::Max, first, second);
+                        Ok(module.binary(BinaryOperator::Min, low_bounded, third))
                     }
+                }
+            }
+            XExpr::Swizzle { expr, components } => {
+                Self::convert_swizzle(expr, components, module, bindings)
+            }
+            XExpr::Let { name, value, body } => {
+                if body.count_var(name) > 1 {
+                    Self::check_single_draw(value, "let", bindings)?;
+                }
+                let handle = Self::convert_bound(value, module, bindings)?;
+                let mut bindings = bindings.clone();
+                bindings.handles.insert(name.clone(), handle);
+                if Self::is_random(value, &bindings) {
+                    let is_builtin = Self::is_rand_builtin(value, &bindings);
+                    bindings.random.insert(name.clone(), is_builtin);
+                } else {
+                    bindings.random.remove(name);
+                }
+                if let Some(value_type) = Self::result_type(value, &bindings) {
+                    bindings.types.insert(name.clone(), value_type);
+                }
+                Self::convert_bound(body, module, &bindings)
+            }
+            XExpr::Var(name) => bindings
+                .handles
+                .get(name)
+                .copied()
+                .ok_or_else(|| XError::UnknownVariable(name.clone())),
+        }
+    }
+
+    /// Convert a comparison, component-wise for vectors.
+    fn convert_comparison(
+        left: &XExpr,
+        op: XBinaryOp,
+        right: &XExpr,
+        module: &mut Module,
+        bindings: &Bindings,
+    ) -> Result<ExprHandle, XError>
+    {
+        if matches!(op, XBinaryOp::Eq | XBinaryOp::Neq) {
+            Self::check_single_draw(left, "==", bindings)?;
+            Self::check_single_draw(right, "==", bindings)?;
+        }
+        let left = Self::convert_bound(left, module, bindings)?;
+        let right = Self::convert_bound(right, module, bindings)?;
+        let hanabi_op = match op {
+            XBinaryOp::Lt => BinaryOperator::LessThan,
+            XBinaryOp::Lte => BinaryOperator::LessThanOrEqual,
+            XBinaryOp::Ge => BinaryOperator::GreaterThan,
+            XBinaryOp::Gte => BinaryOperator::GreaterThanOrEqual,
+            // No equality operator in Hanabi: a == b <=> max(a, b) <= min(a, b).
+            XBinaryOp::Eq | XBinaryOp::Neq => {
+                let max = module.binary(BinaryOperator::Max, left, right);
+                let min = module.binary(BinaryOperator::Min, left, right);
+                let cmp = if op == XBinaryOp::Eq {
+                    BinaryOperator::LessThanOrEqual
+                } else {
+                    BinaryOperator::GreaterThan
                 };
-                Ok(module.binary(hanabi_op, left_handle, right_handle))
+                return Ok(module.binary(cmp, max, min));
+            }
+            _ => {
+                return Err(XError::UnknownBinaryOperator(format!(
+                    "{} is not a comparison",
+                    op
+                )));
+            }
+        };
+        Ok(module.binary(hanabi_op, left, right))
+    }
+
+    /// Build a vector from its components.
+    ///
+    /// Folds to a literal when every component is constant. Otherwise the Hanabi graph
+    /// has no vector constructor, so the vector is the sum of `unit_axis * component`,
+    /// with the constant components merged into one literal term.
+    fn build_vector(components: &[Component], module: &mut Module) -> ExprHandle
+    {
+        if let [Component::Scalar(handle)] = components {
+            return *handle;
+        }
+        let mut constant_part = vec![0.0; components.len()];
+        let mut sum = None;
+        for (axis, component) in components.iter().enumerate() {
+            let component = match component {
+                Component::Constant(value) => {
+                    constant_part[axis] = *value;
+                    continue;
+                }
+                Component::Scalar(handle) => *handle,
+            };
+            let mut unit = vec![0.0; components.len()];
+            unit[axis] = 1.0;
+            // Vector on the left so the product is typed as a vector.
+            let unit = module.lit(Self::vector_value(&unit));
+            let term = module.binary(BinaryOperator::Mul, unit, component);
+            sum = Some(match sum {
+                Some(acc) => module.binary(BinaryOperator::Add, acc, term),
+                None => term,
+            });
+        }
+
+        let constant_is_zero = constant_part.iter().all(|v| *v == 0.0);
+        match sum {
+            Some(sum) if constant_is_zero => sum,
+            Some(sum) => {
+                let constant = module.lit(Self::vector_value(&constant_part));
+                module.binary(BinaryOperator::Add, sum, constant)
             }
+            None => module.lit(Self::vector_value(&constant_part)),
+        }
+    }
+
+    /// Components of a vector constructor. Vector arguments are converted once and split
+    /// into their components.
+    fn vector_components(
+        expr: &XExpr,
+        module: &mut Module,
+        bindings: &Bindings,
+    ) -> Result<Vec<Component>, XError>
+    {
+        if let XExpr::Binary {
+            left,
+            op: XBinaryOp::Vec2 | XBinaryOp::Vec3 | XBinaryOp::Vec4,
+            right,
+        } = expr
+        {
+            let mut components = Self::vector_components(left, module, bindings)?;
+            components.extend(Self::vector_components(right, module, bindings)?);
+            return Ok(components);
+        }
+        if let Some(value) = Self::constant_scalar(expr) {
+            return Ok(vec![Component::Constant(value)]);
+        }
+        match (expr, Self::vector_width(expr, bindings)) {
+            (XExpr::Lit(value), Some(_)) => Ok(value
+                .float_components()
+                .unwrap_or_default()
+                .into_iter()
+                .map(Component::Constant)
+                .collect()),
+            (_, Some(width)) => {
+                // Random built-ins have independent components, which may be drawn apart.
+                if !Self::is_rand_builtin(expr, bindings) {
+                    Self::check_single_draw(expr, "a vector constructor", bindings)?;
+                }
+                let vector = Self::convert_bound(expr, module, bindings)?;
+                let axes: Vec<usize> = (0..width).collect();
+                Ok(Self::axis_components(vector, width, &axes, module))
+            }
+            (_, None) => {
+                let scalar = Self::convert_bound(expr, module, bindings)?;
+                Ok(vec![Component::Scalar(scalar)])
+            }
+        }
+    }
+
+    /// Components of a vector in the graph at the given axes. Hanabi has no swizzle, so
+    /// each one is `dot(v, unit_axis)`.
+    fn axis_components(
+        vector: ExprHandle,
+        width: usize,
+        axes: &[usize],
+        module: &mut Module,
+    ) -> Vec<Component>
+    {
+        axes.iter()
+            .map(|&axis| {
+                let mut unit = vec![0.0; width];
+                unit[axis] = 1.0;
+                let unit = module.lit(Self::vector_value(&unit));
+                Component::Scalar(module.binary(BinaryOperator::Dot, vector, unit))
+            })
+            .collect()
+    }
+
+    /// Convert `normal(mean, sd)` to Hanabi.
+    ///
+    /// Hanabi only has uniform random values, so the standard normal is approximated by
+    /// the sum of `NORMAL_SAMPLES` uniform values, centered and scaled to unit variance.
+    fn convert_normal(
+        mean: &XExpr,
+        sd: &XExpr,
+        module: &mut Module,
+        bindings: &Bindings,
+    ) -> Result<ExprHandle, XError>
+    {
+        let width = Self::vector_width(mean, bindings).or_else(|| Self::vector_width(sd, bindings));
+        let rand_type = match width {
+            Some(2) => ValueType::Vector(VectorType::VEC2F),
+            Some(3) => ValueType::Vector(VectorType::VEC3F),
+            Some(_) => ValueType::Vector(VectorType::VEC4F),
+            None => ValueType::Scalar(ScalarType::Float),
+        };
+        let mean = Self::convert_bound(mean, module, bindings)?;
+        let sd = Self::convert_bound(sd, module, bindings)?;
+
+        let mut sum = module.builtin(BuiltInOperator::Rand(rand_type));
+        for _ in 1..NORMAL_SAMPLES {
+            let sample = module.builtin(BuiltInOperator::Rand(rand_type));
+            sum = module.binary(BinaryOperator::Add, sum, sample);
+        }
+        let center = module.lit(Value::Scalar(ScalarValue::Float(
+            NORMAL_SAMPLES as f32 / 2.0,
+        )));
+        let centered = module.binary(BinaryOperator::Sub, sum, center);
+        let scale = module.lit(Value::Scalar(ScalarValue::Float(NORMAL_SCALE)));
+        let noise = module.binary(BinaryOperator::Mul, centered, scale);
+        let deviation = module.binary(BinaryOperator::Mul, sd, noise);
+        Ok(module.binary(BinaryOperator::Add, mean, deviation))
+    }
+
+    /// Build `clamp(x * SATURATE_SCALE, low, 1)`, with bounds of the width of `x`.
+    fn saturate(x: ExprHandle, low: f32, width: usize, module: &mut Module) -> ExprHandle
+    {
+        let scale = module.lit(Value::Scalar(ScalarValue::Float(SATURATE_SCALE)));
+        let scaled = module.binary(BinaryOperator::Mul, x, scale);
+        let low = module.lit(Self::vector_value(&vec![low; width]));
+        let high = module.lit(Self::vector_value(&vec![1.0; width]));
+        let bounded = module.binary(BinaryOperator::Max, scaled, low);
+        module.binary(BinaryOperator::Min, bounded, high)
+    }
+
+    /// Convert a swizzle to Hanabi.
+    ///
+    /// Literal vectors pick their components directly. Otherwise the vector is converted
+    /// once, and the picked components are recombined into a vector.
+    fn convert_swizzle(
+        expr: &XExpr,
+        components: &str,
+        module: &mut Module,
+        bindings: &Bindings,
+    ) -> Result<ExprHandle, XError>
+    {
+        let invalid = || XError::InvalidSwizzle(format!("{}.{}", expr, components));
+        let indices = XExpr::swizzle_indices(components).ok_or_else(invalid)?;
+        let width = Self::vector_width(expr, bindings).ok_or_else(invalid)?;
+        if indices.iter().any(|&i| i >= width) {
+            return Err(invalid());
+        }
+
+        if let XExpr::Lit(value) = expr {
+            let values = match value {
+                XValue::Vec2(x, y) => vec![*x, *y],
+                XValue::Vec3(x, y, z) => vec![*x, *y, *z],
+                XValue::Vec4(x, y, z, w) => vec![*x, *y, *z, *w],
+                _ => return Err(invalid()),
+            };
+            let picked: Vec<f32> = indices.iter().map(|&i| values[i]).collect();
+            return Ok(module.lit(Self::vector_value(&picked)));
+        }
+
+        // Random built-ins have independent components, which may be drawn apart but once.
+        let is_distinct = indices
+            .iter()
+            .enumerate()
+            .all(|(i, a)| !indices[..i].contains(a));
+        if indices.len() > 1 && !(is_distinct && Self::is_rand_builtin(expr, bindings)) {
+            Self::check_single_draw(expr, "a swizzle", bindings)?;
+        }
+        let vector = Self::convert_bound(expr, module, bindings)?;
+        let components = Self::axis_components(vector, width, &indices, module);
+        Ok(Self::build_vector(&components, module))
+    }
+
+    /// Refuse a random value as an operand the lowering of `what` refers to more than once.
+    ///
+    /// Hanabi writes an expression out again at each reference to its handle, so each
+    /// reference to a random value draws a new one.
+    fn check_single_draw(expr: &XExpr, what: &str, bindings: &Bindings) -> Result<(), XError>
+    {
+        if Self::is_random(expr, bindings) {
+            return Err(XError::SharedRandomValue(format!("{} in {}", expr, what)));
+        }
+        Ok(())
+    }
+
+    /// Whether an expression draws random values, directly or through bindings in scope.
+    fn is_random(expr: &XExpr, bindings: &Bindings) -> bool
+    {
+        expr.is_random() || bindings.random.keys().any(|name| expr.uses_var(name))
+    }
+
+    /// Whether an expression is a random built-in, or a binding to one.
+    fn is_rand_builtin(expr: &XExpr, bindings: &Bindings) -> bool
+    {
+        match expr {
+            XExpr::BuiltIn(op) => matches!(
+                op,
+                XBuiltInOp::Rand | XBuiltInOp::RandVec2 | XBuiltInOp::RandVec3
+            ),
+            XExpr::Var(name) => bindings.random.get(name).copied().unwrap_or_default(),
+            _ => false,
+        }
+    }
+
+    /// Number of components of a vector expression, from its type.
+    fn vector_width(expr: &XExpr, bindings: &Bindings) -> Option<usize>
+    {
+        let result_type = Self::result_type(expr, bindings)?;
+        result_type.width().filter(|&width| width > 1)
+    }
+
+    /// Type of an expression, from the attribute and property types and the bindings in scope.
+    fn result_type(expr: &XExpr, bindings: &Bindings) -> Option<XExprReturnType>
+    {
+        let attributes = XAttr::iter()
+            .map(|attr| (attr.to_string(), attr.get_type()))
+            .collect();
+        expr.get_result_type_in(&attributes, &bindings.props, &bindings.types)
+    }
+
+    fn vector_value(values: &[f32]) -> Value
+    {
+        match values {
+            [x, y] => Value::Vector(bevy::math::Vec2::new(*x, *y).into()),
+            [x, y, z] => Value::Vector(bevy::math::Vec3::new(*x, *y, *z).into()),
+            [x, y, z, w, ..] => Value::Vector(bevy::math::Vec4::new(*x, *y, *z, *w).into()),
+            _ => Value::Scalar(ScalarValue::Float(
+                values.first().copied().unwrap_or_default(),
+            )),
+        }
+    }
+
+    /// Scalar value of a constant expression, if it is one.
+    fn constant_scalar(expr: &XExpr) -> Option<f32>
+    {
+        match expr {
+            XExpr::Lit(XValue::Float(f)) => Some(*f),
+            XExpr::Lit(XValue::Integer(i)) => Some(*i as f32),
+            XExpr::Unary {
+                op: XUnaryOp::Neg,
+                expr,
+            } => Self::constant_scalar(expr).map(|v| -v),
+            _ => None,
         }
     }
 
@@ -663,6 +1246,359 @@ pub struct AssetState
     pub force_fields: Vec<XForceFieldSource>,
 
     pub properties: Vec<KeyValueEntry>,
+
+    // Parts of the imported effect that cannot be edited.
+    pub raw_init_modifiers:   Vec<XRawEntry>,
+    pub raw_update_modifiers: Vec<XRawEntry>,
+    pub raw_render_modifiers: Vec<XRawEntry>,
+    pub raw_properties:       Vec<XRawEntry>,
+    /// Expressions of the imported effect that the raw modifiers refer to by handle.
+    pub raw_module:           Module,
+}
+
+impl AssetState
+{
+    /// Describe the parts of the effect that are kept as written, without being editable.
+    pub fn raw_summary(&self) -> Vec<String>
+    {
+        let lists = [
+            ("Init modifier", &self.raw_init_modifiers),
+            ("Update modifier", &self.raw_update_modifiers),
+            ("Render modifier", &self.raw_render_modifiers),
+            ("Property", &self.raw_properties),
+        ];
+        lists
+            .iter()
+            .flat_map(|(kind, entries)| {
+                entries
+                    .iter()
+                    .map(move |entry| format!("{} '{}': {}", kind, entry.name, entry.reason))
+            })
+            .collect()
+    }
+}
+
+/// Environment that expression previews and plots are evaluated in, shared with every
+/// expression inspector: the property values of the asset being edited, and the lifetime
+/// its particles are given.
+#[derive(Clone, Debug, Default)]
+pub struct PreviewEnv(pub XEnv);
+
+impl Global for PreviewEnv {}
+
+impl PreviewEnv
+{
+    pub fn from_state(state: &AssetState) -> Self
+    {
+        let mut env = XEnv::new();
+        for entry in &state.properties {
+            env.props
+                .insert(entry.key.to_string(), entry.value.get_expr_value());
+        }
+        // A random lifetime is previewed with its first sample.
+        let lifetime = state
+            .init_modifiers
+            .iter()
+            .find_map(|modifier| match modifier {
+                XInitModifier::XSetAttribute(m) if m.attr == XAttr::Lifetime => {
+                    m.value.eval(&mut env.clone()).ok()
+                }
+                _ => None,
+            });
+        if let Some(lifetime @ XValue::Float(_)) = lifetime {
+            env.attributes.insert(XAttr::Lifetime.to_string(), lifetime);
+        }
+        Self(env)
+    }
+
+    /// Environment that plots are evaluated in, where the attributes the preview has no
+    /// value for are zero, so that plots over age of expressions reading them still draw.
+    pub fn plot_env(&self) -> XEnv
+    {
+        let mut env = self.0.clone();
+        for attr in XAttr::iter() {
+            let width = attr.get_type().width().unwrap_or(1);
+            if let Some(zero) = XValue::from_components(&vec![0.0; width]) {
+                env.attributes.entry(attr.to_string()).or_insert(zero);
+            }
+        }
+        env
+    }
+}
+
+/// Modifier or property of an imported effect that the editor cannot convert.
+///
+/// Kept as RON and written back untouched, at its position in the imported effect.
+#[derive(Clone, Debug, PartialEq)]
+pub struct XRawEntry
+{
+    /// Type name of the modifier, or name of the property.
+    pub name:   String,
+    /// Why it could not be converted.
+    pub reason: String,
+    /// Position in its list in the imported effect.
+    pub index:  usize,
+    pub ron:    String,
+}
+
+impl XRawEntry
+{
+    fn modifier(modifier: &BoxedModifier, index: usize, error: &XError) -> Result<Self, XError>
+    {
+        let ron = Self::to_ron(modifier)?;
+        // Modifiers are tagged with their type name, as in `{"AccelModifier":(...)}`
+        let name = ron.split('"').nth(1).unwrap_or("Unknown").to_string();
+        Ok(Self {
+            name,
+            reason: error.to_string(),
+            index,
+            ron,
+        })
+    }
+
+    fn property(property: &Property, index: usize, error: &XError) -> Result<Self, XError>
+    {
+        Ok(Self {
+            name: property.name().to_string(),
+            reason: error.to_string(),
+            index,
+            ron: Self::to_ron(property)?,
+        })
+    }
+
+    fn to_ron<T: Serialize + ?Sized>(value: &T) -> Result<String, XError>
+    {
+        ron::to_string(value).map_err(|e| XError::InvalidRawEntry(e.to_string()))
+    }
+}
+
+/// Expressions of an imported effect that its raw modifiers refer to, copied to a module
+/// of their own so that the rest of the imported module is not kept.
+#[derive(Default)]
+struct RawExprs
+{
+    module: Module,
+    /// Handle of the copy of each expression copied, by its address in the imported module.
+    copies: std::collections::HashMap<*const Expr, ExprHandle>,
+}
+
+impl RawExprs
+{
+    /// Copy of a modifier of the imported effect, referring to the copies of its expressions.
+    fn modifier(&mut self, modifier: &BoxedModifier, m: &Module) -> Result<BoxedModifier, XError>
+    {
+        let mut modifier = modifier.clone();
+        self.copy_handles(modifier.as_reflect_mut(), m)?;
+        Ok(modifier)
+    }
+
+    /// Replace the expression handles found in a value by the handles of their copies.
+    fn copy_handles(&mut self, value: &mut dyn Reflect, m: &Module) -> Result<(), XError>
+    {
+        if let Some(handle) = value.downcast_mut::<ExprHandle>() {
+            *handle = self.copy_expr(*handle, m)?;
+            return Ok(());
+        }
+        match value.reflect_mut() {
+            ReflectMut::Struct(v) => {
+                for index in 0..v.field_len() {
+                    if let Some(field) = v.field_at_mut(index) {
+                        self.copy_handles(field, m)?;
+                    }
+                }
+            }
+            ReflectMut::TupleStruct(v) => {
+                for index in 0..v.field_len() {
+                    if let Some(field) = v.field_mut(index) {
+                        self.copy_handles(field, m)?;
+                    }
+                }
+            }
+            ReflectMut::Tuple(v) => {
+                for index in 0..v.field_len() {
+                    if let Some(field) = v.field_mut(index) {
+                        self.copy_handles(field, m)?;
+                    }
+                }
+            }
+            ReflectMut::List(v) => {
+                for index in 0..v.len() {
+                    if let Some(item) = v.get_mut(index) {
+                        self.copy_handles(item, m)?;
+                    }
+                }
+            }
+            ReflectMut::Array(v) => {
+                for index in 0..v.len() {
+                    if let Some(item) = v.get_mut(index) {
+                        self.copy_handles(item, m)?;
+                    }
+                }
+            }
+            ReflectMut::Enum(v) => {
+                for index in 0..v.field_len() {
+                    if let Some(field) = v.field_at_mut(index) {
+                        self.copy_handles(field, m)?;
+                    }
+                }
+            }
+            // Modifiers hold no handles in maps.
+            ReflectMut::Map(_) | ReflectMut::Value(_) => {}
+        }
+        Ok(())
+    }
+
+    /// Copy an expression of the imported module along with the expressions it refers to.
+    fn copy_expr(&mut self, handle: ExprHandle, m: &Module) -> Result<ExprHandle, XError>
+    {
+        let expr = m
+            .get(handle)
+            .ok_or_else(|| XError::ExpressionNotFound(format!("{:?}", handle)))?;
+        if let Some(copy) = self.copies.get(&(expr as *const Expr)) {
+            return Ok(*copy);
+        }
+        let copy = match expr {
+            Expr::Unary { op, expr } => {
+                let expr = self.copy_expr(*expr, m)?;
+                self.module.unary(*op, expr)
+            }
+            Expr::Binary { op, left, right } => {
+                let left = self.copy_expr(*left, m)?;
+                let right = self.copy_expr(*right, m)?;
+                self.module.binary(*op, left, right)
+            }
+            Expr::Literal(_) | Expr::Attribute(_) | Expr::Property(_) | Expr::BuiltIn(_) => {
+                self.module.push(expr.clone())
+            }
+        };
+        self.copies.insert(expr as *const Expr, copy);
+        Ok(copy)
+    }
+}
+
+/// Change made to an effect by converting it to editor models, which saving would keep.
+#[derive(Clone, Debug, PartialEq)]
+pub enum XImportWarning
+{
+    /// A force field source without mass is inactive, and dropped.
+    MasslessForceField
+    {
+        index: usize, source: usize
+    },
+    /// The axis of a tangent velocity init modifier is replaced by the Y axis.
+    TangentAxis
+    {
+        index: usize
+    },
+}
+
+impl std::fmt::Display for XImportWarning
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        match self {
+            XImportWarning::MasslessForceField { index, source } => {
+                write!(
+                    f,
+                    "Update modifier {}: force field source {} has no mass, and is dropped",
+                    index, source
+                )
+            }
+            XImportWarning::TangentAxis { index } => {
+                write!(
+                    f,
+                    "Init modifier {}: tangent velocity axis is replaced by Y",
+                    index
+                )
+            }
+        }
+    }
+}
+
+/// Editor models of an effect, with the changes the conversion made to it.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct XImport
+{
+    pub state:    AssetState,
+    pub warnings: Vec<XImportWarning>,
+}
+
+/// Names of vector components, by axis.
+const AXES: [&str; 4] = ["x", "y", "z", "w"];
+
+/// Expressions of a Hanabi graph that several expressions refer to, and the `let`
+/// bindings they are read back as. Expressions are identified by their address in the
+/// module, as `Module::get` does not give their handle back.
+///
+/// `random` tells the expressions that draw random values, which Hanabi draws again at
+/// each reference.
+#[derive(Default)]
+struct SharedExprs
+{
+    uses:     std::collections::HashMap<*const Expr, usize>,
+    random:   std::collections::HashMap<*const Expr, bool>,
+    names:    std::collections::HashMap<*const Expr, String>,
+    bindings: Vec<(String, XExpr)>,
+}
+
+impl SharedExprs
+{
+    /// Find the expressions reachable from `expr` that draw random values.
+    fn mark_random(&mut self, expr: &Expr, m: &Module) -> bool
+    {
+        let key = expr as *const Expr;
+        if let Some(random) = self.random.get(&key) {
+            return *random;
+        }
+        let mut mark = |handle: ExprHandle| m.get(handle).is_some_and(|e| self.mark_random(e, m));
+        let random = match expr {
+            Expr::BuiltIn(builtin) => matches!(builtin.operator, BuiltInOperator::Rand(_)),
+            Expr::Unary { expr, .. } => mark(*expr),
+            Expr::Binary { op, left, right } => {
+                let left = mark(*left);
+                let right = mark(*right);
+                matches!(op, BinaryOperator::UniformRand) || left || right
+            }
+            Expr::Literal(_) | Expr::Attribute(_) | Expr::Property(_) => false,
+        };
+        self.random.insert(key, random);
+        random
+    }
+
+    /// Whether the expression draws random values.
+    fn is_random(&self, expr: &Expr) -> bool
+    {
+        self.random
+            .get(&(expr as *const Expr))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Expression at `handle`, as a part of a lowering, which the graph must not share.
+    fn part<'a>(&self, m: &'a Module, handle: ExprHandle) -> Option<&'a Expr>
+    {
+        let expr = m.get(handle)?;
+        let uses = self
+            .uses
+            .get(&(expr as *const Expr))
+            .copied()
+            .unwrap_or_default();
+        (uses < 2).then_some(expr)
+    }
+
+    /// Wrap `body` in the bindings it refers to. Values referred to once are inlined, such
+    /// as the operand the lowering of `tan(x)` shares.
+    fn bind(self, body: XExpr) -> XExpr
+    {
+        self.bindings
+            .into_iter()
+            .rev()
+            .fold(body, |body, (name, value)| match body.count_var(&name) {
+                0 => body,
+                1 => body.substitute(&[name], &[value]),
+                _ => XExpr::bind(name, value, body),
+            })
+    }
 }
 
 /// Converter for Hanabi to editor models.
@@ -672,34 +1608,85 @@ impl FromHanabi
 {
     pub fn asset_state(asset: &EffectAsset) -> Result<AssetState, XError>
     {
+        Self::import(asset).map(|import| import.state)
+    }
+
+    /// Convert an effect, reporting the values that the editor models cannot hold.
+    pub fn import(asset: &EffectAsset) -> Result<XImport, XError>
+    {
+        let mut warnings = Vec::new();
+
         let spawner = &asset.spawner;
-        let num_particles = spawner.num_particles.range()[0];
-        let spawn_time = spawner.spawn_time.range()[0];
-        let period = spawner.period.range()[0];
-        let properties = asset
-            .properties
-            .iter()
-            .filter_map(|prop| Self::prop(prop).ok())
-            .collect();
+        let num_particles = Self::spawner_value(&spawner.num_particles);
+        let spawn_time = Self::spawner_value(&spawner.spawn_time);
+        let period = Self::spawner_value(&spawner.period);
+        // Parts that cannot be converted are kept raw, so that saving does not drop them
+        let mut raw = RawExprs::default();
+        let mut properties = Vec::new();
+        let mut raw_properties = Vec::new();
+        for (index, prop) in asset.properties.iter().enumerate() {
+            match Self::prop(prop) {
+                Ok(entry) => properties.push(entry),
+                Err(error) => raw_properties.push(XRawEntry::property(prop, index, &error)?),
+            }
+        }
 
-        let init_modifiers = asset
-            .init_modifiers
-            .iter()
-            .filter_map(|modifier| Self::init_modifier(modifier, &asset.module).ok())
-            .collect();
+        let mut init_modifiers = Vec::new();
+        let mut raw_init_modifiers = Vec::new();
+        for (index, modifier) in asset.init_modifiers.iter().enumerate() {
+            if let Some(v) = modifier
+                .as_any()
+                .downcast_ref::<SetVelocityTangentModifier>()
+            {
+                if !Self::is_y_axis(v.axis, &asset.module) {
+                    warnings.push(XImportWarning::TangentAxis { index });
+                }
+            }
+            match Self::init_modifier(modifier, &asset.module) {
+                Ok(converted_modifier) => init_modifiers.push(converted_modifier),
+                Err(error) => {
+                    let modifier = raw.modifier(modifier, &asset.module)?;
+                    raw_init_modifiers.push(XRawEntry::modifier(&modifier, index, &error)?)
+                }
+            }
+        }
 
         // Extract update modifiers and force fields
         let mut update_modifiers = Vec::new();
+        let mut raw_update_modifiers = Vec::new();
         let mut force_fields: Vec<XForceFieldSource> = Vec::new();
 
-        for modifier in &asset.update_modifiers {
-            if let Ok(converted_modifier) = Self::update_modifier(modifier, &asset.module) {
-                update_modifiers.push(converted_modifier);
-            }
-
-            // Check if this is a force field modifier and extract sources
-            if let Ok(sources) = Self::extract_force_field_sources(modifier) {
-                force_fields.extend(sources);
+        for (index, modifier) in asset.update_modifiers.iter().enumerate() {
+            // Force field modifiers are merged into the force fields section
+            let converted = if let Some(force_field) = modifier
+                .as_any()
+                .downcast_ref::<modifier::ForceFieldModifier>()
+            {
+                let default = modifier::ForceFieldSource::default();
+                for (source_index, source) in force_field.sources.iter().enumerate() {
+                    // Unused slots of the source array are left at their default
+                    let is_unused = source.position == default.position
+                        && source.max_radius == default.max_radius
+                        && source.min_radius == default.min_radius
+                        && source.force_exponent == default.force_exponent;
+                    if source.mass <= 0.0 && !is_unused {
+                        warnings.push(XImportWarning::MasslessForceField {
+                            index,
+                            source: source_index,
+                        });
+                    }
+                }
+                Self::extract_force_field_sources(modifier).map(|sources| {
+                    force_fields.extend(sources);
+                })
+            } else {
+                Self::update_modifier(modifier, &asset.module).map(|converted_modifier| {
+                    update_modifiers.push(converted_modifier);
+                })
+            };
+            if let Err(error) = converted {
+                let modifier = raw.modifier(modifier, &asset.module)?;
+                raw_update_modifiers.push(XRawEntry::modifier(&modifier, index, &error)?);
             }
         }
 
@@ -707,22 +1694,29 @@ impl FromHanabi
         let mut size_over_time = Vec::new();
         let mut color_over_time = Vec::new();
         let mut render_modifiers = Vec::new();
+        let mut raw_render_modifiers = Vec::new();
 
-        for modifier in &asset.render_modifiers {
+        for (index, modifier) in asset.render_modifiers.iter().enumerate() {
             // Try to extract as orient modifier first
             if let Ok(orient_modifier) = Self::render_modifier_from_hanabi(modifier, &asset.module)
             {
                 render_modifiers.push(orient_modifier);
-            } else {
-                // Fall back to legacy render modifiers (size/color over time)
-                if let Ok((color_keys, size_keys)) = Self::render_modifier_legacy(modifier) {
+                continue;
+            }
+            // Fall back to legacy render modifiers (size/color over time)
+            match Self::render_modifier_legacy(modifier) {
+                Ok((color_keys, size_keys)) => {
                     color_over_time.extend(color_keys);
                     size_over_time.extend(size_keys);
                 }
+                Err(error) => {
+                    let modifier = raw.modifier(modifier, &asset.module)?;
+                    raw_render_modifiers.push(XRawEntry::modifier(&modifier, index, &error)?)
+                }
             }
         }
 
-        Ok(AssetState {
+        let state = AssetState {
             name: asset.name.clone(),
             capacity: asset.capacity as i32,
             z_layer_2d: asset.z_layer_2d,
@@ -747,7 +1741,34 @@ impl FromHanabi
             force_fields,
 
             properties,
-        })
+
+            raw_init_modifiers,
+            raw_update_modifiers,
+            raw_render_modifiers,
+            raw_properties,
+            raw_module: raw.module,
+        };
+        Ok(XImport { state, warnings })
+    }
+
+    /// Spawner value of a Hanabi value, which is a range only when its bounds differ.
+    fn spawner_value(value: &CpuValue<f32>) -> SpawnerValue
+    {
+        let [min, max] = value.range();
+        if min == max {
+            SpawnerValue::Single(min)
+        } else {
+            SpawnerValue::Uniform { min, max }
+        }
+    }
+
+    /// Whether an expression is the literal Y axis, which tangent velocities are edited with.
+    fn is_y_axis(handle: ExprHandle, m: &Module) -> bool
+    {
+        match m.get(handle).map(|expr| Self::xepr(expr, m)) {
+            Some(Ok(XExpr::Lit(XValue::Vec3(x, y, z)))) => (x, y, z) == (0.0, 1.0, 0.0),
+            _ => false,
+        }
     }
 
     fn prop(prop: &Property) -> Result<KeyValueEntry, XError>
@@ -757,7 +1778,7 @@ impl FromHanabi
             &Value::Scalar(scalar) => match scalar {
                 ScalarValue::Float(f) => Ok(KeyValue::Float(f)),
                 ScalarValue::Int(i) => Ok(KeyValue::Integer(i)),
-                ScalarValue::Bool(b) => Ok(KeyValue::Integer(if b { 1 } else { 0 })),
+                ScalarValue::Bool(b) => Ok(KeyValue::Bool(b)),
                 _ => Err(XError::UnsupportedPropertyType(format!(
                     "Scalar type: {:?}",
                     scalar
@@ -941,7 +1962,59 @@ impl FromHanabi
         }
     }
 
+    /// Convert a Hanabi expression. Expressions the graph shares are bound with `let`, but
+    /// for random values: Hanabi draws them again at each reference, and they are read
+    /// back as separate draws.
     pub fn xepr(expr: &Expr, m: &Module) -> Result<XExpr, XError>
+    {
+        let mut shared = SharedExprs::default();
+        Self::count_uses(expr, m, &mut shared.uses);
+        shared.mark_random(expr, m);
+        let body = Self::xepr_node(expr, m, &mut shared)?;
+        Ok(shared.bind(body))
+    }
+
+    /// Convert an expression, or refer to its binding if the graph shares it.
+    fn xepr_node(expr: &Expr, m: &Module, shared: &mut SharedExprs) -> Result<XExpr, XError>
+    {
+        let key = expr as *const Expr;
+        let is_plain = matches!(
+            expr,
+            Expr::Literal(_) | Expr::Attribute(_) | Expr::Property(_) | Expr::BuiltIn(_)
+        );
+        let uses = shared.uses.get(&key).copied().unwrap_or_default();
+        if is_plain || uses < 2 || shared.is_random(expr) {
+            return Self::xepr_inner(expr, m, shared);
+        }
+        if let Some(name) = shared.names.get(&key) {
+            return Ok(XExpr::var(name.clone()));
+        }
+        let value = Self::xepr_inner(expr, m, shared)?;
+        let name = format!("v{}", shared.bindings.len());
+        shared.names.insert(key, name.clone());
+        shared.bindings.push((name.clone(), value));
+        Ok(XExpr::var(name))
+    }
+
+    /// Count the expressions referring to each expression reachable from `expr`.
+    fn count_uses(expr: &Expr, m: &Module, uses: &mut std::collections::HashMap<*const Expr, usize>)
+    {
+        let children = match expr {
+            Expr::Unary { expr, .. } => vec![*expr],
+            Expr::Binary { left, right, .. } => vec![*left, *right],
+            _ => Vec::new(),
+        };
+        for child in children.iter().filter_map(|handle| m.get(*handle)) {
+            let count = uses.entry(child as *const Expr).or_default();
+            *count += 1;
+            if *count == 1 {
+                Self::count_uses(child, m, uses);
+            }
+        }
+    }
+
+    /// Convert an expression, reading the lowerings of editor functions back as them.
+    fn xepr_inner(expr: &Expr, m: &Module, shared: &mut SharedExprs) -> Result<XExpr, XError>
     {
         match expr {
             Expr::Literal(lit) => Self::convert_literal_value(&lit.value),
@@ -954,12 +2027,168 @@ impl FromHanabi
                 let inner_expr = m.get(*expr).ok_or_else(|| {
                     XError::ExpressionNotFound(format!("Unary expression handle {:?}", expr))
                 })?;
-                let converted_inner = Self::xepr(inner_expr, m)?;
+                let converted_inner = Self::xepr_node(inner_expr, m, shared)?;
                 Ok(XExpr::unary(
                     Self::convert_unary_operator(op.clone())?,
                     converted_inner,
                 ))
             }
+            Expr::Binary {
+                op: BinaryOperator::Mul,
+                left,
+                right,
+            } if Self::is_minus_one(m, *right) => {
+                let inner = m.get(*left).ok_or_else(|| {
+                    XError::ExpressionNotFound(format!("Binary left expression handle {:?}", left))
+                })?;
+                Ok(XExpr::unary(
+                    XUnaryOp::Neg,
+                    Self::xepr_node(inner, m, shared)?,
+                ))
+            }
+            Expr::Binary {
+                op: BinaryOperator::Dot,
+                left,
+                right,
+            } if Self::unit_axis(m, *right).is_some() => {
+                let inner = m.get(*left).ok_or_else(|| {
+                    XError::ExpressionNotFound(format!("Binary left expression handle {:?}", left))
+                })?;
+                let (axis, _) = Self::unit_axis(m, *right)
+                    .ok_or_else(|| XError::ExpressionNotFound("Swizzle axis".to_string()))?;
+                Ok(XExpr::swizzle(
+                    Self::xepr_node(inner, m, shared)?,
+                    AXES[axis],
+                ))
+            }
+            Expr::Binary {
+                op: BinaryOperator::Min,
+                left,
+                right,
+            } if Self::saturated(m, shared, *left, *right, -1.0).is_some() => {
+                let x = Self::saturated(m, shared, *left, *right, -1.0)
+                    .ok_or_else(|| XError::ExpressionNotFound("Sign operand".to_string()))?;
+                Ok(XExpr::unary(XUnaryOp::Sign, Self::xepr_node(x, m, shared)?))
+            }
+            Expr::Binary {
+                op: BinaryOperator::Sub,
+                left,
+                right,
+            } if Self::step_operands(m, shared, *left, *right).is_some() => {
+                let (edge, x) = Self::step_operands(m, shared, *left, *right)
+                    .ok_or_else(|| XError::ExpressionNotFound("Step operands".to_string()))?;
+                Ok(XExpr::binary(
+                    Self::xepr_node(edge, m, shared)?,
+                    XBinaryOp::Step,
+                    Self::xepr_node(x, m, shared)?,
+                ))
+            }
+            Expr::Binary {
+                op: BinaryOperator::Dot,
+                left,
+                right,
+            } if Self::length_operand(m, shared, *left, *right).is_some() => {
+                let v = Self::length_operand(m, shared, *left, *right)
+                    .ok_or_else(|| XError::ExpressionNotFound("Length operand".to_string()))?;
+                // The difference is only shared by the lowering, and read back as a distance.
+                let delta = match v {
+                    Expr::Binary {
+                        op: BinaryOperator::Sub,
+                        left,
+                        right,
+                    } if shared.uses.get(&(v as *const Expr)).copied() == Some(2) => {
+                        m.get(*left).zip(m.get(*right))
+                    }
+                    _ => None,
+                };
+                match delta {
+                    Some((a, b)) => Ok(XExpr::binary(
+                        Self::xepr_node(a, m, shared)?,
+                        XBinaryOp::Distance,
+                        Self::xepr_node(b, m, shared)?,
+                    )),
+                    None => Ok(XExpr::unary(
+                        XUnaryOp::Length,
+                        Self::xepr_node(v, m, shared)?,
+                    )),
+                }
+            }
+            Expr::Binary {
+                op: BinaryOperator::Min,
+                left,
+                right,
+            } if Self::clamp_operands(m, shared, *left, *right).is_some() => {
+                let (x, low, high) = Self::clamp_operands(m, shared, *left, *right)
+                    .ok_or_else(|| XError::ExpressionNotFound("Clamp operands".to_string()))?;
+                Ok(XExpr::ternary(
+                    XTernaryOp::Clamp,
+                    Self::xepr_node(x, m, shared)?,
+                    Self::xepr_node(low, m, shared)?,
+                    Self::xepr_node(high, m, shared)?,
+                ))
+            }
+            Expr::Binary {
+                op: BinaryOperator::Add,
+                left,
+                right,
+            } if Self::normal_operands(m, shared, *left, *right).is_some() => {
+                let (mean, sd) = Self::normal_operands(m, shared, *left, *right)
+                    .ok_or_else(|| XError::ExpressionNotFound("Normal operands".to_string()))?;
+                Ok(XExpr::binary(
+                    Self::xepr_node(mean, m, shared)?,
+                    XBinaryOp::Normal,
+                    Self::xepr_node(sd, m, shared)?,
+                ))
+            }
+            Expr::Binary {
+                op: BinaryOperator::Add,
+                left,
+                right,
+            } if Self::mix_operands(m, shared, *left, *right).is_some() => {
+                let (a, b, t) = Self::mix_operands(m, shared, *left, *right)
+                    .ok_or_else(|| XError::ExpressionNotFound("Mix operands".to_string()))?;
+                Ok(XExpr::ternary(
+                    XTernaryOp::Mix,
+                    Self::xepr_node(a, m, shared)?,
+                    Self::xepr_node(b, m, shared)?,
+                    Self::xepr_node(t, m, shared)?,
+                ))
+            }
+            Expr::Binary {
+                op: BinaryOperator::Add | BinaryOperator::Mul,
+                ..
+            } if Self::vector_terms(m, shared, expr).is_some() => {
+                let components = Self::vector_terms(m, shared, expr)
+                    .ok_or_else(|| XError::ExpressionNotFound("Vector components".to_string()))?;
+                Self::xepr_vector(&components, m, shared)
+            }
+            Expr::Binary {
+                op: BinaryOperator::Div,
+                left,
+                right,
+            } if Self::tan_operand(m, shared, *left, *right).is_some() => {
+                let x = Self::tan_operand(m, shared, *left, *right)
+                    .ok_or_else(|| XError::ExpressionNotFound("Tangent operand".to_string()))?;
+                Ok(XExpr::unary(XUnaryOp::Tan, Self::xepr_node(x, m, shared)?))
+            }
+            Expr::Binary {
+                op: op @ (BinaryOperator::LessThanOrEqual | BinaryOperator::GreaterThan),
+                left,
+                right,
+            } if Self::equality_operands(m, shared, *left, *right).is_some() => {
+                let (a, b) = Self::equality_operands(m, shared, *left, *right)
+                    .ok_or_else(|| XError::ExpressionNotFound("Equality operands".to_string()))?;
+                let xop = if matches!(op, BinaryOperator::LessThanOrEqual) {
+                    XBinaryOp::Eq
+                } else {
+                    XBinaryOp::Neq
+                };
+                Ok(XExpr::binary(
+                    Self::xepr_node(a, m, shared)?,
+                    xop,
+                    Self::xepr_node(b, m, shared)?,
+                ))
+            }
             Expr::Binary { op, left, right } => {
                 let left_expr = m.get(*left).ok_or_else(|| {
                     XError::ExpressionNotFound(format!("Binary left expression handle {:?}", left))
@@ -970,8 +2199,8 @@ impl FromHanabi
                         right
                     ))
                 })?;
-                let converted_left = Self::xepr(left_expr, m)?;
-                let converted_right = Self::xepr(right_expr, m)?;
+                let converted_left = Self::xepr_node(left_expr, m, shared)?;
+                let converted_right = Self::xepr_node(right_expr, m, shared)?;
                 Ok(XExpr::binary(
                     converted_left,
                     Self::convert_binary_operator(op.clone())?,
@@ -981,12 +2210,515 @@ impl FromHanabi
         }
     }
 
+    /// Whether the handle is the `-1.0` or `-1` literal emitted when lowering negation.
+    fn is_minus_one(m: &Module, handle: ExprHandle) -> bool
+    {
+        match m.get(handle) {
+            Some(Expr::Literal(lit)) => {
+                matches!(
+                    lit.value,
+                    Value::Scalar(ScalarValue::Float(f)) if f == -1.0
+                ) || matches!(lit.value, Value::Scalar(ScalarValue::Int(-1)))
+            }
+            _ => false,
+        }
+    }
+
+    /// Axis and width of a unit vector literal, which the lowerings of swizzles and vector
+    /// constructors pick and place components with.
+    fn unit_axis(m: &Module, handle: ExprHandle) -> Option<(usize, usize)>
+    {
+        let Some(Expr::Literal(lit)) = m.get(handle) else {
+            return None;
+        };
+        let Value::Vector(vector) = &lit.value else {
+            return None;
+        };
+        if !matches!(
+            vector.vector_type(),
+            VectorType::VEC2F | VectorType::VEC3F | VectorType::VEC4F
+        ) {
+            return None;
+        }
+        let values = vector.get_all::<f32>();
+        let is_unit = values.iter().filter(|v| **v == 1.0).count() == 1
+            && values.iter().all(|v| *v == 0.0 || *v == 1.0);
+        if !is_unit {
+            return None;
+        }
+        let axis = values.iter().position(|v| *v == 1.0)?;
+        Some((axis, values.len()))
+    }
+
+    /// Vector, axis and width of `dot(v, unit_axis)`, the lowering of a single component
+    /// swizzle.
+    fn dot_axis(m: &Module, handle: ExprHandle) -> Option<(ExprHandle, usize, usize)>
+    {
+        let Expr::Binary {
+            op: BinaryOperator::Dot,
+            left,
+            right,
+        } = m.get(handle)?
+        else {
+            return None;
+        };
+        let (axis, width) = Self::unit_axis(m, *right)?;
+        Some((*left, axis, width))
+    }
+
+    /// Components of `unit_x * x + unit_y * y + ... + constant`, the lowering of a vector
+    /// constructor, in axis order.
+    fn vector_terms(m: &Module, shared: &SharedExprs, expr: &Expr) -> Option<Vec<Component>>
+    {
+        // The sum is built left to right, with the constant term last.
+        let mut terms = Vec::new();
+        let mut rest = expr;
+        while let Expr::Binary {
+            op: BinaryOperator::Add,
+            left,
+            right,
+        } = rest
+        {
+            terms.push(shared.part(m, *right)?);
+            rest = shared.part(m, *left)?;
+        }
+        terms.push(rest);
+        terms.reverse();
+        let constant = match terms.last().copied() {
+            Some(Expr::Literal(lit)) if terms.len() > 1 => {
+                terms.pop();
+                match &lit.value {
+                    Value::Vector(vector) => Some(vector.get_all::<f32>()),
+                    _ => return None,
+                }
+            }
+            _ => None,
+        };
+
+        let mut scalars: Vec<Option<ExprHandle>> = Vec::new();
+        for term in terms {
+            let Expr::Binary {
+                op: BinaryOperator::Mul,
+                left,
+                right,
+            } = term
+            else {
+                return None;
+            };
+            let (axis, width) = Self::unit_axis(m, *left)?;
+            if scalars.is_empty() {
+                scalars = vec![None; width];
+            }
+            // Axes come in order, each once, with scalar components.
+            let is_after = scalars.iter().skip(axis).all(Option::is_none);
+            if width != scalars.len() || !is_after || !Self::is_scalar(m, *right) {
+                return None;
+            }
+            scalars[axis] = Some(*right);
+        }
+        let constant = constant.unwrap_or_else(|| vec![0.0; scalars.len()]);
+        if constant.len() != scalars.len() {
+            return None;
+        }
+        scalars
+            .iter()
+            .zip(constant)
+            .map(|(scalar, value)| match scalar {
+                Some(handle) if value == 0.0 => Some(Component::Scalar(*handle)),
+                Some(_) => None,
+                None => Some(Component::Constant(value)),
+            })
+            .collect()
+    }
+
+    /// Whether an expression of the graph is a scalar.
+    ///
+    /// The types of properties are not known here, and they are taken for scalars.
+    fn is_scalar(m: &Module, handle: ExprHandle) -> bool
+    {
+        let is_scalar_type = |value_type: ValueType| matches!(value_type, ValueType::Scalar(_));
+        match m.get(handle) {
+            Some(Expr::Literal(lit)) => matches!(lit.value, Value::Scalar(_)),
+            Some(Expr::Attribute(attr)) => is_scalar_type(attr.attr.value_type()),
+            Some(Expr::BuiltIn(builtin)) => is_scalar_type(builtin.operator.value_type()),
+            Some(Expr::Property(_)) => true,
+            Some(Expr::Unary { op, expr }) => match op {
+                UnaryOperator::All | UnaryOperator::Any => true,
+                UnaryOperator::Normalize => false,
+                _ => Self::is_scalar(m, *expr),
+            },
+            Some(Expr::Binary { op, left, right }) => match op {
+                BinaryOperator::Dot => true,
+                BinaryOperator::Cross => false,
+                _ => Self::is_scalar(m, *left) && Self::is_scalar(m, *right),
+            },
+            None => false,
+        }
+    }
+
+    /// Convert the components of a vector constructor.
+    ///
+    /// Components of one vector side by side are read back as a swizzle of it, or as the
+    /// vector itself when they are all of it, in order.
+    fn xepr_vector(
+        components: &[Component],
+        m: &Module,
+        shared: &mut SharedExprs,
+    ) -> Result<XExpr, XError>
+    {
+        let not_found = |handle: ExprHandle| {
+            XError::ExpressionNotFound(format!("Vector component handle {:?}", handle))
+        };
+        let mut args = Vec::new();
+        let mut axis = 0;
+        while axis < components.len() {
+            let handle = match components[axis] {
+                Component::Constant(value) => {
+                    args.push(XExpr::lit(value));
+                    axis += 1;
+                    continue;
+                }
+                Component::Scalar(handle) => handle,
+            };
+            let Some((vector, first, width)) = Self::dot_axis(m, handle) else {
+                let expr = m.get(handle).ok_or_else(|| not_found(handle))?;
+                args.push(Self::xepr_node(expr, m, shared)?);
+                axis += 1;
+                continue;
+            };
+            // Components of a random vector are separate draws: only distinct components of
+            // a random built-in, which are independent, read back as one swizzle.
+            let expr = m.get(vector).ok_or_else(|| not_found(vector))?;
+            let is_random = shared.is_random(expr);
+            let is_builtin = matches!(expr, Expr::BuiltIn(_));
+            let mut picked = vec![first];
+            for component in &components[axis + 1..] {
+                let next = match component {
+                    Component::Scalar(handle) => Self::dot_axis(m, *handle),
+                    Component::Constant(_) => None,
+                };
+                match next {
+                    Some((other, index, _))
+                        if other == vector
+                            && (!is_random || (is_builtin && !picked.contains(&index))) =>
+                    {
+                        picked.push(index)
+                    }
+                    _ => break,
+                }
+            }
+            axis += picked.len();
+            let operand = Self::xepr_node(expr, m, shared)?;
+            if picked.iter().copied().eq(0..width) {
+                args.push(operand);
+            } else {
+                let letters: String = picked.iter().map(|&index| AXES[index]).collect();
+                args.push(XExpr::swizzle(operand, letters));
+            }
+        }
+
+        // Arguments are nested as the parser nests them: `vec4(a, b, c)` is
+        // `vec4(vec3(a, b), c)`, with the leading constants folded.
+        let size = components.len();
+        let count = args.len();
+        let mut args = args.into_iter();
+        let first = args
+            .next()
+            .ok_or_else(|| XError::ExpressionNotFound("Vector components".to_string()))?;
+        Ok(args.enumerate().fold(first, |expr, (index, arg)| {
+            let op = match size + 2 + index - count {
+                2 => XBinaryOp::Vec2,
+                3 => XBinaryOp::Vec3,
+                _ => XBinaryOp::Vec4,
+            };
+            let folded = match (&expr, &arg) {
+                (XExpr::Lit(left), XExpr::Lit(right)) => XValue::binary(op, left, right),
+                _ => None,
+            };
+            folded.map_or_else(|| XExpr::binary(expr, op, arg), XExpr::Lit)
+        }))
+    }
+
+    /// Operands of `mean + sd * ((rand + ... + rand - center) * scale)`, the lowering of
+    /// `normal(mean, sd)`.
+    fn normal_operands<'a>(
+        m: &'a Module,
+        shared: &SharedExprs,
+        mean: ExprHandle,
+        deviation: ExprHandle,
+    ) -> Option<(&'a Expr, &'a Expr)>
+    {
+        let Expr::Binary {
+            op: BinaryOperator::Mul,
+            left: sd,
+            right: noise,
+        } = shared.part(m, deviation)?
+        else {
+            return None;
+        };
+        let Expr::Binary {
+            op: BinaryOperator::Mul,
+            left: centered,
+            right: scale,
+        } = shared.part(m, *noise)?
+        else {
+            return None;
+        };
+        let Expr::Binary {
+            op: BinaryOperator::Sub,
+            left: sum,
+            right: center,
+        } = shared.part(m, *centered)?
+        else {
+            return None;
+        };
+
+        let is_float = |handle: ExprHandle, expected: f32| {
+            matches!(
+                m.get(handle),
+                Some(Expr::Literal(lit))
+                    if matches!(lit.value, Value::Scalar(ScalarValue::Float(f)) if f == expected)
+            )
+        };
+        if !is_float(*scale, NORMAL_SCALE)
+            || !is_float(*center, NORMAL_SAMPLES as f32 / 2.0)
+            || Self::rand_sum_len(m, *sum) != NORMAL_SAMPLES
+        {
+            return None;
+        }
+        Some((m.get(mean)?, m.get(*sd)?))
+    }
+
+    /// Number of random builtins in a sum of random builtins, or 0 if it is not one.
+    fn rand_sum_len(m: &Module, handle: ExprHandle) -> usize
+    {
+        match m.get(handle) {
+            Some(Expr::BuiltIn(builtin)) => {
+                usize::from(matches!(builtin.operator, BuiltInOperator::Rand(_)))
+            }
+            Some(Expr::Binary {
+                op: BinaryOperator::Add,
+                left,
+                right,
+            }) => {
+                let left = Self::rand_sum_len(m, *left);
+                let right = Self::rand_sum_len(m, *right);
+                if left == 0 || right == 0 {
+                    0
+                } else {
+                    left + right
+                }
+            }
+            _ => 0,
+        }
+    }
+
+    /// Operands of `min(max(x, low), high)`, the lowering of `clamp(x, low, high)`.
+    fn clamp_operands<'a>(
+        m: &'a Module,
+        shared: &SharedExprs,
+        max: ExprHandle,
+        high: ExprHandle,
+    ) -> Option<(&'a Expr, &'a Expr, &'a Expr)>
+    {
+        match shared.part(m, max)? {
+            Expr::Binary {
+                op: BinaryOperator::Max,
+                left,
+                right,
+            } => Some((m.get(*left)?, m.get(*right)?, m.get(high)?)),
+            _ => None,
+        }
+    }
+
+    /// Operands of `a + (b - a) * t`, the lowering of `mix(a, b, t)`.
+    fn mix_operands<'a>(
+        m: &'a Module,
+        shared: &SharedExprs,
+        a: ExprHandle,
+        scaled: ExprHandle,
+    ) -> Option<(&'a Expr, &'a Expr, &'a Expr)>
+    {
+        let Expr::Binary {
+            op: BinaryOperator::Mul,
+            left: delta,
+            right: t,
+        } = shared.part(m, scaled)?
+        else {
+            return None;
+        };
+        match shared.part(m, *delta)? {
+            Expr::Binary {
+                op: BinaryOperator::Sub,
+                left: b,
+                right: a_again,
+            } if *a_again == a && !shared.is_random(m.get(a)?) => {
+                Some((m.get(a)?, m.get(*b)?, m.get(*t)?))
+            }
+            _ => None,
+        }
+    }
+
+    /// Operands of `max(a, b) <op> min(a, b)`, the lowering of `a == b` and `a != b`.
+    fn equality_operands<'a>(
+        m: &'a Module,
+        shared: &SharedExprs,
+        max: ExprHandle,
+        min: ExprHandle,
+    ) -> Option<(&'a Expr, &'a Expr)>
+    {
+        match (shared.part(m, max)?, shared.part(m, min)?) {
+            (
+                Expr::Binary {
+                    op: BinaryOperator::Max,
+                    left: a,
+                    right: b,
+                },
+                Expr::Binary {
+                    op: BinaryOperator::Min,
+                    left: c,
+                    right: d,
+                },
+            ) if a == c && b == d => {
+                let (a, b) = (m.get(*a)?, m.get(*b)?);
+                (!shared.is_random(a) && !shared.is_random(b)).then_some((a, b))
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether the handle is a float literal, or a vector literal of it in each component.
+    fn is_splat(m: &Module, handle: ExprHandle, value: f32) -> bool
+    {
+        match m.get(handle) {
+            Some(Expr::Literal(lit)) => match &lit.value {
+                Value::Scalar(ScalarValue::Float(f)) => *f == value,
+                Value::Vector(vector) => vector.get_all::<f32>().iter().all(|v| *v == value),
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    /// Operand of `min(max(x * SATURATE_SCALE, low), 1)`, the lowering of `sign(x)` for
+    /// `low = -1`, and part of the lowering of `step(edge, x)` for `low = 0`.
+    fn saturated<'a>(
+        m: &'a Module,
+        shared: &SharedExprs,
+        max: ExprHandle,
+        high: ExprHandle,
+        low: f32,
+    ) -> Option<&'a Expr>
+    {
+        let Expr::Binary {
+            op: BinaryOperator::Max,
+            left: scaled,
+            right: low_handle,
+        } = shared.part(m, max)?
+        else {
+            return None;
+        };
+        if !Self::is_splat(m, high, 1.0) || !Self::is_splat(m, *low_handle, low) {
+            return None;
+        }
+        match shared.part(m, *scaled)? {
+            Expr::Binary {
+                op: BinaryOperator::Mul,
+                left: x,
+                right: scale,
+            } if Self::is_splat(m, *scale, SATURATE_SCALE) => m.get(*x),
+            _ => None,
+        }
+    }
+
+    /// Operands of `1 - min(max((edge - x) * SATURATE_SCALE, 0), 1)`, the lowering of
+    /// `step(edge, x)`.
+    fn step_operands<'a>(
+        m: &'a Module,
+        shared: &SharedExprs,
+        one: ExprHandle,
+        below: ExprHandle,
+    ) -> Option<(&'a Expr, &'a Expr)>
+    {
+        if !Self::is_splat(m, one, 1.0) {
+            return None;
+        }
+        let Expr::Binary {
+            op: BinaryOperator::Min,
+            left: max,
+            right: high,
+        } = shared.part(m, below)?
+        else {
+            return None;
+        };
+        let delta = Self::saturated(m, shared, *max, *high, 0.0)?;
+        if shared
+            .uses
+            .get(&(delta as *const Expr))
+            .copied()
+            .unwrap_or_default()
+            > 1
+        {
+            return None;
+        }
+        match delta {
+            Expr::Binary {
+                op: BinaryOperator::Sub,
+                left: edge,
+                right: x,
+            } => Some((m.get(*edge)?, m.get(*x)?)),
+            _ => None,
+        }
+    }
+
+    /// Operand of `dot(v, normalize(v))`, the lowering of `length(v)` and of
+    /// `distance(a, b)` for `v = a - b`.
+    fn length_operand<'a>(
+        m: &'a Module,
+        shared: &SharedExprs,
+        v: ExprHandle,
+        unit: ExprHandle,
+    ) -> Option<&'a Expr>
+    {
+        match shared.part(m, unit)? {
+            Expr::Unary {
+                op: UnaryOperator::Normalize,
+                expr,
+            } if *expr == v => m.get(v).filter(|v| !shared.is_random(v)),
+            _ => None,
+        }
+    }
+
+    /// Operand of `sin(x) / cos(x)`, the lowering of `tan(x)`.
+    fn tan_operand<'a>(
+        m: &'a Module,
+        shared: &SharedExprs,
+        sin: ExprHandle,
+        cos: ExprHandle,
+    ) -> Option<&'a Expr>
+    {
+        match (shared.part(m, sin)?, shared.part(m, cos)?) {
+            (
+                Expr::Unary {
+                    op: UnaryOperator::Sin,
+                    expr: a,
+                },
+                Expr::Unary {
+                    op: UnaryOperator::Cos,
+                    expr: b,
+                },
+            ) if a == b => m.get(*a).filter(|x| !shared.is_random(x)),
+            _ => None,
+        }
+    }
+
     fn convert_literal_value(lit: &Value) -> Result<XExpr, XError>
     {
         match lit {
             Value::Scalar(scalar) => match scalar {
                 ScalarValue::Float(f) => Ok(XExpr::lit(*f)),
                 ScalarValue::Int(i) => Ok(XExpr::lit(*i)),
+                ScalarValue::Bool(b) => Ok(XExpr::lit(*b)),
                 _ => Err(XError::UnsupportedScalarType(format!("{:?}", scalar))),
             },
             Value::Vector(vector) => match vector.vector_type() {
@@ -998,6 +2730,12 @@ impl FromHanabi
                     let values = vector.get_all::<f32>();
                     Ok(XExpr::lit(XValue::vec3(values[0], values[1], values[2])))
                 }
+                VectorType::VEC4F => {
+                    let values = vector.get_all::<f32>();
+                    Ok(XExpr::lit(XValue::vec4(
+                        values[0], values[1], values[2], values[3],
+                    )))
+                }
                 _ => Err(XError::UnsupportedVectorType(format!(
                     "{:?}",
                     vector.vector_type()
@@ -1012,6 +2750,8 @@ impl FromHanabi
         match op {
             BuiltInOperator::Time => Ok(XBuiltInOp::Time),
             BuiltInOperator::DeltaTime => Ok(XBuiltInOp::DeltaTime),
+            BuiltInOperator::Rand(ValueType::Vector(VectorType::VEC2F)) => Ok(XBuiltInOp::RandVec2),
+            BuiltInOperator::Rand(ValueType::Vector(VectorType::VEC3F)) => Ok(XBuiltInOp::RandVec3),
             BuiltInOperator::Rand(_) => Ok(XBuiltInOp::Rand),
         }
     }
@@ -1043,9 +2783,7 @@ impl FromHanabi
             BinaryOperator::Max => Ok(XBinaryOp::Max),
             BinaryOperator::Dot => Ok(XBinaryOp::Dot),
             BinaryOperator::Cross => Ok(XBinaryOp::Cross),
-            BinaryOperator::UniformRand => Err(XError::UnknownBinaryOperator(
-                "UniformRand not supported in editor expressions".to_string(),
-            )),
+            BinaryOperator::UniformRand => Ok(XBinaryOp::Uniform),
         }
     }
 
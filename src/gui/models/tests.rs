@@ -0,0 +1,140 @@
+#[cfg(test)]
+mod tests
+{
+    use bevy::math::Vec3;
+    use bevy_hanabi::graph::{ScalarValue, Value};
+    use bevy_hanabi::{
+        AccelModifier,
+        Attribute,
+        CpuValue,
+        ForceFieldModifier,
+        ForceFieldSource,
+        KillSphereModifier,
+        Module,
+        Property,
+        Spawner,
+    };
+
+    use crate::gui::models::spawner::SpawnerValue;
+    use crate::gui::models::state::{AssetState, FromHanabi, ToHanabi, XImportWarning};
+
+    #[test]
+    fn test_hanabi_keeps_unsupported_properties()
+    {
+        let mut asset = ToHanabi::effect_asset(&AssetState::default()).unwrap();
+        asset.properties.push(Property::new(
+            "speed",
+            Value::Scalar(ScalarValue::Float(2.0)),
+        ));
+        asset
+            .properties
+            .push(Property::new("count", Value::Scalar(ScalarValue::Uint(3))));
+
+        let state = FromHanabi::asset_state(&asset).unwrap();
+        assert_eq!(state.properties.len(), 1);
+        assert_eq!(state.raw_properties.len(), 1);
+        assert_eq!(state.raw_properties[0].name, "count");
+        assert_eq!(state.raw_properties[0].index, 1);
+        assert_eq!(state.raw_summary().len(), 1);
+        // The module is only kept for raw modifiers.
+        assert_eq!(state.raw_module, Module::default());
+
+        let saved = ToHanabi::effect_asset(&state).unwrap();
+        let names: Vec<&str> = saved.properties.iter().map(|prop| prop.name()).collect();
+        assert_eq!(names, vec!["speed", "count"]);
+        assert_eq!(
+            saved.properties[1].default_value(),
+            asset.properties[1].default_value()
+        );
+    }
+
+    #[test]
+    fn test_hanabi_raw_modifiers_keep_only_their_expressions()
+    {
+        let mut asset = ToHanabi::effect_asset(&AssetState::default()).unwrap();
+        let radius = Value::Scalar(ScalarValue::Float(4.0));
+        let accel = asset.module.attr(Attribute::VELOCITY);
+        let center = asset.module.attr(Attribute::POSITION);
+        let sqr_radius = asset.module.lit(radius);
+        asset
+            .update_modifiers
+            .push(Box::new(AccelModifier { accel }));
+        asset
+            .update_modifiers
+            .push(Box::new(KillSphereModifier::new(center, sqr_radius)));
+
+        let state = FromHanabi::asset_state(&asset).unwrap();
+        assert_eq!(state.update_modifiers.len(), 1);
+        assert_eq!(state.raw_update_modifiers.len(), 1);
+        assert_eq!(state.raw_update_modifiers[0].index, 1);
+        let mut expected = Module::default();
+        expected.attr(Attribute::POSITION);
+        expected.lit(radius);
+        assert_eq!(state.raw_module, expected);
+
+        // Saving and opening again does not accumulate the expressions of saved modifiers.
+        let saved = ToHanabi::effect_asset(&state).unwrap();
+        let reopened = FromHanabi::asset_state(&saved).unwrap();
+        assert_eq!(reopened.raw_module, state.raw_module);
+        assert_eq!(reopened.raw_update_modifiers, state.raw_update_modifiers);
+    }
+
+    #[test]
+    fn test_hanabi_massless_force_field_warning()
+    {
+        let mut asset = ToHanabi::effect_asset(&AssetState::default()).unwrap();
+        let accel = asset.module.attr(Attribute::VELOCITY);
+        asset
+            .update_modifiers
+            .push(Box::new(AccelModifier { accel }));
+        let mut sources = [ForceFieldSource::default(); ForceFieldSource::MAX_SOURCES];
+        sources[0].mass = 1.0;
+        sources[2].position = Vec3::X;
+        sources[2].mass = 0.0;
+        asset
+            .update_modifiers
+            .push(Box::new(ForceFieldModifier { sources }));
+
+        let import = FromHanabi::import(&asset).unwrap();
+        let warning = XImportWarning::MasslessForceField {
+            index:  1,
+            source: 2,
+        };
+        assert_eq!(import.warnings, vec![warning.clone()]);
+        assert_eq!(
+            warning.to_string(),
+            "Update modifier 1: force field source 2 has no mass, and is dropped"
+        );
+    }
+
+    #[test]
+    fn test_hanabi_spawner_ranges()
+    {
+        let mut asset = ToHanabi::effect_asset(&AssetState::default()).unwrap();
+        assert!(FromHanabi::import(&asset).unwrap().warnings.is_empty());
+
+        asset.spawner = Spawner::new(
+            CpuValue::Uniform((2.0, 5.0)),
+            CpuValue::Single(0.5),
+            CpuValue::Uniform((1.0, 2.0)),
+        );
+        let import = FromHanabi::import(&asset).unwrap();
+        assert!(import.warnings.is_empty());
+        let spawner = &import.state.spawner;
+        assert_eq!(
+            spawner.num_particles,
+            SpawnerValue::Uniform { min: 2.0, max: 5.0 }
+        );
+        assert_eq!(spawner.spawn_time, SpawnerValue::Single(0.5));
+        assert_eq!(spawner.period, SpawnerValue::Uniform { min: 1.0, max: 2.0 });
+
+        let exported = ToHanabi::effect_asset(&import.state).unwrap();
+        assert_eq!(exported.spawner.num_particles.range(), [2.0, 5.0]);
+        assert_eq!(exported.spawner.spawn_time.range(), [0.5, 0.5]);
+        assert_eq!(exported.spawner.period.range(), [1.0, 2.0]);
+        assert_eq!(
+            FromHanabi::asset_state(&exported).unwrap().spawner,
+            *spawner
+        );
+    }
+}
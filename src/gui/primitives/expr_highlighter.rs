@@ -1,3 +1,5 @@
+use std::ops::Range;
+
 // ====================
 // Editor.
 // ====================
@@ -5,10 +7,11 @@ use crate::gui::expr::{SyntaxHighlight, Tokenizer};
 use crate::gui::primitives::text_input::{HighlightSpan, Highlighter};
 use crate::gui::styling::colors::*;
 
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Debug, Default)]
 pub struct ExprHighlighter
 {
     pub matching_parens: Option<(usize, usize)>,
+    pub diagnostics:     Vec<Range<usize>>,
 }
 
 impl ExprHighlighter
@@ -17,6 +20,7 @@ impl ExprHighlighter
     {
         Self {
             matching_parens: None,
+            diagnostics:     Vec::new(),
         }
     }
 
@@ -25,6 +29,12 @@ impl ExprHighlighter
         self.matching_parens = parens;
         self
     }
+
+    pub fn with_diagnostics(mut self, diagnostics: Vec<Range<usize>>) -> Self
+    {
+        self.diagnostics = diagnostics;
+        self
+    }
 }
 
 impl Highlighter for ExprHighlighter
@@ -45,6 +55,7 @@ impl Highlighter for ExprHighlighter
                 Some(SyntaxHighlight::Punctuation) => syntax_punctuation(),
                 Some(SyntaxHighlight::Error) => syntax_error(),
                 Some(SyntaxHighlight::Keyword) => syntax_keyword(),
+                Some(SyntaxHighlight::Swizzle) => syntax_swizzle(),
                 None => continue,
             };
 
@@ -71,4 +82,13 @@ impl Highlighter for ExprHighlighter
 
         spans
     }
+
+    fn squiggles(&self, text: &str) -> Vec<Range<usize>>
+    {
+        self.diagnostics
+            .iter()
+            .filter(|span| span.end <= text.len())
+            .cloned()
+            .collect()
+    }
 }
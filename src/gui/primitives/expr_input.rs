@@ -14,6 +14,7 @@ use gpui::{
     IntoElement,
     KeyDownEvent,
     ParentElement,
+    Pixels,
     SharedString,
     Styled,
     Window,
@@ -23,6 +24,7 @@ use gpui::{
     px,
     relative,
 };
+use strum::IntoEnumIterator;
 
 // ====================
 // Editor.
@@ -30,7 +32,21 @@ use gpui::{
 use crate::gui::expr::tokenizer::CompletionKind;
 use crate::gui::expr::xexpr::XExpr;
 use crate::gui::expr::xval::XExprReturnType;
-use crate::gui::expr::{CompletionItem, XParseError, get_completions};
+use crate::gui::expr::{
+    CompletionContext,
+    CompletionItem,
+    SignatureHelp,
+    XFormatOptions,
+    XMacroLibrary,
+    XParseDiagnostic,
+    XParseError,
+    XTypeError,
+    get_completion_context,
+    get_context_completions,
+    get_signature_help,
+};
+use crate::gui::models::attr::XAttr;
+use crate::gui::models::key_value::PropertyTypes;
 use crate::gui::primitives::dropdown_menu::{DropdownMenu, MenuItem};
 use crate::gui::primitives::events::{DropdownMenuEvent, ExprInputEvent, TextInputEvent};
 use crate::gui::primitives::expr_highlighter::ExprHighlighter;
@@ -49,14 +65,20 @@ pub struct ExprInput
     pub text_input:       Entity<TextInput<ExprHighlighter>>,
     pub completions_menu: Entity<DropdownMenu>,
     pub completions:      Vec<CompletionItem>,
+    pub signature_help:   Option<SignatureHelp>,
     pub parsed_expr:      Option<Result<XExpr, XParseError>>,
+    pub diagnostics:      Vec<XParseDiagnostic>,
     pub size_variant:     SizeVariant,
     pub placeholder:      SharedString,
     pub on_change:        Option<Box<dyn Fn(&str) + 'static>>,
     pub on_submit:        Option<Box<dyn Fn(&str) + 'static>>,
     pub matching_paren:   Option<(usize, usize)>, // (open_pos, close_pos)
+    pub show_simplified:  bool,
+    /// Report functions that cannot be built in a Hanabi graph as type errors.
+    pub hanabi_only:      bool,
     pub attributes:       HashMap<String, XExprReturnType>,
     pub props:            HashMap<String, XExprReturnType>,
+    pub macros:           XMacroLibrary,
 }
 
 // ====================
@@ -66,7 +88,7 @@ pub struct ExprInput
 pub mod actions
 {
     use gpui::actions;
-    actions!(expr_input, [Tab, Up, Down, Enter, Escape]);
+    actions!(expr_input, [Tab, Up, Down, Enter, Escape, Format]);
 }
 
 impl ExprInput
@@ -139,14 +161,24 @@ impl ExprInput
             text_input,
             completions_menu,
             completions: Vec::new(),
+            signature_help: None,
             parsed_expr: None,
+            diagnostics: Vec::new(),
             size_variant: SizeVariant::Medium,
             placeholder: SharedString::from("Enter expression..."),
             on_change: None,
             on_submit: None,
             matching_paren: None,
-            attributes: HashMap::new(),
+            show_simplified: false,
+            hanabi_only: false,
+            attributes: XAttr::iter()
+                .map(|attr| (attr.to_string(), attr.get_type()))
+                .collect(),
             props: HashMap::new(),
+            macros: cx
+                .try_global::<XMacroLibrary>()
+                .cloned()
+                .unwrap_or_default(),
         }
     }
 
@@ -162,6 +194,20 @@ impl ExprInput
         self
     }
 
+    /// Show the simplified form of the expression next to it, when it differs.
+    pub fn with_show_simplified(mut self, show_simplified: bool) -> Self
+    {
+        self.show_simplified = show_simplified;
+        self
+    }
+
+    /// Only accept functions that can be built in a Hanabi graph.
+    pub fn with_hanabi_only(mut self, hanabi_only: bool) -> Self
+    {
+        self.hanabi_only = hanabi_only;
+        self
+    }
+
     pub fn with_content(mut self, content: impl Into<SharedString>, cx: &mut Context<Self>)
     -> Self
     {
@@ -208,6 +254,27 @@ impl ExprInput
             .and_then(|expr| expr.get_result_type(&self.attributes, &self.props))
     }
 
+    /// Get the simplified form of the current expression, if it differs from it
+    pub fn get_simplified_expr(&self) -> Option<XExpr>
+    {
+        let expr = self.parsed_expr.as_ref()?.as_ref().ok()?;
+        let simplified = expr.simplify();
+        (simplified != *expr).then_some(simplified)
+    }
+
+    /// Get the type error of the current expression, if it parses but does not type check
+    pub fn get_type_error(&self) -> Option<XTypeError>
+    {
+        let expr = self.parsed_expr.as_ref()?.as_ref().ok()?;
+        if let Err(err) = expr.check_type(&self.attributes, &self.props) {
+            return Some(err);
+        }
+        if self.hanabi_only {
+            return expr.check_hanabi().err();
+        }
+        None
+    }
+
     /// Set the available attributes and their types
     pub fn set_attributes(
         &mut self,
@@ -308,14 +375,57 @@ impl ExprInput
         self.focus_handle.is_focused(window)
     }
 
+    /// Replace the content with the canonical form of the expression, if it parses.
+    pub fn format_content(&mut self, cx: &mut Context<Self>)
+    {
+        let Some(Ok(expr)) = &self.parsed_expr else {
+            return;
+        };
+        // The input holds a single line, and keeps the macro calls it was written with
+        let formatted = expr.format_with_macros(&XFormatOptions::single_line(), &self.macros);
+        if formatted == self.get_content(cx) {
+            return;
+        }
+
+        self.text_input.update(cx, |input, _| {
+            input.content = SharedString::from(formatted.clone());
+            input.selected_range = formatted.len()..formatted.len();
+        });
+        self.on_text_changed(SharedString::from(formatted), cx);
+    }
+
     // ====================
     // Event handlers.
     // ====================
 
+    fn on_format(&mut self, _: &actions::Format, _window: &mut Window, cx: &mut Context<Self>)
+    {
+        self.format_content(cx);
+    }
+
     fn on_text_changed(&mut self, text: SharedString, cx: &mut Context<Self>)
     {
-        // Parse the expression
-        self.parsed_expr = Some(XExpr::parse(&text));
+        // Follow the properties of the edited asset, when an asset editor shares them
+        if let Some(props) = cx.try_global::<PropertyTypes>() {
+            self.props = props.0.clone();
+        }
+        if let Some(macros) = cx.try_global::<XMacroLibrary>() {
+            self.macros = macros.clone();
+        }
+
+        // Parse the expression, expanding macros and keeping every error for the squiggles
+        let parsed = XExpr::parse_with_macros(&text, &self.macros);
+        self.diagnostics = parsed.as_ref().err().cloned().unwrap_or_default();
+        self.parsed_expr = Some(parsed.map_err(|diagnostics| {
+            diagnostics
+                .into_iter()
+                .next()
+                .map_or(XParseError::UnexpectedEndOfInput, |d| d.error)
+        }));
+        let squiggles = self.diagnostics.iter().map(|d| d.span.clone()).collect();
+        self.text_input.update(cx, |input, _| {
+            input.highlighter.diagnostics = squiggles;
+        });
 
         // Update completions
         self.update_completions(&text, cx);
@@ -338,17 +448,28 @@ impl ExprInput
         // Get cursor position
         let cursor_pos = self.text_input.read(cx).selected_range.end;
 
-        // Find the word being typed at cursor
+        // Find the word being typed at cursor, and the call it is an argument of
         let prefix = self.get_prefix_at_cursor(text, cursor_pos);
+        self.signature_help = get_signature_help(text, cursor_pos);
+
+        // Names and swizzles are suggested before their first character is typed
+        let context = get_completion_context(text, cursor_pos, &self.attributes);
+        let is_expression = matches!(context, CompletionContext::Expression(_));
 
-        if prefix.is_empty() {
+        if prefix.is_empty() && is_expression {
             self.completions.clear();
             self.completions_menu.update(cx, |menu, _cx| {
                 menu.items.clear();
                 menu.selected_index = None;
             });
         } else {
-            self.completions = get_completions(&prefix, cursor_pos);
+            self.completions = get_context_completions(
+                text,
+                cursor_pos,
+                &self.attributes,
+                &self.props,
+                &self.macros,
+            );
 
             // Create menu items before the closure to avoid borrow issues
             let menu_items: Vec<MenuItem> = self
@@ -372,6 +493,7 @@ impl ExprInput
                         CompletionKind::Attribute => "[Attr.]",
                         CompletionKind::Property => "[Prop.]",
                         CompletionKind::Keyword => "[Keyword]",
+                        CompletionKind::Swizzle => "[Swizzle]",
                     };
 
                     let mut item = MenuItem::new(comp.label.clone());
@@ -448,25 +570,24 @@ impl ExprInput
         let cursor_pos = self.text_input.read(cx).selected_range.end;
         let prefix = self.get_prefix_at_cursor(&content, cursor_pos);
 
-        if !prefix.is_empty() {
-            let start = cursor_pos - prefix.len();
-            let new_content = format!(
-                "{}{}{}",
-                &content[..start],
-                completion_text,
-                &content[cursor_pos..]
-            );
+        // The prefix may be empty for names and swizzles
+        let start = cursor_pos - prefix.len();
+        let new_content = format!(
+            "{}{}{}",
+            &content[..start],
+            completion_text,
+            &content[cursor_pos..]
+        );
 
-            self.text_input.update(cx, |input, _| {
-                input.content = SharedString::from(new_content.clone());
-                let new_cursor_pos = start + completion_text.len();
-                input.selected_range = new_cursor_pos..new_cursor_pos;
-            });
+        self.text_input.update(cx, |input, _| {
+            input.content = SharedString::from(new_content.clone());
+            let new_cursor_pos = start + completion_text.len();
+            input.selected_range = new_cursor_pos..new_cursor_pos;
+        });
 
-            // Manually trigger text change since we updated content directly
-            let text = SharedString::from(new_content);
-            self.on_text_changed(text, cx);
-        }
+        // Manually trigger text change since we updated content directly
+        let text = SharedString::from(new_content);
+        self.on_text_changed(text, cx);
 
         // Clear the menu and completions
         self.completions.clear();
@@ -487,7 +608,7 @@ impl ExprInput
         if text.is_empty() {
             if old_matching_paren.is_some() {
                 self.text_input.update(cx, |input, _| {
-                    input.highlighter = ExprHighlighter::new();
+                    input.highlighter.matching_parens = None;
                 });
             }
             return;
@@ -500,7 +621,7 @@ impl ExprInput
             // Only update if we had matching parens before
             if old_matching_paren.is_some() {
                 self.text_input.update(cx, |input, _| {
-                    input.highlighter = ExprHighlighter::new();
+                    input.highlighter.matching_parens = None;
                 });
             }
             return;
@@ -535,8 +656,7 @@ impl ExprInput
         // Only update the highlighter if the matching parens state actually changed
         if self.matching_paren != old_matching_paren {
             self.text_input.update(cx, |input, _| {
-                input.highlighter =
-                    ExprHighlighter::new().with_matching_parens(self.matching_paren);
+                input.highlighter.matching_parens = self.matching_paren;
             });
         }
     }
@@ -616,6 +736,7 @@ impl Render for ExprInput
             .w_full()
             .key_context("ExprInput")
             .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::on_format))
             .when(has_completions && is_focused, |el| {
                 el.on_key_down(cx.listener(|this, event: &KeyDownEvent, _window, cx| {
                     match event.keystroke.key.as_str() {
@@ -660,12 +781,21 @@ impl Render for ExprInput
                     .items_center()
                     .w_full()
                     .child(div().flex_1().child(self.text_input.clone()))
+                    .when(self.show_simplified, |el| {
+                        el.when_some(self.get_simplified_expr(), |el, simplified| {
+                            el.child(self.render_inline_simplified(&simplified, cx))
+                        })
+                    })
                     .when(self.get_error().is_none(), |el| {
                         let content = self.text_input.read(cx).content.to_string();
-                        el.when(!content.is_empty(), |el| {
-                            el.when_some(self.get_inferred_type(), |el, expr_type| {
+                        let type_error = self.get_type_error();
+                        el.when(!content.is_empty(), |el| match type_error {
+                            Some(
+                                err @ (XTypeError::Mismatch { .. } | XTypeError::NotInHanabi(_)),
+                            ) => el.child(self.render_inline_type_error(&err, cx)),
+                            _ => el.when_some(self.get_inferred_type(), |el, expr_type| {
                                 el.child(self.render_inline_type_info(expr_type, cx))
-                            })
+                            }),
                         })
                     }),
             )
@@ -685,6 +815,14 @@ impl Render for ExprInput
                     el.child(self.render_error(err, has_completions, window, cx))
                 })
             })
+            .when(
+                is_focused && !has_completions && self.get_error().is_none(),
+                |el| {
+                    el.when_some(self.signature_help, |el, help| {
+                        el.child(self.render_signature_help(help, dropdown_top))
+                    })
+                },
+            )
     }
 }
 
@@ -813,6 +951,8 @@ impl ExprInput
             XExprReturnType::Integer => (text_success(), ProductIcon::SymbolNumeric),
             XExprReturnType::Vec2 => (text_info(), ProductIcon::SymbolArray),
             XExprReturnType::Vec3 => (text_info(), ProductIcon::SymbolArray),
+            XExprReturnType::Vec4 => (text_info(), ProductIcon::SymbolArray),
+            XExprReturnType::Bool => (text_success(), ProductIcon::Check),
             XExprReturnType::Error => (text_danger(), ProductIcon::OctagonAlert),
         };
 
@@ -842,6 +982,82 @@ impl ExprInput
     }
 }
 
+impl ExprInput
+{
+    fn render_signature_help(&self, help: SignatureHelp, top: Pixels) -> impl IntoElement
+    {
+        div()
+            .absolute()
+            .top(top)
+            .left_0()
+            .px_2()
+            .py_1()
+            .rounded_md()
+            .bg(autocomplete_bg())
+            .border_1()
+            .border_color(border_default())
+            .child(
+                with_default_font(div())
+                    .text_xs()
+                    .text_color(text_secondary())
+                    .child(format!(
+                        "{}  [argument {}]",
+                        help.signature(),
+                        help.argument + 1
+                    )),
+            )
+    }
+
+    fn render_inline_simplified(&self, expr: &XExpr, _cx: &mut Context<Self>) -> impl IntoElement
+    {
+        div().ml_2().px_2().py_1().max_w(px(280.)).child(
+            with_default_font(div())
+                .text_xs()
+                .text_color(text_muted())
+                .truncate()
+                .child(format!("= {}", expr)),
+        )
+    }
+
+    fn render_inline_type_error(
+        &self,
+        error: &XTypeError,
+        _cx: &mut Context<Self>,
+    ) -> impl IntoElement
+    {
+        div()
+            .flex()
+            .items_center()
+            .gap_2()
+            .ml_2()
+            .px_2()
+            .py_1()
+            .max_w(px(280.))
+            .child(
+                div()
+                    .flex_shrink_0()
+                    .size_4()
+                    .flex()
+                    .items_center()
+                    .justify_center()
+                    .child(
+                        ProductIcon::OctagonAlert
+                            .to_svg()
+                            .size_4()
+                            .text_color(text_danger()),
+                    ),
+            )
+            .child(
+                with_default_font(div())
+                    .text_xs()
+                    .text_color(text_danger())
+                    .font_weight(gpui::FontWeight::MEDIUM)
+                    .truncate()
+                    .child(error.to_string()),
+            )
+    }
+}
+
 impl Focusable for ExprInput
 {
     fn focus_handle(&self, _cx: &gpui::App) -> FocusHandle
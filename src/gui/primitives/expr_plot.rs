@@ -0,0 +1,309 @@
+use std::ops::Range;
+
+use gpui::prelude::*;
+use gpui::{
+    Bounds,
+    Context,
+    IntoElement,
+    MouseButton,
+    ParentElement,
+    Pixels,
+    Rgba,
+    Styled,
+    Window,
+    canvas,
+    div,
+    fill,
+    point,
+    px,
+    size,
+};
+
+// ====================
+// Editor.
+// ====================
+use crate::gui::expr::xexpr::XExpr;
+use crate::gui::expr::{XEnv, XEvalError, XPlot, XPlotVariable, XValue};
+use crate::gui::styling::colors::*;
+use crate::gui::styling::fonts::*;
+
+/// Number of points an expression is sampled at across the plot.
+///
+const PLOT_POINTS: usize = 96;
+
+/// Plot of an expression over the age of a particle or the simulation time.
+/// Vector expressions are plotted per component, and random expressions show
+/// the band between their lowest and highest samples.
+///
+pub struct ExprPlot
+{
+    pub expr:     Option<XExpr>,
+    pub variable: XPlotVariable,
+    pub lifetime: f32,
+    pub duration: f32,
+    pub env:      XEnv,
+    pub height:   Pixels,
+    plot:         Option<Result<XPlot, XEvalError>>,
+}
+
+impl ExprPlot
+{
+    pub fn new(_cx: &mut Context<Self>) -> Self
+    {
+        Self {
+            expr:     None,
+            variable: XPlotVariable::default(),
+            lifetime: 1.0,
+            duration: 5.0,
+            env:      XEnv::new(),
+            height:   px(96.),
+            plot:     None,
+        }
+    }
+
+    pub fn with_expr(mut self, expr: Option<XExpr>) -> Self
+    {
+        self.expr = expr;
+        self.resample();
+        self
+    }
+
+    pub fn with_variable(mut self, variable: XPlotVariable) -> Self
+    {
+        self.variable = variable;
+        self.resample();
+        self
+    }
+
+    pub fn with_lifetime(mut self, lifetime: f32) -> Self
+    {
+        self.lifetime = lifetime;
+        self.resample();
+        self
+    }
+
+    pub fn with_duration(mut self, duration: f32) -> Self
+    {
+        self.duration = duration;
+        self.resample();
+        self
+    }
+
+    /// Evaluate in `env`, over the lifetime it gives particles, if any.
+    pub fn with_env(mut self, env: XEnv) -> Self
+    {
+        self.set_env_lifetime(env);
+        self.resample();
+        self
+    }
+
+    pub fn set_expr(&mut self, expr: Option<XExpr>, cx: &mut Context<Self>)
+    {
+        if self.expr != expr {
+            self.expr = expr;
+            self.resample();
+            cx.notify();
+        }
+    }
+
+    pub fn set_variable(&mut self, variable: XPlotVariable, cx: &mut Context<Self>)
+    {
+        if self.variable != variable {
+            self.variable = variable;
+            self.resample();
+            cx.notify();
+        }
+    }
+
+    pub fn set_env(&mut self, env: XEnv, cx: &mut Context<Self>)
+    {
+        self.set_env_lifetime(env);
+        self.resample();
+        cx.notify();
+    }
+
+    /// Range of the plotted variable.
+    ///
+    pub fn range(&self) -> Range<f32>
+    {
+        match self.variable {
+            XPlotVariable::Age => 0.0..self.lifetime,
+            XPlotVariable::Time => 0.0..self.duration,
+        }
+    }
+
+    fn set_env_lifetime(&mut self, env: XEnv)
+    {
+        if let Some(XValue::Float(lifetime)) = env.attributes.get("lifetime") {
+            self.lifetime = *lifetime;
+        }
+        self.env = env;
+    }
+
+    fn resample(&mut self)
+    {
+        let Some(expr) = &self.expr else {
+            self.plot = None;
+            return;
+        };
+        // Expressions of the age often normalize it by the lifetime of the particle.
+        let mut env = self.env.clone();
+        env.attributes
+            .entry("lifetime".to_string())
+            .or_insert(XValue::Float(self.lifetime));
+        self.plot = Some(XPlot::sample(
+            expr,
+            self.variable,
+            self.range(),
+            PLOT_POINTS,
+            &env,
+        ));
+    }
+}
+
+// ====================
+// Rendering.
+// ====================
+
+impl ExprPlot
+{
+    fn render_variable_button(
+        &self,
+        variable: XPlotVariable,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement
+    {
+        let is_selected = self.variable == variable;
+        with_default_font(div())
+            .px_2()
+            .rounded_md()
+            .text_xs()
+            .cursor_pointer()
+            .when(is_selected, |el| {
+                el.bg(selection_active()).text_color(text_primary())
+            })
+            .when(!is_selected, |el| {
+                el.text_color(text_muted())
+                    .hover(|style| style.text_color(text_secondary()))
+            })
+            .on_mouse_down(
+                MouseButton::Left,
+                cx.listener(move |this, _ev, _window, cx| {
+                    this.set_variable(variable, cx);
+                }),
+            )
+            .child(variable.to_string())
+    }
+
+    fn paint_plot(plot: &XPlot, bounds: Bounds<Pixels>, window: &mut Window)
+    {
+        let y_range = plot.y_range();
+        let x_range = match (plot.xs.first(), plot.xs.last()) {
+            (Some(first), Some(last)) if last > first => *first..*last,
+            _ => return,
+        };
+        let to_x = |x: f32| {
+            let t = (x - x_range.start) / (x_range.end - x_range.start);
+            bounds.origin.x.0 + bounds.size.width.0 * t
+        };
+        let to_y = |y: f32| {
+            let t = (y.clamp(y_range.start, y_range.end) - y_range.start)
+                / (y_range.end - y_range.start);
+            bounds.origin.y.0 + bounds.size.height.0 * (1.0 - t)
+        };
+        // Vertical run from `top` to `bottom` in the column between `left` and `right`.
+        let column = |left: f32, right: f32, top: f32, bottom: f32| {
+            Bounds::new(
+                point(px(left), px(top.min(bottom))),
+                size(
+                    px((right - left).max(1.0)),
+                    px((bottom - top).abs().max(1.5)),
+                ),
+            )
+        };
+
+        if y_range.contains(&0.0) {
+            let zero = to_y(0.0);
+            window.paint_quad(fill(
+                column(to_x(x_range.start), to_x(x_range.end), zero, zero),
+                plot_axis(),
+            ));
+        }
+
+        let colors = [plot_x(), plot_y(), plot_z(), plot_w()];
+        for (curve, color) in plot.curves.iter().zip(colors) {
+            for i in 1..plot.xs.len() {
+                let (left, right) = (to_x(plot.xs[i - 1]), to_x(plot.xs[i]));
+                if plot.is_random {
+                    let band = Rgba { a: 0.2, ..color };
+                    let (low, high) = (to_y(curve.min[i]), to_y(curve.max[i]));
+                    window.paint_quad(fill(column(left, right, high, low), band));
+                }
+                let (previous, current) = (curve.values[i - 1], curve.values[i]);
+                if previous.is_finite() && current.is_finite() {
+                    window.paint_quad(fill(
+                        column(left, right, to_y(previous), to_y(current)),
+                        color,
+                    ));
+                }
+            }
+        }
+    }
+}
+
+impl Render for ExprPlot
+{
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement
+    {
+        let range = self.range();
+        let plot = self.plot.clone();
+
+        div()
+            .flex()
+            .flex_col()
+            .gap_1()
+            .w_full()
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_1()
+                    .child(self.render_variable_button(XPlotVariable::Age, cx))
+                    .child(self.render_variable_button(XPlotVariable::Time, cx))
+                    .child(
+                        with_default_font(div())
+                            .ml_auto()
+                            .text_xs()
+                            .text_color(text_muted())
+                            .child(format!("{} → {}", range.start, range.end)),
+                    ),
+            )
+            .child(match plot {
+                Some(Ok(plot)) => div()
+                    .relative()
+                    .w_full()
+                    .h(self.height)
+                    .rounded_md()
+                    .overflow_hidden()
+                    .bg(plot_background())
+                    .border_1()
+                    .border_color(border_default())
+                    .child(
+                        canvas(
+                            |_, _, _| {},
+                            move |bounds, _, window, _| Self::paint_plot(&plot, bounds, window),
+                        )
+                        .absolute()
+                        .left_0()
+                        .right_0()
+                        .top_0()
+                        .bottom_0(),
+                    ),
+                Some(Err(error)) => with_default_font(div())
+                    .text_xs()
+                    .text_color(text_danger())
+                    .child(error.to_string()),
+                None => div(),
+            })
+    }
+}
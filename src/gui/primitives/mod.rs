@@ -6,6 +6,7 @@ pub mod dropdown_menu;
 pub mod events;
 pub mod expr_highlighter;
 pub mod expr_input;
+pub mod expr_plot;
 pub mod increment_buttons;
 pub mod slider;
 pub mod text_input;
@@ -64,6 +64,13 @@ pub struct HighlightSpan
 pub trait Highlighter: 'static
 {
     fn highlight(&self, text: &str) -> Vec<HighlightSpan>;
+
+    /// Byte ranges of the text to underline with a red squiggle, e.g. errors.
+    ///
+    fn squiggles(&self, _text: &str) -> Vec<Range<usize>>
+    {
+        vec![]
+    }
 }
 
 #[derive(Clone, Copy, Debug, Default)]
@@ -344,7 +351,53 @@ where
             }
         }
 
-        runs.into_iter().filter(|run| run.len > 0).collect()
+        let runs = runs.into_iter().filter(|run| run.len > 0).collect();
+        Self::apply_squiggles(runs, &self.highlighter.squiggles(content))
+    }
+
+    /// Split runs at squiggle boundaries and underline the runs inside them.
+    ///
+    fn apply_squiggles(runs: Vec<TextRun>, squiggles: &[Range<usize>]) -> Vec<TextRun>
+    {
+        if squiggles.is_empty() {
+            return runs;
+        }
+
+        let mut result = Vec::with_capacity(runs.len());
+        let mut pos = 0;
+        for run in runs {
+            let end = pos + run.len;
+            let mut cuts = vec![pos, end];
+            for squiggle in squiggles {
+                cuts.extend(
+                    [squiggle.start, squiggle.end]
+                        .into_iter()
+                        .filter(|&cut| cut > pos && cut < end),
+                );
+            }
+            cuts.sort_unstable();
+            cuts.dedup();
+
+            for piece in cuts.windows(2) {
+                let squiggled = squiggles
+                    .iter()
+                    .any(|squiggle| squiggle.start <= piece[0] && piece[1] <= squiggle.end);
+                let mut piece_run = TextRun {
+                    len: piece[1] - piece[0],
+                    ..run.clone()
+                };
+                if squiggled {
+                    piece_run.underline = Some(UnderlineStyle {
+                        color:     Some(syntax_error().into()),
+                        thickness: px(1.0),
+                        wavy:      true,
+                    });
+                }
+                result.push(piece_run);
+            }
+            pos = end;
+        }
+        result
     }
     pub fn with_highlighter(highlighter: H, cx: &mut Context<Self>) -> Self
     {
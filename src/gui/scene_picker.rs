@@ -0,0 +1,225 @@
+use std::path::PathBuf;
+
+// ====================
+// GPUI.
+// ====================
+use gpui::prelude::*;
+use gpui::{Context, Entity, EventEmitter, IntoElement, ParentElement, Styled, Window, div, px};
+use tracing::error;
+
+// ====================
+// Editor.
+// ====================
+use crate::gui::primitives::button::{Button, ButtonVariant};
+use crate::gui::primitives::dropdown_input::{Dropdown, DropdownItem, DropdownSizeVariant};
+use crate::gui::primitives::events::DropdownEvent;
+use crate::gui::primitives::text_input::{SizeVariant as TextSizeVariant, TextInput};
+use crate::gui::styling::colors::*;
+use crate::gui::styling::fonts::*;
+use crate::gui::utils::text::ValidationMode;
+use crate::shared::scene::ViewerScene;
+
+/// Event emitted by the scene picker.
+#[derive(Debug, Clone)]
+pub enum ScenePickerEvent
+{
+    SceneSelected(ViewerScene),
+    SaveRequested(PathBuf),
+}
+
+impl EventEmitter<ScenePickerEvent> for ScenePicker {}
+
+/// Dropdown listing the viewer scene presets found in the assets root.
+///
+/// The first entry is always the built-in default scene, which is saved under the name
+/// typed next to it.
+///
+pub struct ScenePicker
+{
+    assets_root:    PathBuf,
+    scenes:         Vec<PathBuf>,
+    dropdown:       Entity<Dropdown>,
+    name_input:     Entity<TextInput>,
+    save_button:    Entity<Button>,
+    _subscriptions: Vec<gpui::Subscription>,
+}
+
+impl ScenePicker
+{
+    pub fn new(cx: &mut Context<Self>, assets_root: PathBuf) -> Self
+    {
+        let scenes = ViewerScene::list(&assets_root);
+        let dropdown = cx.new(|cx| {
+            Dropdown::new(cx)
+                .with_items(Self::dropdown_items(&scenes))
+                .with_selected_index(Some(0))
+                .with_size_variant(DropdownSizeVariant::Small)
+        });
+        let name_input = cx.new(|cx| {
+            TextInput::new(cx)
+                .with_placeholder("New scene name")
+                .with_validation_mode(ValidationMode::AlphaNumeric)
+                .with_size_variant(TextSizeVariant::Small)
+        });
+        let save_button = cx.new(|_| {
+            Button::new("Save")
+                .variant(ButtonVariant::Secondary)
+                .font_size("sm")
+        });
+
+        let dropdown_subscription = cx.subscribe(
+            &dropdown,
+            |this, _dropdown, event: &DropdownEvent, cx| match event {
+                DropdownEvent::SelectionChanged(index) => this.on_scene_selected(*index, cx),
+            },
+        );
+        let save_subscription = cx.subscribe(&save_button, |this, _button, _event, cx| {
+            this.on_save_clicked(cx);
+        });
+
+        Self {
+            assets_root,
+            scenes,
+            dropdown,
+            name_input,
+            save_button,
+            _subscriptions: vec![dropdown_subscription, save_subscription],
+        }
+    }
+
+    /// Re-scan the assets root for scene files, keeping the current selection when possible.
+    pub fn refresh(&mut self, cx: &mut Context<Self>)
+    {
+        let selected = self.selected_path(cx);
+        let scenes = ViewerScene::list(&self.assets_root);
+        self.set_scenes(scenes, selected, cx);
+    }
+
+    fn set_scenes(
+        &mut self,
+        scenes: Vec<PathBuf>,
+        selected: Option<PathBuf>,
+        cx: &mut Context<Self>,
+    )
+    {
+        self.scenes = scenes;
+        let selected_index = selected
+            .and_then(|path| self.scenes.iter().position(|p| *p == path))
+            .map_or(0, |ix| ix + 1);
+        let items = Self::dropdown_items(&self.scenes);
+        self.dropdown.update(cx, |dropdown, cx| {
+            dropdown.items = items;
+            dropdown.selected_index = Some(selected_index);
+            cx.notify();
+        });
+        cx.notify();
+    }
+
+    fn dropdown_items(scenes: &[PathBuf]) -> Vec<DropdownItem>
+    {
+        std::iter::once(DropdownItem::new("Default"))
+            .chain(scenes.iter().map(|path| {
+                DropdownItem::new(
+                    ViewerScene::name_from_path(path)
+                        .unwrap_or_default()
+                        .to_string(),
+                )
+            }))
+            .collect()
+    }
+
+    fn selected_path(&self, cx: &Context<Self>) -> Option<PathBuf>
+    {
+        self.dropdown
+            .read(cx)
+            .selected_index
+            .and_then(|ix| ix.checked_sub(1))
+            .and_then(|ix| self.scenes.get(ix).cloned())
+    }
+
+    // ====================
+    // Event handlers.
+    // ====================
+
+    fn on_scene_selected(&mut self, index: usize, cx: &mut Context<Self>)
+    {
+        let scene = match index.checked_sub(1).and_then(|ix| self.scenes.get(ix)) {
+            Some(path) => match ViewerScene::load(path) {
+                Ok(scene) => scene,
+                Err(e) => {
+                    error!("Failed to load scene: {}", e);
+                    return;
+                }
+            },
+            None => ViewerScene::default(),
+        };
+        cx.emit(ScenePickerEvent::SceneSelected(scene));
+        cx.notify();
+    }
+
+    fn on_save_clicked(&mut self, cx: &mut Context<Self>)
+    {
+        let path = match self.selected_path(cx) {
+            Some(path) => path,
+            None => {
+                // The default scene is built in, so it is saved as a new scene file.
+                let name = self.name_input.read(cx).content.trim().to_string();
+                if name.is_empty() {
+                    error!("Enter a scene name to save the default scene");
+                    return;
+                }
+                let path =
+                    self.assets_root
+                        .join(format!("{}.{}", name, ViewerScene::FILE_EXTENSION));
+                if path.exists() {
+                    error!("Scene {} already exists", path.display());
+                    return;
+                }
+                self.name_input.update(cx, |input, cx| {
+                    input.content = "".into();
+                    cx.notify();
+                });
+                path
+            }
+        };
+
+        // The viewer writes the file asynchronously, so list the new scene right away.
+        if !self.scenes.contains(&path) {
+            let mut scenes = self.scenes.clone();
+            scenes.push(path.clone());
+            scenes.sort();
+            self.set_scenes(scenes, Some(path.clone()), cx);
+        }
+        cx.emit(ScenePickerEvent::SaveRequested(path));
+    }
+}
+
+impl Render for ScenePicker
+{
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement
+    {
+        let is_default = self.selected_path(cx).is_none();
+        div()
+            .id("scene-picker")
+            .w_full()
+            .flex()
+            .items_center()
+            .gap_2()
+            .px(px(12.0))
+            .py(px(4.0))
+            .bg(background_primary())
+            .border_b_1()
+            .border_color(border_separator())
+            .child(
+                with_default_font(div())
+                    .text_color(text_secondary())
+                    .text_size(px(11.0))
+                    .child("Scene"),
+            )
+            .child(div().flex_1().child(self.dropdown.clone()))
+            .when(is_default, |el| {
+                el.child(div().w(px(120.0)).child(self.name_input.clone()))
+            })
+            .child(self.save_button.clone())
+    }
+}
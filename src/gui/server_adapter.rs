@@ -1,7 +1,9 @@
 // ====================
 // Std.
 // ====================
+use std::ffi::OsString;
 use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
 use std::process::{Child, Command, Stdio};
 use std::thread;
 
@@ -11,6 +13,32 @@ use std::thread;
 use kanal::{Receiver, Sender, unbounded};
 use tracing::{debug, error, info, warn};
 
+/// Environment variable overriding the viewer executable path.
+pub const VIEWER_PATH_ENV: &str = "DRAKKAR_VFX_VIEWER";
+const VIEWER_BINARY_NAME: &str = "drakkar-vfx-viewer";
+
+/// The viewer executable could not be found.
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum ViewerNotFound
+{
+    /// The path given with `--viewer-path` or `DRAKKAR_VFX_VIEWER` does not exist.
+    #[error("Viewer executable {} (from {origin}) does not exist", path.display())]
+    ExplicitPath
+    {
+        path:   PathBuf,
+        origin: &'static str,
+    },
+    /// None of the searched locations contains the viewer.
+    #[error(
+        "Viewer executable not found. Tried:\n{}",
+        tried.iter().map(|p| format!("  {}", p.display())).collect::<Vec<_>>().join("\n")
+    )]
+    NotFound
+    {
+        tried: Vec<PathBuf>
+    },
+}
+
 #[derive(Debug, Clone)]
 pub enum ServerMessage
 {
@@ -28,6 +56,7 @@ pub enum ServerResponse
 
 pub struct ViewerServerAdapter
 {
+    viewer_path:     Option<PathBuf>,
     process:         Option<Child>,
     input_sender:    Option<Sender<ServerMessage>>,
     output_receiver: Option<Receiver<ServerResponse>>,
@@ -39,6 +68,7 @@ impl ViewerServerAdapter
     pub fn new() -> Self
     {
         Self {
+            viewer_path:     None,
             process:         None,
             input_sender:    None,
             output_receiver: None,
@@ -46,22 +76,89 @@ impl ViewerServerAdapter
         }
     }
 
+    /// Use an explicit viewer executable, e.g. from the `--viewer-path` CLI flag.
+    pub fn with_viewer_path(mut self, viewer_path: Option<PathBuf>) -> Self
+    {
+        self.viewer_path = viewer_path;
+        self
+    }
+
+    /// Locate the viewer executable.
+    ///
+    /// An explicit path or `DRAKKAR_VFX_VIEWER` is used as is and must exist. Otherwise
+    /// the directory of the editor binary is searched, then `PATH`.
+    pub fn find_viewer(&self) -> Result<PathBuf, ViewerNotFound>
+    {
+        let exe_dir = std::env::current_exe()
+            .ok()
+            .and_then(|exe| exe.parent().map(|dir| dir.to_path_buf()));
+        Self::find_viewer_in(
+            self.viewer_path.clone(),
+            std::env::var_os(VIEWER_PATH_ENV),
+            exe_dir,
+            std::env::var_os("PATH"),
+        )
+    }
+
+    /// Locate the viewer executable from an explicit path, the value of
+    /// `DRAKKAR_VFX_VIEWER`, the directory of the editor binary and the value of `PATH`.
+    pub fn find_viewer_in(
+        viewer_path: Option<PathBuf>,
+        env_path: Option<OsString>,
+        exe_dir: Option<PathBuf>,
+        path_var: Option<OsString>,
+    ) -> Result<PathBuf, ViewerNotFound>
+    {
+        let explicit = match viewer_path {
+            Some(path) => Some((path, "--viewer-path")),
+            None => env_path
+                .filter(|p| !p.is_empty())
+                .map(|path| (PathBuf::from(path), VIEWER_PATH_ENV)),
+        };
+        if let Some((path, origin)) = explicit {
+            if path.is_file() {
+                return Ok(path);
+            }
+            return Err(ViewerNotFound::ExplicitPath { path, origin });
+        }
+
+        let binary_name = format!("{}{}", VIEWER_BINARY_NAME, std::env::consts::EXE_SUFFIX);
+        let mut candidates = Vec::new();
+        if let Some(dir) = exe_dir {
+            candidates.push(dir.join(&binary_name));
+        }
+        if let Some(paths) = path_var {
+            candidates.extend(std::env::split_paths(&paths).map(|dir| dir.join(&binary_name)));
+        }
+
+        match candidates.iter().find(|path| path.is_file()) {
+            Some(path) => Ok(path.clone()),
+            None => Err(ViewerNotFound::NotFound { tried: candidates }),
+        }
+    }
+
     pub fn start(&mut self) -> Result<(), String>
+    {
+        self.start_with_args(&[])
+    }
+
+    /// Start the viewer process with extra command line arguments, e.g. `--embedded`.
+    pub fn start_with_args(&mut self, args: &[String]) -> Result<(), String>
     {
         if self.process.is_some() {
             return Err("Server is already running".to_string());
         }
         info!("Starting viewer server process...");
 
-        // TODO: Make this configurable.
-        let viewer_path = if std::path::Path::new("target/release/drakkar-vfx-viewer").exists() {
-            "target/release/drakkar-vfx-viewer"
-        } else {
-            "target/debug/drakkar-vfx-viewer"
-        };
+        let viewer_path = self.find_viewer().map_err(|e| {
+            error!("{}", e);
+            e.to_string()
+        })?;
+        info!("Using viewer executable: {}", viewer_path.display());
 
         let mut cmd = Command::new(viewer_path);
-        cmd.env("RUST_LOG", "warn")
+        cmd.args(args)
+            .env("RUST_LOG", "warn")
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
             .stderr(Stdio::piped());
@@ -396,6 +396,11 @@ pub fn syntax_keyword() -> Rgba
     rgb(0xC678DD) // Purple for keywords (One Dark)
 }
 
+pub fn syntax_swizzle() -> Rgba
+{
+    rgb(0xE5C07B) // Yellow for swizzles (One Dark)
+}
+
 pub fn syntax_operator() -> Rgba
 {
     rgb(0x56B6C2) // Cyan for operators (One Dark)
@@ -756,3 +761,34 @@ pub fn revert_button_glow() -> Hsla
 {
     hsla(0.0 / 360.0, 0.6, 0.5, 0.3) // Red glow effect
 }
+
+// Expression plot colors
+pub fn plot_background() -> Rgba
+{
+    rgb(0x1a1b1e) // Dark background behind curves
+}
+
+pub fn plot_axis() -> Rgba
+{
+    rgb(0x3f4147) // Gray for the zero line
+}
+
+pub fn plot_x() -> Rgba
+{
+    rgb(0xE06C75) // Red for the x component
+}
+
+pub fn plot_y() -> Rgba
+{
+    rgb(0x98C379) // Green for the y component
+}
+
+pub fn plot_z() -> Rgba
+{
+    rgb(0x61AFEF) // Blue for the z component
+}
+
+pub fn plot_w() -> Rgba
+{
+    rgb(0xE5C07B) // Yellow for the w component
+}
@@ -0,0 +1,117 @@
+#[cfg(test)]
+mod tests
+{
+    use std::path::PathBuf;
+
+    use crate::gui::expr::xplot::{XPlot, XPlotVariable};
+    use crate::gui::expr::{XEvalError, XExpr};
+    use crate::gui::models::attr::XAttr;
+    use crate::gui::models::modifier::{XInitModifier, XSetAttributeModifier};
+    use crate::gui::models::state::{AssetState, PreviewEnv};
+    use crate::gui::server_adapter::{VIEWER_PATH_ENV, ViewerNotFound, ViewerServerAdapter};
+
+    /// Empty directory of its own for a test, under the system temp directory.
+    fn test_dir(name: &str) -> PathBuf
+    {
+        let dir = std::env::temp_dir().join(format!("drakkar_{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn viewer_binary(dir: &PathBuf) -> PathBuf
+    {
+        dir.join(format!(
+            "drakkar-vfx-viewer{}",
+            std::env::consts::EXE_SUFFIX
+        ))
+    }
+
+    #[test]
+    fn test_find_viewer_next_to_editor()
+    {
+        let exe_dir = test_dir("viewer_next_to_editor");
+        let path_dir = test_dir("viewer_next_to_editor_path");
+        std::fs::write(viewer_binary(&exe_dir), "").unwrap();
+        std::fs::write(viewer_binary(&path_dir), "").unwrap();
+        let path_var = std::env::join_paths([&path_dir]).unwrap();
+
+        // The editor's directory is searched before `PATH`.
+        assert_eq!(
+            ViewerServerAdapter::find_viewer_in(
+                None,
+                None,
+                Some(exe_dir.clone()),
+                Some(path_var.clone()),
+            )
+            .unwrap(),
+            viewer_binary(&exe_dir)
+        );
+        assert_eq!(
+            ViewerServerAdapter::find_viewer_in(None, None, None, Some(path_var)).unwrap(),
+            viewer_binary(&path_dir)
+        );
+        let _ = std::fs::remove_dir_all(&exe_dir);
+        let _ = std::fs::remove_dir_all(&path_dir);
+    }
+
+    #[test]
+    fn test_find_viewer_not_found()
+    {
+        let exe_dir = test_dir("viewer_not_found");
+        let path_dir = test_dir("viewer_not_found_path");
+        let path_var = std::env::join_paths([&path_dir]).unwrap();
+
+        match ViewerServerAdapter::find_viewer_in(None, None, Some(exe_dir.clone()), Some(path_var))
+        {
+            Err(ViewerNotFound::NotFound { tried }) => {
+                assert_eq!(
+                    tried,
+                    vec![viewer_binary(&exe_dir), viewer_binary(&path_dir)]
+                )
+            }
+            other => panic!("{:?}", other),
+        }
+
+        // Explicit paths are not searched further when they do not exist.
+        std::fs::write(viewer_binary(&exe_dir), "").unwrap();
+        let missing = exe_dir.join("missing");
+        match ViewerServerAdapter::find_viewer_in(
+            None,
+            Some(missing.clone().into_os_string()),
+            Some(exe_dir.clone()),
+            None,
+        ) {
+            Err(ViewerNotFound::ExplicitPath { path, origin }) => {
+                assert_eq!(path, missing);
+                assert_eq!(origin, VIEWER_PATH_ENV);
+            }
+            other => panic!("{:?}", other),
+        }
+        let _ = std::fs::remove_dir_all(&exe_dir);
+        let _ = std::fs::remove_dir_all(&path_dir);
+    }
+
+    #[test]
+    fn test_plot_env_attribute_fallback()
+    {
+        let state = AssetState {
+            init_modifiers: vec![XInitModifier::XSetAttribute(XSetAttributeModifier {
+                attr:  XAttr::Lifetime,
+                value: XExpr::lit(2.0),
+            })],
+            ..Default::default()
+        };
+        let preview = PreviewEnv::from_state(&state);
+        let expr = XExpr::parse("attr(size) + attr(age) / attr(lifetime)").unwrap();
+        assert_eq!(
+            XPlot::sample(&expr, XPlotVariable::Age, 0.0..1.0, 3, &preview.0),
+            Err(XEvalError::UnknownAttribute("size".to_string()))
+        );
+
+        // Attributes without a preview value read as zero, the lifetime keeps its own.
+        let plot =
+            XPlot::sample(&expr, XPlotVariable::Age, 0.0..1.0, 3, &preview.plot_env()).unwrap();
+        assert_eq!(plot.curves[0].values, vec![0.0, 0.25, 0.5]);
+    }
+}
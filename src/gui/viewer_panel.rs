@@ -0,0 +1,365 @@
+// ====================
+// Std.
+// ====================
+use std::io::{BufReader, Read};
+use std::net::TcpStream;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+// ====================
+// GPUI.
+// ====================
+use gpui::prelude::*;
+use gpui::{
+    Context,
+    EventEmitter,
+    IntoElement,
+    MouseButton,
+    MouseDownEvent,
+    MouseMoveEvent,
+    MouseUpEvent,
+    ObjectFit,
+    ParentElement,
+    Pixels,
+    Point,
+    RenderImage,
+    ScrollWheelEvent,
+    Styled,
+    Window,
+    canvas,
+    div,
+    img,
+    px,
+};
+use kanal::{Sender, bounded};
+use tracing::debug;
+
+// ====================
+// Editor.
+// ====================
+use crate::gui::styling::colors::*;
+use crate::gui::styling::fonts::*;
+
+const RECONNECT_DELAY: Duration = Duration::from_millis(500);
+const ZOOM_PER_PIXEL: f32 = 0.002;
+/// Largest frame width or height accepted from the viewer.
+const MAX_FRAME_DIMENSION: u32 = 8192;
+
+/// Where the viewer panel is docked relative to the asset editor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ViewerDock
+{
+    #[default]
+    Right,
+    Bottom,
+}
+
+/// Event emitted by the viewer panel.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ViewerPanelEvent
+{
+    Resized
+    {
+        width:  u32,
+        height: u32,
+    },
+    PanZoom
+    {
+        pan:  [f32; 2],
+        zoom: f32,
+    },
+    DockChanged(ViewerDock),
+}
+
+impl EventEmitter<ViewerPanelEvent> for ViewerPanel {}
+
+/// Panel showing the frames streamed by a viewer started with `--embedded`.
+///
+pub struct ViewerPanel
+{
+    pub dock:     ViewerDock,
+    latest_frame: Arc<Mutex<Option<Arc<RenderImage>>>>,
+    shown_frame:  Option<Arc<RenderImage>>,
+    last_size:    Option<(u32, u32)>,
+    drag_origin:  Option<Point<Pixels>>,
+    /// Set when the panel is dropped, for the frame thread to exit.
+    closed:       Arc<AtomicBool>,
+}
+
+impl ViewerPanel
+{
+    pub fn new(cx: &mut Context<Self>) -> Self
+    {
+        let latest_frame = Arc::new(Mutex::new(None));
+        let frame_slot = latest_frame.clone();
+        let (frame_ready, frame_ready_receiver) = bounded(1);
+        let closed = Arc::new(AtomicBool::new(false));
+        let thread_closed = closed.clone();
+        let builder = std::thread::Builder::new().name("viewer-frame-thread".to_string());
+        if let Err(e) =
+            builder.spawn(move || frame_receiver_main(frame_slot, frame_ready, thread_closed))
+        {
+            tracing::error!("Failed to spawn viewer frame thread: {}", e);
+        }
+
+        // Redraw only when the frame thread has received a new frame.
+        cx.spawn(async move |this, cx| {
+            let frame_ready = frame_ready_receiver.to_async();
+            while frame_ready.recv().await.is_ok() {
+                if this.update(cx, |_, cx| cx.notify()).is_err() {
+                    break;
+                }
+            }
+        })
+        .detach();
+
+        Self {
+            dock: ViewerDock::default(),
+            latest_frame,
+            shown_frame: None,
+            last_size: None,
+            drag_origin: None,
+            closed,
+        }
+    }
+
+    // ====================
+    // Event handlers.
+    // ====================
+
+    fn on_resized(&mut self, width: u32, height: u32, cx: &mut Context<Self>)
+    {
+        if self.last_size != Some((width, height)) && width > 0 && height > 0 {
+            self.last_size = Some((width, height));
+            cx.emit(ViewerPanelEvent::Resized { width, height });
+        }
+    }
+
+    fn on_scroll(&mut self, ev: &ScrollWheelEvent, _: &mut Window, cx: &mut Context<Self>)
+    {
+        let delta = ev.delta.pixel_delta(px(16.0));
+        let zoom = (1.0 - delta.y.0 * ZOOM_PER_PIXEL).max(0.1);
+        cx.emit(ViewerPanelEvent::PanZoom {
+            pan: [0.0, 0.0],
+            zoom,
+        });
+    }
+
+    fn on_mouse_down(&mut self, ev: &MouseDownEvent, _: &mut Window, _: &mut Context<Self>)
+    {
+        self.drag_origin = Some(ev.position);
+    }
+
+    fn on_mouse_up(&mut self, _: &MouseUpEvent, _: &mut Window, _: &mut Context<Self>)
+    {
+        self.drag_origin = None;
+    }
+
+    fn on_mouse_move(&mut self, ev: &MouseMoveEvent, _: &mut Window, cx: &mut Context<Self>)
+    {
+        let Some(origin) = self.drag_origin else {
+            return;
+        };
+        if ev.pressed_button != Some(MouseButton::Left) {
+            self.drag_origin = None;
+            return;
+        }
+
+        // Dragging moves the scene with the cursor, so the camera moves the other way.
+        let delta = ev.position - origin;
+        self.drag_origin = Some(ev.position);
+        cx.emit(ViewerPanelEvent::PanZoom {
+            pan:  [-delta.x.0, delta.y.0],
+            zoom: 1.0,
+        });
+    }
+
+    fn on_toggle_dock(&mut self, _: &MouseDownEvent, _: &mut Window, cx: &mut Context<Self>)
+    {
+        self.dock = match self.dock {
+            ViewerDock::Right => ViewerDock::Bottom,
+            ViewerDock::Bottom => ViewerDock::Right,
+        };
+        cx.emit(ViewerPanelEvent::DockChanged(self.dock));
+        cx.notify();
+    }
+}
+
+// ====================
+// Rendering.
+// ====================
+
+impl Render for ViewerPanel
+{
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement
+    {
+        let latest = self
+            .latest_frame
+            .lock()
+            .ok()
+            .and_then(|frame| frame.clone());
+
+        // Each frame is a new image, so release the previous one from the sprite atlas.
+        if let Some(latest) = latest {
+            let is_new = self
+                .shown_frame
+                .as_ref()
+                .is_none_or(|shown| !Arc::ptr_eq(shown, &latest));
+            if is_new {
+                if let Some(previous) = self.shown_frame.replace(latest) {
+                    let _ = window.drop_image(previous);
+                }
+            }
+        }
+
+        let this = cx.entity().downgrade();
+        let dock_label = match self.dock {
+            ViewerDock::Right => "Dock Bottom",
+            ViewerDock::Bottom => "Dock Right",
+        };
+
+        div()
+            .id("viewer-panel")
+            .size_full()
+            .flex()
+            .flex_col()
+            .bg(panel_file_browser())
+            .child(
+                div()
+                    .h(px(24.0))
+                    .px(px(8.0))
+                    .flex()
+                    .items_center()
+                    .justify_between()
+                    .border_b_1()
+                    .border_color(border_separator())
+                    .child(
+                        with_default_font(div())
+                            .text_color(text_secondary())
+                            .text_size(px(11.0))
+                            .child("Viewer"),
+                    )
+                    .child(
+                        with_default_font(div())
+                            .text_color(text_muted())
+                            .text_size(px(11.0))
+                            .cursor_pointer()
+                            .hover(|el| el.text_color(text_primary()))
+                            .on_mouse_down(MouseButton::Left, cx.listener(Self::on_toggle_dock))
+                            .child(dock_label),
+                    ),
+            )
+            .child(
+                div()
+                    .id("viewer-panel-frame")
+                    .relative()
+                    .flex_1()
+                    .overflow_hidden()
+                    .on_scroll_wheel(cx.listener(Self::on_scroll))
+                    .on_mouse_down(MouseButton::Left, cx.listener(Self::on_mouse_down))
+                    .on_mouse_up(MouseButton::Left, cx.listener(Self::on_mouse_up))
+                    .on_mouse_move(cx.listener(Self::on_mouse_move))
+                    .child(
+                        canvas(
+                            move |bounds, _, cx| {
+                                let width = bounds.size.width.0.round() as u32;
+                                let height = bounds.size.height.0.round() as u32;
+                                let _ = this.update(cx, |panel, cx| {
+                                    panel.on_resized(width, height, cx);
+                                });
+                            },
+                            |_, _, _, _| {},
+                        )
+                        .absolute()
+                        .size_full(),
+                    )
+                    .map(|el| match &self.shown_frame {
+                        Some(frame) => el.child(
+                            img(frame.clone())
+                                .size_full()
+                                .object_fit(ObjectFit::Contain),
+                        ),
+                        None => el.child(
+                            with_default_font(div())
+                                .size_full()
+                                .flex()
+                                .items_center()
+                                .justify_center()
+                                .text_color(text_muted())
+                                .text_size(px(12.0))
+                                .child("Waiting for viewer..."),
+                        ),
+                    }),
+            )
+    }
+}
+
+impl Drop for ViewerPanel
+{
+    fn drop(&mut self)
+    {
+        self.closed.store(true, Ordering::Release);
+    }
+}
+
+// ====================
+// Frame stream.
+// ====================
+
+/// Receive frames from the embedded viewer, reconnecting whenever it restarts, until the
+/// panel is closed.
+fn frame_receiver_main(
+    latest_frame: Arc<Mutex<Option<Arc<RenderImage>>>>,
+    frame_ready: Sender<()>,
+    closed: Arc<AtomicBool>,
+)
+{
+    while !closed.load(Ordering::Acquire) {
+        let stream = crate::common::FRAME_PORTS
+            .iter()
+            .find_map(|&port| TcpStream::connect(("127.0.0.1", port)).ok());
+
+        if let Some(stream) = stream {
+            debug!("Connected to viewer frame stream");
+            let mut reader = BufReader::new(stream);
+            while let Ok(frame) = read_frame(&mut reader) {
+                if closed.load(Ordering::Acquire) {
+                    return;
+                }
+                if let Ok(mut slot) = latest_frame.lock() {
+                    *slot = Some(frame);
+                }
+                // A pending notification already covers this frame.
+                let _ = frame_ready.try_send(());
+            }
+            debug!("Viewer frame stream closed");
+        }
+
+        std::thread::sleep(RECONNECT_DELAY);
+    }
+    debug!("Viewer frame thread exited");
+}
+
+fn read_frame(reader: &mut impl Read) -> std::io::Result<Arc<RenderImage>>
+{
+    let mut header = [0u8; 8];
+    reader.read_exact(&mut header)?;
+    let width = u32::from_le_bytes([header[0], header[1], header[2], header[3]]);
+    let height = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+    if width > MAX_FRAME_DIMENSION || height > MAX_FRAME_DIMENSION {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("Frame size {}x{} exceeds the limit", width, height),
+        ));
+    }
+
+    let mut pixels = vec![0u8; width as usize * height as usize * 4];
+    reader.read_exact(&mut pixels)?;
+
+    // Frames arrive as BGRA, which is what RenderImage expects.
+    let buffer = image::RgbaImage::from_raw(width, height, pixels).ok_or_else(|| {
+        std::io::Error::new(std::io::ErrorKind::InvalidData, "Invalid frame size")
+    })?;
+    Ok(Arc::new(RenderImage::new(vec![image::Frame::new(buffer)])))
+}
@@ -0,0 +1,199 @@
+use std::path::PathBuf;
+
+// ====================
+// GPUI.
+// ====================
+use gpui::prelude::*;
+use gpui::{Context, Entity, EventEmitter, IntoElement, ParentElement, Styled, Window, div, px};
+
+// ====================
+// Editor.
+// ====================
+use crate::gui::inspectors::float::FloatInspector;
+use crate::gui::inspectors::integer::IntegerInspector;
+use crate::gui::inspectors::{Inspector, InspectorEvent};
+use crate::gui::primitives::button::{Button, ButtonVariant};
+use crate::gui::primitives::checkbox_input::{Checkbox, SizeVariant};
+use crate::gui::primitives::events::CheckboxEvent;
+use crate::gui::primitives::text_input::{SizeVariant as TextSizeVariant, TextInput};
+use crate::gui::styling::colors::*;
+use crate::gui::styling::fonts::*;
+
+const DEFAULT_PROFILE_FILE_NAME: &str = "profile.csv";
+/// Default length of a profile capture, in seconds.
+const DEFAULT_PROFILE_DURATION: f32 = 10.0;
+const DEFAULT_SPAWNER_SEED: i32 = 0;
+const DEFAULT_FIXED_TIMESTEP: f32 = 1.0 / 60.0;
+
+/// Event emitted by the viewer settings panel.
+#[derive(Debug, Clone)]
+pub enum ViewerSettingsEvent
+{
+    CaptureProfileRequested
+    {
+        path: PathBuf, duration: f32
+    },
+    /// Spawner seed and fixed timestep of the viewer, both `None` when fixed timestep mode
+    /// is off.
+    FixedTimestepChanged
+    {
+        spawner_seed:   Option<u32>,
+        fixed_timestep: Option<f32>,
+    },
+}
+
+impl EventEmitter<ViewerSettingsEvent> for ViewerSettingsPanel {}
+
+/// Profile capture and fixed timestep mode settings of the viewer.
+///
+/// Profiles are written to the path typed in, relative to the assets root unless absolute,
+/// for the duration set next to it. Changing the spawner seed or the timestep while fixed
+/// timestep mode is on applies them right away. The spawner seed fixes when particles are
+/// spawned, not the values `rand` expressions draw on the GPU.
+///
+pub struct ViewerSettingsPanel
+{
+    assets_root:      PathBuf,
+    profile_path:     Entity<TextInput>,
+    profile_duration: Entity<FloatInspector>,
+    profile_button:   Entity<Button>,
+    fixed_timestep:   Entity<Checkbox>,
+    spawner_seed:     Entity<IntegerInspector>,
+    timestep:         Entity<FloatInspector>,
+    _subscriptions:   Vec<gpui::Subscription>,
+}
+
+impl ViewerSettingsPanel
+{
+    pub fn new(cx: &mut Context<Self>, assets_root: PathBuf) -> Self
+    {
+        let default_path = assets_root.join(DEFAULT_PROFILE_FILE_NAME);
+        let profile_path = cx.new(|cx| {
+            TextInput::new(cx)
+                .with_content(default_path.display().to_string().into(), cx)
+                .with_placeholder("Profile CSV path")
+                .with_size_variant(TextSizeVariant::Small)
+                .with_full_width(true)
+        });
+        let profile_duration = cx.new(|cx| FloatInspector::new(cx, DEFAULT_PROFILE_DURATION));
+        let profile_button = cx.new(|_| {
+            Button::new("Profile")
+                .variant(ButtonVariant::Secondary)
+                .font_size("sm")
+        });
+        let fixed_timestep = cx.new(|cx| Checkbox::new(cx).with_size_variant(SizeVariant::Small));
+        let spawner_seed = cx.new(|cx| IntegerInspector::new(cx, DEFAULT_SPAWNER_SEED));
+        let timestep = cx.new(|cx| FloatInspector::new(cx, DEFAULT_FIXED_TIMESTEP));
+
+        let profile_subscription = cx.subscribe(&profile_button, |this, _button, _event, cx| {
+            this.on_profile_clicked(cx);
+        });
+        let fixed_timestep_subscription = cx.subscribe(
+            &fixed_timestep,
+            |this, _checkbox, event: &CheckboxEvent, cx| match event {
+                CheckboxEvent::Changed(_) => this.emit_fixed_timestep(cx),
+            },
+        );
+        let seed_subscription = cx.subscribe(
+            &spawner_seed,
+            |this, _seed, _event: &InspectorEvent<i32>, cx| {
+                this.emit_fixed_timestep(cx);
+            },
+        );
+        let timestep_subscription = cx.subscribe(
+            &timestep,
+            |this, _timestep, _event: &InspectorEvent<f32>, cx| {
+                this.emit_fixed_timestep(cx);
+            },
+        );
+
+        Self {
+            assets_root,
+            profile_path,
+            profile_duration,
+            profile_button,
+            fixed_timestep,
+            spawner_seed,
+            timestep,
+            _subscriptions: vec![
+                profile_subscription,
+                fixed_timestep_subscription,
+                seed_subscription,
+                timestep_subscription,
+            ],
+        }
+    }
+
+    // ====================
+    // Event handlers.
+    // ====================
+
+    fn on_profile_clicked(&mut self, cx: &mut Context<Self>)
+    {
+        let path = self.profile_path.read(cx).content.trim().to_string();
+        if path.is_empty() {
+            return;
+        }
+        cx.emit(ViewerSettingsEvent::CaptureProfileRequested {
+            path:     self.assets_root.join(path),
+            duration: self.profile_duration.read(cx).get_value(cx),
+        });
+    }
+
+    fn emit_fixed_timestep(&mut self, cx: &mut Context<Self>)
+    {
+        let checked = self.fixed_timestep.read(cx).is_checked();
+        let seed = self.spawner_seed.read(cx).get_value(cx).max(0) as u32;
+        let timestep = self.timestep.read(cx).get_value(cx);
+        cx.emit(ViewerSettingsEvent::FixedTimestepChanged {
+            spawner_seed:   checked.then_some(seed),
+            fixed_timestep: (checked && timestep > 0.0).then_some(timestep),
+        });
+    }
+}
+
+impl Render for ViewerSettingsPanel
+{
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement
+    {
+        let label = |text: &'static str| {
+            with_default_font(div())
+                .text_color(text_secondary())
+                .text_size(px(11.0))
+                .child(text)
+        };
+        div()
+            .id("viewer-settings")
+            .w_full()
+            .flex()
+            .flex_col()
+            .gap_1()
+            .px(px(12.0))
+            .py(px(4.0))
+            .bg(background_primary())
+            .border_b_1()
+            .border_color(border_separator())
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .child(label("Profile to"))
+                    .child(div().flex_1().child(self.profile_path.clone()))
+                    .child(label("for (s)"))
+                    .child(div().w(px(60.0)).child(self.profile_duration.clone()))
+                    .child(self.profile_button.clone()),
+            )
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .child(self.fixed_timestep.clone())
+                    .child(label("Fixed timestep (s)"))
+                    .child(div().w(px(80.0)).child(self.timestep.clone()))
+                    .child(label("Spawner seed"))
+                    .child(div().w(px(60.0)).child(self.spawner_seed.clone())),
+            )
+    }
+}
@@ -6,4 +6,5 @@ pub mod common
 {
     pub const VIEWER_FONT: &str = "Roboto.ttf";
     pub const SERVER_PORTS: &[u16] = &[8080, 8081, 8082, 8083, 8084];
+    pub const FRAME_PORTS: &[u16] = &[8090, 8091, 8092, 8093, 8094];
 }
@@ -0,0 +1,10 @@
+pub mod xeval;
+pub mod xexpr;
+pub mod xgraph;
+pub mod xop;
+pub mod xval;
+
+pub use xeval::{XEnv, XEvalError};
+pub use xexpr::XExpr;
+pub use xop::{XBinaryOp, XBuiltInOp, XTernaryOp, XUnaryOp};
+pub use xval::{XExprReturnType, XValue};
@@ -0,0 +1,510 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::shared::expr::xexpr::XExpr;
+use crate::shared::expr::xop::{XBinaryOp, XBuiltInOp, XTernaryOp, XUnaryOp};
+use crate::shared::expr::xval::XValue;
+
+/// Uniform samples summed to approximate a normal distribution, as in the Hanabi lowering.
+pub const NORMAL_SAMPLES: usize = 4;
+/// Scale giving the centered sum of `NORMAL_SAMPLES` uniform samples unit variance.
+pub const NORMAL_SCALE: f32 = 1.732_050_8; // sqrt(12 / NORMAL_SAMPLES)
+
+// ====================
+// Evaluation errors.
+// ====================
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum XEvalError
+{
+    UnknownAttribute(String),
+    UnknownProperty(String),
+    UnknownVariable(String),
+    /// Operands of the expression do not have values its operator accepts.
+    InvalidOperands(String),
+}
+
+impl fmt::Display for XEvalError
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        match self {
+            XEvalError::UnknownAttribute(name) => write!(f, "Unknown attribute: '{}'", name),
+            XEvalError::UnknownProperty(name) => write!(f, "Unknown property: '{}'", name),
+            XEvalError::UnknownVariable(name) => write!(f, "Unknown variable: '{}'", name),
+            XEvalError::InvalidOperands(expr) => write!(f, "Invalid operands in '{}'", expr),
+        }
+    }
+}
+
+impl std::error::Error for XEvalError {}
+
+// ====================
+// Environment.
+// ====================
+
+/// Values of attributes, properties and builtins for evaluating expressions on the CPU.
+///
+/// Random builtins and functions draw from a seeded generator, so an evaluation can be
+/// repeated with the same results.
+///
+#[derive(Debug, Clone)]
+pub struct XEnv
+{
+    pub attributes:   HashMap<String, XValue>,
+    pub props:        HashMap<String, XValue>,
+    pub time:         f32,
+    pub delta_time:   f32,
+    pub alpha_cutoff: f32,
+    pub particle_id:  f32,
+    rng_state:        u64,
+}
+
+impl Default for XEnv
+{
+    fn default() -> Self
+    {
+        Self::new()
+    }
+}
+
+impl XEnv
+{
+    /// Create environment at time zero, with no attributes or properties.
+    ///
+    pub fn new() -> Self
+    {
+        Self {
+            attributes:   HashMap::new(),
+            props:        HashMap::new(),
+            time:         0.0,
+            delta_time:   1.0 / 60.0,
+            alpha_cutoff: 0.5,
+            particle_id:  0.0,
+            rng_state:    0,
+        }
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self
+    {
+        self.rng_state = seed;
+        self
+    }
+
+    pub fn with_time(mut self, time: f32) -> Self
+    {
+        self.time = time;
+        self
+    }
+
+    pub fn with_attribute(mut self, name: impl Into<String>, value: impl Into<XValue>) -> Self
+    {
+        self.attributes.insert(name.into(), value.into());
+        self
+    }
+
+    pub fn with_prop(mut self, name: impl Into<String>, value: impl Into<XValue>) -> Self
+    {
+        self.props.insert(name.into(), value.into());
+        self
+    }
+
+    /// Next random value in `[0, 1)` (SplitMix64).
+    ///
+    pub fn rand(&mut self) -> f32
+    {
+        self.rng_state = self.rng_state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.rng_state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        (z >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// Random value between `low` and `high`, per component.
+    ///
+    fn uniform(&mut self, low: &XValue, high: &XValue) -> Option<XValue>
+    {
+        let low = low.float_components()?;
+        let high = high.float_components()?;
+        if low.len() != high.len() {
+            return None;
+        }
+        let values: Vec<f32> = low
+            .iter()
+            .zip(&high)
+            .map(|(l, h)| l + (h - l) * self.rand())
+            .collect();
+        XValue::from_components(&values)
+    }
+
+    /// Normally distributed value, per component, with a scalar or per component deviation.
+    ///
+    /// Approximated by the centered and scaled sum of `NORMAL_SAMPLES` uniform values, the
+    /// same distribution as the Hanabi lowering.
+    ///
+    fn normal(&mut self, mean: &XValue, sd: &XValue) -> Option<XValue>
+    {
+        let mean = mean.float_components()?;
+        let sd = sd.float_components()?;
+        if sd.len() != mean.len() && sd.len() != 1 {
+            return None;
+        }
+        let values: Vec<f32> = mean
+            .iter()
+            .enumerate()
+            .map(|(i, m)| {
+                let sum: f32 = (0..NORMAL_SAMPLES).map(|_| self.rand()).sum();
+                let z = (sum - NORMAL_SAMPLES as f32 / 2.0) * NORMAL_SCALE;
+                m + sd[i.min(sd.len() - 1)] * z
+            })
+            .collect();
+        XValue::from_components(&values)
+    }
+}
+
+// ====================
+// Evaluation.
+// ====================
+
+impl XExpr
+{
+    /// Evaluate an expression on the CPU.
+    ///
+    pub fn eval(&self, env: &mut XEnv) -> Result<XValue, XEvalError>
+    {
+        self.eval_in(env, &HashMap::new())
+    }
+
+    fn eval_in(
+        &self,
+        env: &mut XEnv,
+        locals: &HashMap<String, XValue>,
+    ) -> Result<XValue, XEvalError>
+    {
+        let invalid = || XEvalError::InvalidOperands(self.to_string());
+
+        match self {
+            XExpr::Lit(value) => Ok(value.clone()),
+            XExpr::Attr(name) => env
+                .attributes
+                .get(name)
+                .cloned()
+                .ok_or_else(|| XEvalError::UnknownAttribute(name.clone())),
+            XExpr::Prop(name) => env
+                .props
+                .get(name)
+                .cloned()
+                .ok_or_else(|| XEvalError::UnknownProperty(name.clone())),
+            XExpr::Var(name) => locals
+                .get(name)
+                .cloned()
+                .ok_or_else(|| XEvalError::UnknownVariable(name.clone())),
+            XExpr::Let { name, value, body } => {
+                let value = value.eval_in(env, locals)?;
+                let mut locals = locals.clone();
+                locals.insert(name.clone(), value);
+                body.eval_in(env, &locals)
+            }
+            XExpr::BuiltIn(op) => Ok(match op {
+                XBuiltInOp::Time => XValue::Float(env.time),
+                XBuiltInOp::DeltaTime => XValue::Float(env.delta_time),
+                XBuiltInOp::Rand => XValue::Float(env.rand()),
+                XBuiltInOp::RandVec2 => XValue::Vec2(env.rand(), env.rand()),
+                XBuiltInOp::RandVec3 => XValue::Vec3(env.rand(), env.rand(), env.rand()),
+                XBuiltInOp::AlphaCutoff => XValue::Float(env.alpha_cutoff),
+                XBuiltInOp::ParticleId => XValue::Float(env.particle_id),
+            }),
+            XExpr::Unary { op, expr } => match (op, &**expr) {
+                // Vector comparisons are component-wise, and only valid when reduced here.
+                (
+                    XUnaryOp::All | XUnaryOp::Any,
+                    XExpr::Binary {
+                        left,
+                        op: cmp,
+                        right,
+                    },
+                ) if cmp.is_comparison() => {
+                    let left = left.eval_in(env, locals)?;
+                    let right = right.eval_in(env, locals)?;
+                    let results = XValue::compare(*cmp, &left, &right).ok_or_else(invalid)?;
+                    Ok(XValue::Bool(match op {
+                        XUnaryOp::All => results.iter().all(|r| *r),
+                        _ => results.iter().any(|r| *r),
+                    }))
+                }
+                _ => expr.eval_in(env, locals)?.unary(*op).ok_or_else(invalid),
+            },
+            XExpr::Binary { left, op, right } => {
+                let left = left.eval_in(env, locals)?;
+                let right = right.eval_in(env, locals)?;
+                let value = match op {
+                    XBinaryOp::Uniform => env.uniform(&left, &right),
+                    XBinaryOp::Normal => env.normal(&left, &right),
+                    _ => XValue::binary(*op, &left, &right),
+                };
+                value.ok_or_else(invalid)
+            }
+            XExpr::Ternary {
+                op,
+                first,
+                second,
+                third,
+            } => {
+                let first = first.eval_in(env, locals)?;
+                let second = second.eval_in(env, locals)?;
+                let third = third.eval_in(env, locals)?;
+                XValue::ternary(*op, &first, &second, &third).ok_or_else(invalid)
+            }
+            XExpr::Swizzle { expr, components } => expr
+                .eval_in(env, locals)?
+                .swizzle(components)
+                .ok_or_else(invalid),
+        }
+    }
+}
+
+// ====================
+// Value operations.
+// ====================
+
+impl XValue
+{
+    /// Apply a unary operator, or `None` if it does not accept this value.
+    ///
+    pub fn unary(&self, op: XUnaryOp) -> Option<XValue>
+    {
+        match (op, self) {
+            // Integers wrap around on overflow, as on the GPU.
+            (XUnaryOp::Neg, XValue::Integer(i)) => Some(XValue::Integer(i.wrapping_neg())),
+            (XUnaryOp::Abs, XValue::Integer(i)) => Some(XValue::Integer(i.wrapping_abs())),
+            (XUnaryOp::All | XUnaryOp::Any, XValue::Bool(b)) => Some(XValue::Bool(*b)),
+            // Float vectors are reduced on their non-zero components.
+            (XUnaryOp::All, _) => {
+                let values = self.float_components().filter(|values| values.len() > 1)?;
+                Some(XValue::Bool(values.iter().all(|v| *v != 0.0)))
+            }
+            (XUnaryOp::Any, _) => {
+                let values = self.float_components().filter(|values| values.len() > 1)?;
+                Some(XValue::Bool(values.iter().any(|v| *v != 0.0)))
+            }
+            (XUnaryOp::Length, _) => {
+                let values = self.float_components()?;
+                XValue::from_components(&[length(&values)])
+            }
+            (XUnaryOp::Norm, _) => {
+                let values = self.float_components().filter(|values| values.len() > 1)?;
+                let length = length(&values);
+                XValue::from_components(&values.iter().map(|v| v / length).collect::<Vec<_>>())
+            }
+            (op, _) => {
+                let f: fn(f32) -> f32 = match op {
+                    XUnaryOp::Neg => |v| -v,
+                    XUnaryOp::Abs => f32::abs,
+                    XUnaryOp::Sin => f32::sin,
+                    XUnaryOp::Cos => f32::cos,
+                    XUnaryOp::Tan => f32::tan,
+                    XUnaryOp::Sign => |v| if v == 0.0 { 0.0 } else { v.signum() },
+                    _ => return None,
+                };
+                let values = self.float_components()?;
+                XValue::from_components(&values.into_iter().map(f).collect::<Vec<_>>())
+            }
+        }
+    }
+
+    /// Apply a binary operator, or `None` if it does not accept these values.
+    ///
+    /// Random operators need an [`XEnv`] and are not applied.
+    ///
+    pub fn binary(op: XBinaryOp, left: &XValue, right: &XValue) -> Option<XValue>
+    {
+        // Only scalar comparisons give a value, vector ones must be reduced by `all`/`any`.
+        if op.is_comparison() {
+            return match Self::compare(op, left, right)?.as_slice() {
+                [result] => Some(XValue::Bool(*result)),
+                _ => None,
+            };
+        }
+
+        // Integers wrap around on overflow and divide by zero to the dividend, as in WGSL.
+        if let (XValue::Integer(l), XValue::Integer(r)) = (left, right) {
+            return match op {
+                XBinaryOp::Add => Some(XValue::Integer(l.wrapping_add(*r))),
+                XBinaryOp::Sub => Some(XValue::Integer(l.wrapping_sub(*r))),
+                XBinaryOp::Mul => Some(XValue::Integer(l.wrapping_mul(*r))),
+                XBinaryOp::Div => Some(XValue::Integer(l.checked_div(*r).unwrap_or(*l))),
+                XBinaryOp::Min => Some(XValue::Integer(*l.min(r))),
+                XBinaryOp::Max => Some(XValue::Integer(*l.max(r))),
+                _ => None,
+            };
+        }
+
+        let l = left.float_components()?;
+        let r = right.float_components()?;
+        match op {
+            XBinaryOp::Add => zip(&l, &r, true, |a, b| a + b),
+            XBinaryOp::Sub => zip(&l, &r, true, |a, b| a - b),
+            XBinaryOp::Mul => zip(&l, &r, true, |a, b| a * b),
+            XBinaryOp::Div => zip(&l, &r, true, |a, b| a / b),
+            XBinaryOp::Min => zip(&l, &r, true, f32::min),
+            XBinaryOp::Max => zip(&l, &r, true, f32::max),
+            XBinaryOp::Step => zip(&l, &r, false, |edge, x| if x < edge { 0.0 } else { 1.0 }),
+            XBinaryOp::Distance => {
+                let delta = zip(&l, &r, false, |a, b| a - b)?.float_components()?;
+                XValue::from_components(&[length(&delta)])
+            }
+            XBinaryOp::Dot if l.len() == r.len() && l.len() > 1 => {
+                XValue::from_components(&[l.iter().zip(&r).map(|(a, b)| a * b).sum()])
+            }
+            XBinaryOp::Cross => match (l.as_slice(), r.as_slice()) {
+                ([ax, ay, az], [bx, by, bz]) => XValue::from_components(&[
+                    ay * bz - az * by,
+                    az * bx - ax * bz,
+                    ax * by - ay * bx,
+                ]),
+                _ => None,
+            },
+            XBinaryOp::Vec2 | XBinaryOp::Vec3 | XBinaryOp::Vec4 => {
+                let size = match op {
+                    XBinaryOp::Vec2 => 2,
+                    XBinaryOp::Vec3 => 3,
+                    _ => 4,
+                };
+                let values: Vec<f32> = l.iter().chain(&r).copied().collect();
+                XValue::from_components(&values).filter(|_| values.len() == size)
+            }
+            XBinaryOp::Lt
+            | XBinaryOp::Lte
+            | XBinaryOp::Ge
+            | XBinaryOp::Gte
+            | XBinaryOp::Eq
+            | XBinaryOp::Neq
+            | XBinaryOp::Dot
+            | XBinaryOp::Uniform
+            | XBinaryOp::Normal => None,
+        }
+    }
+
+    /// Compare two scalars of the same type, or two vectors component-wise.
+    ///
+    pub fn compare(op: XBinaryOp, left: &XValue, right: &XValue) -> Option<Vec<bool>>
+    {
+        let cmp = |a: f64, b: f64| match op {
+            XBinaryOp::Lt => Some(a < b),
+            XBinaryOp::Lte => Some(a <= b),
+            XBinaryOp::Ge => Some(a > b),
+            XBinaryOp::Gte => Some(a >= b),
+            XBinaryOp::Eq => Some(a == b),
+            XBinaryOp::Neq => Some(a != b),
+            _ => None,
+        };
+        if let (XValue::Integer(l), XValue::Integer(r)) = (left, right) {
+            return Some(vec![cmp(*l as f64, *r as f64)?]);
+        }
+        let l = left.float_components()?;
+        let r = right.float_components()?;
+        if l.len() != r.len() {
+            return None;
+        }
+        l.iter()
+            .zip(&r)
+            .map(|(a, b)| cmp(*a as f64, *b as f64))
+            .collect()
+    }
+
+    /// Apply a ternary operator, or `None` if it does not accept these values.
+    ///
+    pub fn ternary(
+        op: XTernaryOp,
+        first: &XValue,
+        second: &XValue,
+        third: &XValue,
+    ) -> Option<XValue>
+    {
+        let a = first.float_components()?;
+        let b = second.float_components()?;
+        let c = third.float_components()?;
+        match op {
+            // mix(a, b, t) = a + (b - a) * t
+            XTernaryOp::Mix => {
+                let delta = zip(&b, &a, false, |b, a| b - a)?.float_components()?;
+                let scaled = zip(&delta, &c, true, |d, t| d * t)?.float_components()?;
+                zip(&a, &scaled, false, |a, s| a + s)
+            }
+            // clamp(x, low, high) = min(max(x, low), high)
+            XTernaryOp::Clamp => {
+                let low_bounded = zip(&a, &b, false, f32::max)?.float_components()?;
+                zip(&low_bounded, &c, false, f32::min)
+            }
+        }
+    }
+
+    /// Pick components of a vector value (`xy`, `zyx`, ...).
+    ///
+    pub fn swizzle(&self, components: &str) -> Option<XValue>
+    {
+        let values = self.float_components().filter(|values| values.len() > 1)?;
+        let picked: Option<Vec<f32>> = XExpr::swizzle_indices(components)?
+            .into_iter()
+            .map(|i| values.get(i).copied())
+            .collect();
+        XValue::from_components(&picked?)
+    }
+
+    /// Components of a float or float vector value.
+    ///
+    pub fn float_components(&self) -> Option<Vec<f32>>
+    {
+        match self {
+            XValue::Float(f) => Some(vec![*f]),
+            XValue::Vec2(x, y) => Some(vec![*x, *y]),
+            XValue::Vec3(x, y, z) => Some(vec![*x, *y, *z]),
+            XValue::Vec4(x, y, z, w) => Some(vec![*x, *y, *z, *w]),
+            XValue::Integer(_) | XValue::Bool(_) => None,
+        }
+    }
+
+    /// Float or float vector value with 1 to 4 components.
+    ///
+    pub fn from_components(values: &[f32]) -> Option<XValue>
+    {
+        match *values {
+            [f] => Some(XValue::Float(f)),
+            [x, y] => Some(XValue::Vec2(x, y)),
+            [x, y, z] => Some(XValue::Vec3(x, y, z)),
+            [x, y, z, w] => Some(XValue::Vec4(x, y, z, w)),
+            _ => None,
+        }
+    }
+
+    /// Whether no component is infinite or NaN.
+    ///
+    pub fn is_finite(&self) -> bool
+    {
+        self.float_components()
+            .unwrap_or_default()
+            .iter()
+            .all(|v| v.is_finite())
+    }
+}
+
+fn length(values: &[f32]) -> f32
+{
+    values.iter().map(|v| v * v).sum::<f32>().sqrt()
+}
+
+/// Apply `f` component-wise to operands of the same width, or a scalar and a vector if
+/// `broadcast` is set.
+///
+fn zip(left: &[f32], right: &[f32], broadcast: bool, f: impl Fn(f32, f32) -> f32)
+-> Option<XValue>
+{
+    let values: Vec<f32> = match (left.len(), right.len()) {
+        (l, r) if l == r => left.iter().zip(right).map(|(a, b)| f(*a, *b)).collect(),
+        (1, _) if broadcast => right.iter().map(|b| f(left[0], *b)).collect(),
+        (_, 1) if broadcast => left.iter().map(|a| f(*a, right[0])).collect(),
+        _ => return None,
+    };
+    XValue::from_components(&values)
+}
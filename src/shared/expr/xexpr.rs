@@ -0,0 +1,424 @@
+use std::fmt;
+
+use crate::shared::expr::{XBinaryOp, XBuiltInOp, XTernaryOp, XUnaryOp, XValue};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum XExpr
+{
+    Lit(XValue),
+    Attr(String),
+    Prop(String),
+    BuiltIn(XBuiltInOp),
+    Unary
+    {
+        op:   XUnaryOp,
+        expr: Box<XExpr>,
+    },
+    Binary
+    {
+        left:  Box<XExpr>,
+        op:    XBinaryOp,
+        right: Box<XExpr>,
+    },
+    Ternary
+    {
+        op:     XTernaryOp,
+        first:  Box<XExpr>,
+        second: Box<XExpr>,
+        third:  Box<XExpr>,
+    },
+    Swizzle
+    {
+        expr:       Box<XExpr>,
+        components: String,
+    },
+    /// Local binding `let name = value; body`, referenced in `body` as `Var(name)`.
+    Let
+    {
+        name:  String,
+        value: Box<XExpr>,
+        body:  Box<XExpr>,
+    },
+    Var(String),
+}
+
+impl XExpr
+{
+    /// Create literal expression.
+    ///
+    pub fn lit<V: Into<XValue>>(value: V) -> Self
+    {
+        XExpr::Lit(value.into())
+    }
+
+    /// Create attribute expression.
+    ///
+    pub fn attr(name: impl Into<String>) -> Self
+    {
+        XExpr::Attr(name.into())
+    }
+
+    /// Create property expression.
+    ///
+    pub fn prop(name: impl Into<String>) -> Self
+    {
+        XExpr::Prop(name.into())
+    }
+
+    /// Create builtin expression.
+    ///
+    pub fn builtin(op: XBuiltInOp) -> Self
+    {
+        XExpr::BuiltIn(op)
+    }
+
+    /// Create unary expression.
+    ///
+    pub fn unary(op: XUnaryOp, expr: impl Into<Box<XExpr>>) -> Self
+    {
+        XExpr::Unary {
+            op,
+            expr: expr.into(),
+        }
+    }
+
+    /// Create binary expression.
+    ///
+    pub fn binary(left: impl Into<Box<XExpr>>, op: XBinaryOp, right: impl Into<Box<XExpr>>)
+    -> Self
+    {
+        XExpr::Binary {
+            left: left.into(),
+            op,
+            right: right.into(),
+        }
+    }
+
+    /// Create ternary expression.
+    ///
+    pub fn ternary(
+        op: XTernaryOp,
+        first: impl Into<Box<XExpr>>,
+        second: impl Into<Box<XExpr>>,
+        third: impl Into<Box<XExpr>>,
+    ) -> Self
+    {
+        XExpr::Ternary {
+            op,
+            first: first.into(),
+            second: second.into(),
+            third: third.into(),
+        }
+    }
+
+    /// Create swizzle expression (`v.x`, `v.xy`, `v.zyx`).
+    ///
+    pub fn swizzle(expr: impl Into<Box<XExpr>>, components: impl Into<String>) -> Self
+    {
+        XExpr::Swizzle {
+            expr:       expr.into(),
+            components: components.into(),
+        }
+    }
+
+    /// Create local binding expression (`let name = value; body`).
+    ///
+    pub fn bind(
+        name: impl Into<String>,
+        value: impl Into<Box<XExpr>>,
+        body: impl Into<Box<XExpr>>,
+    ) -> Self
+    {
+        XExpr::Let {
+            name:  name.into(),
+            value: value.into(),
+            body:  body.into(),
+        }
+    }
+
+    /// Create reference to a local binding.
+    ///
+    pub fn var(name: impl Into<String>) -> Self
+    {
+        XExpr::Var(name.into())
+    }
+
+    /// Component indices of a swizzle, or `None` if it is not one (1 to 4 of `x`, `y`, `z`, `w`).
+    ///
+    pub fn swizzle_indices(components: &str) -> Option<Vec<usize>>
+    {
+        if components.is_empty() || components.len() > 4 {
+            return None;
+        }
+        components
+            .chars()
+            .map(|c| match c {
+                'x' => Some(0),
+                'y' => Some(1),
+                'z' => Some(2),
+                'w' => Some(3),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Create addition expression.
+    ///
+    pub fn add(self, other: Self) -> Self
+    {
+        Self::binary(self, XBinaryOp::Add, other)
+    }
+
+    /// Create subtraction expression.
+    ///
+    pub fn sub(self, other: Self) -> Self
+    {
+        Self::binary(self, XBinaryOp::Sub, other)
+    }
+
+    /// Create multiplication expression.
+    ///
+    pub fn mul(self, other: Self) -> Self
+    {
+        Self::binary(self, XBinaryOp::Mul, other)
+    }
+
+    /// Create division expression.
+    ///
+    pub fn div(self, other: Self) -> Self
+    {
+        Self::binary(self, XBinaryOp::Div, other)
+    }
+
+    /// Create sine expression.
+    ///
+    pub fn sin(expr: impl Into<Box<XExpr>>) -> Self
+    {
+        Self::unary(XUnaryOp::Sin, expr)
+    }
+
+    /// Create cosine expression.
+    ///
+    pub fn cos(expr: impl Into<Box<XExpr>>) -> Self
+    {
+        Self::unary(XUnaryOp::Cos, expr)
+    }
+
+    /// Create absolute value expression.
+    ///
+    pub fn abs(expr: impl Into<Box<XExpr>>) -> Self
+    {
+        Self::unary(XUnaryOp::Abs, expr)
+    }
+
+    /// Create normalize expression.
+    ///
+    pub fn norm(expr: impl Into<Box<XExpr>>) -> Self
+    {
+        Self::unary(XUnaryOp::Norm, expr)
+    }
+
+    /// Format expression with optional parentheses control
+    fn fmt_with_parens(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        parent_precedence: Option<u8>,
+    ) -> fmt::Result
+    {
+        match self {
+            XExpr::Lit(value) => write!(f, "{}", value.to_literal()),
+            XExpr::Attr(name) => write!(f, "attr({})", name),
+            XExpr::Prop(name) => write!(f, "prop({})", name),
+            XExpr::BuiltIn(op) => write!(f, "{}", op),
+            XExpr::Var(name) => write!(f, "{}", name),
+
+            XExpr::Let { name, value, body } => {
+                // Bindings extend to the end of the expression, so nested ones are wrapped.
+                let needs_parens = parent_precedence.is_some();
+                if needs_parens {
+                    write!(f, "(")?;
+                }
+                write!(f, "let {} = ", name)?;
+                value.fmt_with_parens(f, Some(0))?;
+                write!(f, "; ")?;
+                body.fmt_with_parens(f, None)?;
+                if needs_parens {
+                    write!(f, ")")?;
+                }
+                Ok(())
+            }
+
+            XExpr::Unary { op, expr } => {
+                if matches!(op, XUnaryOp::Neg) {
+                    write!(f, "-")?;
+                    expr.fmt_with_parens(f, Some(100)) // High precedence for unary operators
+                } else {
+                    write!(f, "{}(", op)?;
+                    expr.fmt_with_parens(f, None)?;
+                    write!(f, ")")
+                }
+            }
+
+            XExpr::Binary {
+                op: XBinaryOp::Vec3 | XBinaryOp::Vec4,
+                ..
+            } if self.vector_components().is_some() => {
+                let components = self.vector_components().unwrap_or_default();
+                write!(f, "vec{}(", components.len())?;
+                for (i, component) in components.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    component.fmt_with_parens(f, None)?;
+                }
+                write!(f, ")")
+            }
+
+            XExpr::Binary { left, op, right } => {
+                if matches!(
+                    op,
+                    XBinaryOp::Dot
+                        | XBinaryOp::Cross
+                        | XBinaryOp::Min
+                        | XBinaryOp::Max
+                        | XBinaryOp::Vec2
+                        | XBinaryOp::Vec3
+                        | XBinaryOp::Vec4
+                        | XBinaryOp::Step
+                        | XBinaryOp::Distance
+                        | XBinaryOp::Uniform
+                        | XBinaryOp::Normal
+                ) {
+                    write!(f, "{}(", op)?;
+                    left.fmt_with_parens(f, None)?;
+                    write!(f, ", ")?;
+                    right.fmt_with_parens(f, None)?;
+                    write!(f, ")")
+                } else {
+                    let current_precedence = self.get_precedence();
+                    let needs_parens = if let Some(parent_prec) = parent_precedence {
+                        current_precedence < parent_prec
+                    } else {
+                        false // No parentheses needed at top level
+                    };
+
+                    if needs_parens {
+                        write!(f, "(")?;
+                    }
+                    left.fmt_with_parens(f, Some(current_precedence))?;
+                    write!(f, " {} ", op)?;
+                    // Operators associate to the left, so a right operand of the same
+                    // precedence keeps its parentheses, as in `a - (b - c)`.
+                    right.fmt_with_parens(f, Some(current_precedence + 1))?;
+                    if needs_parens {
+                        write!(f, ")")?;
+                    }
+                    Ok(())
+                }
+            }
+
+            XExpr::Ternary {
+                op,
+                first,
+                second,
+                third,
+            } => {
+                write!(f, "{}(", op)?;
+                first.fmt_with_parens(f, None)?;
+                write!(f, ", ")?;
+                second.fmt_with_parens(f, None)?;
+                write!(f, ", ")?;
+                third.fmt_with_parens(f, None)?;
+                write!(f, ")")
+            }
+
+            XExpr::Swizzle { expr, components } => {
+                let needs_parens = match &**expr {
+                    XExpr::Unary { op, .. } => matches!(op, XUnaryOp::Neg),
+                    inner => inner.get_precedence() < 100,
+                };
+                if needs_parens {
+                    write!(f, "(")?;
+                    expr.fmt_with_parens(f, None)?;
+                    write!(f, ")")?;
+                } else {
+                    expr.fmt_with_parens(f, None)?;
+                }
+                write!(f, ".{}", components)
+            }
+        }
+    }
+
+    /// Components of a nested vector constructor, e.g. `x, y, z` for `vec3(x, y, z)`.
+    ///
+    pub(crate) fn vector_components(&self) -> Option<Vec<&XExpr>>
+    {
+        let XExpr::Binary { left, op, right } = self else {
+            return None;
+        };
+        let mut components = match op {
+            XBinaryOp::Vec2 => vec![&**left],
+            XBinaryOp::Vec3 | XBinaryOp::Vec4 => {
+                let inner_op = if *op == XBinaryOp::Vec3 {
+                    XBinaryOp::Vec2
+                } else {
+                    XBinaryOp::Vec3
+                };
+                match &**left {
+                    XExpr::Binary { op: nested, .. } if *nested == inner_op => {
+                        left.vector_components()?
+                    }
+                    _ => return None,
+                }
+            }
+            _ => return None,
+        };
+        components.push(&**right);
+        Some(components)
+    }
+
+    /// Get the precedence of this expression (higher number = higher precedence)
+    pub(crate) fn get_precedence(&self) -> u8
+    {
+        match self {
+            XExpr::Lit(_)
+            | XExpr::Attr(_)
+            | XExpr::Prop(_)
+            | XExpr::BuiltIn(_)
+            | XExpr::Var(_)
+            | XExpr::Ternary { .. }
+            | XExpr::Swizzle { .. } => 100, // Highest precedence
+            XExpr::Unary { .. } => 90, // High precedence for unary operators
+            XExpr::Let { .. } => 0,
+            XExpr::Binary { op, .. } => match op {
+                XBinaryOp::Mul | XBinaryOp::Div => 3,
+                XBinaryOp::Add | XBinaryOp::Sub => 2,
+                XBinaryOp::Lt
+                | XBinaryOp::Lte
+                | XBinaryOp::Ge
+                | XBinaryOp::Gte
+                | XBinaryOp::Eq
+                | XBinaryOp::Neq => 1,
+                XBinaryOp::Dot
+                | XBinaryOp::Cross
+                | XBinaryOp::Min
+                | XBinaryOp::Max
+                | XBinaryOp::Vec2
+                | XBinaryOp::Vec3
+                | XBinaryOp::Vec4
+                | XBinaryOp::Step
+                | XBinaryOp::Distance
+                | XBinaryOp::Uniform
+                | XBinaryOp::Normal => 100, // Function-like, highest precedence
+            },
+        }
+    }
+}
+
+impl fmt::Display for XExpr
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        self.fmt_with_parens(f, None)
+    }
+}
@@ -0,0 +1,97 @@
+use bevy_hanabi::graph::{ScalarValue, Value};
+use bevy_hanabi::{
+    BinaryOperator,
+    BuiltInOperator,
+    Expr,
+    ExprHandle,
+    Module,
+    UnaryOperator,
+    ValueType,
+    VectorType,
+};
+
+use crate::shared::expr::{XBinaryOp, XBuiltInOp, XExpr, XUnaryOp, XValue};
+
+// ====================
+// Hanabi graphs.
+// ====================
+
+impl XExpr
+{
+    /// Read an expression of a Hanabi graph node by node, to evaluate it on the CPU.
+    ///
+    /// Unlike the editor's import, lowerings are not read back as the functions they come
+    /// from, and shared nodes are repeated. Returns `None` for missing handles and values
+    /// the editor has no type for.
+    ///
+    pub fn from_graph(module: &Module, handle: ExprHandle) -> Option<XExpr>
+    {
+        let expr = match module.get(handle)? {
+            Expr::Literal(lit) => XExpr::Lit(XValue::from_hanabi(&lit.value)?),
+            Expr::Attribute(attr) => XExpr::attr(attr.attr.name()),
+            Expr::Property(prop) => XExpr::prop(prop.property_name.clone()),
+            Expr::BuiltIn(builtin) => XExpr::builtin(match builtin.operator {
+                BuiltInOperator::Time => XBuiltInOp::Time,
+                BuiltInOperator::DeltaTime => XBuiltInOp::DeltaTime,
+                BuiltInOperator::Rand(ValueType::Vector(VectorType::VEC2F)) => XBuiltInOp::RandVec2,
+                BuiltInOperator::Rand(ValueType::Vector(VectorType::VEC3F)) => XBuiltInOp::RandVec3,
+                BuiltInOperator::Rand(_) => XBuiltInOp::Rand,
+            }),
+            Expr::Unary { op, expr } => {
+                let op = match op {
+                    UnaryOperator::Abs => XUnaryOp::Abs,
+                    UnaryOperator::All => XUnaryOp::All,
+                    UnaryOperator::Any => XUnaryOp::Any,
+                    UnaryOperator::Normalize => XUnaryOp::Norm,
+                    UnaryOperator::Cos => XUnaryOp::Cos,
+                    UnaryOperator::Sin => XUnaryOp::Sin,
+                };
+                XExpr::unary(op, Self::from_graph(module, *expr)?)
+            }
+            Expr::Binary { op, left, right } => {
+                let op = match op {
+                    BinaryOperator::Add => XBinaryOp::Add,
+                    BinaryOperator::Sub => XBinaryOp::Sub,
+                    BinaryOperator::Mul => XBinaryOp::Mul,
+                    BinaryOperator::Div => XBinaryOp::Div,
+                    BinaryOperator::LessThan => XBinaryOp::Lt,
+                    BinaryOperator::LessThanOrEqual => XBinaryOp::Lte,
+                    BinaryOperator::GreaterThan => XBinaryOp::Ge,
+                    BinaryOperator::GreaterThanOrEqual => XBinaryOp::Gte,
+                    BinaryOperator::Min => XBinaryOp::Min,
+                    BinaryOperator::Max => XBinaryOp::Max,
+                    BinaryOperator::Dot => XBinaryOp::Dot,
+                    BinaryOperator::Cross => XBinaryOp::Cross,
+                    BinaryOperator::UniformRand => XBinaryOp::Uniform,
+                };
+                XExpr::binary(
+                    Self::from_graph(module, *left)?,
+                    op,
+                    Self::from_graph(module, *right)?,
+                )
+            }
+        };
+        Some(expr)
+    }
+}
+
+impl XValue
+{
+    /// Value of a Hanabi literal or property, or `None` for types the editor has none for.
+    ///
+    pub fn from_hanabi(value: &Value) -> Option<XValue>
+    {
+        match value {
+            Value::Scalar(ScalarValue::Float(f)) => Some(XValue::Float(*f)),
+            Value::Scalar(ScalarValue::Int(i)) => Some(XValue::Integer(*i)),
+            Value::Scalar(ScalarValue::Bool(b)) => Some(XValue::Bool(*b)),
+            Value::Vector(vector) => match vector.vector_type() {
+                VectorType::VEC2F | VectorType::VEC3F | VectorType::VEC4F => {
+                    XValue::from_components(&vector.get_all::<f32>())
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
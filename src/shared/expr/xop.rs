@@ -10,6 +10,9 @@ pub enum XUnaryOp
     Neg,
     All,
     Any,
+    Sign,
+    Length,
+    Tan,
 }
 
 impl fmt::Display for XUnaryOp
@@ -24,6 +27,9 @@ impl fmt::Display for XUnaryOp
             XUnaryOp::Neg => write!(f, "-"),
             XUnaryOp::All => write!(f, "all"),
             XUnaryOp::Any => write!(f, "any"),
+            XUnaryOp::Sign => write!(f, "sign"),
+            XUnaryOp::Length => write!(f, "length"),
+            XUnaryOp::Tan => write!(f, "tan"),
         }
     }
 }
@@ -47,6 +53,29 @@ pub enum XBinaryOp
     Neq,
     Vec2,
     Vec3,
+    Vec4,
+    Step,
+    Distance,
+    Uniform,
+    Normal,
+}
+
+impl XBinaryOp
+{
+    /// Whether the operator compares its operands and returns a bool.
+    ///
+    pub fn is_comparison(&self) -> bool
+    {
+        matches!(
+            self,
+            XBinaryOp::Lt
+                | XBinaryOp::Lte
+                | XBinaryOp::Ge
+                | XBinaryOp::Gte
+                | XBinaryOp::Eq
+                | XBinaryOp::Neq
+        )
+    }
 }
 
 impl fmt::Display for XBinaryOp
@@ -70,6 +99,29 @@ impl fmt::Display for XBinaryOp
             XBinaryOp::Neq => write!(f, "!="),
             XBinaryOp::Vec2 => write!(f, "vec2"),
             XBinaryOp::Vec3 => write!(f, "vec3"),
+            XBinaryOp::Vec4 => write!(f, "vec4"),
+            XBinaryOp::Step => write!(f, "step"),
+            XBinaryOp::Distance => write!(f, "distance"),
+            XBinaryOp::Uniform => write!(f, "uniform"),
+            XBinaryOp::Normal => write!(f, "normal"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum XTernaryOp
+{
+    Mix,
+    Clamp,
+}
+
+impl fmt::Display for XTernaryOp
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        match self {
+            XTernaryOp::Mix => write!(f, "mix"),
+            XTernaryOp::Clamp => write!(f, "clamp"),
         }
     }
 }
@@ -80,6 +132,8 @@ pub enum XBuiltInOp
     Time,
     DeltaTime,
     Rand,
+    RandVec2,
+    RandVec3,
     AlphaCutoff,
     ParticleId,
 }
@@ -92,6 +146,8 @@ impl fmt::Display for XBuiltInOp
             XBuiltInOp::Time => write!(f, "time"),
             XBuiltInOp::DeltaTime => write!(f, "delta_time"),
             XBuiltInOp::Rand => write!(f, "rand"),
+            XBuiltInOp::RandVec2 => write!(f, "rand_vec2"),
+            XBuiltInOp::RandVec3 => write!(f, "rand_vec3"),
             XBuiltInOp::AlphaCutoff => write!(f, "alpha_cutoff"),
             XBuiltInOp::ParticleId => write!(f, "particle_id"),
         }
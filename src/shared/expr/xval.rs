@@ -7,6 +7,8 @@ pub enum XValue
     Integer(i32),
     Vec2(f32, f32),
     Vec3(f32, f32, f32),
+    Vec4(f32, f32, f32, f32),
+    Bool(bool),
 }
 
 impl XValue
@@ -38,6 +40,46 @@ impl XValue
     {
         XValue::Vec3(x, y, z)
     }
+
+    /// Create a 4D vector value.
+    ///
+    pub fn vec4(x: f32, y: f32, z: f32, w: f32) -> Self
+    {
+        XValue::Vec4(x, y, z, w)
+    }
+
+    /// Create a boolean value.
+    ///
+    pub fn bool(v: bool) -> Self
+    {
+        XValue::Bool(v)
+    }
+
+    /// Get the type of this value.
+    ///
+    pub fn get_type(&self) -> XExprReturnType
+    {
+        match self {
+            XValue::Float(_) => XExprReturnType::Float,
+            XValue::Integer(_) => XExprReturnType::Integer,
+            XValue::Vec2(..) => XExprReturnType::Vec2,
+            XValue::Vec3(..) => XExprReturnType::Vec3,
+            XValue::Vec4(..) => XExprReturnType::Vec4,
+            XValue::Bool(_) => XExprReturnType::Bool,
+        }
+    }
+
+    /// Text of the value as a literal of an expression. Negative scalars are wrapped,
+    /// as `-1.0` reads back as the negation of `1.0`.
+    ///
+    pub fn to_literal(&self) -> String
+    {
+        match self {
+            XValue::Float(v) if v.is_sign_negative() => format!("({})", self),
+            XValue::Integer(v) if *v < 0 => format!("({})", self),
+            _ => self.to_string(),
+        }
+    }
 }
 
 impl fmt::Display for XValue
@@ -84,6 +126,17 @@ impl fmt::Display for XValue
                 };
                 write!(f, "vec3({}, {}, {})", x_str, y_str, z_str)
             }
+            XValue::Vec4(x, y, z, w) => {
+                let [x_str, y_str, z_str, w_str] = [x, y, z, w].map(|v| {
+                    if v.fract() == 0.0 {
+                        format!("{:.1}", v)
+                    } else {
+                        format!("{}", v)
+                    }
+                });
+                write!(f, "vec4({}, {}, {}, {})", x_str, y_str, z_str, w_str)
+            }
+            XValue::Bool(v) => write!(f, "{}", v),
         }
     }
 }
@@ -120,6 +173,22 @@ impl From<(f32, f32, f32)> for XValue
     }
 }
 
+impl From<(f32, f32, f32, f32)> for XValue
+{
+    fn from((x, y, z, w): (f32, f32, f32, f32)) -> Self
+    {
+        XValue::Vec4(x, y, z, w)
+    }
+}
+
+impl From<bool> for XValue
+{
+    fn from(v: bool) -> Self
+    {
+        XValue::Bool(v)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum XExprReturnType
 {
@@ -127,9 +196,47 @@ pub enum XExprReturnType
     Integer,
     Vec2,
     Vec3,
+    Vec4,
+    Bool,
     Error,
 }
 
+impl XExprReturnType
+{
+    /// Number of components of float and float vector types.
+    ///
+    pub fn width(&self) -> Option<usize>
+    {
+        match self {
+            XExprReturnType::Float => Some(1),
+            XExprReturnType::Vec2 => Some(2),
+            XExprReturnType::Vec3 => Some(3),
+            XExprReturnType::Vec4 => Some(4),
+            _ => None,
+        }
+    }
+
+    /// Float or float vector type with the given number of components.
+    ///
+    pub fn with_width(width: usize) -> Option<Self>
+    {
+        match width {
+            1 => Some(XExprReturnType::Float),
+            2 => Some(XExprReturnType::Vec2),
+            3 => Some(XExprReturnType::Vec3),
+            4 => Some(XExprReturnType::Vec4),
+            _ => None,
+        }
+    }
+
+    /// Whether this is a float vector type.
+    ///
+    pub fn is_vector(&self) -> bool
+    {
+        self.width().is_some_and(|width| width > 1)
+    }
+}
+
 impl fmt::Display for XExprReturnType
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
@@ -139,6 +246,8 @@ impl fmt::Display for XExprReturnType
             XExprReturnType::Integer => write!(f, "integer"),
             XExprReturnType::Vec2 => write!(f, "vec2"),
             XExprReturnType::Vec3 => write!(f, "vec3"),
+            XExprReturnType::Vec4 => write!(f, "vec4"),
+            XExprReturnType::Bool => write!(f, "bool"),
             XExprReturnType::Error => write!(f, "error"),
         }
     }
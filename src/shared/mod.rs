@@ -1,4 +1,7 @@
 // ====================
 // Common.
 // ====================
+pub mod expr;
 pub mod proto;
+pub mod scene;
+pub mod tests;
@@ -1,6 +1,8 @@
 use bevy_hanabi::EffectAsset;
 use serde::{Deserialize, Serialize};
 
+use crate::shared::scene::ViewerScene;
+
 /// Messages that can be sent from external processes to the server
 #[derive(Clone, Serialize, Deserialize)]
 pub enum ServerCommandMessage
@@ -14,6 +16,44 @@ pub enum ServerCommandMessage
     {
         path: String,
     },
+    /// Fix the seed of the CPU spawner PRNG and the simulation timestep, then restart the
+    /// effect. `None` restores the default (random seed, real frame time) behavior.
+    ///
+    /// Hanabi seeds the GPU PRNG that `rand` expressions draw from on its own, every frame,
+    /// so particle attributes drawn with `rand` still differ between restarts.
+    SetFixedTimestep
+    {
+        spawner_seed:   Option<u32>,
+        fixed_timestep: Option<f32>,
+    },
+    /// Apply a scene preset: backdrop, camera, grid, bloom and extra effect instances.
+    LoadScene
+    {
+        scene: ViewerScene,
+    },
+    /// Write the viewer's current scene settings as a scene preset to `path`.
+    SaveScene
+    {
+        path: String,
+    },
+    /// Resize the offscreen image rendered in embedded mode.
+    ResizeViewport
+    {
+        width:  u32,
+        height: u32,
+    },
+    /// Move the camera by `pan` screen pixels and multiply its zoom by `zoom`.
+    PanZoomCamera
+    {
+        pan:  [f32; 2],
+        zoom: f32,
+    },
+    /// Record profiler metrics for `duration` seconds and write them as CSV to `path`.
+    CaptureProfile
+    {
+        path:     String,
+        duration: f32,
+    },
 }
 
 /// Responses sent back to clients
@@ -0,0 +1,256 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// Viewer scene preset stored as `<name>.scene.ron` in the assets root.
+///
+/// Describes the context an effect is previewed in: backdrop, camera,
+/// grid and bloom settings, plus extra effect instances placed around it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ViewerScene
+{
+    pub name:            String,
+    pub background:      [f32; 4],
+    pub reference_image: Option<SceneImage>,
+    pub camera:          SceneCamera,
+    pub grid:            SceneGrid,
+    pub bloom:           SceneBloom,
+    pub effects:         Vec<SceneEffect>,
+}
+
+/// Image drawn behind the effect, e.g. a level screenshot.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SceneImage
+{
+    pub path:     String,
+    pub position: [f32; 2],
+    pub scale:    f32,
+    pub opacity:  f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SceneCamera
+{
+    pub position: [f32; 2],
+    pub zoom:     f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SceneGrid
+{
+    pub show_grid: bool,
+    pub show_bbox: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SceneBloom
+{
+    pub enabled:   bool,
+    pub intensity: f32,
+}
+
+/// Additional effect instance spawned alongside the edited effect.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SceneEffect
+{
+    pub path:     String,
+    pub position: [f32; 2],
+    pub z_layer:  f32,
+}
+
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum SceneError
+{
+    #[error("File error for {}: {message}", path.display())]
+    File
+    {
+        path: PathBuf, message: String
+    },
+    #[error("Parse error for {}: {message}", path.display())]
+    Parse
+    {
+        path: PathBuf, message: String
+    },
+}
+
+impl Default for ViewerScene
+{
+    fn default() -> Self
+    {
+        Self {
+            name:            "Default".to_string(),
+            background:      [4.0 / 255.0, 4.0 / 255.0, 10.0 / 255.0, 1.0],
+            reference_image: None,
+            camera:          SceneCamera::default(),
+            grid:            SceneGrid::default(),
+            bloom:           SceneBloom::default(),
+            effects:         Vec::new(),
+        }
+    }
+}
+
+impl Default for SceneImage
+{
+    fn default() -> Self
+    {
+        Self {
+            path:     String::new(),
+            position: [0.0, 0.0],
+            scale:    1.0,
+            opacity:  1.0,
+        }
+    }
+}
+
+impl Default for SceneCamera
+{
+    fn default() -> Self
+    {
+        Self {
+            position: [0.0, 0.0],
+            zoom:     0.8,
+        }
+    }
+}
+
+impl Default for SceneGrid
+{
+    fn default() -> Self
+    {
+        Self {
+            show_grid: false,
+            show_bbox: true,
+        }
+    }
+}
+
+impl Default for SceneBloom
+{
+    fn default() -> Self
+    {
+        Self {
+            enabled:   true,
+            intensity: 0.15,
+        }
+    }
+}
+
+impl Default for SceneEffect
+{
+    fn default() -> Self
+    {
+        Self {
+            path:     String::new(),
+            position: [0.0, 0.0],
+            z_layer:  -0.5,
+        }
+    }
+}
+
+impl ViewerScene
+{
+    pub const FILE_EXTENSION: &'static str = "scene.ron";
+
+    /// Name of a scene file, which is its file name without the extension.
+    pub fn name_from_path(path: &Path) -> Option<&str>
+    {
+        path.file_name()?
+            .to_str()?
+            .strip_suffix(Self::FILE_EXTENSION)?
+            .strip_suffix('.')
+    }
+
+    /// List scene files in the assets root, sorted by name.
+    pub fn list(assets_root: &Path) -> Vec<PathBuf>
+    {
+        let mut scenes: Vec<PathBuf> = fs::read_dir(assets_root)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .map(|entry| entry.path())
+                    .filter(|path| {
+                        path.file_name()
+                            .and_then(|name| name.to_str())
+                            .is_some_and(|name| name.ends_with(Self::FILE_EXTENSION))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        scenes.sort();
+        scenes
+    }
+
+    /// Load a scene, resolving relative image and effect paths against the scene's directory.
+    pub fn load(path: &Path) -> Result<Self, SceneError>
+    {
+        let content = fs::read_to_string(path).map_err(|e| SceneError::File {
+            path:    path.to_path_buf(),
+            message: e.to_string(),
+        })?;
+        let mut scene = ron::from_str::<Self>(&content).map_err(|e| SceneError::Parse {
+            path:    path.to_path_buf(),
+            message: e.to_string(),
+        })?;
+
+        if let Some(dir) = path.parent() {
+            scene.resolve_paths(dir);
+        }
+        Ok(scene)
+    }
+
+    /// Save a scene, storing image and effect paths relative to the scene's directory when
+    /// possible.
+    pub fn save(&self, path: &Path) -> Result<(), SceneError>
+    {
+        let mut scene = self.clone();
+        if let Some(dir) = path.parent() {
+            scene.relativize_paths(dir);
+        }
+        let content = ron::ser::to_string_pretty(&scene, ron::ser::PrettyConfig::default())
+            .map_err(|e| SceneError::Parse {
+                path:    path.to_path_buf(),
+                message: e.to_string(),
+            })?;
+        fs::write(path, content).map_err(|e| SceneError::File {
+            path:    path.to_path_buf(),
+            message: e.to_string(),
+        })
+    }
+
+    fn resolve_paths(&mut self, dir: &Path)
+    {
+        let resolve = |p: &mut String| {
+            if !p.is_empty() && Path::new(p.as_str()).is_relative() {
+                *p = dir.join(p.as_str()).to_string_lossy().to_string();
+            }
+        };
+        if let Some(image) = &mut self.reference_image {
+            resolve(&mut image.path);
+        }
+        for effect in &mut self.effects {
+            resolve(&mut effect.path);
+        }
+    }
+
+    fn relativize_paths(&mut self, dir: &Path)
+    {
+        let relativize = |p: &mut String| {
+            if let Ok(relative) = Path::new(p.as_str()).strip_prefix(dir) {
+                *p = relative.to_string_lossy().to_string();
+            }
+        };
+        if let Some(image) = &mut self.reference_image {
+            relativize(&mut image.path);
+        }
+        for effect in &mut self.effects {
+            relativize(&mut effect.path);
+        }
+    }
+}
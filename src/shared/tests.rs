@@ -0,0 +1,119 @@
+#[cfg(test)]
+mod tests
+{
+    use std::path::PathBuf;
+
+    use bevy_hanabi::graph::{ScalarValue, Value};
+    use bevy_hanabi::{BinaryOperator, BuiltInOperator, Module, UnaryOperator};
+
+    use crate::shared::expr::{XBinaryOp, XBuiltInOp, XEnv, XExpr, XUnaryOp, XValue};
+    use crate::shared::scene::{SceneEffect, SceneError, SceneImage, ViewerScene};
+
+    /// Empty directory of its own for a test, under the system temp directory.
+    fn scene_dir(test: &str) -> PathBuf
+    {
+        let dir = std::env::temp_dir().join(format!("drakkar_{}_{}", test, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_scene_round_trip()
+    {
+        let dir = scene_dir("scene_round_trip");
+        let scene = ViewerScene {
+            name: "Cave".to_string(),
+            background: [0.1, 0.2, 0.3, 1.0],
+            reference_image: Some(SceneImage {
+                path: dir.join("cave.png").to_string_lossy().to_string(),
+                scale: 2.0,
+                ..Default::default()
+            }),
+            effects: vec![SceneEffect {
+                path: dir.join("torch.hanabi.ron").to_string_lossy().to_string(),
+                position: [10.0, -4.0],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let path = dir.join(format!("cave.{}", ViewerScene::FILE_EXTENSION));
+        scene.save(&path).unwrap();
+
+        // Paths inside the scene's directory are stored relative to it.
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.contains("\"torch.hanabi.ron\""), "{}", content);
+
+        assert_eq!(ViewerScene::load(&path).unwrap(), scene);
+        assert_eq!(ViewerScene::list(&dir), vec![path.clone()]);
+        assert_eq!(ViewerScene::name_from_path(&path), Some("cave"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_scene_malformed_file()
+    {
+        let dir = scene_dir("scene_malformed_file");
+        let path = dir.join(format!("broken.{}", ViewerScene::FILE_EXTENSION));
+        std::fs::write(&path, "(name: \"Broken\", background: [0.0, ").unwrap();
+        assert!(matches!(
+            ViewerScene::load(&path),
+            Err(SceneError::Parse { .. })
+        ));
+
+        // Missing fields take their default values.
+        std::fs::write(&path, "(name: \"Sparse\")").unwrap();
+        let scene = ViewerScene::load(&path).unwrap();
+        assert_eq!(scene.name, "Sparse");
+        assert_eq!(scene.camera, ViewerScene::default().camera);
+
+        assert!(matches!(
+            ViewerScene::load(&dir.join("missing.scene.ron")),
+            Err(SceneError::File { .. })
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_expr_from_graph()
+    {
+        let mut module = Module::default();
+        let time = module.builtin(BuiltInOperator::Time);
+        let sin = module.unary(UnaryOperator::Sin, time);
+        let scale = module.prop("scale");
+        let scaled = module.binary(BinaryOperator::Mul, sin, scale);
+        let two = module.lit(Value::Scalar(ScalarValue::Float(2.0)));
+        let radius = module.binary(BinaryOperator::Add, scaled, two);
+
+        let expr = XExpr::from_graph(&module, radius).unwrap();
+        assert_eq!(
+            expr,
+            XExpr::sin(XExpr::builtin(XBuiltInOp::Time))
+                .mul(XExpr::prop("scale"))
+                .add(XExpr::lit(2.0))
+        );
+        let mut env = XEnv::new()
+            .with_time(std::f32::consts::FRAC_PI_2)
+            .with_prop("scale", 3.0);
+        let Ok(XValue::Float(value)) = expr.eval(&mut env) else {
+            panic!("Expected a float");
+        };
+        assert!((value - 5.0).abs() < 1e-5);
+
+        // Lowerings are read node by node, and evaluate to the same values.
+        let minus_one = module.lit(Value::Scalar(ScalarValue::Float(-1.0)));
+        let negated = module.binary(BinaryOperator::Mul, radius, minus_one);
+        assert_eq!(
+            XExpr::from_graph(&module, negated),
+            Some(XExpr::binary(expr, XBinaryOp::Mul, XExpr::lit(-1.0)))
+        );
+        let cos = module.unary(UnaryOperator::Cos, time);
+        assert_eq!(
+            XExpr::from_graph(&module, cos),
+            Some(XExpr::unary(
+                XUnaryOp::Cos,
+                XExpr::builtin(XBuiltInOp::Time)
+            ))
+        );
+    }
+}
@@ -1,3 +1,5 @@
+use std::collections::{HashMap, VecDeque};
+
 // ====================
 // Bevy stuff.
 // ====================
@@ -13,25 +15,46 @@ use bevy::render::mesh::shape;
 use bevy::render::render_resource::WgpuFeatures;
 use bevy::render::settings::WgpuSettings;
 use bevy::sprite::MaterialMesh2dBundle;
+use bevy::time::TimeUpdateStrategy;
 use bevy::ui::Val;
-use bevy::window::WindowResolution;
+use bevy::utils::{Duration, Instant};
+use bevy::window::{ExitCondition, WindowResolution};
 // ====================
 // Particles.
 // ====================
+use bevy_hanabi::graph::{ScalarValue, Value};
 use bevy_hanabi::{
+    Attribute,
+    CpuValue,
     EffectAsset,
     EffectSpawner,
+    Expr,
     ForceFieldModifier,
     HanabiPlugin,
     ParticleEffect,
     ParticleEffectBundle,
+    Random,
+    SetAttributeModifier,
+    SetPositionCircleModifier,
+    SetPositionCone3dModifier,
+    SetPositionSphereModifier,
 };
+use clap::Parser;
+use rand_pcg::Pcg32;
 
 // ====================
 // Editor.
 // ====================
+use crate::shared::expr::{XEnv, XExpr, XValue};
+use crate::viewer::embedded::EmbeddedPlugin;
+use crate::viewer::profiler::ProfilerPlugin;
+use crate::viewer::scene::ScenePlugin;
 use crate::viewer::server::{ServerPlugin, ViewerCommandEvent};
 const DEFAULT_FONT: &str = crate::common::VIEWER_FONT;
+const PCG_STREAM: u64 = 0xa02b_dbf7_bb3c_0a7;
+const DEFAULT_FIXED_TIMESTEP: f32 = 1.0 / 60.0;
+/// Longest spawn period previewed as is. Longer ones are previewed with this period.
+pub const MAX_PREVIEW_PERIOD: f32 = 3.0;
 
 /// Component to track force field sources for gizmo rendering
 #[derive(Component)]
@@ -45,6 +68,31 @@ pub struct ForceFieldSource
     pub conform_to_sphere: bool,
 }
 
+/// Emitter shape of a position init modifier, drawn as a gizmo
+pub enum EmitterShape
+{
+    /// Circles and spheres, seen from the front.
+    Circle
+    {
+        center: XExpr, radius: XExpr
+    },
+    /// Cone along the Y axis, with its base at the origin.
+    Cone
+    {
+        height:      XExpr,
+        base_radius: XExpr,
+        top_radius:  XExpr,
+    },
+}
+
+/// Emitter shapes of the current effect, with the property values their expressions read
+#[derive(Resource, Default)]
+pub struct EmitterShapes
+{
+    pub shapes: Vec<EmitterShape>,
+    pub props:  HashMap<String, XValue>,
+}
+
 /// Component to mark force field gizmo entities
 #[derive(Component)]
 pub struct ForceFieldGizmo;
@@ -57,59 +105,97 @@ pub struct VisualBbox
     pub max: Vec3,
 }
 
+#[derive(Parser)]
+#[command(name = "drakkar-vfx-viewer", about = "Drakkar VFX Particle Viewer")]
+pub struct ViewerCliArgs
+{
+    /// Render offscreen and stream frames to the editor instead of opening a window.
+    #[clap(long, default_value = "false")]
+    pub embedded: bool,
+    #[clap(long, default_value = "800")]
+    pub width:    u32,
+    #[clap(long, default_value = "800")]
+    pub height:   u32,
+}
+
 pub fn viewer_main()
 {
+    let args = ViewerCliArgs::parse();
+
     let mut wgpu_settings = WgpuSettings::default();
     wgpu_settings
         .features
         .set(WgpuFeatures::VERTEX_WRITABLE_STORAGE, true);
 
-    App::new()
-        .add_plugins(
-            DefaultPlugins
-                .set(LogPlugin {
-                    level: bevy::log::Level::INFO,
-                    filter: "wgpu=error,naga=warn".to_string(),
-                    ..default()
-                })
-                .set(RenderPlugin { wgpu_settings })
-                .set(WindowPlugin {
-                    primary_window: Some(Window {
-                        title: "Drakkar VFX :: Viewer".to_string(),
-                        position: WindowPosition::Automatic,
-                        resolution: WindowResolution::new(800.0, 800.0),
-                        ..default()
-                    }),
-                    ..default()
-                }),
-        )
-        .add_plugins(HanabiPlugin)
-        .add_plugins(bevy::diagnostic::FrameTimeDiagnosticsPlugin)
-        .add_plugins(ServerPlugin)
-        .init_resource::<CameraController>()
-        .init_resource::<ViewerSettings>()
-        .init_resource::<ParticleStats>()
-        .init_resource::<BackgroundColor>()
-        .init_resource::<ViewerState>()
-        .add_systems(Startup, sys_setup)
-        .add_systems(
-            Update,
-            (
-                sys_camera_controller_system,
-                sys_input_system,
-                sys_particle_stats,
-                sys_ui_text,
-                sys_update_grid_gizmos,
-                sys_update_grid,
-                sys_handle_server_commands,
-                sys_update_background,
-                sys_handle_buttons,
-                sys_button_hover_effects,
-                bevy::window::close_on_esc,
-            ),
-        )
-        .add_systems(Update, sys_render_force_field_gizmos)
-        .run();
+    let window_plugin = if args.embedded {
+        WindowPlugin {
+            primary_window: None,
+            exit_condition: ExitCondition::DontExit,
+            ..default()
+        }
+    } else {
+        WindowPlugin {
+            primary_window: Some(Window {
+                title: "Drakkar VFX :: Viewer".to_string(),
+                position: WindowPosition::Automatic,
+                resolution: WindowResolution::new(args.width as f32, args.height as f32),
+                ..default()
+            }),
+            ..default()
+        }
+    };
+
+    let mut app = App::new();
+    app.add_plugins(
+        DefaultPlugins
+            .set(LogPlugin {
+                level: bevy::log::Level::INFO,
+                filter: "wgpu=error,naga=warn".to_string(),
+                ..default()
+            })
+            .set(RenderPlugin { wgpu_settings })
+            .set(window_plugin),
+    )
+    .add_plugins(HanabiPlugin)
+    .add_plugins(bevy::diagnostic::FrameTimeDiagnosticsPlugin)
+    .add_plugins(ServerPlugin)
+    .add_plugins(ProfilerPlugin)
+    .add_plugins(ScenePlugin)
+    .init_resource::<CameraController>()
+    .init_resource::<ViewerSettings>()
+    .init_resource::<ParticleStats>()
+    .init_resource::<BackgroundColor>()
+    .init_resource::<ViewerState>()
+    .init_resource::<EmitterShapes>()
+    .add_systems(Startup, sys_setup)
+    .add_systems(
+        Update,
+        (
+            sys_camera_controller_system,
+            sys_input_system,
+            sys_particle_stats,
+            sys_ui_text,
+            sys_update_grid_gizmos,
+            sys_update_grid,
+            sys_handle_server_commands,
+            sys_update_background,
+            sys_handle_buttons,
+            sys_button_hover_effects,
+            bevy::window::close_on_esc,
+        ),
+    )
+    .add_systems(
+        Update,
+        (sys_render_force_field_gizmos, sys_render_emitter_gizmos),
+    );
+
+    if args.embedded {
+        app.add_plugins(EmbeddedPlugin {
+            width:  args.width,
+            height: args.height,
+        });
+    }
+    app.run();
 }
 
 #[derive(Component)]
@@ -145,6 +231,7 @@ pub struct ViewerState
 {
     pub has_asset:     bool,
     pub error_message: Option<String>,
+    pub current_asset: Option<EffectAsset>,
 }
 
 impl Default for ViewerState
@@ -154,6 +241,7 @@ impl Default for ViewerState
         Self {
             has_asset:     false,
             error_message: None,
+            current_asset: None,
         }
     }
 }
@@ -226,6 +314,7 @@ fn sys_input_system(
     kb: Res<Input<KeyCode>>,
     mut viewer_settings: ResMut<ViewerSettings>,
     mut camera_controller: ResMut<CameraController>,
+    mut command_events: EventWriter<ViewerCommandEvent>,
 )
 {
     if kb.just_pressed(KeyCode::G) {
@@ -237,10 +326,24 @@ fn sys_input_system(
     if kb.just_pressed(KeyCode::I) {
         viewer_settings.show_stats = !viewer_settings.show_stats;
     }
+    if kb.just_pressed(KeyCode::P) {
+        viewer_settings.show_profiler = !viewer_settings.show_profiler;
+    }
     if kb.just_pressed(KeyCode::R) {
         camera_controller.position = Vec2::ZERO;
         camera_controller.zoom = 1.0;
     }
+    if kb.just_pressed(KeyCode::T) {
+        let (spawner_seed, fixed_timestep) = if viewer_settings.is_fixed_timestep() {
+            (None, None)
+        } else {
+            (Some(0), Some(DEFAULT_FIXED_TIMESTEP))
+        };
+        command_events.send(ViewerCommandEvent::SetFixedTimestep {
+            spawner_seed,
+            fixed_timestep,
+        });
+    }
 }
 
 fn sys_particle_stats(
@@ -252,11 +355,9 @@ fn sys_particle_stats(
     let current_time = time.elapsed_seconds_f64();
     let delta_time = time.delta_seconds();
 
-    particle_stats.particle_count = 0;
     particle_stats.bbox_size = Vec2::new(32.0, 32.0);
 
     if let Some((_entity, spawner)) = particle_query.iter().next() {
-        // TODO: update particle count
         let spawn_count = spawner.spawn_count();
         let spawner_config = spawner.spawner();
         if spawner_config.is_once() {
@@ -266,8 +367,24 @@ fn sys_particle_stats(
         }
         particle_stats.effect_age += delta_time;
         particle_stats.total_spawned = particle_stats.total_spawned.saturating_add(spawn_count);
+
+        // Hanabi keeps the alive count on the GPU, so estimate it from the particles
+        // spawned within one lifetime, capped by the effect capacity.
+        let window_start = current_time - particle_stats.estimated_lifetime as f64;
+        particle_stats
+            .spawn_history
+            .push_back((current_time, spawn_count));
+        while particle_stats
+            .spawn_history
+            .front()
+            .map_or(false, |&(t, _)| t < window_start)
+        {
+            particle_stats.spawn_history.pop_front();
+        }
+        let alive: u32 = particle_stats.spawn_history.iter().map(|&(_, n)| n).sum();
+        particle_stats.particle_count = alive.min(particle_stats.capacity);
     } else {
-        // TODO: update particle count
+        particle_stats.particle_count = 0;
         particle_stats.spawner_type = "None".to_string();
     }
     particle_stats.last_update_time = current_time;
@@ -305,7 +422,15 @@ fn sys_ui_text(
                 text.sections[0].value = if viewer_settings.show_stats && viewer_state.has_asset {
                     let current_time = time.elapsed_seconds_f64();
                     let sin_time = current_time.sin();
-                    format!("Time: {:.1}s | Sin(time): {:.3}", current_time, sin_time)
+                    let mut info =
+                        format!("Time: {:.1}s | Sin(time): {:.3}", current_time, sin_time);
+                    if let Some(seed) = viewer_settings.spawner_seed {
+                        info.push_str(&format!(" | Spawner seed: {}", seed));
+                    }
+                    if let Some(dt) = viewer_settings.fixed_timestep {
+                        info.push_str(&format!(" | Fixed dt: {:.4}s", dt));
+                    }
+                    info
                 } else {
                     String::new()
                 };
@@ -339,7 +464,7 @@ fn sys_ui_text(
             }
             UiText::Controls => {
                 text.sections[0].value =
-                    "WASD: Move | Q/E: Zoom | G: Grid | B: BBox | I: Info & Stats | R: Reset | Esc: Exit"
+                    "WASD: Move | Q/E: Zoom | G: Grid | B: BBox | I: Info & Stats | P: Profiler | T: Fixed timestep | R: Reset | Esc: Exit"
                         .to_string();
             }
             UiText::EmptyState => {
@@ -387,6 +512,9 @@ fn sys_handle_server_commands(
     mut cmds: Commands,
     mut effects: ResMut<Assets<EffectAsset>>,
     mut viewer_state: ResMut<ViewerState>,
+    mut viewer_settings: ResMut<ViewerSettings>,
+    mut particle_stats: ResMut<ParticleStats>,
+    mut random: ResMut<Random>,
     query_effects: Query<Entity, With<ParticleEffectEntity>>,
     query_force_fields: Query<Entity, With<ForceFieldSource>>,
 )
@@ -395,9 +523,7 @@ fn sys_handle_server_commands(
         match event {
             ViewerCommandEvent::OpenAsset { asset } => {
                 let mut asset = asset.clone();
-                if asset.spawner.period.single() > 3.0 {
-                    asset.spawner.period = 1.0.into();
-                }
+                shorten_preview_period(&mut asset);
 
                 spawn_effect(
                     &mut cmds,
@@ -406,17 +532,18 @@ fn sys_handle_server_commands(
                     &query_effects,
                     &query_force_fields,
                 );
+                particle_stats.reset_for(&asset);
+                reset_simulation(&mut cmds, &mut random, &viewer_settings);
                 viewer_state.has_asset = true;
                 viewer_state.error_message = None;
+                viewer_state.current_asset = Some(asset);
             }
 
             ViewerCommandEvent::OpenAssetFile { path: file_path } => {
                 match std::fs::read_to_string(file_path) {
                     Ok(content) => match ron::from_str::<EffectAsset>(&content) {
                         Ok(mut effect) => {
-                            if effect.spawner.period.single() > 3.0 {
-                                effect.spawner.period = 1.0.into();
-                            }
+                            shorten_preview_period(&mut effect);
 
                             spawn_effect(
                                 &mut cmds,
@@ -425,8 +552,11 @@ fn sys_handle_server_commands(
                                 &query_effects,
                                 &query_force_fields,
                             );
+                            particle_stats.reset_for(&effect);
+                            reset_simulation(&mut cmds, &mut random, &viewer_settings);
                             viewer_state.has_asset = true;
                             viewer_state.error_message = None;
+                            viewer_state.current_asset = Some(effect);
                         }
                         Err(e) => {
                             error!("Failed to parse effect file {}: {}", file_path, e);
@@ -441,10 +571,76 @@ fn sys_handle_server_commands(
                     }
                 }
             }
+
+            ViewerCommandEvent::SetFixedTimestep {
+                spawner_seed,
+                fixed_timestep,
+            } => {
+                viewer_settings.spawner_seed = *spawner_seed;
+                viewer_settings.fixed_timestep = fixed_timestep.filter(|dt| *dt > 0.0);
+                cmds.insert_resource(match viewer_settings.fixed_timestep {
+                    Some(dt) => TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(dt)),
+                    None => TimeUpdateStrategy::Automatic,
+                });
+
+                if let Some(asset) = viewer_state.current_asset.clone() {
+                    spawn_effect(
+                        &mut cmds,
+                        &mut effects,
+                        &asset,
+                        &query_effects,
+                        &query_force_fields,
+                    );
+                    particle_stats.reset_for(&asset);
+                    reset_simulation(&mut cmds, &mut random, &viewer_settings);
+                }
+            }
+
+            // Handled by the profiler plugin.
+            ViewerCommandEvent::CaptureProfile { .. } => {}
+
+            // Handled by the embedded plugin.
+            ViewerCommandEvent::ResizeViewport { .. }
+            | ViewerCommandEvent::PanZoomCamera { .. } => {}
+
+            // Handled by the scene plugin.
+            ViewerCommandEvent::LoadScene { .. } | ViewerCommandEvent::SaveScene { .. } => {}
         }
     }
 }
 
+/// Clamp the spawn period of an effect, or both bounds of its range, to the longest period
+/// that is previewed. A ranged period stays a range.
+pub fn shorten_preview_period(asset: &mut EffectAsset)
+{
+    asset.spawner.period = match asset.spawner.period {
+        CpuValue::Single(period) => CpuValue::Single(period.min(MAX_PREVIEW_PERIOD)),
+        CpuValue::Uniform((min, max)) => {
+            CpuValue::Uniform((min.min(MAX_PREVIEW_PERIOD), max.min(MAX_PREVIEW_PERIOD)))
+        }
+    };
+}
+
+/// Hanabi's spawner PRNG, in the state a fixed timestep restart with `seed` starts from.
+pub fn seeded_random(seed: u32) -> Random
+{
+    Random(Pcg32::new(seed as u64, PCG_STREAM))
+}
+
+/// Reseed Hanabi's spawner PRNG and restart the clock, so that in fixed timestep mode every
+/// restart of the effect spawns as many particles at the same times.
+/// Hanabi draws the per-frame GPU seed inside its render world with `rand::random`, which
+/// has no seed to set, so the attributes particles draw with `rand` differ between restarts.
+fn reset_simulation(cmds: &mut Commands, random: &mut Random, viewer_settings: &ViewerSettings)
+{
+    if let Some(seed) = viewer_settings.spawner_seed {
+        *random = seeded_random(seed);
+    }
+    if viewer_settings.is_fixed_timestep() {
+        cmds.insert_resource(Time::new(Instant::now()));
+    }
+}
+
 fn spawn_effect(
     cmds: &mut Commands,
     effects: &mut Assets<EffectAsset>,
@@ -453,6 +649,7 @@ fn spawn_effect(
     query_force_fields: &Query<Entity, With<ForceFieldSource>>,
 )
 {
+    // Scene effects are added back by the scene plugin.
     effects.clear();
     for entity in query_effects.iter() {
         cmds.entity(entity).despawn();
@@ -490,6 +687,7 @@ fn spawn_effect(
             }
         }
     }
+    cmds.insert_resource(EmitterShapes::from_asset(asset));
 }
 
 fn sys_setup(
@@ -926,11 +1124,16 @@ pub struct ViewerSettings
     pub grid_subdivisions: u32,
 
     // Stats display toggle
-    pub show_stats: bool,
+    pub show_stats:    bool,
+    pub show_profiler: bool,
 
     // Stats layout
     pub stats_panel_width: f32,
     pub compact_stats:     bool,
+
+    // Fixed timestep simulation
+    pub spawner_seed:   Option<u32>,
+    pub fixed_timestep: Option<f32>,
 }
 
 impl Default for ViewerSettings
@@ -943,12 +1146,24 @@ impl Default for ViewerSettings
             grid_size:         32.0,
             grid_subdivisions: 4,
             show_stats:        true,
+            show_profiler:     false,
             stats_panel_width: 250.0,
             compact_stats:     false,
+            spawner_seed:      None,
+            fixed_timestep:    None,
         }
     }
 }
 
+impl ViewerSettings
+{
+    /// Whether restarts should spawn particles at the same times, at a fixed timestep.
+    pub fn is_fixed_timestep(&self) -> bool
+    {
+        self.spawner_seed.is_some() && self.fixed_timestep.is_some()
+    }
+}
+
 // Statistics about the particle effect being displayed
 #[derive(Resource)]
 pub struct ParticleStats
@@ -964,6 +1179,8 @@ pub struct ParticleStats
     // Effect timing
     pub effect_age:         f32, // How long effect has been running (seconds)
     pub estimated_lifetime: f32, // Estimated particle lifetime (seconds)
+    pub capacity:           u32, // Effect capacity, upper bound of the particle count
+    pub spawn_history:      VecDeque<(f64, u32)>, // Spawn counts within the last lifetime
 
     // Update timing
     pub last_update_time: f64, // When stats were last updated
@@ -980,11 +1197,44 @@ impl Default for ParticleStats
             spawner_type:       String::from("Unknown"),
             effect_age:         0.0,
             estimated_lifetime: 5.0,
+            capacity:           u32::MAX,
+            spawn_history:      VecDeque::new(),
             last_update_time:   0.0,
         }
     }
 }
 
+impl ParticleStats
+{
+    /// Reset the stats for a newly spawned effect.
+    pub fn reset_for(&mut self, asset: &EffectAsset)
+    {
+        *self = Self {
+            estimated_lifetime: estimate_lifetime(asset).unwrap_or(5.0),
+            capacity: asset.capacity,
+            ..Default::default()
+        };
+    }
+}
+
+/// Read the particle lifetime from a literal `SetAttributeModifier` on `Attribute::LIFETIME`.
+fn estimate_lifetime(asset: &EffectAsset) -> Option<f32>
+{
+    asset.init_modifiers.iter().find_map(|modifier| {
+        let set_attr = modifier.as_any().downcast_ref::<SetAttributeModifier>()?;
+        if set_attr.attribute != Attribute::LIFETIME {
+            return None;
+        }
+        match asset.module.get(set_attr.value)? {
+            Expr::Literal(lit) => match &lit.value {
+                Value::Scalar(ScalarValue::Float(f)) => Some(*f),
+                _ => None,
+            },
+            _ => None,
+        }
+    })
+}
+
 /// Component marker for grid coordinate labels
 #[derive(Component)]
 pub struct GridLabel;
@@ -1283,6 +1533,121 @@ impl BackgroundColor
     }
 }
 
+impl EmitterShapes
+{
+    /// Read the shapes of the position init modifiers of an effect.
+    ///
+    /// Shapes whose expressions cannot be read from the graph are left out.
+    ///
+    pub fn from_asset(asset: &EffectAsset) -> Self
+    {
+        let read = |handle| XExpr::from_graph(&asset.module, handle);
+        let mut shapes = Vec::new();
+        for modifier in &asset.init_modifiers {
+            let modifier = modifier.as_any();
+            let shape = if let Some(m) = modifier.downcast_ref::<SetPositionCircleModifier>() {
+                read(m.center)
+                    .zip(read(m.radius))
+                    .map(|(center, radius)| EmitterShape::Circle { center, radius })
+            } else if let Some(m) = modifier.downcast_ref::<SetPositionSphereModifier>() {
+                read(m.center)
+                    .zip(read(m.radius))
+                    .map(|(center, radius)| EmitterShape::Circle { center, radius })
+            } else if let Some(m) = modifier.downcast_ref::<SetPositionCone3dModifier>() {
+                read(m.height)
+                    .zip(read(m.base_radius))
+                    .zip(read(m.top_radius))
+                    .map(|((height, base_radius), top_radius)| EmitterShape::Cone {
+                        height,
+                        base_radius,
+                        top_radius,
+                    })
+            } else {
+                None
+            };
+            shapes.extend(shape);
+        }
+        let props = asset
+            .properties
+            .iter()
+            .filter_map(|prop| {
+                XValue::from_hanabi(prop.default_value()).map(|v| (prop.name().to_string(), v))
+            })
+            .collect();
+        Self { shapes, props }
+    }
+}
+
+/// Evaluate a scalar shape expression.
+fn eval_scalar(expr: &XExpr, env: &mut XEnv) -> Option<f32>
+{
+    match expr.eval(env).ok()? {
+        XValue::Float(f) => Some(f),
+        _ => None,
+    }
+}
+
+/// Evaluate a shape position, a scalar standing for all of its components, seen from the front.
+fn eval_position(expr: &XExpr, env: &mut XEnv) -> Option<Vec2>
+{
+    match expr.eval(env).ok()?.float_components()?.as_slice() {
+        [v] => Some(Vec2::splat(*v)),
+        [x, y, ..] => Some(Vec2::new(*x, *y)),
+        [] => None,
+    }
+}
+
+/// System to render emitter shape gizmos, with their expressions evaluated at the current time
+///
+/// Shapes reading particle attributes have no single value and are not drawn. Random values
+/// are drawn with the same seed every frame, so that their shapes hold still.
+pub fn sys_render_emitter_gizmos(
+    viewer_settings: Res<ViewerSettings>,
+    emitter_shapes: Res<EmitterShapes>,
+    time: Res<Time>,
+    mut gizmos: Gizmos,
+)
+{
+    if !viewer_settings.show_bbox {
+        return;
+    }
+
+    let color = Color::rgba(0.4, 0.8, 1.0, 0.15);
+    for shape in &emitter_shapes.shapes {
+        let mut env = XEnv::new().with_time(time.elapsed_seconds());
+        env.props = emitter_shapes.props.clone();
+        match shape {
+            EmitterShape::Circle { center, radius } => {
+                let center = eval_position(center, &mut env);
+                let radius = eval_scalar(radius, &mut env);
+                if let (Some(center), Some(radius)) = (center, radius) {
+                    gizmos.circle_2d(center, radius.abs(), color);
+                }
+            }
+            EmitterShape::Cone {
+                height,
+                base_radius,
+                top_radius,
+            } => {
+                let height = eval_scalar(height, &mut env);
+                let base = eval_scalar(base_radius, &mut env);
+                let top = eval_scalar(top_radius, &mut env);
+                if let (Some(height), Some(base), Some(top)) = (height, base, top) {
+                    let corners = [
+                        Vec2::new(-base, 0.0),
+                        Vec2::new(base, 0.0),
+                        Vec2::new(top, height),
+                        Vec2::new(-top, height),
+                    ];
+                    for (i, corner) in corners.iter().enumerate() {
+                        gizmos.line_2d(*corner, corners[(i + 1) % corners.len()], color);
+                    }
+                }
+            }
+        }
+    }
+}
+
 /// System to render force field gizmos
 pub fn sys_render_force_field_gizmos(
     viewer_settings: Res<ViewerSettings>,
@@ -0,0 +1,436 @@
+// ====================
+// Std.
+// ====================
+use std::io::Write;
+use std::net::{TcpListener, TcpStream};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU8, AtomicU64, Ordering};
+use std::thread;
+
+// ====================
+// Bevy stuff.
+// ====================
+use bevy::prelude::*;
+use bevy::render::camera::RenderTarget;
+use bevy::render::extract_resource::{ExtractResource, ExtractResourcePlugin};
+use bevy::render::main_graph::node::CAMERA_DRIVER;
+use bevy::render::render_asset::RenderAssets;
+use bevy::render::render_graph::{Node, NodeRunError, RenderGraph, RenderGraphContext};
+use bevy::render::render_resource::{
+    Buffer,
+    BufferDescriptor,
+    BufferUsages,
+    Extent3d,
+    ImageCopyBuffer,
+    ImageDataLayout,
+    MapMode,
+    TextureDescriptor,
+    TextureDimension,
+    TextureFormat,
+    TextureUsages,
+};
+use bevy::render::renderer::{RenderContext, RenderDevice};
+use bevy::render::{Render, RenderApp, RenderSet};
+use kanal::{Receiver, Sender};
+
+// ====================
+// Viewer.
+// ====================
+use crate::viewer::app::CameraController;
+use crate::viewer::server::ViewerCommandEvent;
+
+const FRAME_COPY_NODE: &str = "drakkar_embedded_frame_copy";
+const BYTES_PER_PIXEL: u32 = 4;
+/// Pixels per world unit of the viewer camera (`ScalingMode::WindowSize`).
+const PIXELS_PER_UNIT: f32 = 3.0;
+
+const SLOT_IDLE: u8 = 0;
+const SLOT_COPIED: u8 = 1;
+const SLOT_MAPPING: u8 = 2;
+const SLOT_MAPPED: u8 = 3;
+
+/// Plugin rendering the viewer into an offscreen image and streaming
+/// the frames to the editor, which shows them in its viewer panel.
+///
+/// Frames are sent over TCP as `[width: u32][height: u32][BGRA pixels]`,
+/// little endian, with no row padding.
+pub struct EmbeddedPlugin
+{
+    pub width:  u32,
+    pub height: u32,
+}
+
+/// Offscreen image the viewer camera renders into.
+#[derive(Resource, Clone, ExtractResource)]
+pub struct EmbeddedTarget
+{
+    pub image:  Handle<Image>,
+    pub width:  u32,
+    pub height: u32,
+}
+
+/// Frame read back from the offscreen image.
+pub struct EmbeddedFrame
+{
+    pub width:  u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl Plugin for EmbeddedPlugin
+{
+    fn build(&self, app: &mut App)
+    {
+        let mut image = Image {
+            texture_descriptor: TextureDescriptor {
+                label:           Some("drakkar_embedded_target"),
+                size:            Extent3d {
+                    width:                 self.width,
+                    height:                self.height,
+                    depth_or_array_layers: 1,
+                },
+                dimension:       TextureDimension::D2,
+                format:          TextureFormat::Bgra8UnormSrgb,
+                mip_level_count: 1,
+                sample_count:    1,
+                usage:           TextureUsages::TEXTURE_BINDING
+                    | TextureUsages::COPY_SRC
+                    | TextureUsages::RENDER_ATTACHMENT,
+                view_formats:    &[],
+            },
+            ..default()
+        };
+        image.resize(image.texture_descriptor.size);
+        let image = app.world.resource_mut::<Assets<Image>>().add(image);
+
+        app.insert_resource(EmbeddedTarget {
+            image,
+            width: self.width,
+            height: self.height,
+        })
+        .add_plugins(ExtractResourcePlugin::<EmbeddedTarget>::default())
+        .add_systems(PostStartup, sys_target_embedded_image)
+        .add_systems(Update, sys_handle_embedded_commands);
+    }
+
+    fn finish(&self, app: &mut App)
+    {
+        let (sender, receiver) = kanal::bounded(1);
+        let builder = thread::Builder::new().name("frame-stream-thread".to_string());
+        if let Err(e) = builder.spawn(move || frame_stream_main(receiver)) {
+            error!("Failed to spawn frame stream thread: {}", e);
+            return;
+        }
+
+        let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        let mut graph = render_app.world.resource_mut::<RenderGraph>();
+        graph.add_node(FRAME_COPY_NODE, FrameCopyNode);
+        graph.add_node_edge(CAMERA_DRIVER, FRAME_COPY_NODE);
+
+        render_app
+            .insert_resource(FrameSender { sender })
+            .add_systems(
+                Render,
+                sys_prepare_frame_readback.in_set(RenderSet::Prepare),
+            )
+            .add_systems(Render, sys_readback_frame.in_set(RenderSet::Cleanup));
+    }
+}
+
+// ====================
+// Main world.
+// ====================
+
+fn sys_target_embedded_image(
+    target: Res<EmbeddedTarget>,
+    mut query_camera: Query<&mut Camera, With<Camera2d>>,
+)
+{
+    for mut camera in query_camera.iter_mut() {
+        camera.target = RenderTarget::Image(target.image.clone());
+    }
+}
+
+fn sys_handle_embedded_commands(
+    mut events: EventReader<ViewerCommandEvent>,
+    mut target: ResMut<EmbeddedTarget>,
+    mut images: ResMut<Assets<Image>>,
+    mut camera_controller: ResMut<CameraController>,
+)
+{
+    for event in events.iter() {
+        match event {
+            ViewerCommandEvent::ResizeViewport { width, height } => {
+                let (width, height) = ((*width).max(1), (*height).max(1));
+                if width == target.width && height == target.height {
+                    continue;
+                }
+                if let Some(image) = images.get_mut(&target.image) {
+                    image.resize(Extent3d {
+                        width,
+                        height,
+                        depth_or_array_layers: 1,
+                    });
+                }
+                target.width = width;
+                target.height = height;
+            }
+
+            ViewerCommandEvent::PanZoomCamera { pan, zoom } => {
+                let zoom_level = camera_controller.zoom;
+                camera_controller.position += Vec2::from(*pan) * zoom_level / PIXELS_PER_UNIT;
+                camera_controller.zoom = (zoom_level * zoom)
+                    .clamp(camera_controller.min_zoom, camera_controller.max_zoom);
+            }
+
+            _ => {}
+        }
+    }
+}
+
+// ====================
+// Render world.
+// ====================
+
+#[derive(Resource)]
+struct FrameSender
+{
+    sender: Sender<EmbeddedFrame>,
+}
+
+/// Buffer one frame is copied into and mapped from, with the state of its readback.
+struct ReadbackSlot
+{
+    buffer: Buffer,
+    state:  Arc<AtomicU8>,
+    /// Number of the frame last copied into the buffer.
+    frame:  AtomicU64,
+}
+
+/// Pair of buffers the offscreen image is copied into, with rows padded to the copy
+/// alignment. A frame is copied into one while the other is mapped.
+#[derive(Resource)]
+struct FrameReadback
+{
+    slots:         [ReadbackSlot; 2],
+    width:         u32,
+    height:        u32,
+    padded_stride: u32,
+    /// Number of the next copied frame, so that frames are never sent out of order.
+    next_frame:    AtomicU64,
+    last_sent:     AtomicU64,
+}
+
+fn sys_prepare_frame_readback(
+    mut cmds: Commands,
+    target: Option<Res<EmbeddedTarget>>,
+    readback: Option<Res<FrameReadback>>,
+    render_device: Res<RenderDevice>,
+)
+{
+    let Some(target) = target else {
+        return;
+    };
+    if readback.is_some_and(|r| r.width == target.width && r.height == target.height) {
+        return;
+    }
+
+    let stride = target.width * BYTES_PER_PIXEL;
+    let padded_stride = RenderDevice::align_copy_bytes_per_row(stride as usize) as u32;
+    let slot = || ReadbackSlot {
+        buffer: render_device.create_buffer(&BufferDescriptor {
+            label:              Some("drakkar_embedded_readback_buffer"),
+            size:               (padded_stride * target.height) as u64,
+            usage:              BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        }),
+        state:  Arc::new(AtomicU8::new(SLOT_IDLE)),
+        frame:  AtomicU64::new(0),
+    };
+    cmds.insert_resource(FrameReadback {
+        slots: [slot(), slot()],
+        width: target.width,
+        height: target.height,
+        padded_stride,
+        next_frame: AtomicU64::new(1),
+        last_sent: AtomicU64::new(0),
+    });
+}
+
+/// Render graph node copying the offscreen image into the readback buffer.
+struct FrameCopyNode;
+
+impl Node for FrameCopyNode
+{
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        world: &World,
+    ) -> Result<(), NodeRunError>
+    {
+        let (Some(target), Some(readback)) = (
+            world.get_resource::<EmbeddedTarget>(),
+            world.get_resource::<FrameReadback>(),
+        ) else {
+            return Ok(());
+        };
+        let Some(gpu_image) = world.resource::<RenderAssets<Image>>().get(&target.image) else {
+            return Ok(());
+        };
+
+        // The image is re-created one frame after a resize; skip until sizes agree.
+        let size = gpu_image.texture.size();
+        if size.width != readback.width || size.height != readback.height {
+            return Ok(());
+        }
+
+        // Frames are skipped while both buffers are still being read back.
+        let Some(slot) = readback
+            .slots
+            .iter()
+            .find(|slot| slot.state.load(Ordering::Acquire) == SLOT_IDLE)
+        else {
+            return Ok(());
+        };
+
+        render_context.command_encoder().copy_texture_to_buffer(
+            gpu_image.texture.as_image_copy(),
+            ImageCopyBuffer {
+                buffer: &slot.buffer,
+                layout: ImageDataLayout {
+                    offset:         0,
+                    bytes_per_row:  Some(readback.padded_stride),
+                    rows_per_image: None,
+                },
+            },
+            size,
+        );
+        let frame = readback.next_frame.fetch_add(1, Ordering::Relaxed);
+        slot.frame.store(frame, Ordering::Relaxed);
+        slot.state.store(SLOT_COPIED, Ordering::Release);
+        Ok(())
+    }
+}
+
+/// Map the buffers copied into this frame, and send the frames of the mapped ones.
+///
+/// Mapping completes when a later queue submission polls the device, so the render thread
+/// never waits for the GPU.
+fn sys_readback_frame(readback: Option<Res<FrameReadback>>, frame_sender: Res<FrameSender>)
+{
+    let Some(readback) = readback else {
+        return;
+    };
+
+    for slot in &readback.slots {
+        match slot.state.load(Ordering::Acquire) {
+            SLOT_COPIED => {
+                slot.state.store(SLOT_MAPPING, Ordering::Release);
+                let state = slot.state.clone();
+                slot.buffer
+                    .slice(..)
+                    .map_async(MapMode::Read, move |result| {
+                        let next = if result.is_ok() {
+                            SLOT_MAPPED
+                        } else {
+                            SLOT_IDLE
+                        };
+                        state.store(next, Ordering::Release);
+                    });
+            }
+            SLOT_MAPPED => {
+                let stride = (readback.width * BYTES_PER_PIXEL) as usize;
+                let mut pixels = Vec::with_capacity(stride * readback.height as usize);
+                {
+                    let data = slot.buffer.slice(..).get_mapped_range();
+                    for row in data.chunks(readback.padded_stride as usize) {
+                        pixels.extend_from_slice(&row[..stride]);
+                    }
+                }
+                slot.buffer.unmap();
+                slot.state.store(SLOT_IDLE, Ordering::Release);
+
+                let frame = slot.frame.load(Ordering::Relaxed);
+                if frame < readback.last_sent.load(Ordering::Relaxed) {
+                    continue;
+                }
+                readback.last_sent.store(frame, Ordering::Relaxed);
+                // Frames are dropped while the stream thread is still sending the previous one.
+                let _ = frame_sender.sender.try_send(EmbeddedFrame {
+                    width: readback.width,
+                    height: readback.height,
+                    pixels,
+                });
+            }
+            _ => {}
+        }
+    }
+}
+
+// ====================
+// Frame stream.
+// ====================
+
+fn frame_stream_main(receiver: Receiver<EmbeddedFrame>)
+{
+    let ports = crate::common::FRAME_PORTS;
+    let Some((listener, port)) = try_bind_ports(ports) else {
+        error!(
+            "Failed to bind frame stream socket on any port: {:?}",
+            ports
+        );
+        return;
+    };
+    if let Err(e) = listener.set_nonblocking(true) {
+        error!("Failed to set frame listener to non-blocking: {}", e);
+        return;
+    }
+    info!("Frame stream listening on 127.0.0.1:{}", port);
+
+    let mut client: Option<TcpStream> = None;
+    loop {
+        if let Ok((stream, addr)) = listener.accept() {
+            debug!("Frame stream client connected: {}", addr);
+            if stream.set_nonblocking(false).is_ok() {
+                let _ = stream.set_nodelay(true);
+                client = Some(stream);
+            }
+        }
+
+        let frame = match receiver.recv() {
+            Ok(frame) => frame,
+            Err(_) => {
+                debug!("Frame channel closed, stopping frame stream");
+                break;
+            }
+        };
+
+        if let Some(stream) = &mut client {
+            if let Err(e) = write_frame(stream, &frame) {
+                debug!("Frame stream client disconnected: {}", e);
+                client = None;
+            }
+        }
+    }
+}
+
+fn write_frame(stream: &mut TcpStream, frame: &EmbeddedFrame) -> std::io::Result<()>
+{
+    stream.write_all(&frame.width.to_le_bytes())?;
+    stream.write_all(&frame.height.to_le_bytes())?;
+    stream.write_all(&frame.pixels)?;
+    stream.flush()
+}
+
+fn try_bind_ports(ports: &[u16]) -> Option<(TcpListener, u16)>
+{
+    ports.iter().find_map(|&port| {
+        TcpListener::bind(("127.0.0.1", port))
+            .ok()
+            .map(|listener| (listener, port))
+    })
+}
@@ -2,4 +2,8 @@
 // Viewer modules.
 // ====================
 pub mod app;
+pub mod embedded;
+pub mod profiler;
+pub mod scene;
 pub mod server;
+pub mod tests;
@@ -0,0 +1,607 @@
+// ====================
+// Std.
+// ====================
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+// ====================
+// Bevy stuff.
+// ====================
+use bevy::diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin};
+use bevy::prelude::*;
+use bevy::render::render_graph::{Node, NodeRunError, RenderGraph, RenderGraphContext};
+use bevy::render::render_resource::{
+    Buffer,
+    BufferDescriptor,
+    BufferUsages,
+    Maintain,
+    MapMode,
+    WgpuFeatures,
+};
+use bevy::render::renderer::{RenderContext, RenderDevice, RenderQueue};
+use bevy::render::{Render, RenderApp, RenderSet};
+use kanal::{Receiver, Sender};
+
+// ====================
+// Viewer.
+// ====================
+use crate::viewer::app::{ParticleStats, ViewerSettings, ViewerState};
+use crate::viewer::server::ViewerCommandEvent;
+
+const DEFAULT_FONT: &str = crate::common::VIEWER_FONT;
+
+/// Render graph label of the Hanabi node running the init/update compute passes.
+const HANABI_SIM_NODE: &str = "hanabi_sim_node";
+const PROFILER_BEGIN_NODE: &str = "drakkar_profiler_begin";
+const PROFILER_END_NODE: &str = "drakkar_profiler_end";
+
+/// Number of samples kept for each graph.
+const HISTORY_LEN: usize = 120;
+const BAR_WIDTH: f32 = 2.0;
+const GRAPH_HEIGHT: f32 = 32.0;
+
+/// Rough per-particle cost of the Hanabi passes, used when timestamp queries are unavailable.
+const ESTIMATED_NS_PER_PARTICLE: f32 = 2.0;
+
+const TIMER_IDLE: u8 = 0;
+const TIMER_COPIED: u8 = 1;
+const TIMER_MAPPING: u8 = 2;
+const TIMER_MAPPED: u8 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GpuTimingSource
+{
+    TimestampQuery,
+    Estimated,
+}
+
+impl GpuTimingSource
+{
+    pub fn label(&self) -> &'static str
+    {
+        match self {
+            GpuTimingSource::TimestampQuery => "timestamp",
+            GpuTimingSource::Estimated => "estimated",
+        }
+    }
+}
+
+#[derive(Component, Clone, Copy, PartialEq, Eq)]
+pub enum ProfilerGraph
+{
+    FrameTime,
+    GpuTime,
+    ParticleCount,
+}
+
+/// Component marker for a single bar of a profiler graph.
+#[derive(Component)]
+pub struct ProfilerBar
+{
+    pub graph: ProfilerGraph,
+    pub index: usize,
+}
+
+/// Component marker for the profiler overlay root.
+#[derive(Component)]
+pub struct ProfilerOverlay;
+
+/// Component marker for the caption above each profiler graph.
+#[derive(Component)]
+pub struct ProfilerCaption(pub ProfilerGraph);
+
+/// One row of profiler metrics, recorded every frame.
+#[derive(Debug, Clone, Copy)]
+pub struct ProfileSample
+{
+    pub time:           f64,
+    pub frame_time_ms:  f32,
+    pub gpu_time_ms:    f32,
+    pub gpu_source:     GpuTimingSource,
+    pub particle_count: u32,
+}
+
+/// Rolling history of the profiler metrics.
+#[derive(Resource)]
+pub struct ProfilerHistory
+{
+    pub samples:    VecDeque<ProfileSample>,
+    pub gpu_source: GpuTimingSource,
+}
+
+impl Default for ProfilerHistory
+{
+    fn default() -> Self
+    {
+        Self {
+            samples:    VecDeque::with_capacity(HISTORY_LEN),
+            gpu_source: GpuTimingSource::Estimated,
+        }
+    }
+}
+
+impl ProfilerHistory
+{
+    fn push(&mut self, sample: ProfileSample)
+    {
+        if self.samples.len() == HISTORY_LEN {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    fn values(&self, graph: ProfilerGraph) -> impl Iterator<Item = f32> + '_
+    {
+        self.samples.iter().map(move |s| match graph {
+            ProfilerGraph::FrameTime => s.frame_time_ms,
+            ProfilerGraph::GpuTime => s.gpu_time_ms,
+            ProfilerGraph::ParticleCount => s.particle_count as f32,
+        })
+    }
+}
+
+/// Timed capture of profiler metrics requested through the protocol.
+#[derive(Resource)]
+pub struct ProfileCapture
+{
+    pub path:     String,
+    pub duration: f32,
+    pub started:  f64,
+    pub samples:  Vec<ProfileSample>,
+}
+
+impl ProfileCapture
+{
+    pub fn to_csv(&self) -> String
+    {
+        let mut csv =
+            String::from("time_s,frame_time_ms,fps,hanabi_gpu_ms,gpu_time_source,particle_count\n");
+        for s in &self.samples {
+            let fps = if s.frame_time_ms > 0.0 {
+                1000.0 / s.frame_time_ms
+            } else {
+                0.0
+            };
+            csv.push_str(&format!(
+                "{:.4},{:.4},{:.2},{:.4},{},{}\n",
+                s.time - self.started,
+                s.frame_time_ms,
+                fps,
+                s.gpu_time_ms,
+                s.gpu_source.label(),
+                s.particle_count
+            ));
+        }
+        csv
+    }
+}
+
+/// Main world end of the GPU timestamp readback channel.
+#[derive(Resource)]
+pub struct GpuTimingReceiver
+{
+    pub receiver: Receiver<f32>,
+}
+
+/// Render world state for measuring the Hanabi simulation passes with timestamp queries.
+#[derive(Resource)]
+struct HanabiGpuTimer
+{
+    query_set:       wgpu::QuerySet,
+    resolve_buffer:  Buffer,
+    readback_buffer: Buffer,
+    state:           Arc<AtomicU8>,
+    sender:          Sender<f32>,
+}
+
+pub struct ProfilerPlugin;
+
+impl Plugin for ProfilerPlugin
+{
+    fn build(&self, app: &mut App)
+    {
+        app.init_resource::<ProfilerHistory>()
+            .add_systems(Startup, sys_setup_profiler_overlay)
+            .add_systems(
+                Update,
+                (
+                    sys_collect_profiler_metrics,
+                    sys_handle_profile_commands,
+                    sys_update_profiler_overlay,
+                    sys_finish_profile_capture,
+                )
+                    .chain(),
+            );
+    }
+
+    fn finish(&self, app: &mut App)
+    {
+        let (sender, receiver) = kanal::unbounded();
+        app.insert_resource(GpuTimingReceiver { receiver });
+
+        let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+
+        let render_device = render_app.world.resource::<RenderDevice>().clone();
+        if !render_device
+            .features()
+            .contains(WgpuFeatures::TIMESTAMP_QUERY)
+        {
+            info!("Timestamp queries not supported, Hanabi GPU time will be estimated");
+            return;
+        }
+
+        let mut graph = render_app.world.resource_mut::<RenderGraph>();
+        if graph.get_node_state(HANABI_SIM_NODE).is_err() {
+            warn!("Hanabi simulation node not found, Hanabi GPU time will be estimated");
+            return;
+        }
+        graph.add_node(PROFILER_BEGIN_NODE, TimestampNode { index: 0 });
+        graph.add_node(PROFILER_END_NODE, TimestampNode { index: 1 });
+        graph.add_node_edge(PROFILER_BEGIN_NODE, HANABI_SIM_NODE);
+        graph.add_node_edge(HANABI_SIM_NODE, PROFILER_END_NODE);
+
+        let query_set = render_device
+            .wgpu_device()
+            .create_query_set(&wgpu::QuerySetDescriptor {
+                label: Some("drakkar_profiler_query_set"),
+                ty:    wgpu::QueryType::Timestamp,
+                count: 2,
+            });
+        let resolve_buffer = render_device.create_buffer(&BufferDescriptor {
+            label:              Some("drakkar_profiler_resolve_buffer"),
+            size:               2 * std::mem::size_of::<u64>() as u64,
+            usage:              BufferUsages::QUERY_RESOLVE | BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let readback_buffer = render_device.create_buffer(&BufferDescriptor {
+            label:              Some("drakkar_profiler_readback_buffer"),
+            size:               2 * std::mem::size_of::<u64>() as u64,
+            usage:              BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        render_app
+            .insert_resource(HanabiGpuTimer {
+                query_set,
+                resolve_buffer,
+                readback_buffer,
+                state: Arc::new(AtomicU8::new(TIMER_IDLE)),
+                sender,
+            })
+            .add_systems(
+                Render,
+                sys_readback_gpu_timestamps.in_set(RenderSet::Cleanup),
+            );
+    }
+}
+
+// ====================
+// Render world.
+// ====================
+
+/// Render graph node writing one timestamp around the Hanabi simulation node.
+struct TimestampNode
+{
+    index: u32,
+}
+
+impl Node for TimestampNode
+{
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        world: &World,
+    ) -> Result<(), NodeRunError>
+    {
+        let Some(timer) = world.get_resource::<HanabiGpuTimer>() else {
+            return Ok(());
+        };
+
+        // Timestamps are skipped while the previous readback is still in flight.
+        if timer.state.load(Ordering::Acquire) != TIMER_IDLE {
+            return Ok(());
+        }
+
+        let encoder = render_context.command_encoder();
+        encoder.write_timestamp(&timer.query_set, self.index);
+        if self.index == 1 {
+            encoder.resolve_query_set(&timer.query_set, 0..2, &timer.resolve_buffer, 0);
+            encoder.copy_buffer_to_buffer(
+                &timer.resolve_buffer,
+                0,
+                &timer.readback_buffer,
+                0,
+                timer.readback_buffer.size(),
+            );
+            timer.state.store(TIMER_COPIED, Ordering::Release);
+        }
+        Ok(())
+    }
+}
+
+fn sys_readback_gpu_timestamps(
+    timer: Res<HanabiGpuTimer>,
+    render_device: Res<RenderDevice>,
+    render_queue: Res<RenderQueue>,
+)
+{
+    match timer.state.load(Ordering::Acquire) {
+        TIMER_COPIED => {
+            timer.state.store(TIMER_MAPPING, Ordering::Release);
+            let state = timer.state.clone();
+            timer
+                .readback_buffer
+                .slice(..)
+                .map_async(MapMode::Read, move |result| {
+                    let next = if result.is_ok() {
+                        TIMER_MAPPED
+                    } else {
+                        TIMER_IDLE
+                    };
+                    state.store(next, Ordering::Release);
+                });
+        }
+        TIMER_MAPPED => {
+            let elapsed_ms = {
+                let data = timer.readback_buffer.slice(..).get_mapped_range();
+                let begin = read_timestamp(&data, 0);
+                let end = read_timestamp(&data, 1);
+                let period_ns = render_queue.get_timestamp_period();
+                end.saturating_sub(begin) as f32 * period_ns / 1_000_000.0
+            };
+            timer.readback_buffer.unmap();
+            timer.state.store(TIMER_IDLE, Ordering::Release);
+            let _ = timer.sender.try_send(elapsed_ms);
+        }
+        _ => {}
+    }
+    render_device.wgpu_device().poll(Maintain::Poll);
+}
+
+/// Read one resolved timestamp from the mapped readback bytes.
+fn read_timestamp(data: &[u8], index: usize) -> u64
+{
+    let start = index * std::mem::size_of::<u64>();
+    data.get(start..start + std::mem::size_of::<u64>())
+        .and_then(|bytes| bytes.try_into().ok())
+        .map(u64::from_le_bytes)
+        .unwrap_or(0)
+}
+
+// ====================
+// Main world.
+// ====================
+
+fn sys_collect_profiler_metrics(
+    time: Res<Time>,
+    diagnostics: Res<DiagnosticsStore>,
+    particle_stats: Res<ParticleStats>,
+    gpu_timings: Option<Res<GpuTimingReceiver>>,
+    mut history: ResMut<ProfilerHistory>,
+    mut capture: Option<ResMut<ProfileCapture>>,
+)
+{
+    let frame_time_ms = diagnostics
+        .get(FrameTimeDiagnosticsPlugin::FRAME_TIME)
+        .and_then(|d| d.value())
+        .unwrap_or(time.delta_seconds_f64() * 1000.0) as f32;
+
+    let mut measured = None;
+    if let Some(gpu_timings) = gpu_timings {
+        while let Ok(Some(ms)) = gpu_timings.receiver.try_recv() {
+            measured = Some(ms);
+        }
+    }
+
+    let (gpu_time_ms, gpu_source) = match measured {
+        Some(ms) => (ms, GpuTimingSource::TimestampQuery),
+        None if history.gpu_source == GpuTimingSource::TimestampQuery => (
+            history.samples.back().map_or(0.0, |s| s.gpu_time_ms),
+            GpuTimingSource::TimestampQuery,
+        ),
+        None => (
+            particle_stats.particle_count as f32 * ESTIMATED_NS_PER_PARTICLE / 1_000_000.0,
+            GpuTimingSource::Estimated,
+        ),
+    };
+
+    let sample = ProfileSample {
+        time: time.elapsed_seconds_f64(),
+        frame_time_ms,
+        gpu_time_ms,
+        gpu_source,
+        particle_count: particle_stats.particle_count,
+    };
+    history.gpu_source = gpu_source;
+    history.push(sample);
+
+    if let Some(capture) = capture.as_mut() {
+        capture.samples.push(sample);
+    }
+}
+
+fn sys_handle_profile_commands(
+    mut cmds: Commands,
+    mut events: EventReader<ViewerCommandEvent>,
+    time: Res<Time>,
+)
+{
+    for event in events.iter() {
+        if let ViewerCommandEvent::CaptureProfile { path, duration } = event {
+            info!(
+                "Starting profile capture for {:.1}s into {}",
+                duration, path
+            );
+            cmds.insert_resource(ProfileCapture {
+                path:     path.clone(),
+                duration: duration.max(0.0),
+                started:  time.elapsed_seconds_f64(),
+                samples:  Vec::new(),
+            });
+        }
+    }
+}
+
+fn sys_finish_profile_capture(
+    mut cmds: Commands,
+    time: Res<Time>,
+    capture: Option<Res<ProfileCapture>>,
+)
+{
+    let Some(capture) = capture else {
+        return;
+    };
+    if time.elapsed_seconds_f64() - capture.started < capture.duration as f64 {
+        return;
+    }
+
+    match std::fs::write(&capture.path, capture.to_csv()) {
+        Ok(()) => info!(
+            "Profile capture written to {} ({} samples)",
+            capture.path,
+            capture.samples.len()
+        ),
+        Err(e) => error!("Failed to write profile capture {}: {}", capture.path, e),
+    }
+    cmds.remove_resource::<ProfileCapture>();
+}
+
+fn sys_setup_profiler_overlay(mut cmds: Commands, asset_server: Res<AssetServer>)
+{
+    let caption_style = TextStyle {
+        font_size: 11.0,
+        color: Color::rgba(0.8, 0.8, 0.8, 0.8),
+        font: asset_server.load(DEFAULT_FONT),
+        ..default()
+    };
+
+    cmds.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                right: Val::Px(10.0),
+                top: Val::Px(30.0),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(4.0),
+                padding: UiRect::all(Val::Px(6.0)),
+                ..default()
+            },
+            background_color: Color::rgba(0.0, 0.0, 0.0, 0.45).into(),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        ProfilerOverlay,
+    ))
+    .with_children(|parent| {
+        for (graph, color) in [
+            (ProfilerGraph::FrameTime, Color::rgb(0.3, 0.8, 0.4)),
+            (ProfilerGraph::GpuTime, Color::rgb(0.9, 0.6, 0.2)),
+            (ProfilerGraph::ParticleCount, Color::rgb(0.4, 0.6, 1.0)),
+        ] {
+            parent.spawn((
+                TextBundle::from_section("", caption_style.clone()),
+                ProfilerCaption(graph),
+            ));
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Px(HISTORY_LEN as f32 * BAR_WIDTH),
+                        height: Val::Px(GRAPH_HEIGHT),
+                        flex_direction: FlexDirection::Row,
+                        align_items: AlignItems::FlexEnd,
+                        ..default()
+                    },
+                    background_color: Color::rgba(1.0, 1.0, 1.0, 0.05).into(),
+                    ..default()
+                })
+                .with_children(|graph_node| {
+                    for index in 0..HISTORY_LEN {
+                        graph_node.spawn((
+                            NodeBundle {
+                                style: Style {
+                                    width: Val::Px(BAR_WIDTH),
+                                    height: Val::Percent(0.0),
+                                    ..default()
+                                },
+                                background_color: color.into(),
+                                ..default()
+                            },
+                            ProfilerBar { graph, index },
+                        ));
+                    }
+                });
+        }
+    });
+}
+
+fn sys_update_profiler_overlay(
+    viewer_settings: Res<ViewerSettings>,
+    viewer_state: Res<ViewerState>,
+    history: Res<ProfilerHistory>,
+    mut overlay_query: Query<&mut Visibility, With<ProfilerOverlay>>,
+    mut caption_query: Query<(&mut Text, &ProfilerCaption)>,
+    mut bar_query: Query<(&mut Style, &ProfilerBar)>,
+)
+{
+    let visible = viewer_settings.show_profiler && viewer_state.has_asset;
+    for mut visibility in overlay_query.iter_mut() {
+        *visibility = if visible {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+    }
+    if !visible {
+        return;
+    }
+
+    let graphs = [
+        ProfilerGraph::FrameTime,
+        ProfilerGraph::GpuTime,
+        ProfilerGraph::ParticleCount,
+    ];
+    let mut maxima = [0.0_f32; 3];
+    let mut latest = [0.0_f32; 3];
+    for (i, graph) in graphs.iter().enumerate() {
+        maxima[i] = history.values(*graph).fold(0.0, f32::max);
+        latest[i] = history.values(*graph).last().unwrap_or(0.0);
+    }
+
+    for (mut text, caption) in caption_query.iter_mut() {
+        let i = graphs.iter().position(|g| *g == caption.0).unwrap_or(0);
+        text.sections[0].value = match caption.0 {
+            ProfilerGraph::FrameTime => {
+                format!("Frame: {:.2}ms (max {:.2}ms)", latest[i], maxima[i])
+            }
+            ProfilerGraph::GpuTime => format!(
+                "Hanabi GPU: {:.3}ms ({})",
+                latest[i],
+                history.gpu_source.label()
+            ),
+            ProfilerGraph::ParticleCount => {
+                format!("Particles: {:.0} (max {:.0})", latest[i], maxima[i])
+            }
+        };
+    }
+
+    // Bars are right-aligned so the newest sample is always at the right edge.
+    let offset = HISTORY_LEN - history.samples.len();
+    for (mut style, bar) in bar_query.iter_mut() {
+        let i = graphs.iter().position(|g| *g == bar.graph).unwrap_or(0);
+        let value = bar
+            .index
+            .checked_sub(offset)
+            .and_then(|ix| history.values(bar.graph).nth(ix))
+            .unwrap_or(0.0);
+        let percent = if maxima[i] > 0.0 {
+            (value / maxima[i] * 100.0).clamp(0.0, 100.0)
+        } else {
+            0.0
+        };
+        style.height = Val::Percent(percent);
+    }
+}
@@ -0,0 +1,196 @@
+// ====================
+// Bevy stuff.
+// ====================
+use bevy::core_pipeline::bloom::BloomSettings;
+use bevy::prelude::*;
+// ====================
+// Particles.
+// ====================
+use bevy_hanabi::{EffectAsset, ParticleEffect, ParticleEffectBundle};
+
+// ====================
+// Viewer.
+// ====================
+use crate::shared::scene::{SceneBloom, SceneCamera, SceneEffect, SceneGrid, ViewerScene};
+use crate::viewer::app::{BackgroundColor, CameraController, ViewerSettings};
+use crate::viewer::server::ViewerCommandEvent;
+
+const REFERENCE_IMAGE_Z: f32 = -2.0;
+
+/// Plugin applying and saving viewer scene presets.
+pub struct ScenePlugin;
+
+/// Scene currently applied to the viewer.
+#[derive(Resource, Default)]
+pub struct ActiveScene
+{
+    pub scene: ViewerScene,
+}
+
+/// Marker for entities spawned by the active scene.
+#[derive(Component)]
+pub struct SceneEntity;
+
+/// Effect of a scene effect instance, kept to add it back after the effect assets are cleared.
+#[derive(Component)]
+pub struct SceneEffectAsset(pub EffectAsset);
+
+impl Plugin for ScenePlugin
+{
+    fn build(&self, app: &mut App)
+    {
+        app.init_resource::<ActiveScene>()
+            .add_systems(Update, sys_handle_scene_commands)
+            .add_systems(PostUpdate, sys_restore_scene_effects);
+    }
+}
+
+fn sys_handle_scene_commands(
+    mut events: EventReader<ViewerCommandEvent>,
+    mut cmds: Commands,
+    mut effects: ResMut<Assets<EffectAsset>>,
+    mut active_scene: ResMut<ActiveScene>,
+    mut background_color: ResMut<BackgroundColor>,
+    mut camera_controller: ResMut<CameraController>,
+    mut viewer_settings: ResMut<ViewerSettings>,
+    asset_server: Res<AssetServer>,
+    query_camera: Query<(Entity, Option<&BloomSettings>), With<Camera2d>>,
+    query_scene_entities: Query<Entity, With<SceneEntity>>,
+)
+{
+    for event in events.iter() {
+        match event {
+            ViewerCommandEvent::LoadScene { scene } => {
+                info!("Applying scene: {}", scene.name);
+                for entity in query_scene_entities.iter() {
+                    cmds.entity(entity).despawn_recursive();
+                }
+
+                let [r, g, b, a] = scene.background;
+                background_color.color = Color::rgba(r, g, b, a);
+                camera_controller.position = Vec2::from(scene.camera.position);
+                camera_controller.zoom = scene
+                    .camera
+                    .zoom
+                    .clamp(camera_controller.min_zoom, camera_controller.max_zoom);
+                viewer_settings.show_grid = scene.grid.show_grid;
+                viewer_settings.show_bbox = scene.grid.show_bbox;
+
+                if let Ok((camera, _)) = query_camera.get_single() {
+                    if scene.bloom.enabled {
+                        cmds.entity(camera).insert(BloomSettings {
+                            intensity: scene.bloom.intensity,
+                            ..default()
+                        });
+                    } else {
+                        cmds.entity(camera).remove::<BloomSettings>();
+                    }
+                }
+
+                if let Some(image) = &scene.reference_image {
+                    cmds.spawn((
+                        SpriteBundle {
+                            texture: asset_server.load(image.path.as_str()),
+                            sprite: Sprite {
+                                color: Color::rgba(1.0, 1.0, 1.0, image.opacity),
+                                ..default()
+                            },
+                            transform: Transform::from_translation(
+                                Vec2::from(image.position).extend(REFERENCE_IMAGE_Z),
+                            )
+                            .with_scale(Vec3::splat(image.scale)),
+                            ..default()
+                        },
+                        SceneEntity,
+                    ));
+                }
+
+                for scene_effect in &scene.effects {
+                    spawn_scene_effect(&mut cmds, &mut effects, scene_effect);
+                }
+
+                active_scene.scene = scene.clone();
+            }
+
+            ViewerCommandEvent::SaveScene { path } => {
+                let path = std::path::Path::new(path);
+                let bloom = query_camera.get_single().ok().and_then(|(_, bloom)| bloom);
+                let [r, g, b, a] = background_color.color.as_rgba_f32();
+
+                let scene = ViewerScene {
+                    name: ViewerScene::name_from_path(path)
+                        .unwrap_or(&active_scene.scene.name)
+                        .to_string(),
+                    background: [r, g, b, a],
+                    camera: SceneCamera {
+                        position: camera_controller.position.to_array(),
+                        zoom:     camera_controller.zoom,
+                    },
+                    grid: SceneGrid {
+                        show_grid: viewer_settings.show_grid,
+                        show_bbox: viewer_settings.show_bbox,
+                    },
+                    bloom: SceneBloom {
+                        enabled:   bloom.is_some(),
+                        intensity: bloom
+                            .map_or(active_scene.scene.bloom.intensity, |b| b.intensity),
+                    },
+                    ..active_scene.scene.clone()
+                };
+
+                match scene.save(path) {
+                    Ok(()) => {
+                        info!("Saved scene to {}", path.display());
+                        active_scene.scene = scene;
+                    }
+                    Err(e) => error!("Failed to save scene: {}", e),
+                }
+            }
+
+            _ => {}
+        }
+    }
+}
+
+fn spawn_scene_effect(
+    cmds: &mut Commands,
+    effects: &mut Assets<EffectAsset>,
+    scene_effect: &SceneEffect,
+)
+{
+    let asset = match std::fs::read_to_string(&scene_effect.path)
+        .map_err(|e| e.to_string())
+        .and_then(|content| ron::from_str::<EffectAsset>(&content).map_err(|e| e.to_string()))
+    {
+        Ok(asset) => asset,
+        Err(e) => {
+            error!("Failed to load scene effect {}: {}", scene_effect.path, e);
+            return;
+        }
+    };
+
+    let handle = effects.add(asset.clone());
+    cmds.spawn((
+        ParticleEffectBundle {
+            effect: ParticleEffect::new(handle).with_z_layer_2d(Some(scene_effect.z_layer)),
+            transform: Transform::from_translation(Vec2::from(scene_effect.position).extend(0.0)),
+            visibility: Visibility::Visible,
+            ..Default::default()
+        },
+        SceneEffectAsset(asset),
+        SceneEntity,
+    ));
+}
+
+/// Add back the scene effect assets removed when the edited effect is respawned.
+fn sys_restore_scene_effects(
+    mut effects: ResMut<Assets<EffectAsset>>,
+    query_scene_effects: Query<(&ParticleEffect, &SceneEffectAsset)>,
+)
+{
+    for (effect, source) in query_scene_effects.iter() {
+        if !effects.contains(&effect.handle) {
+            effects.set_untracked(&effect.handle, source.0.clone());
+        }
+    }
+}
@@ -22,6 +22,7 @@ use kanal::{Receiver, Sender};
 // Shared.
 // ====================
 use crate::shared::proto::{ServerCommandMessage, ServerCommandResponse};
+use crate::shared::scene::ViewerScene;
 
 #[derive(Event)]
 pub enum ViewerCommandEvent
@@ -34,6 +35,31 @@ pub enum ViewerCommandEvent
     {
         path: String
     },
+    CaptureProfile
+    {
+        path: String, duration: f32
+    },
+    SetFixedTimestep
+    {
+        spawner_seed:   Option<u32>,
+        fixed_timestep: Option<f32>,
+    },
+    LoadScene
+    {
+        scene: ViewerScene
+    },
+    SaveScene
+    {
+        path: String
+    },
+    ResizeViewport
+    {
+        width: u32, height: u32
+    },
+    PanZoomCamera
+    {
+        pan: [f32; 2], zoom: f32
+    },
 }
 
 /// Bevy resource that implements communication with the server.
@@ -94,6 +120,38 @@ pub fn sys_handle_server_messages(
                 debug!("Received OpenAssetFile command: {}", path);
                 event_writer.send(ViewerCommandEvent::OpenAssetFile { path });
             }
+            ServerCommandMessage::CaptureProfile { path, duration } => {
+                debug!("Received CaptureProfile command: {} ({}s)", path, duration);
+                event_writer.send(ViewerCommandEvent::CaptureProfile { path, duration });
+            }
+            ServerCommandMessage::SetFixedTimestep {
+                spawner_seed,
+                fixed_timestep,
+            } => {
+                debug!(
+                    "Received SetFixedTimestep command: spawner_seed={:?} dt={:?}",
+                    spawner_seed, fixed_timestep
+                );
+                event_writer.send(ViewerCommandEvent::SetFixedTimestep {
+                    spawner_seed,
+                    fixed_timestep,
+                });
+            }
+            ServerCommandMessage::LoadScene { scene } => {
+                debug!("Received LoadScene command: {}", scene.name);
+                event_writer.send(ViewerCommandEvent::LoadScene { scene });
+            }
+            ServerCommandMessage::SaveScene { path } => {
+                debug!("Received SaveScene command: {}", path);
+                event_writer.send(ViewerCommandEvent::SaveScene { path });
+            }
+            ServerCommandMessage::ResizeViewport { width, height } => {
+                debug!("Received ResizeViewport command: {}x{}", width, height);
+                event_writer.send(ViewerCommandEvent::ResizeViewport { width, height });
+            }
+            ServerCommandMessage::PanZoomCamera { pan, zoom } => {
+                event_writer.send(ViewerCommandEvent::PanZoomCamera { pan, zoom });
+            }
             ServerCommandMessage::Ping => {
                 debug!("Received Ping command");
             }
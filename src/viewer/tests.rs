@@ -0,0 +1,57 @@
+#[cfg(test)]
+mod tests
+{
+    use bevy_hanabi::{CpuValue, EffectAsset, EffectSpawner, Spawner};
+
+    use crate::viewer::app::{MAX_PREVIEW_PERIOD, seeded_random, shorten_preview_period};
+
+    #[test]
+    fn test_fixed_timestep_spawner_reset()
+    {
+        let asset = EffectAsset {
+            spawner: Spawner::new(
+                CpuValue::Uniform((5.0, 50.0)),
+                CpuValue::Single(0.2),
+                CpuValue::Uniform((0.3, 0.6)),
+            ),
+            ..Default::default()
+        };
+        let spawn_counts = |seed: u32| {
+            let mut random = seeded_random(seed);
+            let mut spawner = EffectSpawner::new(&asset);
+            (0..120)
+                .map(|_| spawner.tick(1.0 / 60.0, &mut random.0))
+                .collect::<Vec<u32>>()
+        };
+
+        assert_eq!(spawn_counts(7), spawn_counts(7));
+        assert_ne!(spawn_counts(7), spawn_counts(8));
+    }
+
+    #[test]
+    fn test_preview_ranged_period()
+    {
+        let mut asset = EffectAsset {
+            spawner: Spawner::new(
+                CpuValue::Single(10.0),
+                CpuValue::Single(0.5),
+                CpuValue::Uniform((0.5, 2.0)),
+            ),
+            ..Default::default()
+        };
+        shorten_preview_period(&mut asset);
+        assert_eq!(asset.spawner.period, CpuValue::Uniform((0.5, 2.0)));
+
+        // A range reaching past the longest previewed period keeps its shape.
+        asset.spawner.period = CpuValue::Uniform((2.0, 5.0));
+        shorten_preview_period(&mut asset);
+        assert_eq!(
+            asset.spawner.period,
+            CpuValue::Uniform((2.0, MAX_PREVIEW_PERIOD))
+        );
+
+        asset.spawner.period = CpuValue::Single(f32::INFINITY);
+        shorten_preview_period(&mut asset);
+        assert_eq!(asset.spawner.period, CpuValue::Single(MAX_PREVIEW_PERIOD));
+    }
+}